@@ -8,6 +8,34 @@ fn main() {
     bindgen("gMON");
     bindgen("shMON");
     bindgen("erc20");
+    bindgen("AggregatorV3");
+    git_commit();
+}
+
+/// Exposes the commit being built as `GIT_COMMIT`, for the version resource. A `GIT_COMMIT`
+/// set in the environment wins, as builds from a source copy (e.g. Docker) have no `.git`.
+fn git_commit() {
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    for path in [".git/HEAD", ".git/refs/heads", ".git/packed-refs"] {
+        // Watching a missing path would re-run the script on every build
+        if std::path::Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+
+    let commit = std::env::var("GIT_COMMIT").ok().or_else(|| {
+        std::process::Command::new("git")
+            .args(["rev-parse", "--short", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|commit| commit.trim().to_string())
+    });
+    println!(
+        "cargo:rustc-env=GIT_COMMIT={}",
+        commit.unwrap_or_else(|| "unknown".to_string())
+    );
 }
 
 #[allow(dead_code)]
@@ -1,18 +1,25 @@
-use anyhow::Result;
+mod reconnect;
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
 use ethers::{
     signers::{LocalWallet, Signer},
     utils::to_checksum,
 };
-use rmcp::{
-    Peer, RoleClient, ServiceExt,
-    model::{
-        CallToolRequestParam, ClientCapabilities, ClientInfo, Implementation,
-        ReadResourceRequestParam,
-    },
-    transport::SseTransport,
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, ClientCapabilities, ClientInfo, Implementation,
 };
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use crate::reconnect::ReconnectingClient;
+
+/// How often a sent transaction is checked with `tx_status` while waiting for confirmations.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Checks of a sent transaction before giving up on its confirmations.
+const CONFIRMATION_MAX_POLLS: u32 = 60;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
@@ -23,7 +30,8 @@ async fn main() -> Result<()> {
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
-    let transport = SseTransport::start("http://127.0.0.1:8989/sse").await?;
+    let sse_url =
+        std::env::var("SSE_URL").unwrap_or_else(|_| "http://127.0.0.1:8989/sse".to_string());
     let client_info = ClientInfo {
         protocol_version: Default::default(),
         capabilities: ClientCapabilities::default(),
@@ -32,56 +40,69 @@ async fn main() -> Result<()> {
             version: "0.0.1".to_string(),
         },
     };
-    let client = client_info.serve(transport).await.inspect_err(|e| {
-        tracing::error!("client error: {:?}", e);
-    })?;
-
-    // Initialize
-    let server_info = client.peer_info();
+    // Connect and initialize, reconnecting later if the server restarts
+    let mut client = ReconnectingClient::connect(sse_url, client_info)
+        .await
+        .inspect_err(|e| {
+            tracing::error!("client error: {:?}", e);
+        })?;
+    let server_info = client.peer().peer_info();
     tracing::info!("Connected to server: {server_info:#?}");
 
-    // List tools
-    let tools = client.list_tools(Default::default()).await?;
+    // Tools are listed on every (re)connect
+    let tools = client.tools();
     tracing::info!("Available tools: {tools:#?}");
 
     // List resources
-    let resources = client.list_resources(Default::default()).await?;
+    let resources = client
+        .request(|peer| async move { peer.list_resources(Default::default()).await })
+        .await?;
     tracing::info!("Available resources: {resources:#?}");
 
     // List resources template
-    let resource_templates = client.list_resource_templates(Default::default()).await?;
+    let resource_templates = client
+        .request(|peer| async move { peer.list_resource_templates(Default::default()).await })
+        .await?;
     tracing::info!("Available resource templates: {resource_templates:#?}");
 
-    let supported_networks = client
-        .read_resource(ReadResourceRequestParam {
-            uri: "evm://networks".to_string(),
-        })
-        .await?;
+    let supported_networks = client.read_resource("evm://networks").await?;
     println!("Supported networks: {supported_networks:#?}");
 
-    let lst_protocols = client
-        .read_resource(ReadResourceRequestParam {
-            uri: "evm://monadTestnet/lsts".to_string(),
-        })
-        .await?;
+    let lst_protocols = client.read_resource("evm://monadTestnet/lsts").await?;
     println!("LST protocols: {lst_protocols:#?}");
 
-    let protocol_name = "shMON";
+    // aprMON takes native MON, so the example needs no other token to stake
+    let protocol_name = "aprMON";
     let private_key = std::env::var("PRIVATE_KEY").unwrap_or_default();
+    let confirmations = std::env::var("CONFIRMATIONS")
+        .ok()
+        .map(|confirmations| confirmations.parse::<u64>())
+        .transpose()
+        .context("CONFIRMATIONS must be a whole number")?
+        .unwrap_or(1);
 
-    test(client.clone(), protocol_name, private_key.clone()).await?;
+    test(
+        &mut client,
+        protocol_name,
+        private_key.clone(),
+        confirmations,
+    )
+    .await?;
 
-    client.cancel().await?;
+    client.close().await?;
     Ok(())
 }
 
-async fn test(client: Peer<RoleClient>, protocol_name: &str, private_key: String) -> Result<()> {
+async fn test(
+    client: &mut ReconnectingClient,
+    protocol_name: &str,
+    private_key: String,
+    confirmations: u64,
+) -> Result<()> {
     println!("Testing {protocol_name}...");
 
     let lst_protocol = client
-        .read_resource(ReadResourceRequestParam {
-            uri: format!("evm://monadTestnet/lsts/{}", protocol_name),
-        })
+        .read_resource(format!("evm://monadTestnet/lsts/{}", protocol_name))
         .await?;
     println!("LST protocol: {lst_protocol:#?}");
 
@@ -104,29 +125,169 @@ async fn test(client: Peer<RoleClient>, protocol_name: &str, private_key: String
             .await?;
         tracing::info!("Tool result: {tool_result:#?}");
 
-        // Wait for the transaction to be mined
-        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        // Wait until the stake is deep enough for the balance read to see it
+        let tx_hash = tx_hash(&tool_result).context("Stake returned no transaction hash")?;
+        wait_for_confirmations(client, &tx_hash, confirmations).await?;
 
         let wallet = private_key.parse::<LocalWallet>().unwrap();
 
         let balance = client
-            .read_resource(ReadResourceRequestParam {
-                uri: format!(
-                    "evm://monadTestnet/address/{}/lsts/{}/balance",
-                    to_checksum(&wallet.address(), None),
-                    protocol_name,
-                ),
-            })
+            .read_resource(format!(
+                "evm://monadTestnet/address/{}/lsts/{}/balance",
+                to_checksum(&wallet.address(), None),
+                protocol_name,
+            ))
             .await?;
         println!("Balance: {balance:#?}");
     }
 
     let tvl = client
-        .read_resource(ReadResourceRequestParam {
-            uri: format!("evm://monadTestnet/lsts/{}/tvl", protocol_name),
-        })
+        .read_resource(format!("evm://monadTestnet/lsts/{}/tvl", protocol_name))
         .await?;
     println!("TVL: {tvl:#?}");
 
     Ok(())
 }
+
+/// Hash of the transaction a write tool sent, from its `Transaction hash: 0x...` text.
+fn tx_hash(result: &CallToolResult) -> Option<String> {
+    const PREFIX: &str = "Transaction hash: ";
+
+    let text = &result.content.first()?.as_text()?.text;
+    let start = text.find(PREFIX)? + PREFIX.len();
+    text.get(start..start + 66).map(String::from)
+}
+
+/// Polls `tx_status` until the transaction has `confirmations` confirmations, failing if it
+/// reverted or doesn't get there within [`CONFIRMATION_MAX_POLLS`] checks.
+async fn wait_for_confirmations(
+    client: &mut ReconnectingClient,
+    tx_hash: &str,
+    confirmations: u64,
+) -> Result<()> {
+    for _ in 0..CONFIRMATION_MAX_POLLS {
+        let result = client
+            .call_tool(CallToolRequestParam {
+                name: "tx_status".into(),
+                arguments: serde_json::json!({ "tx_hash": tx_hash })
+                    .as_object()
+                    .cloned(),
+            })
+            .await?;
+        let text = &result
+            .content
+            .first()
+            .and_then(|content| content.as_text())
+            .context("tx_status returned no text")?
+            .text;
+        let status: serde_json::Value = serde_json::from_str(text)?;
+
+        let confirmed = status["confirmations"].as_u64().unwrap_or_default();
+        match status["status"].as_str() {
+            Some("failed") => bail!("Transaction {tx_hash} reverted"),
+            Some("success" | "mined") if confirmed >= confirmations => {
+                println!("Transaction {tx_hash} has {confirmed} confirmation(s)");
+                return Ok(());
+            }
+            _ => tracing::info!(
+                "Transaction {tx_hash} has {confirmed} of {confirmations} confirmation(s)"
+            ),
+        }
+        tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+    }
+
+    bail!("Transaction {tx_hash} did not get {confirmations} confirmation(s) in time")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ethers::providers::Provider;
+    use monad_mcp::common::{
+        lst::Lst,
+        rpc::{CallLogger, FailoverHttp},
+    };
+    use rmcp::model::Content;
+
+    use super::*;
+    use crate::reconnect::tests::TestServer;
+
+    /// Provider for a local JSON-RPC endpoint at block 100, where `0x11…11` was mined at block
+    /// 99 and `0x22…22` reverted at block 96.
+    async fn fake_chain() -> Lst {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(
+                |axum::Json(call): axum::Json<serde_json::Value>| async move {
+                    let result = match call["method"].as_str().unwrap_or_default() {
+                        "eth_blockNumber" => serde_json::json!("0x64"),
+                        "eth_getTransactionByHash" => serde_json::json!({ "gasPrice": "0x1" }),
+                        "eth_getTransactionReceipt" => {
+                            let reverted = call["params"][0].as_str().unwrap().ends_with("22");
+                            serde_json::json!({
+                                "blockNumber": if reverted { "0x60" } else { "0x63" },
+                                "status": if reverted { "0x0" } else { "0x1" },
+                                "gasUsed": "0x5208",
+                            })
+                        }
+                        _ => serde_json::Value::Null,
+                    };
+                    axum::Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": result,
+                    }))
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Lst::new(Arc::new(CallLogger::new(
+            Provider::new(FailoverHttp::new(&[url]).unwrap()),
+            false,
+        )))
+    }
+
+    #[test]
+    fn the_tx_hash_is_read_from_a_write_result() {
+        let hash = format!("0x{}", "11".repeat(32));
+        let result = CallToolResult::success(vec![Content::text(format!(
+            "Staked 0.005 MON. Transaction hash: {hash}. Received 0.004 aprMON"
+        ))]);
+        assert_eq!(tx_hash(&result), Some(hash));
+        assert_eq!(
+            tx_hash(&CallToolResult::success(vec![Content::text("Done")])),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn confirmations_are_awaited_through_tx_status() {
+        let bind = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let server = TestServer::serve(bind, fake_chain().await).await;
+        let mut client =
+            ReconnectingClient::connect(format!("http://{}/sse", bind), ClientInfo::default())
+                .await
+                .unwrap();
+
+        let mined = format!("0x{}", "11".repeat(32));
+        wait_for_confirmations(&mut client, &mined, 2)
+            .await
+            .unwrap();
+
+        let reverted = format!("0x{}", "22".repeat(32));
+        let err = wait_for_confirmations(&mut client, &reverted, 1)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("reverted"), "{err}");
+
+        client.close().await.unwrap();
+        server.stop().await;
+    }
+}
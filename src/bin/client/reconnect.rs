@@ -0,0 +1,298 @@
+use std::{future::Future, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use rmcp::{
+    Peer, RoleClient, ServiceError, ServiceExt,
+    model::{
+        CallToolRequestParam, CallToolResult, ClientInfo, ReadResourceRequestParam,
+        ReadResourceResult, Tool,
+    },
+    service::RunningService,
+    transport::SseTransport,
+};
+
+/// Delay before the first reconnect attempt, doubled after every failed one.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+/// MCP client over SSE that survives server restarts: when a request fails because the
+/// connection was lost, it reconnects with exponential backoff, initializes again, re-lists the
+/// tools and retries the request once. Tool calls that may have sent a transaction are not
+/// retried, so a lost response never turns into a second transaction.
+pub struct ReconnectingClient {
+    sse_url: String,
+    client_info: ClientInfo,
+    service: RunningService<RoleClient, ClientInfo>,
+    tools: Vec<Tool>,
+}
+
+impl ReconnectingClient {
+    pub async fn connect(sse_url: impl Into<String>, client_info: ClientInfo) -> Result<Self> {
+        let sse_url = sse_url.into();
+        let (service, tools) = establish(&sse_url, &client_info).await?;
+
+        Ok(ReconnectingClient {
+            sse_url,
+            client_info,
+            service,
+            tools,
+        })
+    }
+
+    pub fn peer(&self) -> &Peer<RoleClient> {
+        self.service.peer()
+    }
+
+    /// Tools listed by the server when the current connection was made.
+    pub fn tools(&self) -> &[Tool] {
+        &self.tools
+    }
+
+    /// Sends `request` to the server, reconnecting and retrying it once if the connection was
+    /// lost. Errors returned by the server itself are not retried.
+    pub async fn request<T, F, Fut>(&mut self, request: F) -> Result<T>
+    where
+        F: Fn(Peer<RoleClient>) -> Fut,
+        Fut: Future<Output = Result<T, ServiceError>>,
+    {
+        match request(self.peer().clone()).await {
+            Err(e) if is_disconnect(&e) => {
+                tracing::warn!("Connection to {} lost: {}", self.sse_url, e);
+                self.reconnect().await?;
+                Ok(request(self.peer().clone()).await?)
+            }
+            result => Ok(result?),
+        }
+    }
+
+    /// Calls a tool, retrying it after a reconnect only if it is a read. A write that lost its
+    /// connection reconnects but fails, as its transaction may already be out: check it with
+    /// `tx_status` before calling again.
+    pub async fn call_tool(&mut self, param: CallToolRequestParam) -> Result<CallToolResult> {
+        let retry = self
+            .tools
+            .iter()
+            .find(|tool| tool.name == param.name)
+            .is_some_and(is_read_tool);
+        if retry {
+            return self
+                .request(|peer| {
+                    let param = param.clone();
+                    async move { peer.call_tool(param).await }
+                })
+                .await;
+        }
+
+        match self.peer().clone().call_tool(param.clone()).await {
+            Err(e) if is_disconnect(&e) => {
+                tracing::warn!("Connection to {} lost: {}", self.sse_url, e);
+                self.reconnect().await?;
+                bail!(
+                    "Connection lost while calling {}; it was not retried as it may have sent a transaction, check with tx_status before calling it again",
+                    param.name
+                )
+            }
+            result => Ok(result?),
+        }
+    }
+
+    pub async fn read_resource(&mut self, uri: impl Into<String>) -> Result<ReadResourceResult> {
+        let uri = uri.into();
+        self.request(|peer| {
+            let uri = uri.clone();
+            async move { peer.read_resource(ReadResourceRequestParam { uri }).await }
+        })
+        .await
+    }
+
+    /// Replaces the connection with a new one, backing off exponentially between attempts.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        let mut backoff = INITIAL_BACKOFF;
+
+        for attempt in 1..=MAX_RECONNECT_ATTEMPTS {
+            match establish(&self.sse_url, &self.client_info).await {
+                Ok((service, tools)) => {
+                    tracing::info!(
+                        "Reconnected to {} after {} attempt(s)",
+                        self.sse_url,
+                        attempt
+                    );
+                    let previous = std::mem::replace(&mut self.service, service);
+                    self.tools = tools;
+                    // The old connection is already gone; this only stops its task
+                    let _ = previous.cancel().await;
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Reconnect attempt {} to {} failed: {:#}; retrying in {:?}",
+                        attempt,
+                        self.sse_url,
+                        e,
+                        backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+
+        bail!(
+            "Failed to reconnect to {} after {} attempts",
+            self.sse_url,
+            MAX_RECONNECT_ATTEMPTS
+        )
+    }
+
+    pub async fn close(self) -> Result<()> {
+        self.service.cancel().await?;
+        Ok(())
+    }
+}
+
+/// Opens the SSE transport, initializes the session and lists the server's tools.
+async fn establish(
+    sse_url: &str,
+    client_info: &ClientInfo,
+) -> Result<(RunningService<RoleClient, ClientInfo>, Vec<Tool>)> {
+    let transport = SseTransport::start(sse_url)
+        .await
+        .with_context(|| format!("Failed to connect to {}", sse_url))?;
+    let service = client_info
+        .clone()
+        .serve(transport)
+        .await
+        .context("Failed to initialize the session")?;
+    let tools = service
+        .list_all_tools()
+        .await
+        .context("Failed to list tools")?;
+
+    Ok((service, tools))
+}
+
+/// Whether retrying `tool` is safe: it takes no private key, so it can't have signed and sent
+/// a transaction. Tools the server didn't list are treated as writes.
+fn is_read_tool(tool: &Tool) -> bool {
+    tool.input_schema
+        .get("properties")
+        .and_then(|properties| properties.get("private_key"))
+        .is_none()
+}
+
+/// Whether a request failed because the connection is gone rather than on the server.
+fn is_disconnect(error: &ServiceError) -> bool {
+    matches!(
+        error,
+        ServiceError::Transport(_) | ServiceError::Cancelled { .. }
+    )
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::{net::SocketAddr, sync::Arc};
+
+    use ethers::providers::Provider;
+    use rmcp::transport::sse_server::{SseServer, SseServerConfig};
+    use tokio_util::sync::CancellationToken;
+
+    use monad_mcp::common::{
+        lst::Lst,
+        rpc::{CallLogger, FailoverHttp},
+    };
+
+    use super::*;
+
+    /// LST service served over SSE, standing in for the server process.
+    pub(crate) struct TestServer {
+        ct: CancellationToken,
+        serving: tokio::task::JoinHandle<std::io::Result<()>>,
+    }
+
+    impl TestServer {
+        async fn start(bind: SocketAddr) -> Self {
+            let rpc = FailoverHttp::new(&["http://127.0.0.1:1".to_string()]).unwrap();
+            Self::serve(
+                bind,
+                Lst::new(Arc::new(CallLogger::new(Provider::new(rpc), false))),
+            )
+            .await
+        }
+
+        /// Serves `lst` at `bind`.
+        pub(crate) async fn serve(bind: SocketAddr, lst: Lst) -> Self {
+            let listener = tokio::net::TcpListener::bind(bind).await.unwrap();
+            let ct = CancellationToken::new();
+            let (sse_server, router) = SseServer::new(SseServerConfig {
+                bind,
+                sse_path: "/sse".to_string(),
+                post_path: "/message".to_string(),
+                ct: ct.clone(),
+                sse_keep_alive: None,
+            });
+            let serving = tokio::spawn(async move { axum::serve(listener, router).await });
+            sse_server.with_service(move || lst.clone());
+
+            TestServer { ct, serving }
+        }
+
+        /// Goes down at once, as a killed process would, freeing the address.
+        pub(crate) async fn stop(self) {
+            self.ct.cancel();
+            self.serving.abort();
+            let _ = self.serving.await;
+        }
+    }
+
+    fn tool(schema: serde_json::Value) -> Tool {
+        let serde_json::Value::Object(schema) = schema else {
+            unreachable!("schemas are objects");
+        };
+        Tool::new("tool", "", Arc::new(schema))
+    }
+
+    #[test]
+    fn only_tools_without_a_private_key_are_retried() {
+        assert!(is_read_tool(&tool(serde_json::json!({ "type": "object" }))));
+        assert!(is_read_tool(&tool(serde_json::json!({
+            "type": "object",
+            "properties": { "protocol": { "type": "string" } },
+        }))));
+        assert!(!is_read_tool(&tool(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "protocol": { "type": "string" },
+                "private_key": { "type": "string" },
+            },
+        }))));
+    }
+
+    #[tokio::test]
+    async fn requests_reconnect_to_a_restarted_server() {
+        let bind = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let server = TestServer::start(bind).await;
+        let mut client =
+            ReconnectingClient::connect(format!("http://{}/sse", bind), ClientInfo::default())
+                .await
+                .unwrap();
+        assert!(client.tools().iter().any(|tool| tool.name == "stake"));
+        client.read_resource("evm://networks").await.unwrap();
+
+        let lost = client.peer().clone();
+        server.stop().await;
+        let restarted = TestServer::start(bind).await;
+        let networks = client.read_resource("evm://networks").await.unwrap();
+
+        assert!(!networks.contents.is_empty());
+        // Served over a new connection, as the one before the restart is gone
+        assert!(lost.list_all_tools().await.is_err());
+        assert!(client.tools().iter().any(|tool| tool.name == "stake"));
+        client.close().await.unwrap();
+        restarted.stop().await;
+    }
+}
@@ -0,0 +1,311 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use ethers::providers::{Middleware, Provider};
+use serde::Serialize;
+
+use monad_mcp::common::{
+    admin::AdminToken,
+    lst::{Lst, LstProtocol},
+    registry::ProtocolOverrides,
+    rpc::{CallLogger, FailoverHttp},
+    tx::GasMultiplier,
+};
+
+use crate::{Args, event_scan, native_currency, timeouts, tls, usd_pricing};
+
+/// How long each RPC check waits for an answer before reporting the endpoint unreachable.
+const RPC_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of one configuration check.
+#[derive(Debug, Serialize)]
+struct Check {
+    name: String,
+    ok: bool,
+    detail: String,
+}
+
+/// Outcome of `--check-config`, printed as JSON.
+#[derive(Debug, Default, Serialize)]
+struct Report {
+    ok: bool,
+    checks: Vec<Check>,
+}
+
+impl Report {
+    fn record(&mut self, name: impl Into<String>, result: anyhow::Result<String>) {
+        let (ok, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(e) => (false, format!("{:#}", e)),
+        };
+        self.checks.push(Check {
+            name: name.into(),
+            ok,
+            detail,
+        });
+    }
+}
+
+/// Validates every setting and the RPC endpoints and contracts they point at, without starting
+/// the server, then prints the report to stdout. Returns whether every check passed.
+pub async fn run(args: &Args) -> bool {
+    let report = report(args).await;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).expect("report serializes")
+    );
+
+    report.ok
+}
+
+async fn report(args: &Args) -> Report {
+    let mut report = Report::default();
+
+    for url in &args.rpc_urls {
+        let result = check_rpc_url(url).await;
+        report.record(format!("rpc_url {}", endpoint_label(url)), result);
+    }
+
+    report.record(
+        "bind",
+        std::net::TcpListener::bind(args.bind)
+            .map(|_| format!("{} is available", args.bind))
+            .map_err(|e| anyhow::anyhow!("Cannot bind {}: {}", args.bind, e)),
+    );
+    if let (Some(cert), Some(key)) = (&args.tls_cert, &args.tls_key) {
+        report.record(
+            "tls",
+            tls::acceptor(cert, key).map(|_| "certificate and key match".to_string()),
+        );
+    }
+    report.record(
+        "argument_patterns",
+        Lst::argument_patterns().map(|_| "tool argument patterns compile".to_string()),
+    );
+    report.record(
+        "event_scan",
+        event_scan(args).map(|config| {
+            format!(
+                "chunk size {}, max lookback {} blocks",
+                config.chunk_size(),
+                config.max_lookback_blocks()
+            )
+        }),
+    );
+    report.record(
+        "timeouts",
+        timeouts(args).map(|timeouts| {
+            format!(
+                "reads {}s, writes {}s",
+                timeouts.read().as_secs(),
+                timeouts.write().as_secs()
+            )
+        }),
+    );
+    report.record(
+        "native_currency",
+        native_currency(args)
+            .map(|native| format!("{} with {} decimals", native.symbol(), native.decimals())),
+    );
+    if let Some(token) = &args.admin_token {
+        report.record(
+            "admin_token",
+            AdminToken::new(token.clone()).map(|_| "admin tools enabled".to_string()),
+        );
+    }
+    if let Some(path) = &args.protocols_file {
+        // Installed so the protocol checks below read the overridden contracts
+        report.record(
+            "protocols_file",
+            ProtocolOverrides::load(path).and_then(|overrides| {
+                let protocols = overrides.protocols();
+                overrides.install()?;
+                Ok(if protocols.is_empty() {
+                    "no protocols overridden".to_string()
+                } else {
+                    format!(
+                        "overrides {}",
+                        protocols
+                            .iter()
+                            .map(LstProtocol::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })
+            }),
+        );
+    }
+    if let Some(multiplier) = args.gas_limit_multiplier {
+        report.record(
+            "gas_limit_multiplier",
+            GasMultiplier::new(multiplier).map(|multiplier| multiplier.value().to_string()),
+        );
+    }
+
+    // Chain and contract checks go through the same failover transport the server uses
+    match FailoverHttp::new(&args.rpc_urls) {
+        Ok(transport) => {
+            let provider = Arc::new(CallLogger::new(Provider::new(transport), false));
+            let mut lst_service = Lst::new(provider.clone());
+            if let Some(chain_id) = args.chain_id {
+                lst_service = lst_service.with_expected_chain_id(chain_id);
+            }
+
+            let result = with_timeout(lst_service.verify_chain_id()).await;
+            report.record(
+                "chain_id",
+                result.map(|_| "matches the expected chain id".to_string()),
+            );
+
+            match usd_pricing(args) {
+                Ok(Some(pricing)) => {
+                    let result = with_timeout(pricing.price(provider.clone())).await;
+                    report.record(
+                        "usd_price",
+                        result.map(|price| {
+                            format!("{} USD from {}", price.price, pricing.source().describe())
+                        }),
+                    );
+                }
+                Ok(None) => {}
+                Err(e) => report.record("usd_price", Err(e)),
+            }
+
+            for protocol in LstProtocol::ALL {
+                let result = with_timeout(protocol.check_deployed(provider.clone()))
+                    .await
+                    .and_then(|reason| match reason {
+                        Some(reason) => Err(anyhow::anyhow!("{}", reason)),
+                        None => Ok("contracts deployed".to_string()),
+                    });
+                report.record(format!("protocol {}", protocol), result);
+            }
+        }
+        Err(_) => report.record(
+            "chain_id",
+            Err(anyhow::anyhow!(
+                "Skipped, along with the protocol checks, as an RPC URL is invalid"
+            )),
+        ),
+    }
+
+    // Errors quote the URL they failed on, whose path often carries an API key
+    for url in &args.rpc_urls {
+        if let Ok(transport) = FailoverHttp::new(std::slice::from_ref(url)) {
+            for check in &mut report.checks {
+                check.detail = transport.redact_urls(&check.detail);
+            }
+        }
+    }
+    report.ok = report.checks.iter().all(|check| check.ok);
+    report
+}
+
+/// Checks that `url` parses and answers `eth_chainId`.
+async fn check_rpc_url(url: &str) -> anyhow::Result<String> {
+    let provider = Provider::new(FailoverHttp::new(&[url.to_string()])?);
+    let chain_id = with_timeout(async {
+        provider
+            .get_chainid()
+            .await
+            .map_err(|e| anyhow::anyhow!("Unreachable: {}", e))
+    })
+    .await?;
+
+    Ok(format!("reachable, chain id {}", chain_id))
+}
+
+/// Names an RPC URL by its origin only, as the path often carries an API key.
+fn endpoint_label(url: &str) -> String {
+    FailoverHttp::new(&[url.to_string()])
+        .map(|transport| transport.active_endpoint())
+        .unwrap_or_else(|_| "(invalid)".to_string())
+}
+
+async fn with_timeout<T>(fut: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+    tokio::time::timeout(RPC_CHECK_TIMEOUT, fut)
+        .await
+        .map_err(|_| anyhow::anyhow!("Timed out after {:?}", RPC_CHECK_TIMEOUT))?
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn args(extra: &[&str]) -> Args {
+        let mut argv = vec!["server", "--bind", "127.0.0.1:0"];
+        argv.extend(extra);
+        Args::try_parse_from(argv).unwrap()
+    }
+
+    fn check<'a>(report: &'a Report, name: &str) -> &'a Check {
+        report
+            .checks
+            .iter()
+            .find(|check| check.name == name)
+            .unwrap_or_else(|| panic!("no {} check in {:?}", name, report))
+    }
+
+    #[tokio::test]
+    async fn a_reachable_rpc_with_deployed_contracts_passes() {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(
+                |axum::Json(call): axum::Json<serde_json::Value>| async move {
+                    let result = match call["method"].as_str().unwrap_or_default() {
+                        "eth_chainId" => serde_json::json!("0x279f"),
+                        "eth_getCode" => serde_json::json!("0x60806040"),
+                        _ => serde_json::Value::Null,
+                    };
+                    axum::Json(
+                        serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }),
+                    )
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let report = report(&args(&["--rpc-urls", &url])).await;
+
+        assert!(report.ok, "{:?}", report);
+        assert_eq!(
+            check(&report, &format!("rpc_url {}", url)).detail,
+            "reachable, chain id 10143"
+        );
+        assert_eq!(
+            check(&report, "chain_id").detail,
+            "matches the expected chain id"
+        );
+        for protocol in LstProtocol::ALL {
+            assert!(check(&report, &format!("protocol {}", protocol)).ok);
+        }
+    }
+
+    #[tokio::test]
+    async fn every_failing_check_is_reported_without_the_url_path() {
+        let report = report(&args(&[
+            "--rpc-urls",
+            "http://127.0.0.1:1/secret-key",
+            "--event-chunk-size",
+            "0",
+        ]))
+        .await;
+
+        assert!(!report.ok);
+        let failed = report
+            .checks
+            .iter()
+            .filter(|check| !check.ok)
+            .map(|check| check.name.as_str())
+            .collect::<Vec<_>>();
+        for name in ["rpc_url http://127.0.0.1:1", "event_scan", "chain_id"] {
+            assert!(failed.contains(&name), "{} passed: {:?}", name, report);
+        }
+        assert!(check(&report, "timeouts").ok);
+        let printed = serde_json::to_string(&report).unwrap();
+        assert!(!printed.contains("secret-key"), "{}", printed);
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use rmcp::{
+    Error as McpError,
+    model::{ClientJsonRpcMessage, JsonRpcError, JsonRpcVersion2_0, ServerJsonRpcMessage},
+    transport::sse_server::SseServerTransport,
+};
+
+use monad_mcp::common::error::McpServiceError;
+
+/// How long a refused client has to send its first request before it is dropped unanswered.
+const REFUSAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Refuses a connection over the cap of `max`: the client's first request, normally
+/// `initialize`, is answered with a `RATE_LIMITED` error saying why, then the transport is
+/// dropped, which ends the SSE stream.
+pub async fn refuse(mut transport: SseServerTransport, max: usize) {
+    tracing::warn!("Refusing connection: {} connections are already open", max);
+
+    let Ok(Some(ClientJsonRpcMessage::Request(request))) =
+        tokio::time::timeout(REFUSAL_TIMEOUT, transport.next()).await
+    else {
+        return;
+    };
+    let error: McpError = McpServiceError::RateLimited(format!(
+        "Server is at its limit of {} connections; try again later",
+        max
+    ))
+    .into();
+
+    if let Err(e) = transport
+        .send(ServerJsonRpcMessage::Error(JsonRpcError {
+            jsonrpc: JsonRpcVersion2_0,
+            id: request.id,
+            error,
+        }))
+        .await
+    {
+        tracing::debug!(error = %e, "Failed to send connection refusal");
+    }
+}
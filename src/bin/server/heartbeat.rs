@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use rmcp::{
+    Peer, RoleServer, ServiceError,
+    model::{PingRequest, ServerRequest},
+};
+use tokio::time::{Instant, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+
+/// Pings the client every `interval` so proxies and load balancers see traffic on an otherwise
+/// idle SSE stream and keep it open. The SSE transport has no way to send comment lines, so the
+/// heartbeat is an MCP `ping` request, which goes out as an SSE event like any other message.
+///
+/// The transport only notices a client that went away when writing to it, so a ping that can
+/// no longer be sent cancels `ct`, the connection's token, to shut the connection down.
+/// Stops once `ct` is cancelled.
+pub async fn run(peer: Peer<RoleServer>, interval: Duration, ct: CancellationToken) {
+    let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ct.cancelled() => return,
+            _ = ticker.tick() => {}
+        }
+
+        let ping = peer.send_request(ServerRequest::PingRequest(PingRequest {
+            method: Default::default(),
+        }));
+        // The ping only has to reach the wire; a client slow to answer is not an error
+        match tokio::time::timeout(interval, ping).await {
+            Ok(Ok(_)) => tracing::trace!("Heartbeat answered"),
+            Ok(Err(ServiceError::Transport(_) | ServiceError::Cancelled { .. })) => {
+                tracing::debug!("Heartbeat could not be sent; closing the connection");
+                ct.cancel();
+                return;
+            }
+            Ok(Err(e)) => tracing::debug!(error = %e, "Heartbeat failed"),
+            Err(_) => tracing::debug!("Heartbeat unanswered after {:?}", interval),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ethers::providers::Provider;
+    use rmcp::ServiceExt;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    use monad_mcp::common::{
+        lst::Lst,
+        rpc::{CallLogger, FailoverHttp},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn pings_an_idle_client_and_closes_once_it_is_gone() {
+        let rpc = FailoverHttp::new(&["http://127.0.0.1:1".to_string()]).unwrap();
+        let lst = Lst::new(Arc::new(CallLogger::new(Provider::new(rpc), false)));
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let (read, mut write) = tokio::io::split(client);
+        let mut lines = BufReader::new(read).lines();
+        let serving = tokio::spawn(lst.serve(server));
+        for message in [
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": { "name": "test", "version": "0" },
+                },
+            }),
+            serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        ] {
+            write
+                .write_all(format!("{}\n", message).as_bytes())
+                .await
+                .unwrap();
+        }
+        lines.next_line().await.unwrap().unwrap();
+        let server = serving.await.unwrap().unwrap();
+
+        let ct = CancellationToken::new();
+        let heartbeat = tokio::spawn(run(
+            server.peer().clone(),
+            Duration::from_millis(20),
+            ct.clone(),
+        ));
+        for _ in 0..2 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            let ping: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(ping["method"], "ping", "{ping}");
+            let pong = serde_json::json!({ "jsonrpc": "2.0", "id": ping["id"], "result": {} });
+            write
+                .write_all(format!("{}\n", pong).as_bytes())
+                .await
+                .unwrap();
+        }
+        assert!(!ct.is_cancelled());
+
+        // The client goes away without a word
+        drop((lines, write));
+        tokio::time::timeout(Duration::from_secs(5), heartbeat)
+            .await
+            .expect("the heartbeat kept going after the client left")
+            .unwrap();
+        assert!(ct.is_cancelled());
+    }
+}
@@ -1,39 +1,729 @@
-use std::sync::Arc;
-
-use ethers::providers::{Http, Provider};
-use rmcp::transport::sse_server::SseServer;
-use tracing_subscriber::{
-    layer::SubscriberExt,
-    util::SubscriberInitExt,
-    {self},
+mod check;
+mod connections;
+mod heartbeat;
+mod readiness;
+mod telemetry;
+mod tls;
+
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use clap::Parser;
+use ethers::{providers::Provider, types::Address};
+use futures::TryFutureExt;
+use rmcp::{
+    ServiceExt,
+    transport::sse_server::{SseServer, SseServerConfig},
 };
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+use monad_mcp::common::{
+    admin::{self, AdminToken},
+    events::EventScanConfig,
+    health::{DeprecatedProtocol, Deprecations},
+    lst::{Lst, LstProtocol, NativeCurrency, ToolTimeout, ToolTimeouts},
+    price::{PriceSource, UsdPricing},
+    registry::ProtocolOverrides,
+    rpc::{CallLogger, FailoverHttp},
+    tx::{AllowanceResets, GasMultiplier},
+};
+
+use crate::telemetry::LogFormat;
+
+const CHAIN_ID_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Parser)]
+#[command(version, about = "MCP server for liquid staking on Monad")]
+struct Args {
+    /// Comma-separated RPC URLs, tried in order when an endpoint fails
+    #[arg(
+        long,
+        env = "RPC_URLS",
+        value_delimiter = ',',
+        default_value = "https://testnet-rpc.monad.xyz"
+    )]
+    rpc_urls: Vec<String>,
+
+    /// Expected chain id of the RPC endpoint [default: Monad testnet]
+    #[arg(long, env = "CHAIN_ID")]
+    chain_id: Option<u64>,
+
+    /// Seconds startup waits for the RPC to answer, retrying, before failing; the server only
+    /// listens once it has
+    #[arg(
+        long,
+        env = "RPC_READY_TIMEOUT_SECS",
+        default_value_t = 30,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    rpc_ready_timeout_secs: u64,
+
+    /// Start serving without waiting for the RPC; the chain id and protocol checks run once
+    /// it answers, and writes are refused until then
+    #[arg(long, env = "LAZY_RPC")]
+    lazy_rpc: bool,
+
+    /// JSON file of contract addresses overriding the built-in protocols' deployments, keyed by
+    /// protocol name
+    #[arg(long, env = "MONAD_PROTOCOLS_FILE")]
+    protocols_file: Option<PathBuf>,
+
+    /// Protocol used when a tool omits `protocol` or a resource URI names `default`
+    #[arg(long, env = "DEFAULT_PROTOCOL")]
+    default_protocol: Option<LstProtocol>,
+
+    /// Protocols to mark deprecated, as `NAME` or `NAME:NOTICE`, separated by `;`
+    #[arg(long, env = "DEPRECATED_PROTOCOLS", value_delimiter = ';')]
+    deprecated_protocols: Vec<DeprecatedProtocol>,
+
+    /// Reject writes against deprecated protocols instead of only warning
+    #[arg(long, env = "BLOCK_DEPRECATED")]
+    block_deprecated: bool,
+
+    /// Chainlink-style USD price oracle of the native currency, for TVL in USD
+    #[arg(long, env = "PRICE_ORACLE", conflicts_with = "price_feed_url")]
+    price_oracle: Option<Address>,
+
+    /// URL of a JSON price feed giving the native currency's USD price, for TVL in USD
+    #[arg(long, env = "PRICE_FEED_URL")]
+    price_feed_url: Option<String>,
+
+    /// JSON pointer to the price in the price feed's response
+    #[arg(long, env = "PRICE_FEED_POINTER", default_value = "/price")]
+    price_feed_pointer: String,
+
+    /// How long after its last update a USD price is still served, in seconds [default: 3600]
+    #[arg(long, env = "PRICE_MAX_AGE_SECS")]
+    price_max_age_secs: Option<u64>,
+
+    /// Block range per log query made by event-scanning resources [default: 100]
+    #[arg(long, env = "EVENT_CHUNK_SIZE")]
+    event_chunk_size: Option<u64>,
+
+    /// How far back event-scanning resources look, in blocks [default: 10000]
+    #[arg(long, env = "EVENT_MAX_LOOKBACK_BLOCKS")]
+    event_max_lookback_blocks: Option<u64>,
+
+    /// Seconds protocol metadata such as decimals is cached for [default: 86400]
+    #[arg(long, env = "METADATA_TTL_SECS")]
+    metadata_ttl_secs: Option<u64>,
+
+    /// Most activities an address history page may return [default: 1000]
+    #[arg(long, env = "HISTORY_MAX_LIMIT", value_parser = clap::value_parser!(u64).range(1..))]
+    history_max_limit: Option<u64>,
+
+    /// Blocks the oracle may trail the chain head before it is reported stale [default: 1000]
+    #[arg(long, env = "ORACLE_STALE_BLOCKS")]
+    oracle_stale_blocks: Option<u64>,
+
+    /// Blocks a redeem request must have been processed for before it is reported claimable
+    /// [default: 0]
+    #[arg(long, env = "CLAIM_CONFIRMATIONS")]
+    claim_confirmations: Option<u64>,
+
+    /// Factor applied to estimated gas for the gas limit of write tools, at most 3. Monad
+    /// charges for the whole gas limit, so headroom is paid on every write [default: 1.05]
+    #[arg(long, env = "GAS_LIMIT_MULTIPLIER")]
+    gas_limit_multiplier: Option<f64>,
+
+    /// Symbol of the network's native currency, used in responses [default: MON]
+    #[arg(long, env = "NATIVE_SYMBOL")]
+    native_symbol: Option<String>,
+
+    /// Decimals of the network's native currency, which native amounts are parsed and
+    /// formatted at, at most 77 [default: 18]
+    #[arg(long, env = "NATIVE_DECIMALS")]
+    native_decimals: Option<u8>,
+
+    /// Decimals amounts are rounded to, half up, in tool result messages and warnings; JSON
+    /// amount fields keep every digit [default: all]
+    #[arg(long, env = "DISPLAY_DECIMALS")]
+    display_decimals: Option<u8>,
+
+    /// RPC reads in flight at once across all connections, a batch counting as one; transaction
+    /// sends are not capped [default: 8]
+    #[arg(long, env = "MAX_CONCURRENT_READS", value_parser = clap::value_parser!(u64).range(1..))]
+    max_concurrent_reads: Option<u64>,
+
+    /// Client connections served at once; further ones are refused until one closes
+    /// [default: 256]
+    #[arg(long, env = "MAX_CONNECTIONS", value_parser = clap::value_parser!(u64).range(1..))]
+    max_connections: Option<u64>,
+
+    /// Seconds a read tool or resource may run before failing with TIMEOUT [default: 30]
+    #[arg(long, env = "READ_TIMEOUT_SECS")]
+    read_timeout_secs: Option<u64>,
+
+    /// Seconds a write tool may run, including waiting for confirmation [default: 300]
+    #[arg(long, env = "WRITE_TIMEOUT_SECS")]
+    write_timeout_secs: Option<u64>,
+
+    /// Comma-separated per-tool timeouts overriding the above, as `NAME=SECS`
+    #[arg(long, env = "TOOL_TIMEOUTS", value_delimiter = ',')]
+    tool_timeouts: Vec<ToolTimeout>,
+
+    /// Seconds between background refreshes of the TVL/APR snapshot
+    #[arg(
+        long,
+        env = "SNAPSHOT_INTERVAL_SECS",
+        default_value_t = 30,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    snapshot_interval_secs: u64,
+
+    /// Seconds between heartbeat pings on each SSE connection, which keep idle connections
+    /// open through proxies; 0 disables them
+    #[arg(long, env = "HEARTBEAT_INTERVAL_SECS", default_value_t = 15)]
+    heartbeat_interval_secs: u64,
+
+    /// Secret that enables admin-only tools such as `diagnostics`, sent by clients as an
+    /// `Authorization: Bearer` header; they are disabled when unset
+    #[arg(long, env = "ADMIN_TOKEN", hide_env_values = true)]
+    admin_token: Option<String>,
+
+    /// Address whose nonces `diagnostics` checks when the call names none
+    #[arg(long, env = "DIAGNOSTICS_ADDRESS")]
+    diagnostics_address: Option<Address>,
+
+    /// Comma-separated ERC-20 tokens whose non-zero allowance is reset to zero before a new one
+    /// is approved, for tokens that revert otherwise
+    #[arg(long, env = "RESET_ALLOWANCE_TOKENS", value_delimiter = ',')]
+    reset_allowance_tokens: Vec<Address>,
+
+    /// Address the SSE server listens on
+    #[arg(long, env = "BIND_ADDRESS", default_value = "0.0.0.0:8989")]
+    bind: SocketAddr,
+
+    /// PEM certificate chain; serves HTTPS instead of plaintext when set with --tls-key
+    #[arg(long, env = "TLS_CERT", requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key matching --tls-cert
+    #[arg(long, env = "TLS_KEY", requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Log line format
+    #[arg(long, env = "LOG_FORMAT", value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+
+    /// Log every RPC call's method, params and duration at debug level
+    #[arg(long, env = "LOG_RPC_CALLS")]
+    log_rpc_calls: bool,
+
+    /// Add `_meta.rpc_calls`, the number of RPC calls made to serve it, to every tool result and
+    /// JSON resource
+    #[arg(long, env = "REPORT_RPC_CALLS")]
+    report_rpc_calls: bool,
+
+    /// OTLP/gRPC endpoint to export traces to, e.g. http://localhost:4317
+    #[arg(long, env = "OTEL_ENDPOINT")]
+    otel_endpoint: Option<String>,
+
+    /// Validate the configuration, RPC endpoints and protocol contracts, print a JSON report
+    /// and exit, non-zero if any check failed, without starting the server
+    #[arg(long)]
+    check_config: bool,
+}
+
+/// Native currency from the arguments, falling back to MON for what isn't set.
+fn native_currency(args: &Args) -> anyhow::Result<NativeCurrency> {
+    let default_native = NativeCurrency::default();
+    let native = NativeCurrency::new(
+        args.native_symbol
+            .clone()
+            .unwrap_or_else(|| default_native.symbol().to_string()),
+        args.native_decimals.unwrap_or(default_native.decimals()),
+    )?;
 
-use monad_mcp::common::lst::Lst;
+    Ok(match args.display_decimals {
+        Some(decimals) => native.with_display_decimals(decimals),
+        None => native,
+    })
+}
+
+/// USD pricing from the price oracle or feed arguments, if either is set.
+fn usd_pricing(args: &Args) -> anyhow::Result<Option<UsdPricing>> {
+    let source = match (args.price_oracle, &args.price_feed_url) {
+        (Some(oracle), _) => PriceSource::Oracle(oracle),
+        (None, Some(url)) => PriceSource::feed(url, args.price_feed_pointer.clone())?,
+        (None, None) => return Ok(None),
+    };
+
+    let mut pricing = UsdPricing::new(source);
+    if let Some(secs) = args.price_max_age_secs {
+        pricing = pricing.with_max_age(Duration::from_secs(secs));
+    }
+
+    Ok(Some(pricing))
+}
+
+/// Tool timeouts from the arguments, falling back to the defaults.
+fn timeouts(args: &Args) -> anyhow::Result<ToolTimeouts> {
+    let default_timeouts = ToolTimeouts::default();
+    ToolTimeouts::new(
+        args.read_timeout_secs
+            .map_or(default_timeouts.read(), Duration::from_secs),
+        args.write_timeout_secs
+            .map_or(default_timeouts.write(), Duration::from_secs),
+        args.tool_timeouts.clone(),
+    )
+}
 
-const BIND_ADDRESS: &str = "0.0.0.0:8989";
+/// Event scan settings from the arguments, falling back to the defaults.
+fn event_scan(args: &Args) -> anyhow::Result<EventScanConfig> {
+    let default_event_scan = EventScanConfig::default();
+    EventScanConfig::new(
+        args.event_chunk_size
+            .unwrap_or(default_event_scan.chunk_size()),
+        args.event_max_lookback_blocks
+            .unwrap_or(default_event_scan.max_lookback_blocks()),
+    )
+}
+
+/// Merges the protocol addresses of the file at `path` over the built-in deployments.
+fn install_protocols_file(path: &Path) -> anyhow::Result<()> {
+    let overrides = ProtocolOverrides::load(path)?;
+    for protocol in overrides.protocols() {
+        tracing::info!("Using addresses from {} for {}", path.display(), protocol);
+    }
+    overrides.install()
+}
+
+/// Serves the SSE server's routes on `listener` until the server is cancelled. With an
+/// `admin_token`, the routes check it on each request's Authorization header.
+fn serve_sse(listener: TcpListener, admin_token: Option<AdminToken>) -> std::io::Result<SseServer> {
+    let (sse_server, router) = SseServer::new(SseServerConfig {
+        bind: listener.local_addr()?,
+        sse_path: "/sse".to_string(),
+        post_path: "/message".to_string(),
+        ct: CancellationToken::new(),
+        // Connections are kept open by the heartbeat instead
+        sse_keep_alive: None,
+    });
+    let router = match admin_token {
+        Some(token) => router.layer(axum::middleware::from_fn_with_state(
+            token,
+            admin::authenticate,
+        )),
+        None => router,
+    };
+
+    let ct = sse_server.config.ct.clone();
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router)
+            .with_graceful_shutdown(async move { ct.cancelled().await })
+            .await
+        {
+            tracing::error!("SSE server stopped: {}", e);
+        }
+    });
+    Ok(sse_server)
+}
+
+/// Serves every client that connects to `sse_server` with its own connection of `lst_service`,
+/// refusing those beyond the connection limit, until the server is cancelled.
+async fn serve_connections(
+    mut sse_server: SseServer,
+    lst_service: Lst,
+    heartbeat_interval: Option<Duration>,
+) {
+    let ct = sse_server.config.ct.clone();
+    while let Some(transport) = sse_server.next_transport().await {
+        let connection_limit = lst_service.connection_limit();
+        let Some(slot) = connection_limit.try_acquire() else {
+            tokio::spawn(connections::refuse(transport, connection_limit.max()));
+            continue;
+        };
+        let connection_ct = CancellationToken::new();
+        let service = lst_service.for_connection(connection_ct.clone());
+        let ct = ct.child_token();
+
+        tokio::spawn(async move {
+            // Abort in-flight provider calls and free the slot once the client goes away
+            let _guard = connection_ct.drop_guard();
+            let _slot = slot;
+            let server = service.serve_with_ct(transport, ct.clone()).await?;
+            if let Some(interval) = heartbeat_interval {
+                tokio::spawn(heartbeat::run(server.peer().clone(), interval, ct));
+            }
+            server.waiting().await?;
+            tokio::io::Result::Ok(())
+        });
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "debug".to_string().into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let args = Args::parse();
+    // Runs before telemetry so stdout carries only the report
+    if args.check_config {
+        if !check::run(&args).await {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let tracer_provider = telemetry::init(args.log_format, args.otel_endpoint.as_deref())?;
 
-    let provider = Provider::<Http>::try_from("https://testnet-rpc.monad.xyz")
-        .expect("Failed to create provider");
+    if let Some(path) = &args.protocols_file {
+        install_protocols_file(path)?;
+    }
+
+    // A tool schema advertising a pattern that doesn't compile stops startup too
+    Lst::argument_patterns()?;
+
+    // Load TLS material up front so a bad certificate or key stops startup
+    let tls_acceptor = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => Some(tls::acceptor(cert, key)?),
+        _ => None,
+    };
+
+    let mut transport = FailoverHttp::new(&args.rpc_urls)?;
+    if let Some(limit) = args.max_concurrent_reads {
+        transport = transport.with_max_concurrent_reads(limit as usize);
+    }
+    let provider = CallLogger::new(Provider::new(transport), args.log_rpc_calls);
     let provider = Arc::new(provider);
 
-    let ct = SseServer::serve(BIND_ADDRESS.parse()?)
-        .await?
-        .with_service({
-            let lst_service = Lst::new(provider);
-            move || lst_service.clone()
+    let mut lst_service = Lst::new(provider.clone())
+        .with_event_scan(event_scan(&args)?)
+        .with_timeouts(timeouts(&args)?)
+        .with_rpc_call_reporting(args.report_rpc_calls);
+    if let Some(chain_id) = args.chain_id {
+        lst_service = lst_service.with_expected_chain_id(chain_id);
+    }
+    if let Some(protocol) = args.default_protocol {
+        lst_service = lst_service.with_default_protocol(protocol);
+    }
+    if let Some(secs) = args.metadata_ttl_secs {
+        lst_service = lst_service.with_metadata_ttl(Duration::from_secs(secs));
+    }
+    if let Some(limit) = args.history_max_limit {
+        lst_service = lst_service.with_history_max_limit(limit as usize);
+    }
+    if let Some(blocks) = args.oracle_stale_blocks {
+        lst_service = lst_service.with_oracle_stale_blocks(blocks);
+    }
+    if let Some(blocks) = args.claim_confirmations {
+        lst_service = lst_service.with_claim_confirmations(blocks);
+    }
+    if let Some(multiplier) = args.gas_limit_multiplier {
+        lst_service = lst_service.with_gas_multiplier(GasMultiplier::new(multiplier)?);
+    }
+    if args.native_symbol.is_some()
+        || args.native_decimals.is_some()
+        || args.display_decimals.is_some()
+    {
+        lst_service = lst_service.with_native_currency(native_currency(&args)?);
+    }
+    if let Some(pricing) = usd_pricing(&args)? {
+        lst_service = lst_service.with_usd_pricing(pricing);
+    }
+    if !args.deprecated_protocols.is_empty() {
+        lst_service = lst_service.with_deprecations(Deprecations::new(
+            args.deprecated_protocols.clone(),
+            args.block_deprecated,
+        ));
+    }
+    if let Some(limit) = args.max_connections {
+        lst_service = lst_service.with_max_connections(limit as usize);
+    }
+    let admin_token = args.admin_token.clone().map(AdminToken::new).transpose()?;
+    if let Some(token) = &admin_token {
+        lst_service = lst_service.with_admin_token(token.clone());
+    }
+    if let Some(address) = args.diagnostics_address {
+        lst_service = lst_service.with_diagnostics_address(address);
+    }
+    if !args.reset_allowance_tokens.is_empty() {
+        lst_service = lst_service.with_allowance_resets(AllowanceResets::new(
+            args.reset_allowance_tokens.iter().copied(),
+        ));
+    }
+
+    // Refuse to serve if the RPC points at an unexpected chain
+    if args.lazy_rpc {
+        tokio::spawn({
+            let lst_service = lst_service.clone();
+            async move {
+                readiness::wait_for_rpc(&provider, None).await?;
+                lst_service.verify_chain_id().await?;
+                lst_service.verify_protocols().await
+            }
+            .inspect_err(|e| tracing::error!("Startup checks failed: {:#}", e))
         });
+    } else {
+        let deadline = Duration::from_secs(args.rpc_ready_timeout_secs);
+        readiness::wait_for_rpc(&provider, Some(deadline)).await?;
+        lst_service.verify_chain_id().await?;
+        lst_service.verify_protocols().await?;
+    }
+    tokio::spawn(lst_service.clone().watch_chain_id(CHAIN_ID_CHECK_INTERVAL));
+    tokio::spawn(
+        lst_service
+            .clone()
+            .watch_snapshot(Duration::from_secs(args.snapshot_interval_secs)),
+    );
+
+    let listener = TcpListener::bind(args.bind).await?;
+    let sse_server = match tls_acceptor {
+        Some(acceptor) => {
+            // The SSE server only speaks plaintext, so it listens on loopback behind the TLS
+            // terminator
+            let sse_server = serve_sse(TcpListener::bind("127.0.0.1:0").await?, admin_token)?;
+            let ct = sse_server.config.ct.clone();
+            tokio::spawn(tls::serve(listener, acceptor, sse_server.config.bind, ct));
+            tracing::info!("Listening on https://{}", args.bind);
+            sse_server
+        }
+        None => {
+            let sse_server = serve_sse(listener, admin_token)?;
+            tracing::info!("Listening on http://{}", args.bind);
+            sse_server
+        }
+    };
+    let ct = sse_server.config.ct.clone();
+    let heartbeat_interval = Some(Duration::from_secs(args.heartbeat_interval_secs))
+        .filter(|interval| !interval.is_zero());
+
+    tokio::spawn(serve_connections(
+        sse_server,
+        lst_service,
+        heartbeat_interval,
+    ));
 
     tokio::signal::ctrl_c().await?;
     ct.cancel();
+
+    if let Some(tracer_provider) = tracer_provider {
+        tracer_provider.shutdown()?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ethers::types::U256;
+    use rmcp::{
+        model::{ReadResourceRequestParam, ResourceContents},
+        transport::SseTransport,
+    };
+    use rustls::{
+        ClientConfig, RootCertStore,
+        crypto::ring,
+        pki_types::{CertificateDer, ServerName, pem::PemObject},
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::TlsConnector;
+
+    use super::*;
+    use monad_mcp::common::rpc::RpcProvider;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/bin/server/fixtures");
+
+    /// Provider for a local JSON-RPC endpoint that stands in for the chain: every LST holder
+    /// has 5 tokens of 18 decimals, vaults take native MON, and other calls return zero.
+    async fn fake_provider() -> Arc<RpcProvider> {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(
+                |axum::Json(call): axum::Json<serde_json::Value>| async move {
+                    let word = |value: U256| {
+                        let mut word = [0u8; 32];
+                        value.to_big_endian(&mut word);
+                        format!("0x{}", ethers::utils::hex::encode(word))
+                    };
+                    let result = match call["method"].as_str().unwrap_or_default() {
+                        "eth_chainId" => serde_json::json!("0x279f"),
+                        "eth_blockNumber" => serde_json::json!("0x64"),
+                        "eth_call" => {
+                            let data = call["params"][0]["data"]
+                                .as_str()
+                                .or(call["params"][0]["input"].as_str())
+                                .unwrap_or_default();
+                            serde_json::json!(match &data[..10.min(data.len())] {
+                                // balanceOf(address)
+                                "0x70a08231" => word(U256::exp10(18) * 5),
+                                // decimals()
+                                "0x313ce567" => word(U256::from(18)),
+                                _ => word(U256::zero()),
+                            })
+                        }
+                        _ => serde_json::Value::Null,
+                    };
+                    axum::Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": result,
+                    }))
+                },
+            ),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Arc::new(CallLogger::new(
+            Provider::new(FailoverHttp::new(&[url]).unwrap()),
+            false,
+        ))
+    }
+
+    #[tokio::test]
+    async fn client_lists_and_reads_through_the_sse_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/sse", listener.local_addr().unwrap());
+        let sse_server = serve_sse(listener, None).unwrap();
+        let ct = sse_server.config.ct.clone();
+        tokio::spawn(serve_connections(
+            sse_server,
+            Lst::new(fake_provider().await),
+            None,
+        ));
+
+        let client = ().serve(SseTransport::start(&url).await.unwrap()).await.unwrap();
+        let tools = client.list_all_tools().await.unwrap();
+        assert!(tools.iter().any(|tool| tool.name == "stake"));
+        let resources = client.list_all_resources().await.unwrap();
+        assert!(
+            resources
+                .iter()
+                .any(|resource| resource.uri == "evm://networks")
+        );
+
+        let uri = "evm://monadTestnet/address/0x00000000000000000000000000000000000000aa/lsts/aprMON/balance";
+        let balance = client
+            .read_resource(ReadResourceRequestParam {
+                uri: uri.to_string(),
+            })
+            .await
+            .unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &balance.contents[0] else {
+            panic!("balance is not text");
+        };
+        let balance: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(balance["balance"], "5.000000000000000000");
+        assert_eq!(balance["protocol"], "aprMON");
+
+        client.cancel().await.unwrap();
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn a_protocol_from_the_protocols_file_is_served_at_its_address() {
+        install_protocols_file(&PathBuf::from(FIXTURES).join("protocols.json")).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/sse", listener.local_addr().unwrap());
+        let sse_server = serve_sse(listener, None).unwrap();
+        let ct = sse_server.config.ct.clone();
+        tokio::spawn(serve_connections(
+            sse_server,
+            Lst::new(fake_provider().await),
+            None,
+        ));
+        let client = ().serve(SseTransport::start(&url).await.unwrap()).await.unwrap();
+        let read = async |uri: &str| {
+            let result = client
+                .read_resource(ReadResourceRequestParam {
+                    uri: uri.to_string(),
+                })
+                .await
+                .unwrap();
+            let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+                panic!("{uri} is not text");
+            };
+            serde_json::from_str::<serde_json::Value>(text).unwrap()
+        };
+
+        let abi = read("evm://monadTestnet/lsts/shMON/abi").await;
+        assert_eq!(
+            abi["contracts"][0]["address"],
+            "0x0000000000000000000000000000000000005555"
+        );
+        let balance = read(
+            "evm://monadTestnet/address/0x00000000000000000000000000000000000000aa/lsts/shMON/balance",
+        )
+        .await;
+        assert_eq!(balance["balance"], "5.000000000000000000");
+
+        client.cancel().await.unwrap();
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn connections_past_the_limit_are_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/sse", listener.local_addr().unwrap());
+        let sse_server = serve_sse(listener, None).unwrap();
+        let ct = sse_server.config.ct.clone();
+        let lst = Lst::new(fake_provider().await).with_max_connections(1);
+        tokio::spawn(serve_connections(sse_server, lst.clone(), None));
+
+        let first = ().serve(SseTransport::start(&url).await.unwrap()).await.unwrap();
+        assert_eq!(lst.connection_limit().active(), 1);
+
+        let Err(refused) = ().serve(SseTransport::start(&url).await.unwrap()).await else {
+            panic!("a second connection was served");
+        };
+        let error = format!("{:?}", refused);
+        assert!(error.contains("limit of 1 connections"), "{error}");
+        // The first client is still served
+        assert!(!first.list_all_tools().await.unwrap().is_empty());
+
+        first.cancel().await.unwrap();
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn serves_sse_over_tls_with_a_self_signed_certificate() {
+        let cert = PathBuf::from(FIXTURES).join("localhost.pem");
+        let acceptor =
+            tls::acceptor(&cert, &PathBuf::from(FIXTURES).join("localhost.key")).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let loopback = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let sse_server = serve_sse(loopback, None).unwrap();
+        let ct = sse_server.config.ct.clone();
+        tokio::spawn(tls::serve(
+            listener,
+            acceptor,
+            sse_server.config.bind,
+            ct.clone(),
+        ));
+
+        let mut roots = RootCertStore::empty();
+        roots
+            .add(CertificateDer::from_pem_file(&cert).unwrap())
+            .unwrap();
+        let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        let mut tls = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+
+        tls.write_all(b"GET /sse HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = [0; 64];
+        let read = tls.read(&mut response).await.unwrap();
+        assert!(
+            response[..read].starts_with(b"HTTP/1.1 200"),
+            "{}",
+            String::from_utf8_lossy(&response[..read])
+        );
+        ct.cancel();
+    }
+}
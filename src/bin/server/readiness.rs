@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use ethers::providers::Middleware;
+use tokio::time::Instant;
+
+use monad_mcp::common::rpc::RpcProvider;
+
+/// How long one readiness probe waits for the RPC to answer.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause between failed readiness probes.
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Polls the RPC with `eth_chainId` until it answers, returning the chain id. Fails once
+/// `deadline` has passed without an answer; with no deadline, polls until the RPC is up.
+pub async fn wait_for_rpc(
+    provider: &RpcProvider,
+    deadline: Option<Duration>,
+) -> anyhow::Result<u64> {
+    let started = Instant::now();
+    let mut attempts = 0u32;
+
+    loop {
+        attempts += 1;
+        let probe_timeout = deadline.map_or(PROBE_TIMEOUT, |deadline| {
+            PROBE_TIMEOUT.min(deadline.saturating_sub(started.elapsed()))
+        });
+        let error = match tokio::time::timeout(probe_timeout, provider.get_chainid()).await {
+            Ok(Ok(chain_id)) => {
+                if attempts > 1 {
+                    tracing::info!("RPC ready after {} attempts", attempts);
+                }
+                return Ok(chain_id.as_u64());
+            }
+            // The path of the URL is left out, as it often carries an API key
+            Ok(Err(e)) => provider.provider().as_ref().redact_urls(&e.to_string()),
+            Err(_) => format!("no answer within {:?}", probe_timeout),
+        };
+
+        let remaining = deadline.map(|deadline| deadline.saturating_sub(started.elapsed()));
+        if remaining.is_some_and(|remaining| remaining.is_zero()) {
+            bail!(
+                "RPC endpoint {} not ready after {:?} ({} attempts, last: {})",
+                provider.provider().as_ref().active_endpoint(),
+                deadline.unwrap_or_default(),
+                attempts,
+                error
+            );
+        }
+        tracing::warn!(
+            "RPC endpoint {} not ready ({}); retrying in {:?}",
+            provider.provider().as_ref().active_endpoint(),
+            error,
+            RETRY_INTERVAL
+        );
+        tokio::time::sleep(
+            remaining.map_or(RETRY_INTERVAL, |remaining| RETRY_INTERVAL.min(remaining)),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use ethers::providers::Provider;
+
+    use monad_mcp::common::rpc::{CallLogger, FailoverHttp};
+
+    use super::*;
+
+    fn provider(url: String) -> RpcProvider {
+        CallLogger::new(Provider::new(FailoverHttp::new(&[url]).unwrap()), false)
+    }
+
+    #[tokio::test]
+    async fn waits_until_the_rpc_answers() {
+        // The endpoint is still starting up for the first probe
+        let probes = Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post({
+                let probes = probes.clone();
+                move |axum::Json(call): axum::Json<serde_json::Value>| async move {
+                    if probes.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Err(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+                    }
+                    Ok(axum::Json(
+                        serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": "0x279f" }),
+                    ))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let chain_id = wait_for_rpc(&provider(url), Some(Duration::from_secs(10)))
+            .await
+            .unwrap();
+
+        assert_eq!(chain_id, 10143);
+        assert_eq!(probes.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_at_the_deadline_without_leaking_the_url_path() {
+        let provider = provider("http://127.0.0.1:1/secret-key".to_string());
+
+        let started = Instant::now();
+        let error = wait_for_rpc(&provider, Some(Duration::from_secs(1)))
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(
+            error.starts_with("RPC endpoint http://127.0.0.1:1 not ready after 1s"),
+            "{error}"
+        );
+        assert!(!error.contains("secret-key"), "{error}");
+    }
+}
@@ -0,0 +1,201 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    Resource,
+    trace::{SdkTracer, SdkTracerProvider},
+};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::{
+    Layer, fmt::MakeWriter, layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt,
+};
+
+/// Format of the log lines written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    Pretty,
+    /// One JSON object per line, for log aggregators
+    Json,
+}
+
+/// Installs the global tracing subscriber, filtered by `RUST_LOG`. When `otel_endpoint` is set,
+/// spans are also exported over OTLP/gRPC; the returned provider must be shut down on exit to
+/// flush pending spans.
+pub fn init(
+    log_format: LogFormat,
+    otel_endpoint: Option<&str>,
+) -> anyhow::Result<Option<SdkTracerProvider>> {
+    let tracer_provider = otel_endpoint.map(tracer_provider).transpose()?;
+    let otel_layer = tracer_provider.as_ref().map(otel_layer);
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "debug".to_string().into()),
+        )
+        .with(log_layer(log_format, std::io::stdout))
+        .with(otel_layer)
+        .init();
+
+    Ok(tracer_provider)
+}
+
+/// Layer writing log lines in `format` to `writer`.
+fn log_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer);
+    match format {
+        LogFormat::Pretty => layer.boxed(),
+        LogFormat::Json => layer.json().boxed(),
+    }
+}
+
+/// Layer turning `tracing` spans, and the events within them, into spans of `provider`.
+fn otel_layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, SdkTracer>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer("monad-mcp"))
+}
+
+fn tracer_provider(endpoint: &str) -> anyhow::Result<SdkTracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name("monad-mcp").build())
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use ethers::providers::Provider;
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+    use rmcp::{ServiceExt, model::CallToolRequestParam};
+
+    use monad_mcp::common::{
+        lst::Lst,
+        rpc::{CallLogger, FailoverHttp},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn tool_calls_are_exported_as_spans_with_their_rpc_calls() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        // The test runs on one thread, so the server's tasks all see this subscriber
+        let _subscriber = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(otel_layer(&provider)),
+        );
+
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(
+                |axum::Json(call): axum::Json<serde_json::Value>| async move {
+                    axum::Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": format!("0x{:064x}", 7),
+                    }))
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let rpc = FailoverHttp::new(&[url]).unwrap();
+        let lst = Lst::new(Arc::new(CallLogger::new(Provider::new(rpc), true)));
+
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(lst.serve(server));
+        let client = ().serve(client).await.unwrap();
+        let arguments = serde_json::json!({
+            "protocol": "aprMON",
+            "signature": "function totalSupply() view returns (uint256)",
+        });
+        client
+            .call_tool(CallToolRequestParam {
+                name: "raw_view".into(),
+                arguments: arguments.as_object().cloned(),
+            })
+            .await
+            .unwrap();
+        client.cancel().await.unwrap();
+        provider.force_flush().unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let span = spans
+            .iter()
+            .find(|span| span.name == "tool_call")
+            .expect("no tool_call span was exported");
+        assert!(
+            span.attributes
+                .iter()
+                .any(|kv| kv.key.as_str() == "tool" && kv.value == Value::from("raw_view")),
+            "{:?}",
+            span.attributes
+        );
+        // The RPC call the tool made is recorded within its span
+        assert!(
+            span.events.iter().any(|event| event.name == "RPC call"),
+            "{:?}",
+            span.events
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_logs_are_one_object_per_line_under_the_filter() {
+        let logs = Captured::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_subscriber::EnvFilter::new("info"))
+            .with(log_layer(LogFormat::Json, {
+                let logs = logs.clone();
+                move || logs.clone()
+            }));
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("tool_call", tool = "stake").entered();
+            tracing::info!(protocol = "aprMON", "Staking");
+            tracing::debug!("Filtered out");
+            tracing::warn!("Slow RPC");
+        });
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let lines = logs
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2, "{logs}");
+        assert!(lines.iter().all(serde_json::Value::is_object));
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["fields"]["message"], "Staking");
+        assert_eq!(lines[0]["fields"]["protocol"], "aprMON");
+        assert_eq!(lines[0]["span"]["tool"], "stake");
+        assert_eq!(lines[1]["level"], "WARN");
+    }
+}
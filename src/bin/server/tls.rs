@@ -0,0 +1,112 @@
+use std::{net::SocketAddr, path::Path, sync::Arc};
+
+use anyhow::Context;
+use rustls::{
+    ServerConfig,
+    crypto::ring,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
+use tokio_util::sync::CancellationToken;
+
+/// Loads a PEM certificate chain and private key, failing if either is unreadable or the key
+/// does not belong to the certificate.
+pub fn acceptor(cert_path: &Path, key_path: &Path) -> anyhow::Result<TlsAcceptor> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read TLS certificate '{}'", cert_path.display()))?;
+    anyhow::ensure!(
+        !certs.is_empty(),
+        "No certificate found in '{}'",
+        cert_path.display()
+    );
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .with_context(|| format!("Failed to read TLS key '{}'", key_path.display()))?;
+
+    let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .context("Failed to select TLS protocol versions")?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("TLS certificate and key do not match")?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Terminates TLS for connections on `listener` and forwards the decrypted stream to the
+/// plaintext SSE server at `upstream`, until `ct` is cancelled.
+pub async fn serve(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    upstream: SocketAddr,
+    ct: CancellationToken,
+) {
+    loop {
+        let (stream, peer) = tokio::select! {
+            _ = ct.cancelled() => return,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!("Failed to accept TLS connection: {}", e);
+                    continue;
+                }
+            },
+        };
+
+        let acceptor = acceptor.clone();
+        tokio::spawn(async move {
+            if let Err(e) = forward(stream, acceptor, upstream).await {
+                tracing::debug!("TLS connection from {} closed: {:#}", peer, e);
+            }
+        });
+    }
+}
+
+async fn forward(
+    stream: TcpStream,
+    acceptor: TlsAcceptor,
+    upstream: SocketAddr,
+) -> anyhow::Result<()> {
+    let mut tls = acceptor
+        .accept(stream)
+        .await
+        .context("TLS handshake failed")?;
+    let mut plain = TcpStream::connect(upstream)
+        .await
+        .context("Failed to reach SSE server")?;
+    tokio::io::copy_bidirectional(&mut tls, &mut plain).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/bin/server/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn acceptor_loads_a_matching_certificate_and_key() {
+        assert!(acceptor(&fixture("localhost.pem"), &fixture("localhost.key")).is_ok());
+
+        let err = acceptor(&fixture("localhost.pem"), &fixture("other.key"))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("do not match"), "{err:#}");
+
+        let err = acceptor(&fixture("missing.pem"), &fixture("localhost.key"))
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains("Failed to read TLS certificate"),
+            "{err:#}"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod aggregatorv3;
 pub mod aprmon;
 pub mod erc20;
 pub mod gmon;
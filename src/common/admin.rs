@@ -0,0 +1,139 @@
+use std::{fmt, sync::Arc};
+
+use anyhow::ensure;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION, request::Parts},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use rmcp::model::Extensions;
+
+/// Shared secret gating admin-only tools such as `diagnostics`, which clients send as an
+/// `Authorization: Bearer` header rather than as a tool argument, so it stays out of the tool
+/// calls clients and agents log. The header is checked by [`authenticate`] on the SSE routes.
+#[derive(Clone)]
+pub struct AdminToken(Arc<str>);
+
+impl AdminToken {
+    pub fn new(token: impl Into<String>) -> anyhow::Result<Self> {
+        let token = token.into();
+        ensure!(!token.trim().is_empty(), "Admin token must not be empty");
+
+        Ok(AdminToken(token.into()))
+    }
+
+    /// Compares in constant time, so response times don't reveal how much of `given` matched.
+    pub fn verify(&self, given: &str) -> bool {
+        let (expected, given) = (self.0.as_bytes(), given.as_bytes());
+        expected.len() == given.len()
+            && expected
+                .iter()
+                .zip(given)
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+impl fmt::Debug for AdminToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AdminToken(..)")
+    }
+}
+
+/// Marks an HTTP request whose `Authorization: Bearer` header carried the admin token, as
+/// checked by [`authenticate`].
+#[derive(Debug, Clone, Copy)]
+pub struct AdminAccess;
+
+/// Middleware for the SSE routes checking the `Authorization: Bearer` header of each request
+/// against `token`. A matching request is marked with [`AdminAccess`], which rmcp hands on to
+/// the tool calls it carries; one without the header goes through unmarked, and one with any
+/// other token is refused.
+pub async fn authenticate(
+    State(token): State<AdminToken>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match bearer_token(request.headers()) {
+        None => {}
+        Some(given) if token.verify(given) => {
+            request.extensions_mut().insert(AdminAccess);
+        }
+        Some(_) => return (StatusCode::UNAUTHORIZED, "Invalid admin token").into_response(),
+    }
+
+    next.run(request).await
+}
+
+/// Whether the tool call with `extensions` came in an HTTP request marked by [`authenticate`].
+pub fn is_admin(extensions: &Extensions) -> bool {
+    extensions
+        .get::<Parts>()
+        .is_some_and(|parts| parts.extensions.get::<AdminAccess>().is_some())
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, middleware::from_fn_with_state, routing::post};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn requests_are_marked_for_the_admin_token_and_refused_for_another() {
+        let app = Router::new()
+            .route(
+                "/message",
+                post(|request: Request| async move {
+                    let (parts, _) = request.into_parts();
+                    let mut extensions = Extensions::new();
+                    extensions.insert(parts);
+                    is_admin(&extensions).to_string()
+                }),
+            )
+            .layer(from_fn_with_state(
+                AdminToken::new("secret").unwrap(),
+                authenticate,
+            ));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/message", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let client = reqwest::Client::new();
+
+        let anonymous = client.post(&url).send().await.unwrap();
+        assert_eq!(anonymous.text().await.unwrap(), "false");
+        let admin = client
+            .post(&url)
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(admin.text().await.unwrap(), "true");
+        let wrong = client.post(&url).bearer_auth("guess").send().await.unwrap();
+        assert_eq!(wrong.status().as_u16(), 401);
+
+        // Calls that didn't come over HTTP carry no request parts at all
+        assert!(!is_admin(&Extensions::new()));
+    }
+
+    #[test]
+    fn tokens_are_compared_whole() {
+        let token = AdminToken::new("secret").unwrap();
+        assert!(token.verify("secret"));
+        assert!(!token.verify("secre"));
+        assert!(!token.verify("secrets"));
+        assert!(!token.verify(""));
+        assert!(AdminToken::new("  ").is_err());
+        assert_eq!(format!("{:?}", token), "AdminToken(..)");
+    }
+}
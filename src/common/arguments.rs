@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use regex::Regex;
+use rmcp::model::{JsonObject, Tool};
+use serde_json::Value;
+
+use crate::common::error::McpServiceError;
+
+/// Patterns tool arguments must match, compiled from the tool schemas: the `pattern` of each
+/// string argument, or of the items of each list argument. The schemas only advertise the
+/// patterns to clients, so calls are checked against them here.
+#[derive(Debug)]
+pub struct ArgumentPatterns {
+    /// Per tool, each argument with a pattern, the pattern as written and compiled.
+    tools: HashMap<String, Vec<(String, String, Regex)>>,
+}
+
+impl ArgumentPatterns {
+    /// Compiles the argument patterns of `tools`, failing on the first that is not a valid
+    /// regular expression.
+    pub fn compile(tools: &[Tool]) -> anyhow::Result<Self> {
+        let mut compiled = HashMap::new();
+        for tool in tools {
+            let Some(properties) = tool
+                .input_schema
+                .get("properties")
+                .and_then(Value::as_object)
+            else {
+                continue;
+            };
+
+            let mut patterns = Vec::new();
+            for (argument, schema) in properties {
+                let Some(pattern) = schema
+                    .get("pattern")
+                    .or_else(|| schema.get("items").and_then(|items| items.get("pattern")))
+                    .and_then(Value::as_str)
+                else {
+                    continue;
+                };
+                let regex = Regex::new(pattern).with_context(|| {
+                    format!(
+                        "Invalid pattern for {}.{}: {}",
+                        tool.name, argument, pattern
+                    )
+                })?;
+                patterns.push((argument.clone(), pattern.to_string(), regex));
+            }
+            compiled.insert(tool.name.to_string(), patterns);
+        }
+
+        Ok(ArgumentPatterns { tools: compiled })
+    }
+
+    /// Checks the string arguments of a call to `tool`, and the strings its list arguments
+    /// hold, against their patterns. Values of other types are left to the tool's own parsing.
+    /// Errors name the argument but not its value, which may be a private key.
+    pub fn check(&self, tool: &str, arguments: Option<&JsonObject>) -> Result<(), McpServiceError> {
+        let (Some(patterns), Some(arguments)) = (self.tools.get(tool), arguments) else {
+            return Ok(());
+        };
+
+        for (argument, pattern, regex) in patterns {
+            let values: Vec<&str> = match arguments.get(argument) {
+                Some(Value::String(value)) => vec![value],
+                Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+                _ => continue,
+            };
+            if !values.iter().all(|value| regex.is_match(value)) {
+                return Err(McpServiceError::invalid_input(format!(
+                    "Invalid {}: must match {}",
+                    argument, pattern
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn arguments(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    fn tool(properties: Value) -> Tool {
+        Tool::new(
+            "stake",
+            "",
+            arguments(json!({ "type": "object", "properties": properties })),
+        )
+    }
+
+    #[test]
+    fn arguments_are_checked_against_their_patterns() {
+        let patterns = ArgumentPatterns::compile(&[tool(json!({
+            "amount": { "type": ["string", "number"], "pattern": "^[0-9]+$" },
+            "private_key": { "type": ["string", "null"], "pattern": "^[0-9a-f]{4}$" },
+            "request_ids": { "type": "array", "items": { "type": "string", "pattern": "^[0-9]+$" } },
+            "note": { "type": "string" },
+        }))])
+        .unwrap();
+
+        let valid = arguments(json!({
+            "amount": "15",
+            "private_key": "ab01",
+            "request_ids": ["1", "2"],
+            "note": "anything",
+        }));
+        assert!(patterns.check("stake", Some(&valid)).is_ok());
+        // Values of other types are left to the tool, as are other tools
+        let float = arguments(json!({ "amount": 1.5 }));
+        assert!(patterns.check("stake", Some(&float)).is_ok());
+        let bad = arguments(json!({ "amount": "1.5" }));
+        assert!(patterns.check("unstake", Some(&bad)).is_ok());
+        assert!(patterns.check("stake", None).is_ok());
+
+        let err = patterns.check("stake", Some(&bad)).unwrap_err();
+        assert_eq!(err.kind(), "INVALID_INPUT");
+        assert!(
+            err.to_string()
+                .contains("Invalid amount: must match ^[0-9]+$"),
+            "{err}"
+        );
+
+        let secret = arguments(json!({ "private_key": "abcdef" }));
+        let err = patterns.check("stake", Some(&secret)).unwrap_err();
+        assert!(err.to_string().contains("Invalid private_key"), "{err}");
+        assert!(!err.to_string().contains("abcdef"), "{err}");
+
+        let ids = arguments(json!({ "request_ids": ["1", "two"] }));
+        let err = patterns.check("stake", Some(&ids)).unwrap_err();
+        assert!(err.to_string().contains("Invalid request_ids"), "{err}");
+    }
+
+    #[test]
+    fn invalid_patterns_fail_to_compile() {
+        let broken = tool(json!({ "amount": { "type": "string", "pattern": "^[0-9" } }));
+
+        let err = ArgumentPatterns::compile(&[broken]).unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid pattern for stake.amount"),
+            "{err:#}"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use ethers::{
+    abi::Detokenize,
+    contract::{builders::ContractCall, decode_function_data},
+    types::{BlockId, BlockNumber},
+};
+
+use crate::common::rpc::{BatchError, RpcProvider};
+
+/// Runs read-only `calls` as one JSON-RPC batch, each at its own block (latest by default).
+/// Falls back to concurrent individual calls only when no endpoint can serve the batch; a call
+/// that fails within a served batch, such as a revert, fails them all.
+pub async fn call_all<D: Detokenize>(
+    provider: &Arc<RpcProvider>,
+    calls: &[ContractCall<RpcProvider, D>],
+) -> anyhow::Result<Vec<D>> {
+    let requests = calls
+        .iter()
+        .map(|call| {
+            let block = call.block.unwrap_or(BlockId::Number(BlockNumber::Latest));
+            (call.tx.clone(), block)
+        })
+        .collect::<Vec<_>>();
+
+    match provider.batch_call(&requests).await {
+        Ok(outputs) => calls
+            .iter()
+            .zip(outputs)
+            .map(|(call, output)| {
+                decode_function_data(&call.function, output, false)
+                    .with_context(|| format!("Failed to decode {} output", call.function.name))
+            })
+            .collect(),
+        Err(BatchError::Unavailable(e)) => {
+            tracing::debug!("Batch call failed, calling individually: {:#}", e);
+            futures::future::try_join_all(calls.iter().map(|call| call.call()))
+                .await
+                .context("Failed to call contracts")
+        }
+        Err(e) => Err(anyhow::Error::new(e).context("Failed to call contracts")),
+    }
+}
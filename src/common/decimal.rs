@@ -0,0 +1,82 @@
+//! `U256` values in responses as base-10 strings. JSON numbers lose precision past 2^53 and
+//! ethers serializes `U256` as hex, so every raw on-chain integer a response carries goes
+//! through here instead.
+
+use std::fmt;
+
+use ethers::types::U256;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+/// A `U256` that serializes as a decimal string, for values put in `serde_json::json!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalU256(pub U256);
+
+impl fmt::Display for DecimalU256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `U256`'s `Display` is base 10
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for DecimalU256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DecimalU256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(DecimalU256)
+    }
+}
+
+/// Serializes a `U256` field as a decimal string, for `#[serde(with = "decimal")]`.
+pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// Reads a `U256` field back from a decimal string, for `#[serde(with = "decimal")]`.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    U256::from_dec_str(&value)
+        .map_err(|e| de::Error::custom(format!("invalid decimal integer '{}': {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Response {
+        #[serde(with = "super")]
+        amount: U256,
+    }
+
+    #[test]
+    fn max_u256_round_trips_through_json_exactly() {
+        let json = serde_json::to_string(&DecimalU256(U256::MAX)).unwrap();
+        assert_eq!(
+            json,
+            "\"115792089237316195423570985008687907853269984665640564039457584007913129639935\""
+        );
+        assert_eq!(
+            serde_json::from_str::<DecimalU256>(&json).unwrap(),
+            DecimalU256(U256::MAX)
+        );
+
+        let response = Response { amount: U256::MAX };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["amount"], U256::MAX.to_string());
+        assert_eq!(serde_json::from_value::<Response>(json).unwrap(), response);
+    }
+
+    #[test]
+    fn only_decimal_strings_are_read_back() {
+        assert!(serde_json::from_str::<DecimalU256>("\"0x10\"").is_err());
+        assert!(serde_json::from_str::<DecimalU256>("16").is_err());
+        assert_eq!(
+            serde_json::from_str::<DecimalU256>("\"16\"").unwrap(),
+            DecimalU256(U256::from(16))
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use std::{error::Error as StdError, fmt};
+
+use ethers::{
+    contract::{ContractError, ContractRevert, EthError},
+    middleware::Middleware,
+    providers::{JsonRpcError, MiddlewareError, ProviderError, RpcError},
+    types::Bytes,
+    utils::hex::encode_prefixed,
+};
+use rmcp::model::{ErrorCode, ErrorData};
+
+use crate::{
+    bindings::{aprmon, gmon, gmonstakemanager},
+    common::{
+        health::ProtocolUnavailable, lst::AmountOverflow, rpc::RpcProvider, tx::WalletSigner,
+    },
+};
+
+/// Error returned by every tool and resource. Each variant maps to a fixed JSON-RPC error code
+/// and a stable `kind` in the error data, so clients can branch on it instead of on messages.
+#[derive(Debug)]
+pub enum McpServiceError {
+    /// The request is malformed or can't be carried out as given. `details` carries any
+    /// structured data, such as a more specific `code`.
+    InvalidInput {
+        message: String,
+        details: Option<serde_json::Value>,
+    },
+    /// A write was asked to move nothing: a zero amount, or an `unstake_all` of an empty
+    /// balance. Rejected before anything is sent, as it would only waste gas or revert.
+    AmountZero(String),
+    /// The RPC node failed or rejected a request.
+    RpcError(String),
+    /// A contract call reverted; `name` is the decoded custom error, `Error` for a revert
+    /// string, or the raw selector when the error is unknown.
+    ContractRevert { name: String, message: String },
+    /// The RPC node did not answer in time.
+    Timeout(String),
+    /// No usable signer was given.
+    Unauthorized(String),
+    /// The RPC node is throttling requests, or the server is at its connection limit.
+    RateLimited(String),
+    /// The network, protocol or operation is not supported here.
+    Unsupported(String),
+    /// The requested resource does not exist.
+    NotFound(String),
+    /// Anything else.
+    Internal(String),
+}
+
+impl McpServiceError {
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        McpServiceError::InvalidInput {
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Classifies a failed operation by its underlying RPC or contract error. The message is
+    /// `context` followed by the outermost error only, which never includes the RPC URL.
+    pub fn from_anyhow(context: &str, e: anyhow::Error) -> Self {
+        let message = format!("{}: {}", context, e);
+
+        for cause in e.chain() {
+            if let Some(err) = cause.downcast_ref::<ProtocolUnavailable>() {
+                return McpServiceError::Unsupported(format!("{}: {}", context, err));
+            }
+            if let Some(err) = cause.downcast_ref::<AmountOverflow>() {
+                return McpServiceError::invalid_input(format!("{}: {}", context, err));
+            }
+
+            let Some(failure) = rpc_failure(cause) else {
+                continue;
+            };
+            return match failure {
+                RpcFailure::Revert(data) => McpServiceError::ContractRevert {
+                    name: revert_name(data),
+                    message,
+                },
+                RpcFailure::Response(response) if is_rate_limit(response) => {
+                    McpServiceError::RateLimited(message)
+                }
+                RpcFailure::Response(_) => McpServiceError::RpcError(message),
+                // Transport errors only describe timeouts and throttling in their text
+                RpcFailure::Transport => {
+                    let full = format!("{:#}", e).to_lowercase();
+                    if full.contains("timed out") || full.contains("timeout") {
+                        McpServiceError::Timeout(message)
+                    } else if full.contains("429") || full.contains("too many requests") {
+                        McpServiceError::RateLimited(message)
+                    } else {
+                        McpServiceError::RpcError(message)
+                    }
+                }
+            };
+        }
+
+        McpServiceError::Internal(message)
+    }
+
+    /// Stable name of the variant, reported as `kind` in the error data.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            McpServiceError::InvalidInput { .. } => "INVALID_INPUT",
+            McpServiceError::AmountZero(_) => "AMOUNT_ZERO",
+            McpServiceError::RpcError(_) => "RPC_ERROR",
+            McpServiceError::ContractRevert { .. } => "CONTRACT_REVERT",
+            McpServiceError::Timeout(_) => "TIMEOUT",
+            McpServiceError::Unauthorized(_) => "UNAUTHORIZED",
+            McpServiceError::RateLimited(_) => "RATE_LIMITED",
+            McpServiceError::Unsupported(_) => "UNSUPPORTED",
+            McpServiceError::NotFound(_) => "NOT_FOUND",
+            McpServiceError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// JSON-RPC error code: invalid params for what the caller can fix, internal error for
+    /// failures on the server or RPC side.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            McpServiceError::InvalidInput { .. }
+            | McpServiceError::AmountZero(_)
+            | McpServiceError::ContractRevert { .. }
+            | McpServiceError::Unsupported(_) => ErrorCode::INVALID_PARAMS,
+            McpServiceError::Unauthorized(_) => ErrorCode::INVALID_REQUEST,
+            McpServiceError::NotFound(_) => ErrorCode::RESOURCE_NOT_FOUND,
+            McpServiceError::RpcError(_)
+            | McpServiceError::Timeout(_)
+            | McpServiceError::RateLimited(_)
+            | McpServiceError::Internal(_) => ErrorCode::INTERNAL_ERROR,
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            McpServiceError::InvalidInput { message, .. }
+            | McpServiceError::ContractRevert { message, .. } => message,
+            McpServiceError::AmountZero(message)
+            | McpServiceError::RpcError(message)
+            | McpServiceError::Timeout(message)
+            | McpServiceError::Unauthorized(message)
+            | McpServiceError::RateLimited(message)
+            | McpServiceError::Unsupported(message)
+            | McpServiceError::NotFound(message)
+            | McpServiceError::Internal(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for McpServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for McpServiceError {}
+
+impl From<McpServiceError> for ErrorData {
+    fn from(err: McpServiceError) -> Self {
+        let mut data = serde_json::json!({ "kind": err.kind() });
+        match &err {
+            McpServiceError::InvalidInput {
+                details: Some(serde_json::Value::Object(details)),
+                ..
+            } => data
+                .as_object_mut()
+                .expect("data is an object")
+                .extend(details.clone()),
+            McpServiceError::ContractRevert { name, .. } => data["revert"] = name.clone().into(),
+            _ => {}
+        }
+
+        ErrorData::new(err.code(), err.message().to_string(), Some(data))
+    }
+}
+
+/// What went wrong talking to the node, as far as the error types tell.
+enum RpcFailure<'a> {
+    Revert(&'a Bytes),
+    Response(&'a JsonRpcError),
+    Transport,
+}
+
+/// Finds the RPC failure behind `cause`, if it is one of the provider or contract errors this
+/// server produces.
+fn rpc_failure<'a>(cause: &'a (dyn StdError + 'static)) -> Option<RpcFailure<'a>> {
+    if let Some(e) = cause.downcast_ref::<ContractError<RpcProvider>>() {
+        return Some(contract_failure(e));
+    }
+    if let Some(e) = cause.downcast_ref::<ContractError<WalletSigner>>() {
+        return Some(contract_failure(e));
+    }
+    if let Some(e) = cause.downcast_ref::<ProviderError>() {
+        return Some(response_failure(RpcError::as_error_response(e)));
+    }
+    if let Some(e) = cause.downcast_ref::<<WalletSigner as Middleware>::Error>() {
+        return Some(response_failure(e.as_error_response()));
+    }
+
+    None
+}
+
+fn contract_failure<M: Middleware>(e: &ContractError<M>) -> RpcFailure<'_> {
+    if let Some(data) = e.as_revert() {
+        return RpcFailure::Revert(data);
+    }
+
+    let response = e
+        .as_middleware_error()
+        .and_then(MiddlewareError::as_error_response)
+        .or_else(|| e.as_provider_error().and_then(RpcError::as_error_response));
+    response_failure(response)
+}
+
+fn response_failure(response: Option<&JsonRpcError>) -> RpcFailure<'_> {
+    match response {
+        Some(response) => RpcFailure::Response(response),
+        None => RpcFailure::Transport,
+    }
+}
+
+/// Whether a JSON-RPC error response reports throttling: HTTP-style `429` or EIP-1474's
+/// "limit exceeded" (`-32005`).
+fn is_rate_limit(response: &JsonRpcError) -> bool {
+    response.code == 429
+        || response.code == -32005
+        || response.message.to_lowercase().contains("rate limit")
+}
+
+/// Names the error a contract reverted with, decoding it with the bindings' error types.
+fn revert_name(data: &Bytes) -> String {
+    if <String as EthError>::decode_with_selector(data).is_some() {
+        return "Error".to_string();
+    }
+
+    decoded_name::<aprmon::aprMONErrors>(data)
+        .or_else(|| decoded_name::<gmon::gMONErrors>(data))
+        .or_else(|| decoded_name::<gmonstakemanager::gMONStakeManagerErrors>(data))
+        .map(str::to_string)
+        .or_else(|| data.get(..4).map(encode_prefixed))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn decoded_name<E: ContractRevert + RevertName>(data: &[u8]) -> Option<&'static str> {
+    E::decode_with_selector(data).map(|error| error.name())
+}
+
+/// Custom error enums generated from the contract ABIs, named by their decoded variant.
+trait RevertName {
+    fn name(&self) -> &'static str;
+}
+
+/// Implements [`RevertName`] for a bindings error enum from its custom error variants. The
+/// match is exhaustive, so regenerating the bindings with a new error fails to build until it
+/// is listed.
+macro_rules! revert_names {
+    ($errors:path { $($variant:ident),* $(,)? }) => {
+        impl RevertName for $errors {
+            fn name(&self) -> &'static str {
+                use $errors as Errors;
+                match self {
+                    $(Errors::$variant(_) => stringify!($variant),)*
+                    Errors::RevertString(_) => "Error",
+                }
+            }
+        }
+    };
+}
+
+revert_names!(aprmon::aprMONErrors {
+    AlreadyClaimed,
+    BelowMinimumRedeemAmount,
+    ERC20InsufficientAllowance,
+    ERC20InsufficientBalance,
+    ERC20InvalidApprover,
+    ERC20InvalidReceiver,
+    ERC20InvalidSender,
+    ERC20InvalidSpender,
+    EnforcedPause,
+    ExpectedPause,
+    InsufficientBalance,
+    InvalidAmount,
+    InvalidBlockNumber,
+    InvalidBurnableShares,
+    InvalidInitialization,
+    InvalidLastProcessedRequestId,
+    InvalidRange,
+    InvalidRequestId,
+    InvalidRewardFee,
+    InvalidRewards,
+    InvalidTotalStaked,
+    InvalidUtilisedPendingDeposit,
+    InvalidWithdrawalFee,
+    NoPendingWithdrawalRequests,
+    NotInitializing,
+    OnlyOracleOperatorAllowed,
+    OwnableInvalidOwner,
+    OwnableUnauthorizedAccount,
+    TotalAssetsIsZero,
+    TransferFailed,
+    UnauthorizedOperator,
+    WaitMoreTime,
+});
+
+revert_names!(gmon::gMONErrors {
+    ContractPaused,
+    ERC20InsufficientAllowance,
+    ERC20InsufficientBalance,
+    ERC20InvalidApprover,
+    ERC20InvalidReceiver,
+    ERC20InvalidSender,
+    ERC20InvalidSpender,
+    InvalidInitialization,
+    InvalidZeroInput,
+    NotGMonMinterBurner,
+    NotInitializing,
+    NotTokenAdmin,
+});
+
+revert_names!(gmonstakemanager::gMONStakeManagerErrors {
+    ContractPaused,
+    FailedToSendMon,
+    InsufficientBalance,
+    InvalidInitialization,
+    InvalidZeroInput,
+    MaxTVLReached,
+    NotDepositWithdrawPauser,
+    NotInitializing,
+    NotStakeManagerAdmin,
+    ReentrancyGuardReentrantCall,
+});
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::AbiEncode;
+
+    use super::*;
+
+    #[test]
+    fn reverts_are_named_by_their_decoded_error() {
+        let pause =
+            Bytes::from(aprmon::aprMONErrors::EnforcedPause(aprmon::EnforcedPause).encode());
+        let max_tvl = Bytes::from(
+            gmonstakemanager::gMONStakeManagerErrors::MaxTVLReached(
+                gmonstakemanager::MaxTVLReached,
+            )
+            .encode(),
+        );
+        // Error(string)
+        let message = [0x08, 0xc3, 0x79, 0xa0]
+            .into_iter()
+            .chain(ethers::abi::encode(&[ethers::abi::Token::String(
+                "Paused".to_string(),
+            )]))
+            .collect::<Bytes>();
+
+        assert_eq!(revert_name(&pause), "EnforcedPause");
+        assert_eq!(revert_name(&max_tvl), "MaxTVLReached");
+        assert_eq!(revert_name(&message), "Error");
+        assert_eq!(
+            revert_name(&Bytes::from(vec![0x12, 0x34, 0x56, 0x78, 0x9a])),
+            "0x12345678"
+        );
+        assert_eq!(revert_name(&Bytes::new()), "unknown");
+    }
+}
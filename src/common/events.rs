@@ -0,0 +1,271 @@
+use anyhow::{Context, bail};
+use ethers::{
+    providers::Middleware,
+    types::{Filter, Log},
+};
+
+use crate::services::constants::{
+    EVENT_CHUNK_SIZE, EVENT_CHUNK_SIZE_LIMIT, EVENT_MAX_LOOKBACK_BLOCKS,
+    EVENT_MAX_LOOKBACK_BLOCKS_LIMIT,
+};
+
+/// Block-range settings shared by every resource that scans event logs.
+#[derive(Debug, Clone, Copy)]
+pub struct EventScanConfig {
+    chunk_size: u64,
+    max_lookback_blocks: u64,
+}
+
+impl EventScanConfig {
+    /// Validates the settings: the chunk size must be between 1 and
+    /// [`EVENT_CHUNK_SIZE_LIMIT`] and the lookback between the chunk size and
+    /// [`EVENT_MAX_LOOKBACK_BLOCKS_LIMIT`].
+    pub fn new(chunk_size: u64, max_lookback_blocks: u64) -> anyhow::Result<Self> {
+        if !(1..=EVENT_CHUNK_SIZE_LIMIT).contains(&chunk_size) {
+            bail!(
+                "Event chunk size must be between 1 and {}, got {}",
+                EVENT_CHUNK_SIZE_LIMIT,
+                chunk_size
+            );
+        }
+        if !(chunk_size..=EVENT_MAX_LOOKBACK_BLOCKS_LIMIT).contains(&max_lookback_blocks) {
+            bail!(
+                "Event max lookback must be between the chunk size ({}) and {} blocks, got {}",
+                chunk_size,
+                EVENT_MAX_LOOKBACK_BLOCKS_LIMIT,
+                max_lookback_blocks
+            );
+        }
+
+        Ok(EventScanConfig {
+            chunk_size,
+            max_lookback_blocks,
+        })
+    }
+
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    pub fn max_lookback_blocks(&self) -> u64 {
+        self.max_lookback_blocks
+    }
+}
+
+impl Default for EventScanConfig {
+    fn default() -> Self {
+        EventScanConfig {
+            chunk_size: EVENT_CHUNK_SIZE,
+            max_lookback_blocks: EVENT_MAX_LOOKBACK_BLOCKS,
+        }
+    }
+}
+
+/// Fetches the logs matching `filter` between `from_block` and `to_block` (inclusive),
+/// splitting the range into windows of `chunk_size` blocks to stay within RPC range limits.
+/// When the provider still rejects a window as too wide, it is halved and retried, and the
+/// smaller size is kept for the rest of the scan, down to a single block.
+pub async fn get_logs_chunked<M: Middleware>(
+    provider: &M,
+    filter: &Filter,
+    from_block: u64,
+    to_block: u64,
+    chunk_size: u64,
+) -> anyhow::Result<Vec<Log>>
+where
+    M::Error: 'static,
+{
+    let mut chunk_size = chunk_size.max(1);
+    let mut logs = Vec::new();
+    let mut start = from_block;
+
+    while start <= to_block {
+        let end = start.saturating_add(chunk_size - 1).min(to_block);
+        let chunk = match provider
+            .get_logs(&filter.clone().from_block(start).to_block(end))
+            .await
+        {
+            Ok(chunk) => chunk,
+            Err(e) if chunk_size > 1 && is_range_limit_error(&e.to_string()) => {
+                chunk_size = (end - start + 1).div_ceil(2);
+                tracing::debug!(
+                    "Provider rejected logs for blocks {}-{} ({}); retrying {} blocks at a time",
+                    start,
+                    end,
+                    e,
+                    chunk_size
+                );
+                continue;
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to get logs for blocks {}-{}", start, end));
+            }
+        };
+        logs.extend(chunk);
+
+        if end == u64::MAX {
+            break;
+        }
+        start = end + 1;
+    }
+
+    Ok(logs)
+}
+
+/// Phrases providers use when an `eth_getLogs` block range or result set is too large, e.g.
+/// "block range too large", "exceeds max block range", "query returned more than 10000
+/// results" or Alchemy's "Log response size exceeded".
+const RANGE_LIMIT_MESSAGES: [&str; 7] = [
+    "block range",
+    "range too large",
+    "range is too large",
+    "range is too wide",
+    "query returned more than",
+    "response size exceeded",
+    "exceeds max results",
+];
+
+/// Phrases of rate-limit errors, which can mention limits and ranges too but wouldn't be fixed
+/// by a narrower range.
+const RATE_LIMIT_MESSAGES: [&str; 4] = [
+    "too many requests",
+    "rate limit",
+    "rate-limit",
+    "request limit",
+];
+
+/// Whether an `eth_getLogs` error says the block range or result set is too large, as opposed
+/// to a failure a narrower range wouldn't fix, such as being rate limited.
+fn is_range_limit_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    !RATE_LIMIT_MESSAGES
+        .iter()
+        .any(|pattern| message.contains(pattern))
+        && RANGE_LIMIT_MESSAGES
+            .iter()
+            .any(|pattern| message.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use ethers::providers::{Http, Provider};
+
+    use super::*;
+
+    /// Provider whose `eth_getLogs` answers with one log per block and records every requested
+    /// range, rejecting ranges wider than `max_range` blocks as many providers do.
+    async fn logs_provider(max_range: u64) -> (Provider<Http>, Arc<Mutex<Vec<(u64, u64)>>>) {
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post({
+                let ranges = ranges.clone();
+                move |axum::Json(call): axum::Json<serde_json::Value>| {
+                    let block = |key: &str| {
+                        let hex = call["params"][0][key].as_str().unwrap();
+                        u64::from_str_radix(hex.trim_start_matches("0x"), 16).unwrap()
+                    };
+                    let (from, to) = (block("fromBlock"), block("toBlock"));
+                    ranges.lock().unwrap().push((from, to));
+                    let response = if to - from + 1 > max_range {
+                        serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": call["id"],
+                            "error": { "code": -32600, "message": "block range too large" },
+                        })
+                    } else {
+                        let logs = (from..=to)
+                            .map(|number| {
+                                serde_json::json!({
+                                    "address": format!("{:?}", ethers::types::Address::zero()),
+                                    "topics": [],
+                                    "data": "0x",
+                                    "blockNumber": format!("0x{:x}", number),
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": logs })
+                    };
+                    async move { axum::Json(response) }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        (Provider::<Http>::try_from(url).unwrap(), ranges)
+    }
+
+    fn block_numbers(logs: &[Log]) -> Vec<u64> {
+        logs.iter()
+            .map(|log| log.block_number.unwrap().as_u64())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn logs_are_requested_in_windows_of_the_chunk_size() {
+        let (provider, ranges) = logs_provider(u64::MAX).await;
+
+        let logs = get_logs_chunked(&provider, &Filter::new(), 100, 124, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(block_numbers(&logs), (100..=124).collect::<Vec<_>>());
+        assert_eq!(
+            *ranges.lock().unwrap(),
+            [(100, 109), (110, 119), (120, 124)]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_ranges_are_halved_and_every_log_is_still_returned() {
+        let (provider, ranges) = logs_provider(3).await;
+
+        let logs = get_logs_chunked(&provider, &Filter::new(), 0, 20, 10)
+            .await
+            .unwrap();
+
+        assert_eq!(block_numbers(&logs), (0..=20).collect::<Vec<_>>());
+        // Halved from 10 to 5 to 3 blocks, then kept at 3 for the rest of the scan
+        let ranges = ranges.lock().unwrap();
+        assert_eq!(ranges[..2], [(0, 9), (0, 4)]);
+        assert_eq!(
+            ranges[2..],
+            (0..=20)
+                .step_by(3)
+                .map(|start| (start, start + 2))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn range_limit_errors_are_recognised() {
+        for message in [
+            "(code: -32600, message: block range too large, data: None)",
+            "exceeds max block range of 1000",
+            "query returned more than 10000 results",
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range",
+            "eth_getLogs block range is too wide",
+        ] {
+            assert!(is_range_limit_error(message), "{}", message);
+        }
+    }
+
+    #[test]
+    fn other_errors_are_not_range_limits() {
+        for message in [
+            "(code: -32005, message: Too Many Requests, data: None)",
+            "HTTP error 429 Too Many Requests",
+            "rate limit exceeded for this block range",
+            "daily request limit exceeded",
+            "connection refused",
+            "invalid params",
+        ] {
+            assert!(!is_range_limit_error(message), "{}", message);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::bail;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{common::lst::LstProtocol, services::constants::MAX_CONNECTIONS};
+
+/// Tracks whether the RPC endpoint is serving the chain the server was configured for.
+#[derive(Debug, Clone)]
+pub struct ChainGuard {
+    expected_chain_id: u64,
+    detected_chain_id: Arc<RwLock<Option<u64>>>,
+}
+
+impl ChainGuard {
+    pub fn new(expected_chain_id: u64) -> Self {
+        ChainGuard {
+            expected_chain_id,
+            detected_chain_id: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn expected_chain_id(&self) -> u64 {
+        self.expected_chain_id
+    }
+
+    pub fn detected_chain_id(&self) -> Option<u64> {
+        *self.detected_chain_id.read().unwrap()
+    }
+
+    /// Records the chain id reported by the RPC, failing if it differs from the expected one.
+    pub fn record(&self, detected_chain_id: u64) -> anyhow::Result<()> {
+        *self.detected_chain_id.write().unwrap() = Some(detected_chain_id);
+        self.ensure_healthy()
+    }
+
+    pub fn ensure_healthy(&self) -> anyhow::Result<()> {
+        match self.detected_chain_id() {
+            Some(detected) if detected == self.expected_chain_id => Ok(()),
+            Some(detected) => bail!(
+                "RPC chain id {} does not match expected chain id {}",
+                detected,
+                self.expected_chain_id
+            ),
+            None => bail!("RPC chain id has not been verified yet"),
+        }
+    }
+}
+
+/// A protocol was found unusable at startup.
+#[derive(Debug)]
+pub struct ProtocolUnavailable {
+    pub protocol: LstProtocol,
+    pub reason: String,
+}
+
+impl fmt::Display for ProtocolUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is unavailable: {}", self.protocol, self.reason)
+    }
+}
+
+impl std::error::Error for ProtocolUnavailable {}
+
+/// Protocols found unusable at startup, with the reason, so their reads fail descriptively
+/// instead of with decode errors.
+#[derive(Debug, Clone, Default)]
+pub struct ProtocolAvailability {
+    unavailable: Arc<RwLock<HashMap<LstProtocol, String>>>,
+}
+
+impl ProtocolAvailability {
+    pub fn mark_unavailable(&self, protocol: LstProtocol, reason: String) {
+        self.unavailable.write().unwrap().insert(protocol, reason);
+    }
+
+    /// Why the protocol is unavailable, or `None` if it can be used.
+    pub fn reason(&self, protocol: LstProtocol) -> Option<String> {
+        self.unavailable.read().unwrap().get(&protocol).cloned()
+    }
+
+    pub fn is_available(&self, protocol: LstProtocol) -> bool {
+        self.reason(protocol).is_none()
+    }
+
+    pub fn ensure_available(&self, protocol: LstProtocol) -> Result<(), ProtocolUnavailable> {
+        match self.reason(protocol) {
+            Some(reason) => Err(ProtocolUnavailable { protocol, reason }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A protocol the operator marked deprecated, as given on the command line:
+/// `NAME` or `NAME:NOTICE`.
+#[derive(Debug, Clone)]
+pub struct DeprecatedProtocol {
+    pub protocol: LstProtocol,
+    pub notice: Option<String>,
+}
+
+impl FromStr for DeprecatedProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, notice) = match s.split_once(':') {
+            Some((name, notice)) => (name, Some(notice.trim().to_string())),
+            None => (s, None),
+        };
+
+        Ok(DeprecatedProtocol {
+            protocol: name.trim().parse()?,
+            notice: notice.filter(|notice| !notice.is_empty()),
+        })
+    }
+}
+
+/// Protocols the operator marked deprecated so agents steer away from them. They keep working
+/// and calls against them carry a warning, unless writes are blocked.
+#[derive(Debug, Clone, Default)]
+pub struct Deprecations {
+    notices: HashMap<LstProtocol, Option<String>>,
+    block_writes: bool,
+}
+
+impl Deprecations {
+    pub fn new(
+        deprecated: impl IntoIterator<Item = DeprecatedProtocol>,
+        block_writes: bool,
+    ) -> Self {
+        Deprecations {
+            notices: deprecated
+                .into_iter()
+                .map(|entry| (entry.protocol, entry.notice))
+                .collect(),
+            block_writes,
+        }
+    }
+
+    pub fn is_deprecated(&self, protocol: LstProtocol) -> bool {
+        self.notices.contains_key(&protocol)
+    }
+
+    /// The operator's notice for a deprecated protocol, if one was given.
+    pub fn notice(&self, protocol: LstProtocol) -> Option<&str> {
+        self.notices.get(&protocol)?.as_deref()
+    }
+
+    /// Describes why `protocol` should be avoided, or `None` if it is not deprecated.
+    pub fn describe(&self, protocol: LstProtocol) -> Option<String> {
+        if !self.is_deprecated(protocol) {
+            return None;
+        }
+
+        Some(match self.notice(protocol) {
+            Some(notice) => format!("{} is deprecated: {}", protocol, notice),
+            None => format!("{} is deprecated", protocol),
+        })
+    }
+
+    /// Fails for a deprecated protocol when writes against deprecated protocols are blocked.
+    pub fn ensure_writable(&self, protocol: LstProtocol) -> anyhow::Result<()> {
+        match self.describe(protocol) {
+            Some(description) if self.block_writes => {
+                bail!("{}; writes against it are blocked", description)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Cap on the client connections served at once, shared by every clone.
+#[derive(Debug, Clone)]
+pub struct ConnectionLimit {
+    slots: Arc<Semaphore>,
+    max: usize,
+}
+
+impl ConnectionLimit {
+    /// `max` must be non-zero.
+    pub fn new(max: usize) -> Self {
+        ConnectionLimit {
+            slots: Arc::new(Semaphore::new(max)),
+            max,
+        }
+    }
+
+    /// Claims a slot for a new connection, held until the permit is dropped; `None` when every
+    /// slot is taken.
+    pub fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        self.slots.clone().try_acquire_owned().ok()
+    }
+
+    pub fn active(&self) -> usize {
+        self.max - self.slots.available_permits()
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+impl Default for ConnectionLimit {
+    fn default() -> Self {
+        ConnectionLimit::new(MAX_CONNECTIONS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_mismatched_chain_id_is_unhealthy_until_the_expected_one_is_seen() {
+        let guard = ChainGuard::new(10143);
+        assert!(guard.ensure_healthy().is_err());
+
+        let mismatch = guard.record(1).unwrap_err();
+        assert_eq!(
+            mismatch.to_string(),
+            "RPC chain id 1 does not match expected chain id 10143"
+        );
+        assert_eq!(guard.detected_chain_id(), Some(1));
+        // Clones share what was detected, as every connection's service does
+        assert!(guard.clone().ensure_healthy().is_err());
+
+        guard.record(10143).unwrap();
+        assert!(guard.ensure_healthy().is_ok());
+    }
+
+    #[test]
+    fn connections_past_the_limit_get_no_slot_until_one_is_freed() {
+        let limit = ConnectionLimit::new(2);
+        let first = limit.try_acquire().unwrap();
+        let _second = limit.clone().try_acquire().unwrap();
+
+        assert!(limit.try_acquire().is_none());
+        assert_eq!(limit.active(), 2);
+
+        drop(first);
+        assert_eq!(limit.active(), 1);
+        assert!(limit.try_acquire().is_some());
+    }
+}
@@ -1,582 +1,6617 @@
 use core::fmt;
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
 
-use anyhow::Context;
+use anyhow::{Context, bail};
 use ethers::{
+    abi::{
+        AbiParser, ParamType, StateMutability, Token,
+        token::{LenientTokenizer, Tokenizer},
+    },
     middleware::SignerMiddleware,
-    providers::{Http, Provider},
+    providers::Middleware,
     signers::{LocalWallet, Signer},
-    types::{Address, TransactionReceipt, U256},
-    utils::{format_units, hex::encode_prefixed, parse_units},
+    types::{
+        Address, BlockId, BlockNumber, Bytes, H256, I256, Signature, TransactionReceipt,
+        TransactionRequest, U256,
+        transaction::eip712::{Eip712, TypedData, Types},
+    },
+    utils::{format_units, hex::encode_prefixed, parse_units, to_checksum},
 };
+use futures::{FutureExt, future::BoxFuture};
 use rmcp::{
-    Error as McpError, RoleServer, ServerHandler, model::*, schemars, service::RequestContext, tool,
+    Error as McpError, Peer, RoleServer, ServerHandler, handler::server::tool::ToolCallContext,
+    model::*, service::RequestContext, tool,
 };
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 use crate::{
-    bindings::{aprmon, erc20, gmon, gmonstakemanager, shmon},
+    common::{
+        admin::{self, AdminToken},
+        arguments::ArgumentPatterns,
+        batch::call_all,
+        decimal::DecimalU256,
+        error::McpServiceError,
+        events::EventScanConfig,
+        health::{ChainGuard, ConnectionLimit, Deprecations, ProtocolAvailability},
+        price::UsdPricing,
+        progress::{Broadcast, Progress, WriteStep, progress_token_from_meta},
+        query::QueryParams,
+        rpc::{RpcProvider, count_rpc_calls},
+        session::Session,
+        snapshot::{Snapshot, SnapshotCache, SnapshotError},
+        tx::{
+            AllowanceResets, GasMultiplier, InsufficientFundsForGas, ReplacedFees, TRANSFER_GAS,
+            TxStatus, WalletSigner, ensure_nonce_pending, pending_transaction, reset_nonce,
+            send_replacement, transaction_status,
+        },
+        warning::{Warning, WarningCode, warnings_json},
+    },
     services::constants::{
-        APRMON_ADDRESS, GMON_ADDRESS, GMON_STAKEMANAGER_ADDRESS, MONAD_TESTNET_CHAIN_ID,
-        SHMON_ADDRESS,
+        APR_LOOKBACK_BLOCKS, BALANCES_MAX_ADDRESSES, CLAIM_CONFIRMATIONS, DIAGNOSTIC_CHECK_TIMEOUT,
+        HIGH_WITHDRAWAL_FEE_BPS, HISTORY_MAX_LIMIT, JOB_MAX_ENTRIES, MIN_RECOMMENDED_TVL,
+        MONAD_TESTNET_CHAIN_ID, ORACLE_STALE_BLOCKS, READ_TIMEOUT, REDEEM_MAX_WAIT,
+        REDEEM_MAX_WAIT_LIMIT, REDEEM_POLL_INTERVAL, WRITE_TIMEOUT,
     },
 };
 
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
-pub enum LstProtocol {
-    #[serde(rename = "aprMON")]
-    AprMON,
-    #[serde(rename = "gMON")]
-    GMON,
-    #[serde(rename = "shMON")]
-    SHMON,
+mod jobs;
+mod protocol;
+mod requests;
+mod resources;
+mod stats;
+mod units;
+
+use self::{jobs::JobStore, resources::with_rpc_calls_meta};
+pub use self::{protocol::*, requests::*, stats::*, units::*};
+
+/// Name of the only network served, as used in resource URIs.
+const NETWORK: &str = "monadTestnet";
+
+/// Tool argument patterns compiled by [`Lst::argument_patterns`], or the error compiling them.
+static ARGUMENT_PATTERNS: OnceLock<Result<ArgumentPatterns, String>> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct Lst {
+    provider: Arc<RpcProvider>,
+    chain_guard: ChainGuard,
+    connection_ct: CancellationToken,
+    session: Session,
+    event_scan: EventScanConfig,
+    snapshot: SnapshotCache,
+    oracle_stale_blocks: u64,
+    availability: ProtocolAvailability,
+    history_max_limit: usize,
+    default_protocol: Option<LstProtocol>,
+    gas_multiplier: GasMultiplier,
+    connections: ConnectionLimit,
+    native: NativeCurrency,
+    usd_pricing: Option<UsdPricing>,
+    deprecations: Deprecations,
+    timeouts: ToolTimeouts,
+    admin_token: Option<AdminToken>,
+    diagnostics_address: Option<Address>,
+    report_rpc_calls: bool,
+    allowance_resets: AllowanceResets,
+    claim_confirmations: u64,
+    decimals: Arc<DecimalsCache>,
+    jobs: Arc<JobStore>,
+    peer: Option<Peer<RoleServer>>,
 }
 
-impl fmt::Display for LstProtocol {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            LstProtocol::AprMON => write!(f, "aprMON"),
-            LstProtocol::GMON => write!(f, "gMON"),
-            LstProtocol::SHMON => write!(f, "shMON"),
+#[tool(tool_box)]
+impl Lst {
+    pub fn new(provider: Arc<RpcProvider>) -> Self {
+        Lst {
+            provider,
+            chain_guard: ChainGuard::new(MONAD_TESTNET_CHAIN_ID),
+            connection_ct: CancellationToken::new(),
+            session: Session::default(),
+            event_scan: EventScanConfig::default(),
+            snapshot: SnapshotCache::default(),
+            oracle_stale_blocks: ORACLE_STALE_BLOCKS,
+            availability: ProtocolAvailability::default(),
+            history_max_limit: HISTORY_MAX_LIMIT,
+            default_protocol: None,
+            gas_multiplier: GasMultiplier::default(),
+            connections: ConnectionLimit::default(),
+            native: NativeCurrency::default(),
+            usd_pricing: None,
+            deprecations: Deprecations::default(),
+            timeouts: ToolTimeouts::default(),
+            admin_token: None,
+            diagnostics_address: None,
+            report_rpc_calls: false,
+            allowance_resets: AllowanceResets::default(),
+            claim_confirmations: CLAIM_CONFIRMATIONS,
+            decimals: Arc::default(),
+            jobs: Arc::default(),
+            peer: None,
         }
     }
-}
 
-impl TryFrom<&str> for LstProtocol {
-    type Error = &'static str;
+    pub fn with_expected_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_guard = ChainGuard::new(chain_id);
+        self
+    }
 
-    fn try_from(value: &str) -> Result<Self, &'static str> {
-        match value {
-            "aprMON" => Ok(LstProtocol::AprMON),
-            "gMON" => Ok(LstProtocol::GMON),
-            "shMON" => Ok(LstProtocol::SHMON),
-            _ => Err("Invalid LST protocol"),
-        }
+    /// Patterns tool arguments are checked against, compiled from the tool schemas on first
+    /// use. Called at startup, so a schema advertising an invalid pattern stops the server
+    /// rather than failing every call to its tool.
+    pub fn argument_patterns() -> anyhow::Result<&'static ArgumentPatterns> {
+        ARGUMENT_PATTERNS
+            .get_or_init(|| {
+                ArgumentPatterns::compile(&Self::tool_box().list()).map_err(|e| format!("{:#}", e))
+            })
+            .as_ref()
+            .map_err(|e| anyhow::anyhow!("{}", e))
     }
-}
 
-impl LstProtocol {
-    fn address(&self) -> Address {
-        match self {
-            LstProtocol::AprMON => *APRMON_ADDRESS,
-            LstProtocol::GMON => *GMON_STAKEMANAGER_ADDRESS,
-            LstProtocol::SHMON => *SHMON_ADDRESS,
-        }
+    pub fn with_event_scan(mut self, event_scan: EventScanConfig) -> Self {
+        self.event_scan = event_scan;
+        self
     }
 
-    fn description(&self) -> &'static str {
-        match self {
-            LstProtocol::AprMON => {
-                "aPriori is the leading MEV-powered liquid staking platform on Monad."
-            }
-            LstProtocol::GMON => {
-                "Magma enables Monad token holders to earn staking rewards while remaining liquid through Magma's Liquid Staking token, gMON."
-            }
-            LstProtocol::SHMON => {
-                "shMONAD is an innovative Liquid Staking Token (LST) built on top of MON (Monad). Designed for users who wish to stake their MON while retaining liquidity, shMONAD allows holders to convert MON into shMON, bond their tokens within distinct policies, and later unbond them after an escrow period."
+    /// Sets the protocol used when a tool omits `protocol` or a resource names `default`.
+    pub fn with_default_protocol(mut self, protocol: LstProtocol) -> Self {
+        self.default_protocol = Some(protocol);
+        self
+    }
+
+    /// Sets how long protocol metadata such as decimals is cached. The cache is shared by the
+    /// clones of this service, i.e. its connections, and starts empty.
+    pub fn with_metadata_ttl(mut self, ttl: Duration) -> Self {
+        self.decimals =
+            Arc::new(DecimalsCache::new(ttl).with_native_decimals(self.native.decimals()));
+        self
+    }
+
+    /// Caps the number of activities an address history page may return.
+    pub fn with_history_max_limit(mut self, limit: usize) -> Self {
+        self.history_max_limit = limit;
+        self
+    }
+
+    /// Sets how many blocks the oracle may trail the chain head before it is reported stale.
+    pub fn with_oracle_stale_blocks(mut self, blocks: u64) -> Self {
+        self.oracle_stale_blocks = blocks;
+        self
+    }
+
+    /// Sets how many blocks deep the processing of a redeem request must be before it is
+    /// reported claimable, so a reorg can't undo it after a client acts on it.
+    pub fn with_claim_confirmations(mut self, blocks: u64) -> Self {
+        self.claim_confirmations = blocks;
+        self
+    }
+
+    /// Sets the factor applied to estimated gas for the gas limit of write tools.
+    pub fn with_gas_multiplier(mut self, multiplier: GasMultiplier) -> Self {
+        self.gas_multiplier = multiplier;
+        self
+    }
+
+    /// Caps how many client connections are served at once. `limit` must be non-zero.
+    pub fn with_max_connections(mut self, limit: usize) -> Self {
+        self.connections = ConnectionLimit::new(limit);
+        self
+    }
+
+    /// Connection cap shared by every connection scoped from this service, for the server to
+    /// claim a slot from as each client connects.
+    pub fn connection_limit(&self) -> &ConnectionLimit {
+        &self.connections
+    }
+
+    /// Sets the symbol and decimals native amounts are labeled, parsed and formatted with. The
+    /// metadata cache starts empty again, as protocols staking the native currency take its
+    /// decimals.
+    pub fn with_native_currency(mut self, native: NativeCurrency) -> Self {
+        self.decimals = Arc::new(
+            DecimalsCache::new(self.decimals.ttl()).with_native_decimals(native.decimals()),
+        );
+        self.native = native;
+        self
+    }
+
+    /// Converts TVL to USD at the native currency's price from `pricing`.
+    pub fn with_usd_pricing(mut self, pricing: UsdPricing) -> Self {
+        self.usd_pricing = Some(pricing);
+        self
+    }
+
+    /// Sets the protocols marked deprecated and whether writes against them are blocked.
+    pub fn with_deprecations(mut self, deprecations: Deprecations) -> Self {
+        self.deprecations = deprecations;
+        self
+    }
+
+    /// Sets how long tools and resource reads may run before failing with `TIMEOUT`.
+    pub fn with_timeouts(mut self, timeouts: ToolTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Enables admin-only tools for clients presenting `token`.
+    pub fn with_admin_token(mut self, token: AdminToken) -> Self {
+        self.admin_token = Some(token);
+        self
+    }
+
+    /// Sets the address whose nonces `diagnostics` checks when the call names none.
+    pub fn with_diagnostics_address(mut self, address: Address) -> Self {
+        self.diagnostics_address = Some(address);
+        self
+    }
+
+    /// Sets the ERC-20 tokens whose non-zero allowance `stake` resets to zero before approving
+    /// a new one.
+    pub fn with_allowance_resets(mut self, allowance_resets: AllowanceResets) -> Self {
+        self.allowance_resets = allowance_resets;
+        self
+    }
+
+    /// Adds `_meta.rpc_calls`, the number of JSON-RPC calls made to serve it, to every tool
+    /// result and JSON resource. The count is logged at debug level either way.
+    pub fn with_rpc_call_reporting(mut self, enabled: bool) -> Self {
+        self.report_rpc_calls = enabled;
+        self
+    }
+
+    /// Reads the chain id from the RPC and checks it against the expected one.
+    pub async fn verify_chain_id(&self) -> anyhow::Result<()> {
+        let chain_id = self
+            .provider
+            .get_chainid()
+            .await
+            .context("Failed to get chain id")?;
+
+        self.chain_guard.record(chain_id.as_u64())
+    }
+
+    /// Checks that every protocol's contracts are deployed on the connected chain. Protocols
+    /// that are not are marked unavailable rather than failing startup.
+    pub async fn verify_protocols(&self) -> anyhow::Result<()> {
+        for protocol in LstProtocol::ALL {
+            if let Some(reason) = protocol.check_deployed(self.provider.clone()).await? {
+                tracing::warn!("{} is unavailable: {}", protocol, reason);
+                self.availability.mark_unavailable(protocol, reason);
             }
         }
+
+        Ok(())
     }
 
-    fn token_address(&self) -> Address {
-        match self {
-            LstProtocol::AprMON => *APRMON_ADDRESS,
-            LstProtocol::GMON => *GMON_ADDRESS,
-            LstProtocol::SHMON => *SHMON_ADDRESS,
+    /// Re-verifies the chain id every `period`, marking the service degraded on mismatch.
+    pub async fn watch_chain_id(self, period: std::time::Duration) {
+        let mut interval = tokio::time::interval(period);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.verify_chain_id().await {
+                tracing::error!("Chain id verification failed: {:#}", e);
+            }
         }
     }
 
-    pub async fn read_balance(
-        &self,
-        provider: Arc<Provider<Http>>,
-        owner: Address,
-    ) -> anyhow::Result<U256> {
-        Ok(match self {
-            LstProtocol::AprMON => {
-                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
-                contract
-                    .balance_of(owner)
-                    .call()
-                    .await
-                    .context("Failed to get balance")?
-            }
-            LstProtocol::GMON => {
-                let contract = gmon::g_mon::gMON::new(self.token_address(), provider.clone());
-                contract
-                    .balance_of(owner)
-                    .call()
-                    .await
-                    .context("Failed to get balance")?
-            }
-            LstProtocol::SHMON => {
-                let contract = erc20::erc20::new(self.token_address(), provider.clone());
-                contract
-                    .balance_of(owner)
-                    .call()
-                    .await
-                    .context("Failed to get balance")?
-            }
-        })
+    /// Reads the stats and exchange rate of every protocol and stores them as the latest snapshot.
+    /// All contract reads go out as a single JSON-RPC batch where the endpoint supports it.
+    pub async fn refresh_snapshot(&self) -> anyhow::Result<Arc<Snapshot>> {
+        self.refresh_protocols(None).await
     }
 
-    pub async fn stake(
+    /// Like [`Lst::refresh_snapshot`], but with `only` set reads just that protocol and merges
+    /// it into the latest snapshot, leaving the other protocols as they were.
+    pub async fn refresh_protocols(
         &self,
-        signer: Arc<SignerMiddleware<Arc<Provider<Http>>, LocalWallet>>,
-        signer_address: Address,
-        amount: U256,
-    ) -> anyhow::Result<Option<TransactionReceipt>> {
-        let receipt = match self {
-            LstProtocol::AprMON => {
-                let contract = aprmon::aprMON::new(self.address(), signer.clone());
-                contract
-                    .deposit(amount, signer_address)
-                    .value(amount)
-                    .send()
-                    .await
-                    .context("Failed to deposit")?
-                    .confirmations(1)
-                    .await
-                    .context("Failed to confirm deposit")?
-            }
-            LstProtocol::GMON => {
-                let contract = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
-                    self.address(),
-                    signer.clone(),
-                );
-                contract
-                    .deposit_mon()
-                    .value(amount)
-                    .send()
-                    .await
-                    .context("Failed to deposit")?
-                    .confirmations(1)
-                    .await
-                    .context("Failed to confirm deposit")?
-            }
-            LstProtocol::SHMON => {
-                let contract = shmon::shMON::new(self.address(), signer.clone());
-                contract
-                    .deposit(amount, signer_address)
-                    .value(amount)
-                    .send()
-                    .await
-                    .context("Failed to deposit")?
-                    .confirmations(1)
+        only: Option<LstProtocol>,
+    ) -> anyhow::Result<Arc<Snapshot>> {
+        let latest = self
+            .provider
+            .get_block(BlockNumber::Latest)
+            .await
+            .context("Failed to get latest block")?
+            .context("Latest block not found")?;
+        let latest_number = latest.number.context("Latest block is pending")?;
+        let past_number = latest_number.saturating_sub(APR_LOOKBACK_BLOCKS.into());
+        let past = self
+            .provider
+            .get_block(past_number)
+            .await
+            .context("Failed to get lookback block")?
+            .context("Lookback block not found")?;
+        let elapsed = latest.timestamp.saturating_sub(past.timestamp).as_u64();
+
+        // Unavailable protocols would fail the whole batch, so they are left out
+        let available = LstProtocol::ALL
+            .into_iter()
+            .filter(|protocol| self.availability.is_available(*protocol))
+            .filter(|protocol| only.is_none_or(|only| only == *protocol))
+            .collect::<Vec<_>>();
+
+        // A protocol that fails to read is reported in `errors` rather than failing the others
+        let mut errors = Vec::new();
+        let decimals = futures::future::join_all(
+            available
+                .iter()
+                .map(|protocol| protocol.decimals(self.provider.clone(), &self.decimals)),
+        )
+        .await;
+        let readable = available
+            .into_iter()
+            .zip(decimals)
+            .filter_map(|(protocol, decimals)| match decimals {
+                Ok(decimals) => Some((protocol, decimals)),
+                Err(e) => {
+                    errors.push(SnapshotError {
+                        protocol,
+                        error: e.to_string(),
+                    });
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let calls = readable
+            .iter()
+            .map(|(protocol, decimals)| {
+                protocol.snapshot_calls(
+                    self.provider.clone(),
+                    *decimals,
+                    latest_number.into(),
+                    past_number.into(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let values = match call_all(&self.provider, &calls.concat()).await {
+            Ok(values) => {
+                let mut values = values.into_iter();
+                calls
+                    .iter()
+                    .map(|calls| Ok(values.by_ref().take(calls.len()).collect::<Vec<_>>()))
+                    .collect::<Vec<_>>()
+            }
+            // One failing call fails the whole batch, so retry per protocol to find which
+            Err(e) => {
+                tracing::debug!("Snapshot batch failed, reading per protocol: {:#}", e);
+                futures::future::join_all(calls.iter().map(|calls| call_all(&self.provider, calls)))
                     .await
-                    .context("Failed to confirm deposit")?
             }
         };
 
-        Ok(receipt)
-    }
+        let as_of = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .context("System clock is before the unix epoch")?
+            .as_secs();
 
-    pub async fn unstake(
-        &self,
-        signer: Arc<SignerMiddleware<Arc<Provider<Http>>, LocalWallet>>,
-        signer_address: Address,
-        amount: U256,
-    ) -> anyhow::Result<Option<TransactionReceipt>> {
-        let receipt = match self {
-            LstProtocol::AprMON => {
-                let contract = aprmon::aprMON::new(self.address(), signer.clone());
-                contract
-                    .request_redeem(amount, signer_address, signer_address)
-                    .send()
-                    .await
-                    .context("Failed to request redeem")?
-                    .confirmations(1)
-                    .await
-                    .context("Failed to confirm request redeem tx")?
-            }
-            LstProtocol::GMON => {
-                let contract = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
-                    self.address(),
-                    signer.clone(),
-                );
-                contract
-                    .withdraw_mon(amount)
-                    .send()
-                    .await
-                    .context("Failed to deposit")?
-                    .confirmations(1)
-                    .await
-                    .context("Failed to confirm deposit")?
+        let mut protocols = Vec::new();
+        for ((protocol, decimals), values) in readable.into_iter().zip(values) {
+            match values
+                .and_then(|values| protocol.snapshot_from(&values, decimals, elapsed, as_of))
+            {
+                Ok(snapshot) => protocols.push(snapshot),
+                Err(e) => errors.push(SnapshotError {
+                    protocol,
+                    error: e.to_string(),
+                }),
             }
-            LstProtocol::SHMON => {
-                let contract = shmon::shMON::new(self.address(), signer.clone());
-                contract
-                    .redeem(amount, signer_address, signer_address)
-                    .send()
-                    .await
-                    .context("Failed to request redeem")?
-                    .confirmations(1)
-                    .await
-                    .context("Failed to confirm request redeem tx")?
+        }
+        for error in &errors {
+            tracing::warn!(
+                "Failed to refresh snapshot of {}: {}",
+                error.protocol,
+                error.error
+            );
+        }
+        if protocols.is_empty() && !errors.is_empty() {
+            match only {
+                Some(protocol) => bail!("Failed to refresh snapshot of {}", protocol),
+                None => bail!("Failed to refresh snapshot of every protocol"),
             }
+        }
+
+        let refreshed = Snapshot {
+            as_of,
+            protocols,
+            errors,
         };
+        Ok(self.snapshot.update(only, refreshed))
+    }
+
+    /// Refreshes the snapshot now and then every `period`, keeping the last good one on failure.
+    pub async fn watch_snapshot(self, period: std::time::Duration) {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.refresh_snapshot().await {
+                tracing::error!("Snapshot refresh failed: {:#}", e);
+            }
+        }
+    }
 
-        Ok(receipt)
+    /// Returns the cached snapshot, reading from the chain when `fresh` is set or none exists.
+    async fn snapshot(&self, fresh: bool) -> anyhow::Result<Arc<Snapshot>> {
+        match self.snapshot.get() {
+            Some(snapshot) if !fresh => Ok(snapshot),
+            _ => self.refresh_snapshot().await,
+        }
     }
 
-    pub async fn tvl(&self, provider: Arc<Provider<Http>>) -> anyhow::Result<U256> {
-        let tvl = match self {
-            LstProtocol::AprMON => {
-                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
-                contract
-                    .total_assets()
-                    .call()
-                    .await
-                    .context("Failed to get total assets")?
-            }
-            LstProtocol::GMON => {
-                let contract = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
-                    self.address(),
-                    provider.clone(),
-                );
-                contract
-                    .calculate_tvl()
-                    .call()
-                    .await
-                    .context("Failed to get total supply")?
+    /// Fills `tvl_usd` and `usd_price` of a TVL response from the configured price source,
+    /// leaving them `null` when none is set. A failed price read is reported under
+    /// `usd_price_error` rather than failing the response.
+    async fn add_usd_value(&self, body: &mut serde_json::Value, tvl: &str) {
+        let Some(pricing) = &self.usd_pricing else {
+            return;
+        };
+
+        match pricing.price(self.provider.clone()).await {
+            Ok(price) => {
+                body["tvl_usd"] = tvl.parse::<f64>().map(|tvl| tvl * price.price).ok().into();
+                body["usd_price"] = serde_json::json!({
+                    "price": price.price,
+                    "source": pricing.source().describe(),
+                    "as_of": iso_timestamp(price.updated_at),
+                });
             }
-            LstProtocol::SHMON => {
-                let contract = shmon::shMON::new(self.token_address(), provider.clone());
-                contract
-                    .total_assets()
-                    .call()
-                    .await
-                    .context("Failed to get total supply")?
+            Err(e) => {
+                tracing::warn!(error = %format!("{:#}", e), "Failed to read USD price");
+                let error = self
+                    .provider
+                    .provider()
+                    .as_ref()
+                    .redact_urls(&format!("{:#}", e));
+                body["usd_price_error"] = error.into();
             }
+        }
+    }
+
+    fn health(&self) -> serde_json::Value {
+        let (status, error) = match self.chain_guard.ensure_healthy() {
+            Ok(()) => ("ok", None),
+            Err(e) => ("degraded", Some(e.to_string())),
         };
 
-        Ok(tvl)
+        serde_json::json!({
+            "status": status,
+            "rpc_endpoint": self.provider.provider().as_ref().active_endpoint(),
+            "expected_chain_id": self.chain_guard.expected_chain_id(),
+            "detected_chain_id": self.chain_guard.detected_chain_id(),
+            "connections": {
+                "active": self.connections.active(),
+                "max": self.connections.max(),
+            },
+            "error": error,
+        })
     }
-}
-
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
-pub struct StakeRequest {
-    pub protocol: LstProtocol,
-    pub private_key: String,
-    pub amount: String,
-}
 
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
-pub struct UnstakeRequest {
-    pub protocol: LstProtocol,
-    pub private_key: String,
-    pub amount: String,
-}
+    /// Scopes the service to a client connection; in-flight calls are aborted once `ct` is
+    /// cancelled, which the server does when the client disconnects. Each connection starts
+    /// with an empty session.
+    pub fn for_connection(&self, ct: CancellationToken) -> Self {
+        Lst {
+            connection_ct: ct,
+            session: Session::default(),
+            ..self.clone()
+        }
+    }
 
-#[derive(Clone)]
-pub struct Lst {
-    provider: Arc<Provider<Http>>,
-}
+    /// Resolves the signer for a write, preferring an explicit key over the session signer.
+    /// It is bound to the expected chain id, as a parsed wallet signs for chain 1 until told
+    /// otherwise.
+    fn signer(&self, private_key: Option<String>) -> Result<LocalWallet, McpServiceError> {
+        let signer = match private_key {
+            Some(private_key) => private_key.parse::<LocalWallet>().map_err(|e| {
+                McpServiceError::invalid_input(format!("Failed to parse private key: {}", e))
+            })?,
+            None => self.session.signer().ok_or_else(|| {
+                McpServiceError::Unauthorized(
+                    "No private_key given and no session signer set".to_string(),
+                )
+            })?,
+        };
 
-#[tool(tool_box)]
-impl Lst {
-    #[allow(dead_code)]
-    pub fn new(provider: Arc<Provider<Http>>) -> Self {
-        Lst { provider }
+        Ok(signer.with_chain_id(self.chain_guard.expected_chain_id()))
     }
 
-    fn _create_resource_text(&self, uri: &str, name: &str) -> Resource {
-        RawResource::new(uri, name.to_string()).no_annotation()
-    }
+    /// Builds the middleware write tools sign and send through: the resolved [`Self::signer`],
+    /// bound to the expected chain id for EIP-155 signing, over the shared provider. Nonces
+    /// are handed out by the process-wide [`NonceTracker`](crate::common::tx::NonceTracker) when
+    /// sending, except for `speed_up` and `cancel_tx`, which set theirs explicitly.
+    fn signer_provider(
+        &self,
+        private_key: Option<String>,
+    ) -> Result<Arc<WalletSigner>, McpServiceError> {
+        let signer = self.signer(private_key)?;
 
-    async fn read_balance(&self, protocol: LstProtocol, owner: Address) -> anyhow::Result<U256> {
-        protocol
-            .read_balance(self.provider.clone(), owner)
-            .await
-            .context("Failed to read balance")
+        Ok(Arc::new(SignerMiddleware::new(
+            self.provider.clone(),
+            signer,
+        )))
     }
 
-    async fn protocol_tvl(&self, protocol: LstProtocol) -> anyhow::Result<U256> {
-        protocol
-            .tvl(self.provider.clone())
-            .await
-            .context("Failed to get TVL")
+    /// Runs `fut` to completion unless the request is cancelled or the client disconnects.
+    async fn cancellable<F: Future>(
+        &self,
+        ct: &CancellationToken,
+        timeout: Duration,
+        fut: F,
+    ) -> Result<F::Output, McpServiceError> {
+        tokio::select! {
+            output = tokio::time::timeout(timeout, fut) => output.map_err(|_| {
+                McpServiceError::Timeout(format!("Timed out after {} seconds", timeout.as_secs()))
+            }),
+            _ = ct.cancelled() => Err(McpServiceError::Internal("Request cancelled".to_string())),
+            _ = self.connection_ct.cancelled() => {
+                Err(McpServiceError::Internal("Client disconnected".to_string()))
+            }
+        }
     }
 
-    #[tool(description = "Stake LST tokens")]
-    async fn stake(
+    /// Runs a write like [`Lst::cancellable`] until it sends a transaction, as tracked by
+    /// `progress`. A transaction can't be called back, so from then on the write is seen
+    /// through despite a cancellation or disconnect, and running out of time reports the last
+    /// transaction sent as pending instead of failing.
+    async fn cancellable_write<F: Future>(
         &self,
-        #[tool(aggr)] StakeRequest {
-            protocol,
-            private_key,
-            amount,
-        }: StakeRequest,
-    ) -> Result<CallToolResult, McpError> {
-        tracing::info!("Staking {} LST tokens using protocol {}", amount, protocol);
+        ct: &CancellationToken,
+        timeout: Duration,
+        progress: &Progress,
+        fut: F,
+    ) -> Result<WriteOutcome<F::Output>, McpServiceError> {
+        let fut = tokio::time::timeout(timeout, fut);
+        tokio::pin!(fut);
+        let (mut cancelled, mut disconnected) = (false, false);
 
-        let signer = private_key
-            .parse::<LocalWallet>()
-            .map_err(|e| {
-                ErrorData::invalid_params(format!("Failed to parse private key: {}", e), None)
-            })?
-            .with_chain_id(MONAD_TESTNET_CHAIN_ID);
-        let signer_address = signer.address();
-        let signer = Arc::new(SignerMiddleware::new(self.provider.clone(), signer));
+        loop {
+            tokio::select! {
+                output = &mut fut => {
+                    return match (output, progress.broadcast()) {
+                        (Ok(output), _) => Ok(WriteOutcome::Finished(output)),
+                        (Err(_), Broadcast::Sent(tx_hash)) => Ok(WriteOutcome::Pending(tx_hash)),
+                        (Err(_), Broadcast::Sending) => Err(McpServiceError::Timeout(format!(
+                            "Timed out after {} seconds while sending a transaction, which may have gone out; check the signer's pending transactions before retrying",
+                            timeout.as_secs()
+                        ))),
+                        (Err(_), Broadcast::None) => Err(McpServiceError::Timeout(format!(
+                            "Timed out after {} seconds",
+                            timeout.as_secs()
+                        ))),
+                    };
+                }
+                _ = ct.cancelled(), if !cancelled => {
+                    if progress.broadcast() == Broadcast::None {
+                        return Err(McpServiceError::Internal("Request cancelled".to_string()));
+                    }
+                    cancelled = true;
+                    tracing::info!("Request cancelled after sending a transaction; finishing the write");
+                }
+                _ = self.connection_ct.cancelled(), if !disconnected => {
+                    if progress.broadcast() == Broadcast::None {
+                        return Err(McpServiceError::Internal("Client disconnected".to_string()));
+                    }
+                    disconnected = true;
+                    tracing::info!("Client disconnected after a transaction was sent; finishing the write");
+                }
+            }
+        }
+    }
 
-        let parsed_amount = parse_units(&amount, "ether").map_err(|e| {
-            ErrorData::invalid_params(format!("Failed to parse amount '{}': {}", amount, e), None)
-        })?;
-        let amount_u256: U256 = parsed_amount.into();
+    /// Result of a write that sent `tx_hash` but didn't finish within its timeout.
+    fn pending_write(&self, tool: &str, tx_hash: H256) -> Result<CallToolResult, McpError> {
+        let tx_hash = encode_prefixed(tx_hash);
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "{} sent transaction {} but it was not confirmed within {} seconds. It is still pending; check it with tx_status before retrying.",
+                tool,
+                tx_hash,
+                self.timeouts.for_tool(tool).as_secs()
+            )),
+            Content::json(serde_json::json!({
+                "status": "pending",
+                "tx_hash": tx_hash,
+            }))?,
+        ]))
+    }
 
-        let receipt = protocol
-            .stake(signer.clone(), signer_address, amount_u256)
-            .await
-            .map_err(|e| ErrorData::internal_error(format!("Staking failed: {}", e), None))?
-            .ok_or_else(|| {
-                ErrorData::internal_error("Staking failed: no receipt returned".to_string(), None)
-            })?;
+    /// Stores a batch job of one `tool` call per entry of `arguments`, signed by the signer
+    /// resolved from `private_key`, and runs it.
+    async fn start_job(
+        &self,
+        tool: &'static str,
+        private_key: Option<String>,
+        arguments: Vec<JsonObject>,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        if arguments.is_empty() || arguments.len() > JOB_MAX_ENTRIES {
+            return Err(McpServiceError::invalid_input(format!(
+                "entries must hold between 1 and {} entries",
+                JOB_MAX_ENTRIES
+            ))
+            .into());
+        }
+        let signer = self.signer(private_key.clone())?.address();
+        let job_id = self.jobs.create(tool, signer, arguments);
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Staked {} {} tokens successfully. Transaction hash: {}",
-            amount,
-            protocol,
-            encode_prefixed(receipt.transaction_hash)
-        ))]))
+        self.run_job(&job_id, private_key, ct).await
     }
 
-    #[tool(description = "Unstake LST tokens")]
-    async fn unstake(
+    /// Runs the entries of job `job_id` not done yet as calls of its tool, each signed with
+    /// `private_key` or the session signer, which must be the job's signer. An entry whose
+    /// deposit or redeem went out on an earlier run has that transaction checked instead.
+    async fn run_job(
         &self,
-        #[tool(aggr)] StakeRequest {
-            protocol,
-            private_key,
-            amount,
-        }: StakeRequest,
+        job_id: &str,
+        private_key: Option<String>,
+        ct: CancellationToken,
     ) -> Result<CallToolResult, McpError> {
-        tracing::info!(
-            "Unstaking {} LST tokens using protocol {}",
-            amount,
-            protocol
-        );
+        let signer = self.signer(private_key.clone())?.address();
+        let job = self
+            .jobs
+            .run(job_id, signer, |tool, mut arguments, sent| {
+                if let Some(private_key) = &private_key {
+                    arguments.insert("private_key".to_string(), private_key.clone().into());
+                }
+                let ct = ct.clone();
+                async move {
+                    if let Some(tx_hash) = sent {
+                        return self.check_sent_entry(tool, tx_hash, &ct).await;
+                    }
 
-        let signer = private_key
-            .parse::<LocalWallet>()
-            .map_err(|e| {
-                ErrorData::invalid_params(format!("Failed to parse private key: {}", e), None)
-            })?
-            .with_chain_id(MONAD_TESTNET_CHAIN_ID);
-        let signer_address = signer.address();
-        let signer = Arc::new(SignerMiddleware::new(self.provider.clone(), signer));
+                    let progress = Progress::default();
+                    let arguments = serde_json::Value::Object(arguments);
+                    let invalid = |e: serde_json::Error| {
+                        McpServiceError::invalid_input(format!("Invalid {} entry: {}", tool, e))
+                    };
+                    let result = match tool {
+                        "stake" => match serde_json::from_value(arguments) {
+                            Ok(request) => self.stake_with(request, &progress, ct).await,
+                            Err(e) => Err(invalid(e).into()),
+                        },
+                        _ => match serde_json::from_value(arguments) {
+                            Ok(request) => self.unstake_with(request, &progress, ct).await,
+                            Err(e) => Err(invalid(e).into()),
+                        },
+                    };
 
-        let parsed_amount = parse_units(&amount, "ether").map_err(|e| {
-            ErrorData::invalid_params(format!("Failed to parse amount '{}': {}", amount, e), None)
-        })?;
-        let amount_u256: U256 = parsed_amount.into();
+                    match (result, progress.transfer()) {
+                        // Only approvals went out, which running the entry again skips
+                        (Ok(result), None) if is_pending_write(&result) => (
+                            Err(McpServiceError::Timeout(format!(
+                                "{} timed out before its transaction was sent",
+                                tool
+                            ))
+                            .into()),
+                            None,
+                        ),
+                        (Ok(result), transfer) if is_pending_write(&result) => {
+                            (Ok(result), transfer)
+                        }
+                        (Ok(result), _) => (Ok(result), None),
+                        (Err(e), transfer) => (Err(e), transfer),
+                    }
+                }
+            })
+            .await?;
+
+        Ok(CallToolResult::success(vec![Content::json(job)?]))
+    }
 
-        let receipt = protocol
-            .unstake(signer.clone(), signer_address, amount_u256)
+    /// Checks transaction `tx_hash` that a job entry of `tool` sent on an earlier run but
+    /// didn't see confirmed. It is left to check again while pending or not found, as sending
+    /// the entry again could move its funds twice, and the entry runs again once it reverted.
+    async fn check_sent_entry(
+        &self,
+        tool: &str,
+        tx_hash: H256,
+        ct: &CancellationToken,
+    ) -> (Result<CallToolResult, McpError>, Option<H256>) {
+        let status = self
+            .cancellable(
+                ct,
+                self.timeouts.for_tool("tx_status"),
+                transaction_status(&self.provider, tx_hash),
+            )
             .await
-            .map_err(|e| ErrorData::internal_error(format!("Staking failed: {}", e), None))?
-            .ok_or_else(|| {
-                ErrorData::internal_error("Staking failed: no receipt returned".to_string(), None)
-            })?;
+            .and_then(|status| {
+                status.map_err(|e| {
+                    McpServiceError::from_anyhow("Failed to get transaction status", e)
+                })
+            });
+        let text = |text: String| Ok(CallToolResult::success(vec![Content::text(text)]));
+        let tx_hash_hex = encode_prefixed(tx_hash);
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Unstaked {} {} tokens successfully. Transaction hash: {}",
-            amount,
-            protocol,
-            encode_prefixed(receipt.transaction_hash)
-        ))]))
+        match status {
+            Ok(Some(TxStatus::Mined {
+                success: Some(false),
+                ..
+            })) => (
+                Err(McpServiceError::Internal(format!(
+                    "{} transaction {} reverted",
+                    tool, tx_hash_hex
+                ))
+                .into()),
+                None,
+            ),
+            Ok(Some(TxStatus::Mined { .. })) => (
+                text(format!(
+                    "{} transaction {} was confirmed",
+                    tool, tx_hash_hex
+                )),
+                None,
+            ),
+            Ok(Some(TxStatus::Pending)) => (
+                text(format!(
+                    "{} transaction {} is still pending",
+                    tool, tx_hash_hex
+                )),
+                Some(tx_hash),
+            ),
+            Ok(None) => (
+                Err(McpServiceError::NotFound(format!(
+                    "{} transaction {} not found; check the signer's pending transactions",
+                    tool, tx_hash_hex
+                ))
+                .into()),
+                Some(tx_hash),
+            ),
+            Err(e) => (Err(e.into()), Some(tx_hash)),
+        }
     }
-}
 
-#[tool(tool_box)]
-impl ServerHandler for Lst {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            instructions: Some("This server provides a LST (Liquid Staking Token) tool that can be used by staking native token and receive a LST token.".into()),
-            capabilities: ServerCapabilities::builder().enable_tools().enable_resources().build(),
-            ..Default::default()
+    fn ensure_chain_healthy(&self) -> Result<(), McpServiceError> {
+        self.chain_guard
+            .ensure_healthy()
+            .map_err(|e| McpServiceError::RpcError(format!("Refusing to send transaction: {}", e)))
+    }
+
+    /// Lets admin-only tools through only for calls whose HTTP request carried the server's
+    /// admin token, as checked by [`admin::authenticate`]. Without one set they are disabled
+    /// altogether.
+    fn ensure_admin(&self, extensions: &Extensions) -> Result<(), McpServiceError> {
+        if self.admin_token.is_none() {
+            return Err(McpServiceError::Unsupported(
+                "Admin tools are disabled; start the server with --admin-token to enable them"
+                    .to_string(),
+            ));
+        }
+        if !admin::is_admin(extensions) {
+            return Err(McpServiceError::Unauthorized(
+                "Admin tools need the admin token as an Authorization: Bearer header".to_string(),
+            ));
         }
+
+        Ok(())
     }
 
-    async fn list_resources(
+    /// Falls back to the default protocol when a tool omits `protocol`.
+    fn resolve_protocol(
         &self,
-        _request: Option<PaginatedRequestParam>,
-        _: RequestContext<RoleServer>,
-    ) -> Result<ListResourcesResult, McpError> {
-        Ok(ListResourcesResult {
-            resources: vec![self._create_resource_text("evm://networks", "Get supported networks")],
-            next_cursor: None,
+        protocol: Option<LstProtocol>,
+    ) -> Result<LstProtocol, McpServiceError> {
+        protocol.or(self.default_protocol).ok_or_else(|| {
+            McpServiceError::invalid_input("No protocol given and no default protocol set")
         })
     }
 
-    async fn read_resource(
-        &self,
-        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
-        _: RequestContext<RoleServer>,
-    ) -> Result<ReadResourceResult, McpError> {
-        // Exact match for networks resource
-        if uri == "evm://networks" {
-            return Ok(ReadResourceResult {
-                contents: vec![ResourceContents::text(
-                    "Supported networks: monadTestnet",
-                    uri,
-                )],
-            });
+    /// Falls back to the server's gas limit multiplier when a tool omits `gas_limit_multiplier`.
+    fn gas_multiplier(&self, multiplier: Option<f64>) -> Result<GasMultiplier, McpServiceError> {
+        match multiplier {
+            Some(multiplier) => GasMultiplier::new(multiplier)
+                .map_err(|e| McpServiceError::invalid_input(e.to_string())),
+            None => Ok(self.gas_multiplier),
         }
+    }
 
-        // Parse the URI into parts
-        let parts: Vec<&str> = uri.split('/').collect();
+    /// Parses the `{lst}` segment of a resource URI, where `default` names the default protocol.
+    fn protocol_segment(&self, segment: &str) -> Result<LstProtocol, McpServiceError> {
+        let protocol = match segment {
+            "default" => self
+                .default_protocol
+                .ok_or_else(|| "No default protocol set".to_string()),
+            _ => segment.try_into(),
+        };
+        protocol.map_err(|e| {
+            McpServiceError::invalid_input(format!("Failed to parse protocol '{}': {}", segment, e))
+        })
+    }
 
-        // Check if the URI starts with evm://
-        if parts.len() >= 2 && parts[0] == "evm:" {
-            let network = parts[2];
+    fn ensure_available(&self, protocol: LstProtocol) -> Result<(), McpServiceError> {
+        self.availability
+            .ensure_available(protocol)
+            .map_err(|e| McpServiceError::Unsupported(e.to_string()))
+    }
 
-            // Validate network
-            if network != "monadTestnet" {
-                return Err(McpError::resource_not_found(
-                    "resource_not_found",
-                    Some(serde_json::json!({
-                        "uri": uri,
-                        "error": "Unsupported network",
-                    })),
-                ));
-            }
+    /// Currency amounts staked into `protocol` are given in, read only for ERC-20 deposits.
+    async fn deposit_currency(
+        &self,
+        protocol: LstProtocol,
+    ) -> Result<NativeCurrency, McpServiceError> {
+        protocol
+            .deposit_currency(self.provider.clone(), &self.native, &self.decimals)
+            .await
+            .map_err(|e| McpServiceError::from_anyhow("Failed to get the deposit currency", e))
+    }
 
-            // Pattern: evm://{network}/lsts
-            if parts.len() == 4 && parts[3] == "lsts" {
-                return Ok(ReadResourceResult {
-                    contents: vec![ResourceContents::text(
-                        "Available LST protocols: aprMON, gMON, shMON",
-                        uri,
-                    )],
-                });
-            }
+    /// Fails a write against a deprecated protocol when the operator blocks them.
+    fn ensure_writable(&self, protocol: LstProtocol) -> Result<(), McpServiceError> {
+        self.deprecations
+            .ensure_writable(protocol)
+            .map_err(|e| McpServiceError::Unsupported(e.to_string()))
+    }
 
-            // Pattern: evm://{network}/lsts/{lst}
-            if parts.len() == 5 && parts[3] == "lsts" {
-                let lst_name = parts[4];
-                let protocol: LstProtocol = lst_name.try_into().map_err(|e| {
-                    ErrorData::invalid_params(
-                        format!("Failed to parse protocol '{}': {}", lst_name, e),
-                        None,
-                    )
-                })?;
+    /// Warnings that apply to every tool call against `protocol`.
+    fn protocol_warnings(&self, protocol: LstProtocol) -> Vec<Warning> {
+        self.deprecations
+            .describe(protocol)
+            .map(|description| {
+                Warning::new(
+                    WarningCode::Deprecated,
+                    format!("{}; consider another protocol", description),
+                )
+            })
+            .into_iter()
+            .collect()
+    }
 
-                return Ok(ReadResourceResult {
-                    contents: vec![ResourceContents::text(protocol.description(), uri)],
-                });
-            }
+    /// Lists every tool and resource with whether it needs a private key, so hosts only prompt
+    /// for one when calling a write tool. Resources are all read-only.
+    fn capabilities(&self) -> serde_json::Value {
+        let tools = Self::tool_box()
+            .list()
+            .into_iter()
+            .map(|tool| {
+                serde_json::json!({
+                    "name": tool.name,
+                    "requires_signer": SIGNER_TOOLS.contains(&tool.name.as_ref()),
+                })
+            })
+            .collect::<Vec<_>>();
 
-            // Pattern: evm://{network}/lsts/{lst}/tvl
-            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "tvl" {
-                let lst_name = parts[4];
-                let protocol: LstProtocol = lst_name.try_into().map_err(|e| {
-                    ErrorData::invalid_params(
-                        format!("Failed to parse protocol '{}': {}", lst_name, e),
-                        None,
-                    )
-                })?;
+        let resources = self
+            .resources()
+            .into_iter()
+            .map(|resource| resource.raw.uri)
+            .chain(
+                Self::resource_templates()
+                    .into_iter()
+                    .map(|template| template.raw.uri_template),
+            )
+            .map(|uri| serde_json::json!({ "uri": uri, "requires_signer": false }))
+            .collect::<Vec<_>>();
 
-                let tvl = self.protocol_tvl(protocol).await.map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to get TVL: {}", e), None)
-                })?;
+        serde_json::json!({
+            "tools": tools,
+            "resources": resources,
+        })
+    }
 
-                return Ok(ReadResourceResult {
-                    contents: vec![ResourceContents::text(
-                        format!("TVL: {} ether", format_units(tvl, "ether").unwrap()),
-                        uri,
-                    )],
-                });
-            }
+    /// Names of the protocols found deployed, in [`LstProtocol::ALL`] order.
+    fn available_protocols(&self) -> Vec<String> {
+        LstProtocol::ALL
+            .into_iter()
+            .filter(|protocol| self.availability.is_available(*protocol))
+            .map(|protocol| protocol.to_string())
+            .collect()
+    }
 
-            // Pattern: evm://{network}/address/{address}/lsts/{lst}/balance
-            if parts.len() == 8
-                && parts[3] == "address"
-                && parts[5] == "lsts"
-                && parts[7] == "balance"
-            {
-                let address_str = parts[4];
-                let lst_name = parts[6];
-
-                // Parse the address
-                let address = match address_str.parse::<Address>() {
-                    Ok(addr) => addr,
-                    Err(e) => {
-                        return Err(McpError::invalid_params(
-                            "invalid_address",
-                            Some(serde_json::json!({
-                                "uri": uri,
-                                "error": format!("Invalid address: {}", e),
-                            })),
-                        ));
-                    }
-                };
+    /// Optional features this server runs with, advertised to clients when they connect under
+    /// `experimental.monad` in the server capabilities.
+    fn server_features(&self) -> JsonObject {
+        let deprecated = LstProtocol::ALL
+            .into_iter()
+            .filter(|protocol| self.deprecations.is_deprecated(*protocol))
+            .map(|protocol| protocol.to_string())
+            .collect::<Vec<_>>();
 
-                let protocol: LstProtocol = lst_name.try_into().map_err(|e| {
-                    ErrorData::invalid_params(format!("Failed to parse protocol: {}", e), None)
-                })?;
+        let serde_json::Value::Object(features) = serde_json::json!({
+            "networks": [NETWORK],
+            "chain_id": self.chain_guard.expected_chain_id(),
+            "protocols": self.available_protocols(),
+            "default_protocol": self.default_protocol.map(|protocol| protocol.to_string()),
+            "deprecated_protocols": deprecated,
+            "admin_tools": self.admin_token.is_some(),
+            "rpc_call_reporting": self.report_rpc_calls,
+        }) else {
+            unreachable!("features are a JSON object");
+        };
 
-                // Get balance
-                let balance = self.read_balance(protocol, address).await.map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to get balance: {}", e), None)
-                })?;
+        features
+    }
 
-                let formatted_balance = format_units(balance, "ether").map_err(|e| {
-                    ErrorData::internal_error(format!("Failed to format balance: {}", e), None)
-                })?;
+    /// Latest block and the fees write tools would pay now, in wei. The suggested fees come from
+    /// the same fee history estimate the write tools use.
+    async fn chain_fees(&self) -> anyhow::Result<serde_json::Value> {
+        let (block, (max_fee_per_gas, max_priority_fee_per_gas)) = tokio::try_join!(
+            async {
+                self.provider
+                    .get_block(BlockNumber::Latest)
+                    .await
+                    .context("Failed to get latest block")?
+                    .context("Latest block not found")
+            },
+            async {
+                self.provider
+                    .estimate_eip1559_fees(None)
+                    .await
+                    .context("Failed to estimate fees")
+            },
+        )?;
 
-                return Ok(ReadResourceResult {
-                    contents: vec![ResourceContents::text(
-                        format!("Balance: {} {}", formatted_balance, lst_name),
-                        uri,
-                    )],
-                });
-            }
-        }
+        Ok(serde_json::json!({
+            "network": NETWORK,
+            "chain_id": self.chain_guard.expected_chain_id(),
+            "block_number": block.number.map(|number| number.as_u64()),
+            "as_of": iso_timestamp(block.timestamp.low_u64()),
+            "base_fee_per_gas": block.base_fee_per_gas.map(DecimalU256),
+            "max_priority_fee_per_gas": DecimalU256(max_priority_fee_per_gas),
+            "max_fee_per_gas": DecimalU256(max_fee_per_gas),
+            "unit": "wei",
+        }))
+    }
+
+    /// Build and protocol details for diagnosing client/server mismatches. The commit is
+    /// `unknown` when the build had neither git nor a `GIT_COMMIT` variable.
+    fn version(&self) -> serde_json::Value {
+        serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "git_commit": env!("GIT_COMMIT"),
+            "mcp_protocol_version": self.get_info().protocol_version,
+            "networks": [NETWORK],
+        })
+    }
 
-        // No match found
-        tracing::warn!("No match found for URI: {}", uri);
+    async fn read_balance(
+        &self,
+        protocol: LstProtocol,
+        owner: Address,
+        block: Option<PinnedBlock>,
+    ) -> anyhow::Result<U256> {
+        self.availability.ensure_available(protocol)?;
 
-        Err(McpError::resource_not_found(
-            "resource_not_found",
-            Some(serde_json::json!({
-                "uri": uri
-            })),
-        ))
+        let balance = protocol
+            .read_balance(self.provider.clone(), owner, block.map(|b| b.id))
+            .await;
+        match block {
+            Some(block) => balance.with_context(|| block.read_context()),
+            None => balance.context("Failed to read balance"),
+        }
     }
 
-    async fn list_resource_templates(
+    /// Reads the LST balances of a comma-separated list of addresses concurrently, keyed by
+    /// checksummed address. An address that fails to parse or read gets an `error` entry, keyed
+    /// as given, without failing the others.
+    async fn balances(
+        &self,
+        protocol: LstProtocol,
+        addresses: &str,
+        block: Option<PinnedBlock>,
+    ) -> Result<serde_json::Value, McpServiceError> {
+        let addresses: Vec<&str> = addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .collect();
+        if addresses.is_empty() {
+            return Err(McpServiceError::invalid_input("No addresses given"));
+        }
+        if addresses.len() > BALANCES_MAX_ADDRESSES {
+            return Err(McpServiceError::invalid_input(format!(
+                "At most {} addresses may be given, got {}",
+                BALANCES_MAX_ADDRESSES,
+                addresses.len()
+            )));
+        }
+        self.ensure_available(protocol)?;
+        let decimals = protocol
+            .decimals(self.provider.clone(), &self.decimals)
+            .await
+            .map_err(|e| McpServiceError::from_anyhow("Failed to get decimals", e))?;
+
+        let entries = futures::future::join_all(addresses.into_iter().map(|input| async move {
+            let address = match input.parse::<Address>() {
+                Ok(address) => address,
+                Err(e) => {
+                    let error = McpServiceError::invalid_input(format!("Invalid address: {}", e));
+                    return (input.to_string(), error_entry(&error));
+                }
+            };
+
+            let entry = match self.read_balance(protocol, address, block).await {
+                Ok(balance) => serde_json::json!({ "balance": decimals.format_shares(balance) }),
+                Err(e) => error_entry(&McpServiceError::from_anyhow("Failed to get balance", e)),
+            };
+            (to_checksum(&address, None), entry)
+        }))
+        .await;
+
+        Ok(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "balances": entries.into_iter().collect::<serde_json::Map<_, _>>(),
+            "block": block.and_then(|b| b.number),
+        }))
+    }
+
+    /// Describes `owner`'s balance of `protocol`'s LST, or of MON for `None`, at the block
+    /// `receipt` was mined in, so it reflects the write even while the node's latest block
+    /// lags behind. A failed read is logged and described as empty, since the write itself
+    /// already went through.
+    async fn post_balance(
+        &self,
+        protocol: Option<LstProtocol>,
+        owner: Address,
+        receipt: &TransactionReceipt,
+    ) -> String {
+        let block = receipt
+            .block_number
+            .map(|number| BlockId::from(number.as_u64()));
+        let balance = match protocol {
+            Some(protocol) => {
+                async {
+                    let balance = protocol
+                        .read_balance(self.provider.clone(), owner, block)
+                        .await?;
+                    let decimals = protocol
+                        .decimals(self.provider.clone(), &self.decimals)
+                        .await?;
+                    anyhow::Ok(self.native.round(decimals.format_shares(balance)))
+                }
+                .await
+            }
+            None => self
+                .provider
+                .get_balance(owner, block)
+                .await
+                .map(|balance| self.native.display(balance))
+                .context("Failed to get balance"),
+        };
+        let token = protocol.map_or_else(
+            || self.native.symbol().to_string(),
+            |protocol| protocol.to_string(),
+        );
+
+        match balance {
+            Ok(balance) => format!(
+                " Post balance of {}: {} {}{}",
+                to_checksum(&owner, None),
+                balance,
+                token,
+                receipt
+                    .block_number
+                    .map(|number| format!(" at block {}", number))
+                    .unwrap_or_default(),
+            ),
+            Err(e) => {
+                tracing::warn!(error = %format!("{:#}", e), "Failed to read post balance");
+                String::new()
+            }
+        }
+    }
+
+    /// Pins the `block` query parameter of a point-in-time read, if given.
+    async fn block_param(
+        &self,
+        query: &QueryParams,
+    ) -> Result<Option<PinnedBlock>, McpServiceError> {
+        let Some(tag) = query.get("block") else {
+            return Ok(None);
+        };
+        let tag = parse_block_tag(tag)
+            .map_err(|e| McpServiceError::invalid_input(format!("Invalid block: {:#}", e)))?;
+
+        self.pin_block(tag)
+            .await
+            .map(Some)
+            .map_err(|e| McpServiceError::from_anyhow("Failed to resolve block", e))
+    }
+
+    /// Resolves `tag` to the block it refers to now, so that a read and the block number and
+    /// time it reports agree.
+    async fn pin_block(&self, tag: BlockNumber) -> anyhow::Result<PinnedBlock> {
+        let block = self
+            .provider
+            .get_block(tag)
+            .await
+            .context("Failed to get block")?
+            .with_context(|| format!("Block {} not found", tag))?;
+        let number = block.number.map(|n| n.as_u64());
+
+        Ok(PinnedBlock {
+            // A pending block may not be numbered yet, in which case the tag is kept
+            id: number.map_or(tag.into(), |n| BlockNumber::Number(n.into()).into()),
+            number,
+            timestamp: block.timestamp.as_u64(),
+        })
+    }
+
+    /// Protocol names, with the reason for any found unavailable at startup and the notice of
+    /// any the operator deprecated.
+    fn protocol_list(&self) -> serde_json::Value {
+        let unavailable = LstProtocol::ALL
+            .into_iter()
+            .filter_map(|p| {
+                self.availability.reason(p).map(|reason| {
+                    serde_json::json!({
+                        "protocol": p.to_string(),
+                        "reason": reason,
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let deprecated = LstProtocol::ALL
+            .into_iter()
+            .filter(|p| self.deprecations.is_deprecated(*p))
+            .map(|p| {
+                serde_json::json!({
+                    "protocol": p.to_string(),
+                    "notice": self.deprecations.notice(p),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "protocols": LstProtocol::ALL.map(|p| p.to_string()),
+            "unavailable": unavailable,
+            "deprecated": deprecated,
+        })
+    }
+
+    /// Builds the [`ProtocolInfo`] of `protocol`. Stats come from the snapshot, read again when
+    /// `fresh` is set, and the token's metadata, asset, pause flag and limits are read at once.
+    /// A read that fails leaves its fields `None` and is reported in `errors`, so the others
+    /// are still served.
+    async fn protocol_info(&self, protocol: LstProtocol, fresh: bool) -> ProtocolInfo {
+        let mut info = ProtocolInfo {
+            protocol: protocol.to_string(),
+            description: protocol.description(),
+            available: self.availability.is_available(protocol),
+            unavailable_reason: self.availability.reason(protocol),
+            deprecated: self.deprecations.is_deprecated(protocol),
+            deprecation_notice: self.deprecations.notice(protocol).map(String::from),
+            address: to_checksum(&protocol.address(), None),
+            token_address: to_checksum(&protocol.token_address(), None),
+            name: None,
+            symbol: None,
+            decimals: None,
+            asset: None,
+            tvl: None,
+            rate: None,
+            apr: None,
+            effective_net_apr: None,
+            fees: None,
+            withdrawal_wait_seconds: None,
+            paused: None,
+            min_deposit: None,
+            max_deposit: None,
+            deposit_unit: None,
+            as_of: None,
+            errors: BTreeMap::new(),
+        };
+        if !info.available {
+            return info;
+        }
+
+        // Limits are the same for every receiver, so they are read for the zero address
+        let (metadata, decimals, asset, currency, paused, limits, wait_seconds, snapshot) = tokio::join!(
+            protocol.token_metadata(self.provider.clone()),
+            protocol.decimals(self.provider.clone(), &self.decimals),
+            protocol.deposit_asset(self.provider.clone()),
+            protocol.deposit_currency(self.provider.clone(), &self.native, &self.decimals),
+            protocol.paused(self.provider.clone()),
+            protocol.deposit_limits(self.provider.clone(), Address::zero()),
+            protocol.withdrawal_wait_seconds(self.provider.clone()),
+            self.snapshot(fresh),
+        );
+        let stats = snapshot.and_then(|snapshot| {
+            match (snapshot.get(protocol), snapshot.error(protocol)) {
+                (Some(stats), _) => Ok(stats.clone()),
+                (None, Some(error)) => bail!("Failed to read {}: {}", protocol, error),
+                (None, None) => bail!("No snapshot for {}", protocol),
+            }
+        });
+        // Records a failed read in `errors`, keyed by `field`
+        fn read<T>(
+            errors: &mut BTreeMap<&'static str, serde_json::Value>,
+            field: &'static str,
+            context: &str,
+            result: anyhow::Result<T>,
+        ) -> Option<T> {
+            result
+                .map_err(|e| {
+                    let error = McpServiceError::from_anyhow(context, e);
+                    errors.insert(field, error_entry(&error));
+                })
+                .ok()
+        }
+        let errors = &mut info.errors;
+        let metadata = read(
+            errors,
+            "metadata",
+            "Failed to read token metadata",
+            metadata,
+        );
+        let decimals = read(errors, "decimals", "Failed to read decimals", decimals);
+        let asset = read(errors, "asset", "Failed to read deposit asset", asset);
+        let currency = read(
+            errors,
+            "deposit_unit",
+            "Failed to read deposit currency",
+            currency,
+        );
+        let paused = read(errors, "paused", "Failed to read pause state", paused);
+        let limits = read(
+            errors,
+            "deposit_limits",
+            "Failed to read deposit limits",
+            limits,
+        );
+        let wait_seconds = read(
+            errors,
+            "withdrawal_wait_seconds",
+            "Failed to read withdrawal wait",
+            wait_seconds,
+        );
+        let stats = read(errors, "stats", "Failed to read stats", stats);
+
+        if let Some((name, symbol)) = metadata {
+            info.name = Some(name);
+            info.symbol = Some(symbol);
+        }
+        info.decimals = decimals.map(|decimals| decimals.shares);
+        info.asset = asset.map(|asset| match asset {
+            Some(asset) => to_checksum(&asset, None),
+            None => self.native.symbol().to_string(),
+        });
+        // Amounts of the asset are only served along with the unit they are counted in
+        info.deposit_unit = currency
+            .as_ref()
+            .map(|currency| currency.symbol().to_string());
+        if let Some(stats) = &stats {
+            info.tvl = currency
+                .as_ref()
+                .map(|currency| currency.format(stats.stats.tvl));
+            info.rate = Some(self.native.format_rate(stats.exchange_rate));
+            info.apr = Some(stats.stats.apr);
+            info.fees = Some(ProtocolFees {
+                withdrawal_fee: stats.stats.withdrawal_fee,
+            });
+            info.as_of = Some(iso_timestamp(stats.as_of));
+        }
+        info.effective_net_apr = stats.as_ref().zip(wait_seconds).map(|(stats, wait)| {
+            effective_net_apr(stats.stats.apr, stats.stats.withdrawal_fee, wait)
+        });
+        info.withdrawal_wait_seconds = wait_seconds;
+        info.paused = paused;
+        if let Some((limits, currency)) = limits.zip(currency) {
+            info.min_deposit = limits.min.map(|min| currency.format(min));
+            info.max_deposit = limits.max.map(|max| currency.format(max));
+        }
+
+        info
+    }
+
+    async fn protocol_tvl(
+        &self,
+        protocol: LstProtocol,
+        fresh: bool,
+        block: Option<PinnedBlock>,
+    ) -> anyhow::Result<(U256, u64)> {
+        self.availability.ensure_available(protocol)?;
+
+        // Point-in-time reads bypass the snapshot, which only holds the latest values
+        if let Some(block) = block {
+            let tvl = protocol
+                .tvl(self.provider.clone(), Some(block.id))
+                .await
+                .with_context(|| block.read_context())?;
+            return Ok((tvl, block.timestamp));
+        }
+
+        let snapshot = self.snapshot(fresh).await?;
+        let (tvl, as_of) = match (snapshot.get(protocol), snapshot.error(protocol)) {
+            (Some(snapshot), _) => (snapshot.stats.tvl, snapshot.as_of),
+            (None, Some(error)) => bail!("Failed to read {}: {}", protocol, error),
+            (None, None) => bail!("No snapshot for {}", protocol),
+        };
+
+        Ok((tvl, as_of))
+    }
+
+    /// Exchange rate of `protocol` with the time it was read, from the snapshot unless `block`
+    /// pins the read to a block.
+    async fn protocol_rate(
+        &self,
+        protocol: LstProtocol,
+        fresh: bool,
+        block: Option<PinnedBlock>,
+    ) -> anyhow::Result<(U256, u64)> {
+        self.availability.ensure_available(protocol)?;
+
+        if let Some(block) = block {
+            let rate = protocol
+                .exchange_rate(self.provider.clone(), &self.decimals, Some(block.id))
+                .await
+                .with_context(|| block.read_context())?;
+            return Ok((rate, block.timestamp));
+        }
+
+        let snapshot = self.snapshot(fresh).await?;
+        match (snapshot.get(protocol), snapshot.error(protocol)) {
+            (Some(snapshot), _) => Ok((snapshot.exchange_rate, snapshot.as_of)),
+            (None, Some(error)) => bail!("Failed to read {}: {}", protocol, error),
+            (None, None) => bail!("No snapshot for {}", protocol),
+        }
+    }
+
+    /// Returns the `limit` most recent activities of `owner` before `before`, oldest first,
+    /// with a cursor to the older ones when more remain. A `from_block` past the lookback
+    /// limit is moved up to it, with a warning.
+    async fn address_history(
+        &self,
+        protocol: LstProtocol,
+        owner: Address,
+        from_block: Option<u64>,
+        before: Option<HistoryCursor>,
+        limit: usize,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.availability.ensure_available(protocol)?;
+
+        let latest = self
+            .provider
+            .get_block_number()
+            .await
+            .context("Failed to get block number")?
+            .as_u64();
+        let earliest = latest.saturating_sub(self.event_scan.max_lookback_blocks());
+        let warnings: Vec<Warning> = from_block
+            .filter(|&block| block < earliest)
+            .map(|block| {
+                Warning::new(
+                    WarningCode::RangeClamped,
+                    format!(
+                        "History is scanned over at most the last {} blocks, so the scan starts at block {} rather than {}; older activities are left out",
+                        self.event_scan.max_lookback_blocks(),
+                        earliest,
+                        block
+                    ),
+                )
+            })
+            .into_iter()
+            .collect();
+        let from_block = from_block.unwrap_or(earliest).max(earliest);
+        let to_block = before.map_or(latest, |cursor| cursor.block_number.min(latest));
+
+        let decimals = protocol
+            .decimals(self.provider.clone(), &self.decimals)
+            .await?;
+        let mut activities = protocol
+            .history(
+                self.provider.clone(),
+                owner,
+                from_block,
+                to_block,
+                self.event_scan.chunk_size(),
+            )
+            .await
+            .context("Failed to get history")?;
+        if let Some(cursor) = before {
+            activities.retain(|a| cursor.precedes(a));
+        }
+
+        let truncated = activities.len() > limit;
+        let activities = &activities[activities.len().saturating_sub(limit)..];
+        let next_cursor = activities
+            .first()
+            .filter(|_| truncated)
+            .map(|a| HistoryCursor::of(a).to_string());
+
+        let activities = activities
+            .iter()
+            .map(|a| {
+                Ok(serde_json::json!({
+                    "type": a.kind.to_string(),
+                    "assets": decimals.format_assets(a.assets),
+                    "shares": decimals.format_shares(a.shares),
+                    "addresses": a.addresses_json(),
+                    "block": a.block_number,
+                    "tx_hash": encode_prefixed(a.transaction_hash),
+                }))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "address": to_checksum(&owner, None),
+            "from_block": from_block,
+            "to_block": to_block,
+            "activities": activities,
+            "truncated": truncated,
+            "next_cursor": next_cursor,
+            "warnings": warnings_json(&warnings),
+        }))
+    }
+
+    /// Reads a redeem request along with the queue and the latest block's timestamp, to tell
+    /// whether it can be claimed yet. `None` when no such request exists.
+    async fn redeem_status(
+        &self,
+        protocol: LstProtocol,
+        request_id: U256,
+    ) -> anyhow::Result<Option<RedeemStatus>> {
+        self.availability.ensure_available(protocol)?;
+
+        let latest = self
+            .provider
+            .get_block(BlockNumber::Latest)
+            .await
+            .context("Failed to get latest block")?
+            .context("Latest block not found")?;
+        let latest_number = latest.number.context("Latest block is pending")?;
+        // The queue as of `claim_confirmations` blocks ago tells whether processing is final
+        let confirmed_number = latest_number.saturating_sub(self.claim_confirmations.into());
+
+        let (request, queue, confirmed_queue) = tokio::try_join!(
+            protocol.redeem_request(self.provider.clone(), request_id),
+            protocol.redeem_queue(self.provider.clone(), latest_number.into()),
+            protocol.redeem_queue(self.provider.clone(), confirmed_number.into()),
+        )?;
+
+        Ok(request.map(|request| RedeemStatus {
+            processed_confirmed: confirmed_queue.is_processed(request_id),
+            request,
+            queue,
+            now: latest.timestamp.as_u64(),
+        }))
+    }
+
+    async fn redeem_request(
+        &self,
+        protocol: LstProtocol,
+        request_id: U256,
+    ) -> anyhow::Result<Option<serde_json::Value>> {
+        let Some(RedeemStatus {
+            request,
+            queue,
+            processed_confirmed,
+            now,
+        }) = self.redeem_status(protocol, request_id).await?
+        else {
+            return Ok(None);
+        };
+        let decimals = protocol
+            .decimals(self.provider.clone(), &self.decimals)
+            .await?;
+
+        Ok(Some(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "request_id": DecimalU256(request.request_id),
+            "shares": decimals.format_shares(request.shares),
+            "assets": decimals.format_assets(request.assets),
+            "controller": to_checksum(&request.controller, None),
+            "claimed": request.claimed,
+            "timestamp": request.timestamp,
+            "claimable_at": request.claimable_at,
+            "claimable_at_iso": iso_timestamp(request.claimable_at),
+            "claimable_in_seconds": request.remaining_wait(now),
+            // Processing is only required once confirmations are, to keep the default as it was
+            "is_claimable": request.is_claimable(now)
+                && (self.claim_confirmations == 0 || processed_confirmed),
+            "processed": queue.is_processed(request_id),
+            "processed_confirmed": processed_confirmed,
+            "confirmations_required": self.claim_confirmations,
+            "queue_position": DecimalU256(queue.position(request_id)),
+            "last_processed_request_id": DecimalU256(queue.last_processed_request_id),
+            "next_request_id": DecimalU256(queue.next_request_id),
+        })))
+    }
+
+    /// When a redeem request can be claimed: its wait time must have passed and the queue must
+    /// have processed it at least `claim_confirmations` blocks ago. `None` when no such request
+    /// exists.
+    async fn claim_estimate(
+        &self,
+        protocol: LstProtocol,
+        request_id: U256,
+    ) -> anyhow::Result<Option<serde_json::Value>> {
+        let Some(status) = self.redeem_status(protocol, request_id).await? else {
+            return Ok(None);
+        };
+        let claimable_now = status.claimable_now();
+        let RedeemStatus {
+            request,
+            queue,
+            processed_confirmed,
+            now,
+        } = status;
+        let processed = queue.is_processed(request_id);
+
+        Ok(Some(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "request_id": DecimalU256(request_id),
+            "claimed": request.claimed,
+            "claimable_at": request.claimable_at,
+            "claimable_at_iso": iso_timestamp(request.claimable_at),
+            "remaining_seconds": request.remaining_wait(now),
+            "processed": processed,
+            "queue_position": DecimalU256(queue.position(request_id)),
+            "processed_confirmed": processed_confirmed,
+            "confirmations_required": self.claim_confirmations,
+            "claimable_now": claimable_now,
+        })))
+    }
+
+    async fn tx_status_json(&self, tx_hash: H256) -> anyhow::Result<Option<serde_json::Value>> {
+        let (status, latest) =
+            tokio::try_join!(transaction_status(&self.provider, tx_hash), async {
+                self.provider
+                    .get_block_number()
+                    .await
+                    .context("Failed to get latest block number")
+            },)?;
+        let Some(status) = status else {
+            return Ok(None);
+        };
+
+        let fee = status.fee().map(|fee| self.native.format(fee));
+        Ok(Some(match status {
+            TxStatus::Pending => serde_json::json!({
+                "tx_hash": encode_prefixed(tx_hash),
+                "status": "pending",
+                "block_number": null,
+                "confirmations": 0,
+                "gas_used": null,
+                "effective_gas_price": null,
+                "fee": null,
+                "fee_unit": self.native.symbol(),
+            }),
+            TxStatus::Mined {
+                block_number,
+                success,
+                gas_used,
+                effective_gas_price,
+            } => serde_json::json!({
+                "tx_hash": encode_prefixed(tx_hash),
+                "status": match success {
+                    Some(true) => "success",
+                    Some(false) => "failed",
+                    None => "mined",
+                },
+                "block_number": block_number,
+                "confirmations": latest.as_u64().saturating_sub(block_number) + 1,
+                "gas_used": gas_used.map(DecimalU256),
+                "effective_gas_price": effective_gas_price.map(DecimalU256),
+                "fee": fee,
+                "fee_unit": self.native.symbol(),
+            }),
+        }))
+    }
+
+    /// Checks each redeem request can be claimed the same way `claim_eta` judges
+    /// `claimable_now`, so a request the queue has not processed `claim_confirmations` blocks
+    /// ago fails before anything is sent.
+    async fn ensure_claimable(
+        &self,
+        protocol: LstProtocol,
+        request_ids: &[U256],
+    ) -> anyhow::Result<()> {
+        let statuses = futures::future::try_join_all(
+            request_ids
+                .iter()
+                .map(|&request_id| self.redeem_status(protocol, request_id)),
+        )
+        .await?;
+        for (&request_id, status) in request_ids.iter().zip(statuses) {
+            let Some(status) = status else {
+                bail!("Redeem request {} not found", request_id);
+            };
+            status.ensure_claimable(self.claim_confirmations)?;
+        }
+        Ok(())
+    }
+
+    /// Checks a redeem request every [`REDEEM_POLL_INTERVAL`], reporting each check as
+    /// progress, until it can be claimed the same way `claim_eta` judges `claimable_now`. Gives
+    /// up after `max_wait` with where the request stands, so the caller can resume the wait.
+    /// A request `controller` can't redeem fails on the first check, before any waiting.
+    /// Returns `None` when no such request exists.
+    async fn wait_until_claimable(
+        &self,
+        protocol: LstProtocol,
+        request_id: U256,
+        controller: Address,
+        max_wait: Duration,
+        progress: &Progress,
+    ) -> anyhow::Result<Option<RedeemWait>> {
+        wait_for_claimable(
+            || self.redeem_status(protocol, request_id),
+            protocol,
+            controller,
+            max_wait,
+            progress,
+        )
+        .await
+    }
+
+    async fn preview_stake(
+        &self,
+        protocol: LstProtocol,
+        assets: U256,
+    ) -> anyhow::Result<serde_json::Value> {
+        self.availability.ensure_available(protocol)?;
+
+        let shares = protocol
+            .preview_stake(self.provider.clone(), &self.decimals, assets)
+            .await?;
+
+        let decimals = protocol
+            .decimals(self.provider.clone(), &self.decimals)
+            .await?;
+
+        // Too small an amount can round down to zero shares, leaving no rate to imply
+        let implied_exchange_rate = if shares.is_zero() {
+            None
+        } else {
+            let rate = decimals.rate(mul_div(assets, decimals.one_share(), shares)?)?;
+            Some(self.native.format_rate(rate))
+        };
+
+        Ok(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "amount": self.native.format(assets),
+            "amount_unit": self.native.symbol(),
+            "expected_shares": decimals.format_shares(shares),
+            "implied_exchange_rate": implied_exchange_rate,
+        }))
+    }
+
+    async fn address_book(&self, protocol: LstProtocol) -> anyhow::Result<serde_json::Value> {
+        self.availability.ensure_available(protocol)?;
+
+        let labels = protocol
+            .labeled_addresses(self.provider.clone())
+            .await
+            .context("Failed to get labeled addresses")?;
+
+        let addresses = labels
+            .iter()
+            .map(|(role, address)| {
+                serde_json::json!({
+                    "role": role,
+                    "address": to_checksum(address, None),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "addresses": addresses,
+        }))
+    }
+
+    /// Lists the functions, events and errors of each of the protocol's contracts with their
+    /// selectors, from the ABIs the bindings were generated from.
+    fn abi_listing(&self, protocol: LstProtocol) -> serde_json::Value {
+        let addresses: HashMap<_, _> = protocol.contracts().into_iter().collect();
+        let contracts = protocol
+            .abis()
+            .into_iter()
+            .map(|(role, abi)| {
+                let functions = abi
+                    .functions()
+                    .map(|function| {
+                        serde_json::json!({
+                            "name": function.name,
+                            "signature": canonical_signature(
+                                &function.name,
+                                function.inputs.iter().map(|input| &input.kind),
+                            ),
+                            "selector": encode_prefixed(function.short_signature()),
+                            "state_mutability": function.state_mutability,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let events = abi
+                    .events()
+                    .map(|event| {
+                        serde_json::json!({
+                            "name": event.name,
+                            "signature": canonical_signature(
+                                &event.name,
+                                event.inputs.iter().map(|input| &input.kind),
+                            ),
+                            "topic": encode_prefixed(event.signature()),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let errors = abi
+                    .errors()
+                    .map(|error| {
+                        serde_json::json!({
+                            "name": error.name,
+                            "signature": canonical_signature(
+                                &error.name,
+                                error.inputs.iter().map(|input| &input.kind),
+                            ),
+                            "selector": encode_prefixed(&error.signature()[..4]),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                serde_json::json!({
+                    "role": role,
+                    "address": addresses.get(role).map(|address| to_checksum(address, None)),
+                    "functions": functions,
+                    "events": events,
+                    "errors": errors,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "protocol": protocol.to_string(),
+            "contracts": contracts,
+        })
+    }
+
+    async fn oracle(&self, protocol: LstProtocol) -> anyhow::Result<serde_json::Value> {
+        self.availability.ensure_available(protocol)?;
+
+        let status = protocol.oracle_status(self.provider.clone()).await?;
+        let stale = status.is_stale(self.oracle_stale_blocks);
+        let warnings: Vec<Warning> = stale
+            .then(|| {
+                Warning::new(
+                    WarningCode::OracleStale,
+                    format!(
+                        "The {} oracle trails the chain head by {} blocks, more than the {} block threshold; exchange rates may be outdated",
+                        protocol,
+                        status.lag(),
+                        self.oracle_stale_blocks
+                    ),
+                )
+            })
+            .into_iter()
+            .collect();
+
+        Ok(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "last_processed_block": status.last_processed_block,
+            "current_block": status.current_block,
+            "lag_blocks": status.lag(),
+            "stale_threshold_blocks": self.oracle_stale_blocks,
+            "stale": stale,
+            "warnings": warnings_json(&warnings),
+        }))
+    }
+
+    fn supported_actions(&self, protocol: LstProtocol) -> serde_json::Value {
+        let capabilities = protocol.capabilities();
+
+        serde_json::json!({
+            "protocol": protocol.to_string(),
+            "withdrawal": capabilities.withdrawal.to_string(),
+            "deposit": capabilities.deposit.to_string(),
+            "actions": {
+                "stake": true,
+                "unstake": true,
+                "unstake_all": true,
+                "preview_unstake": true,
+                "claim_redeem": capabilities.claim_redeem,
+                // None of the contracts can withdraw a redeem request once it is made
+                "cancel_redeem": false,
+            },
+            "stake_for_receiver": capabilities.stake_for_receiver,
+            "unstake_for_owner": capabilities.unstake_for_owner,
+        })
+    }
+
+    async fn compare_protocols(&self, fresh: bool) -> anyhow::Result<serde_json::Value> {
+        let snapshot = self.snapshot(fresh).await?;
+        let stats = snapshot.protocols.iter().map(|p| p.stats.clone()).collect();
+
+        let (ranked, recommended) = rank_protocols(stats);
+
+        let protocols = ranked
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let exchange_rate = snapshot
+                    .get(s.protocol)
+                    .map(|p| p.exchange_rate)
+                    .unwrap_or_default();
+                Ok(serde_json::json!({
+                    "rank": i + 1,
+                    "protocol": s.protocol.to_string(),
+                    "apr": s.apr,
+                    "tvl": self.native.format(s.tvl),
+                    "exchange_rate": self.native.format_rate(exchange_rate),
+                    "withdrawal_fee": s.withdrawal_fee,
+                    "score": s.score(),
+                }))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let errors = snapshot
+            .errors
+            .iter()
+            .map(|e| {
+                serde_json::json!({
+                    "protocol": e.protocol.to_string(),
+                    "error": e.error,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::json!({
+            "protocols": protocols,
+            "recommended": recommended.map(|p| p.to_string()),
+            "errors": errors,
+            "as_of": iso_timestamp(snapshot.as_of),
+        }))
+    }
+
+    /// Quotes staking `assets` MON with every protocol in the snapshot: the shares minted, what
+    /// they would redeem for right away after the withdrawal fee, and a score of APR plus that
+    /// round-trip gain or loss in percent, so the amount itself feeds the ranking. Protocols
+    /// that fail to quote are listed under `errors`. `assets` must be non-zero and fit in a
+    /// `u128`.
+    async fn quote_routes(&self, assets: U256) -> anyhow::Result<serde_json::Value> {
+        let snapshot = self.snapshot(false).await?;
+
+        let quotes = futures::future::join_all(snapshot.protocols.iter().map(|p| async move {
+            let protocol = p.stats.protocol;
+            let quote = async {
+                let shares = protocol
+                    .preview_stake(self.provider.clone(), &self.decimals, assets)
+                    .await?;
+                let preview = protocol
+                    .preview_unstake(self.provider.clone(), shares)
+                    .await?;
+                anyhow::Ok((shares, preview.net_assets))
+            }
+            .await;
+            (&p.stats, quote)
+        }))
+        .await;
+
+        let mut routes = Vec::new();
+        let mut errors = snapshot
+            .errors
+            .iter()
+            .map(|e| {
+                serde_json::json!({
+                    "protocol": e.protocol.to_string(),
+                    "error": e.error,
+                })
+            })
+            .collect::<Vec<_>>();
+        for (stats, quote) in quotes {
+            match quote {
+                Ok((shares, net_assets)) => {
+                    let round_trip = (net_assets.low_u128() as f64 - assets.as_u128() as f64)
+                        / assets.as_u128() as f64
+                        * 100.0;
+                    routes.push((stats, shares, net_assets, stats.apr + round_trip));
+                }
+                Err(e) => errors.push(serde_json::json!({
+                    "protocol": stats.protocol.to_string(),
+                    "error": format!("{:#}", e),
+                })),
+            }
+        }
+
+        routes.sort_by(|a, b| {
+            b.3.total_cmp(&a.3)
+                .then_with(|| b.0.tvl.cmp(&a.0.tvl))
+                .then_with(|| a.0.protocol.to_string().cmp(&b.0.protocol.to_string()))
+        });
+        let min_tvl = U256::from(MIN_RECOMMENDED_TVL) * U256::exp10(18);
+        let recommended = routes
+            .iter()
+            .find(|(stats, ..)| stats.tvl >= min_tvl)
+            .map(|(stats, ..)| stats.protocol.to_string());
+
+        let mut protocols = Vec::with_capacity(routes.len());
+        for (i, (stats, shares, net_assets, score)) in routes.into_iter().enumerate() {
+            let decimals = stats
+                .protocol
+                .decimals(self.provider.clone(), &self.decimals)
+                .await?;
+            protocols.push(serde_json::json!({
+                "rank": i + 1,
+                "protocol": stats.protocol.to_string(),
+                "expected_shares": decimals.format_shares(shares),
+                "value_after_fees": self.native.format(net_assets),
+                "apr": stats.apr,
+                "withdrawal_fee": stats.withdrawal_fee,
+                "tvl": self.native.format(stats.tvl),
+                "score": score,
+            }));
+        }
+
+        Ok(serde_json::json!({
+            "amount": self.native.format(assets),
+            "amount_unit": self.native.symbol(),
+            "protocols": protocols,
+            "recommended": recommended,
+            "errors": errors,
+            "as_of": iso_timestamp(snapshot.as_of),
+        }))
+    }
+
+    /// Projects `protocol`'s APR after a deposit of `assets` from the snapshot's APR and TVL,
+    /// holding the rewards paid per year constant.
+    async fn apr_projection(
+        &self,
+        protocol: LstProtocol,
+        assets: U256,
+    ) -> anyhow::Result<serde_json::Value> {
+        let snapshot = self.snapshot(false).await?;
+        let (stats, as_of) = match (snapshot.get(protocol), snapshot.error(protocol)) {
+            (Some(snapshot), _) => (&snapshot.stats, snapshot.as_of),
+            (None, Some(error)) => bail!("Failed to read {}: {}", protocol, error),
+            (None, None) => bail!("No snapshot for {}", protocol),
+        };
+        let projected_apr = diluted_apr(stats.apr, stats.tvl, assets);
+
+        Ok(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "amount": self.native.format(assets),
+            "amount_unit": self.native.symbol(),
+            "current_apr": stats.apr,
+            "projected_apr": projected_apr,
+            "apr_change": projected_apr - stats.apr,
+            "tvl": self.native.format(stats.tvl),
+            "tvl_after": self.native.format(stats.tvl.saturating_add(assets)),
+            "estimate": true,
+            "assumptions": [
+                format!(
+                    "The current APR is the exchange-rate growth over the last {} blocks, annualised",
+                    APR_LOOKBACK_BLOCKS
+                ),
+                "The rewards paid per year stay the same and are shared across the TVL including the deposit",
+                "No other deposits or withdrawals happen and fees are unchanged",
+            ],
+            "as_of": iso_timestamp(as_of),
+        }))
+    }
+
+    /// Blends the APR of `owner`'s positions, weighting each protocol by the MON value held in
+    /// it at the snapshot's exchange rate. Balances are read live and protocols that fail to
+    /// read are listed under `errors`, along with those missing from the snapshot.
+    async fn portfolio_yield(&self, owner: Address) -> anyhow::Result<serde_json::Value> {
+        let snapshot = self.snapshot(false).await?;
+
+        let reads = futures::future::join_all(snapshot.protocols.iter().map(|p| async move {
+            let protocol = p.stats.protocol;
+            let read = async {
+                let balance = self.read_balance(protocol, owner, None).await?;
+                let decimals = protocol
+                    .decimals(self.provider.clone(), &self.decimals)
+                    .await?;
+                anyhow::Ok((balance, decimals))
+            }
+            .await;
+            (p, read)
+        }))
+        .await;
+
+        let mut errors = snapshot
+            .errors
+            .iter()
+            .map(|e| {
+                serde_json::json!({
+                    "protocol": e.protocol.to_string(),
+                    "error": e.error,
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut holdings = Vec::new();
+        for (p, read) in reads {
+            match read {
+                Ok((balance, decimals)) if !balance.is_zero() => holdings.push((
+                    p.stats.protocol,
+                    decimals.format_shares(balance),
+                    decimals.value_of(balance, p.exchange_rate),
+                    p.stats.apr,
+                )),
+                Ok(_) => {}
+                Err(e) => errors.push(serde_json::json!({
+                    "protocol": p.stats.protocol.to_string(),
+                    "error": format!("{:#}", e),
+                })),
+            }
+        }
+
+        let weights = holdings
+            .iter()
+            .map(|(_, _, value, apr)| (*value, *apr))
+            .collect::<Vec<_>>();
+        let total_value = weights.iter().fold(U256::zero(), |total, (value, _)| {
+            total.saturating_add(*value)
+        });
+
+        let mut positions = Vec::with_capacity(holdings.len());
+        for (protocol, balance, value, apr) in holdings {
+            positions.push(serde_json::json!({
+                "protocol": protocol.to_string(),
+                "balance": balance,
+                "value": self.native.format(value),
+                "apr": apr,
+            }));
+        }
+
+        Ok(serde_json::json!({
+            "address": to_checksum(&owner, None),
+            "positions": positions,
+            "total_value": self.native.format(total_value),
+            "value_unit": self.native.symbol(),
+            "weighted_apr": weighted_apr(&weights),
+            "errors": errors,
+            "as_of": iso_timestamp(snapshot.as_of),
+        }))
+    }
+
+    /// Refreshes the snapshot of `only`, or of every protocol, and reports when each protocol
+    /// it now holds was read.
+    async fn refreshed_snapshots(
+        &self,
+        only: Option<LstProtocol>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let snapshot = self.refresh_protocols(only).await?;
+        let protocols = snapshot
+            .protocols
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "protocol": p.stats.protocol.to_string(),
+                    "refreshed": only.is_none_or(|only| only == p.stats.protocol),
+                    "as_of": iso_timestamp(p.as_of),
+                })
+            })
+            .collect::<Vec<_>>();
+        let errors = snapshot
+            .errors
+            .iter()
+            .map(|e| {
+                serde_json::json!({
+                    "protocol": e.protocol.to_string(),
+                    "error": e.error,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::json!({
+            "protocols": protocols,
+            "errors": errors,
+            "as_of": iso_timestamp(snapshot.as_of),
+        }))
+    }
+
+    /// Runs the checks of the `diagnostics` tool at once, each under its own timeout so that
+    /// one hanging call doesn't hold back the rest of the report.
+    async fn diagnostics_report(&self, address: Option<Address>) -> serde_json::Value {
+        let provider = self.provider.clone();
+        let mut checks: Vec<(String, BoxFuture<'_, anyhow::Result<String>>)> = vec![
+            (
+                "rpc".to_string(),
+                async {
+                    let block = provider
+                        .get_block_number()
+                        .await
+                        .context("Failed to get block number")?;
+                    Ok(format!("reachable, at block {}", block))
+                }
+                .boxed(),
+            ),
+            (
+                "chain_id".to_string(),
+                async {
+                    let chain_id = provider
+                        .get_chainid()
+                        .await
+                        .context("Failed to get chain id")?
+                        .as_u64();
+                    let expected = self.chain_guard.expected_chain_id();
+                    if chain_id != expected {
+                        bail!("Chain id is {}, expected {}", chain_id, expected);
+                    }
+                    Ok(format!("chain id {} as expected", chain_id))
+                }
+                .boxed(),
+            ),
+        ];
+        for protocol in LstProtocol::ALL {
+            let provider = provider.clone();
+            checks.push((
+                format!("protocol {} code", protocol),
+                async move {
+                    match protocol.check_deployed(provider).await? {
+                        Some(reason) => Err(anyhow::anyhow!("{}", reason)),
+                        None => Ok("contracts deployed".to_string()),
+                    }
+                }
+                .boxed(),
+            ));
+            let provider = self.provider.clone();
+            checks.push((
+                format!("protocol {} total_assets", protocol),
+                async move {
+                    let (tvl, decimals) = tokio::try_join!(
+                        protocol.tvl(provider.clone(), None),
+                        protocol.decimals(provider.clone(), &self.decimals),
+                    )?;
+                    Ok(format!(
+                        "{} {}",
+                        decimals.format_assets(tvl),
+                        self.native.symbol()
+                    ))
+                }
+                .boxed(),
+            ));
+        }
+        if let Some(address) = address {
+            let provider = self.provider.clone();
+            checks.push((
+                format!("nonce {}", to_checksum(&address, None)),
+                async move {
+                    let latest = provider
+                        .get_transaction_count(address, Some(BlockNumber::Latest.into()))
+                        .await
+                        .context("Failed to get latest nonce")?;
+                    let pending = provider
+                        .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+                        .await
+                        .context("Failed to get pending nonce")?;
+                    if pending > latest {
+                        bail!(
+                            "{} transactions pending from nonce {}; new ones queue behind them \
+                             until they are mined, sped up or cancelled",
+                            pending - latest,
+                            latest
+                        );
+                    }
+                    Ok(format!("in sync at nonce {}", latest))
+                }
+                .boxed(),
+            ));
+        }
+
+        let (names, checks): (Vec<_>, Vec<_>) = checks.into_iter().unzip();
+        let results = futures::future::join_all(
+            checks
+                .into_iter()
+                .map(|check| tokio::time::timeout(DIAGNOSTIC_CHECK_TIMEOUT, check)),
+        )
+        .await;
+        let transport = self.provider.provider().as_ref();
+        let checks = names
+            .into_iter()
+            .zip(results)
+            .map(|(name, result)| {
+                let (ok, detail) = match result {
+                    Ok(Ok(detail)) => (true, detail),
+                    Ok(Err(e)) => (false, transport.redact_urls(&format!("{:#}", e))),
+                    Err(_) => (
+                        false,
+                        format!("Timed out after {:?}", DIAGNOSTIC_CHECK_TIMEOUT),
+                    ),
+                };
+                serde_json::json!({ "name": name, "ok": ok, "detail": detail })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "ok": checks.iter().all(|check| check["ok"] == true),
+            "rpc_endpoint": transport.active_endpoint(),
+            "checks": checks,
+        })
+    }
+
+    #[tool(
+        description = "Preview the assets received for unstaking an amount of LST tokens (shares), after the withdrawal fee: MON, or the vault's ERC-20 asset for protocols taking one"
+    )]
+    async fn preview_unstake(
+        &self,
+        #[tool(aggr)] PreviewUnstakeRequest {
+            protocol,
+            amount,
+            amount_unit,
+        }: PreviewUnstakeRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let amount_warning = amount.warning();
+        let amount = String::from(amount);
+        let decimals = protocol
+            .decimals(self.provider.clone(), &self.decimals)
+            .await
+            .map_err(|e| McpServiceError::from_anyhow("Failed to get decimals", e))?;
+        let parsed_amount =
+            parse_units(&amount, u32::from(decimals.of(amount_unit))).map_err(|e| {
+                McpServiceError::invalid_input(format!(
+                    "Failed to parse amount '{}': {}",
+                    amount, e
+                ))
+            })?;
+        let amount_u256: U256 = parsed_amount.into();
+
+        let provider = self.provider.clone();
+        let cache = self.decimals.clone();
+        let preview = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("preview_unstake"),
+                async move {
+                    let shares = protocol
+                        .to_shares(provider.clone(), &cache, amount_u256, amount_unit)
+                        .await?;
+                    protocol.preview_unstake(provider, shares).await
+                }
+                .instrument(tracing::info_span!("tool_call", tool = "preview_unstake")),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Preview failed", e))?;
+
+        // Unstaking pays out the asset deposits are made in
+        let currency = self.deposit_currency(protocol).await?;
+        let mut warnings = self.protocol_warnings(protocol);
+        warnings.extend(amount_warning);
+        warnings.extend(withdrawal_fee_warning(
+            preview.gross_assets,
+            preview.withdrawal_fee,
+            &currency,
+        ));
+
+        Ok(CallToolResult::success(vec![Content::json(
+            serde_json::json!({
+                "protocol": protocol.to_string(),
+                "amount": amount,
+                "amount_unit": amount_unit.to_string(),
+                "shares": decimals.format_shares(preview.shares),
+                "gross_assets": currency.format(preview.gross_assets),
+                "withdrawal_fee": currency.format(preview.withdrawal_fee),
+                "net_assets": currency.format(preview.net_assets),
+                "asset_unit": currency.symbol(),
+                "warnings": warnings_json(&warnings),
+            }),
+        )?]))
+    }
+
+    #[tool(
+        description = "Quote staking an exact amount of MON with each protocol: expected shares, MON value after fees if redeemed, APR and a score combining both, ranked best first"
+    )]
+    async fn quote_route(
+        &self,
+        #[tool(aggr)] QuoteRouteRequest { amount }: QuoteRouteRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let assets = self.native.parse(amount.as_str())?;
+        if assets.is_zero() || assets > U256::from(u128::MAX) {
+            return Err(McpServiceError::invalid_input(format!(
+                "Amount must be greater than zero and at most {} {}",
+                self.native.format(U256::from(u128::MAX)),
+                self.native.symbol()
+            ))
+            .into());
+        }
+
+        let quote = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("quote_route"),
+                self.quote_routes(assets)
+                    .instrument(tracing::info_span!("tool_call", tool = "quote_route")),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Quote failed", e))?;
+
+        let mut content = vec![Content::json(quote)?];
+        push_warnings(&mut content, amount.warning().as_slice())?;
+
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "Estimate when a redeem request can be claimed: the time it matures, the seconds remaining and whether it can be claimed now"
+    )]
+    async fn claim_eta(
+        &self,
+        #[tool(aggr)] ClaimEtaRequest {
+            protocol,
+            request_id,
+        }: ClaimEtaRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let request_id = U256::from_dec_str(&request_id).map_err(|e| {
+            McpServiceError::invalid_input(format!("Invalid request id '{}': {}", request_id, e))
+        })?;
+
+        let eta = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("claim_eta"),
+                self.claim_estimate(protocol, request_id)
+                    .instrument(tracing::info_span!("tool_call", tool = "claim_eta")),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Failed to estimate claim time", e))?
+            .ok_or_else(|| {
+                McpServiceError::NotFound(format!("Redeem request {} not found", request_id))
+            })?;
+
+        let mut content = vec![Content::json(eta)?];
+        push_warnings(&mut content, &self.protocol_warnings(protocol))?;
+
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "Estimate a protocol's APR after depositing an amount of MON, assuming its yearly rewards stay the same and are shared across the larger TVL. The result is an estimate, returned with its assumptions"
+    )]
+    async fn estimate_apr_after_deposit(
+        &self,
+        #[tool(aggr)]
+        EstimateAprAfterDepositRequest { protocol, amount }: EstimateAprAfterDepositRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let assets = self.native.parse(amount.as_str())?;
+        if assets.is_zero() {
+            return Err(McpServiceError::invalid_input("Amount must be greater than zero").into());
+        }
+
+        let projection = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("estimate_apr_after_deposit"),
+                self.apr_projection(protocol, assets)
+                    .instrument(tracing::info_span!(
+                        "tool_call",
+                        tool = "estimate_apr_after_deposit"
+                    )),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Failed to estimate APR", e))?;
+
+        let mut warnings = self.protocol_warnings(protocol);
+        warnings.extend(amount.warning());
+        let mut content = vec![Content::json(projection)?];
+        push_warnings(&mut content, &warnings)?;
+
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "Call a view or pure function not wrapped by another tool on one of a protocol's registered contracts, given its human-readable signature, and return the decoded outputs. Never sends a transaction"
+    )]
+    async fn raw_view(
+        &self,
+        #[tool(aggr)] RawViewRequest {
+            protocol,
+            contract,
+            signature,
+            args,
+        }: RawViewRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let (protocol, (role, address)) = match contract {
+            Some(contract) => {
+                let address = parse_address_or(Some(contract), "contract", Address::zero())?;
+                let candidates = match protocol {
+                    Some(protocol) => vec![protocol],
+                    None => LstProtocol::ALL.to_vec(),
+                };
+                candidates
+                    .iter()
+                    .find_map(|protocol| {
+                        protocol
+                            .contracts()
+                            .into_iter()
+                            .find(|(_, registered)| *registered == address)
+                            .map(|contract| (*protocol, contract))
+                    })
+                    .ok_or_else(|| {
+                        let registered = candidates
+                            .iter()
+                            .flat_map(|protocol| {
+                                protocol
+                                    .contracts()
+                                    .into_iter()
+                                    .map(move |(role, address)| {
+                                        format!(
+                                            "{} {} {}",
+                                            protocol,
+                                            role,
+                                            to_checksum(&address, None)
+                                        )
+                                    })
+                            })
+                            .collect::<Vec<_>>();
+                        McpServiceError::invalid_input(format!(
+                            "{} is not a registered contract; raw_view only calls {}",
+                            to_checksum(&address, None),
+                            registered.join(", ")
+                        ))
+                    })?
+            }
+            None => {
+                let protocol = self.resolve_protocol(protocol)?;
+                let contract = protocol.contracts().first().copied().ok_or_else(|| {
+                    McpServiceError::Unsupported(format!("{} has no contracts to call", protocol))
+                })?;
+                (protocol, contract)
+            }
+        };
+        self.ensure_available(protocol)?;
+
+        let function = AbiParser::default()
+            .parse_function(&signature)
+            .map_err(|e| {
+                McpServiceError::invalid_input(format!(
+                    "Invalid function signature '{}': {}",
+                    signature, e
+                ))
+            })?;
+        if !matches!(
+            function.state_mutability,
+            StateMutability::View | StateMutability::Pure
+        ) {
+            return Err(McpServiceError::invalid_input(format!(
+                "{} is not declared view or pure; raw_view only calls functions that cannot change state, e.g. `function {}(...) view returns (...)`",
+                function.name, function.name
+            ))
+            .into());
+        }
+        if args.len() != function.inputs.len() {
+            return Err(McpServiceError::invalid_input(format!(
+                "{} takes {} argument(s) but {} were given",
+                function.name,
+                function.inputs.len(),
+                args.len()
+            ))
+            .into());
+        }
+
+        let tokens = function
+            .inputs
+            .iter()
+            .zip(&args)
+            .map(|(param, arg)| {
+                LenientTokenizer::tokenize(&param.kind, arg).map_err(|e| {
+                    McpServiceError::invalid_input(format!(
+                        "Invalid argument '{}' for {} {}: {}",
+                        arg, param.kind, param.name, e
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = function.encode_input(&tokens).map_err(|e| {
+            McpServiceError::invalid_input(format!("Failed to encode arguments: {}", e))
+        })?;
+
+        let call = TransactionRequest::new().to(address).data(data).into();
+        let output = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("raw_view"),
+                self.provider
+                    .call(&call, None)
+                    .instrument(tracing::info_span!("tool_call", tool = "raw_view")),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Call failed", e.into()))?;
+        let values = function.decode_output(&output).map_err(|e| {
+            McpServiceError::invalid_input(format!(
+                "Failed to decode the output as ({}): {}",
+                function
+                    .outputs
+                    .iter()
+                    .map(|param| param.kind.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                e
+            ))
+        })?;
+
+        let outputs = function
+            .outputs
+            .iter()
+            .zip(values)
+            .map(|(param, value)| {
+                serde_json::json!({
+                    "name": param.name,
+                    "type": param.kind.to_string(),
+                    "value": token_json(value),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut content = vec![Content::json(serde_json::json!({
+            "protocol": protocol.to_string(),
+            "contract": to_checksum(&address, None),
+            "role": role,
+            "function": function.signature(),
+            "outputs": outputs,
+            "raw_output": encode_prefixed(&output),
+        }))?];
+        push_warnings(&mut content, &self.protocol_warnings(protocol))?;
+
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "List the supported LST protocols, the same as the evm://{network}/lsts resource"
+    )]
+    async fn list_protocols(
+        &self,
+        #[tool(aggr)] ListProtocolsRequest { network }: ListProtocolsRequest,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(network) = network.filter(|network| network != NETWORK) {
+            return Err(
+                McpServiceError::Unsupported(format!("Unsupported network '{}'", network)).into(),
+            );
+        }
+
+        Ok(CallToolResult::success(vec![Content::json(
+            self.protocol_list(),
+        )?]))
+    }
+
+    #[tool(
+        description = "Check whether an address signed a message, by recovering the signer of an EIP-191 (personal_sign) signature. Makes no RPC calls"
+    )]
+    async fn verify_signature(
+        &self,
+        #[tool(aggr)] VerifySignatureRequest {
+            address,
+            message,
+            signature,
+        }: VerifySignatureRequest,
+    ) -> Result<CallToolResult, McpError> {
+        let address = address
+            .parse::<Address>()
+            .map_err(|e| McpServiceError::invalid_input(format!("Invalid address: {}", e)))?;
+        let signature = signature
+            .parse::<Signature>()
+            .map_err(|e| McpServiceError::invalid_input(format!("Invalid signature: {}", e)))?;
+
+        // A signature that recovers to no address is reported as invalid rather than an error
+        let recovered = signature.recover(message.as_str()).ok();
+
+        Ok(CallToolResult::success(vec![Content::json(
+            serde_json::json!({
+                "address": to_checksum(&address, None),
+                "recovered": recovered.map(|recovered| to_checksum(&recovered, None)),
+                "valid": recovered == Some(address),
+            }),
+        )?]))
+    }
+
+    #[tool(
+        description = "Sign a message with EIP-191 (personal_sign) for off-chain authentication. Nothing is sent to the chain"
+    )]
+    async fn sign_message(
+        &self,
+        #[tool(aggr)] SignMessageRequest {
+            private_key,
+            message,
+        }: SignMessageRequest,
+    ) -> Result<CallToolResult, McpError> {
+        let signer = self.signer(private_key)?;
+        let signature = signer
+            .sign_message(message.as_str())
+            .await
+            .map_err(|e| McpServiceError::Internal(format!("Signing failed: {}", e)))?;
+
+        Ok(CallToolResult::success(vec![Content::json(
+            serde_json::json!({
+                "address": to_checksum(&signer.address(), None),
+                "signature": encode_prefixed(signature.to_vec()),
+            }),
+        )?]))
+    }
+
+    #[tool(
+        description = "Sign EIP-712 typed data, e.g. a permit or another off-chain approval, from its domain, types, primary type and message. Nothing is sent to the chain"
+    )]
+    async fn sign_typed_data(
+        &self,
+        #[tool(aggr)] SignTypedDataRequest {
+            private_key,
+            domain,
+            types,
+            primary_type,
+            message,
+        }: SignTypedDataRequest,
+    ) -> Result<CallToolResult, McpError> {
+        let typed_data = serde_json::from_value::<TypedData>(serde_json::json!({
+            "domain": domain,
+            "types": types,
+            "primaryType": primary_type,
+            "message": message,
+        }))
+        .map_err(|e| McpServiceError::invalid_input(format!("Invalid typed data: {}", e)))?;
+        // The encoder reads a missing field as zero, which would sign something else than meant
+        check_typed_fields(
+            &typed_data.types,
+            &typed_data.primary_type,
+            &serde_json::to_value(&typed_data.message).unwrap_or_default(),
+            "message",
+        )
+        .map_err(McpServiceError::invalid_input)?;
+        let digest = typed_data.encode_eip712().map_err(|e| {
+            McpServiceError::invalid_input(format!("Failed to encode typed data: {}", e))
+        })?;
+
+        let signer = self.signer(private_key)?;
+        // A signature for another chain could be replayed there, so it is refused like a
+        // transaction would be
+        if let Some(chain_id) = typed_data
+            .domain
+            .chain_id
+            .filter(|chain_id| *chain_id != U256::from(signer.chain_id()))
+        {
+            return Err(McpServiceError::invalid_input(format!(
+                "Domain chain id {} does not match the server's chain id {}",
+                chain_id,
+                signer.chain_id()
+            ))
+            .into());
+        }
+        let signature = signer
+            .sign_typed_data(&typed_data)
+            .await
+            .map_err(|e| McpServiceError::Internal(format!("Signing failed: {}", e)))?;
+
+        Ok(CallToolResult::success(vec![Content::json(
+            serde_json::json!({
+                "address": to_checksum(&signer.address(), None),
+                "digest": encode_prefixed(digest),
+                "signature": encode_prefixed(signature.to_vec()),
+            }),
+        )?]))
+    }
+
+    #[tool(
+        description = "Store a signer for this connection. Write tools use it when no private_key is given. It is never persisted and is dropped on disconnect"
+    )]
+    async fn set_signer(
+        &self,
+        #[tool(aggr)] SetSignerRequest { private_key }: SetSignerRequest,
+    ) -> Result<CallToolResult, McpError> {
+        let signer = private_key.parse::<LocalWallet>().map_err(|e| {
+            McpServiceError::invalid_input(format!("Failed to parse private key: {}", e))
+        })?;
+        let address = signer.address();
+        self.session.set_signer(signer);
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Session signer set to {}",
+            to_checksum(&address, None)
+        ))]))
+    }
+
+    #[tool(
+        description = "Get whether a transaction is pending or mined and, once mined, whether it succeeded, its confirmations and the fee paid"
+    )]
+    async fn tx_status(
+        &self,
+        #[tool(aggr)] TxStatusRequest { tx_hash }: TxStatusRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let tx_hash = tx_hash.parse::<H256>().map_err(|e| {
+            McpServiceError::invalid_input(format!("Invalid transaction hash: {}", e))
+        })?;
+
+        let status = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("tx_status"),
+                self.tx_status_json(tx_hash)
+                    .instrument(tracing::info_span!("tool_call", tool = "tx_status")),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Failed to get transaction status", e))?
+            .ok_or_else(|| {
+                McpServiceError::NotFound(format!(
+                    "Transaction {} not found",
+                    encode_prefixed(tx_hash)
+                ))
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::json(status)?]))
+    }
+
+    #[tool(
+        description = "Speed up a pending transaction by resending it at the same nonce with fees raised by at least 10%"
+    )]
+    async fn speed_up(
+        &self,
+        #[tool(aggr)] SpeedUpRequest {
+            private_key,
+            tx_hash,
+        }: SpeedUpRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!("Speeding up transaction {}", tx_hash);
+
+        self.ensure_chain_healthy()?;
+
+        let progress = Progress::default();
+        let signer = self.signer_provider(private_key)?;
+        let tx_hash = tx_hash.parse::<H256>().map_err(|e| {
+            McpServiceError::invalid_input(format!("Invalid transaction hash: {}", e))
+        })?;
+
+        let (nonce, replacement) = match self
+            .cancellable_write(
+                &ct,
+                self.timeouts.for_tool("speed_up"),
+                &progress,
+                async {
+                    let original = pending_transaction(&signer, tx_hash).await?;
+                    let replacement = send_replacement(
+                        &signer,
+                        original.nonce,
+                        (&original).into(),
+                        original.to,
+                        original.value,
+                        original.input.clone(),
+                        original.gas,
+                    );
+                    // Tracked as a whole, so a cancellation can't drop it mid-send
+                    let replacement = progress.track_send(replacement, |hash| *hash).await?;
+                    anyhow::Ok((original.nonce, replacement))
+                }
+                .instrument(tracing::info_span!("tool_call", tool = "speed_up")),
+            )
+            .await?
+        {
+            WriteOutcome::Finished(output) => {
+                output.map_err(|e| write_error("Speed-up failed", e, &self.native))?
+            }
+            WriteOutcome::Pending(tx_hash) => return self.pending_write("speed_up", tx_hash),
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Sent replacement for nonce {} with higher fees. Transaction hash: {}",
+            nonce,
+            encode_prefixed(replacement)
+        ))]))
+    }
+
+    #[tool(
+        description = "Cancel a pending transaction, given its hash or nonce, by sending a 0-value transfer to the signer at the same nonce with higher fees"
+    )]
+    async fn cancel_tx(
+        &self,
+        #[tool(aggr)] CancelTxRequest {
+            private_key,
+            tx_hash,
+            nonce,
+        }: CancelTxRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_chain_healthy()?;
+
+        let tx_hash = match (tx_hash, nonce) {
+            (Some(tx_hash), None) => Some(tx_hash.parse::<H256>().map_err(|e| {
+                McpServiceError::invalid_input(format!("Invalid transaction hash: {}", e))
+            })?),
+            (None, Some(_)) => None,
+            _ => {
+                return Err(McpServiceError::invalid_input(
+                    "Exactly one of tx_hash and nonce must be given",
+                )
+                .into());
+            }
+        };
+
+        let progress = Progress::default();
+        let signer = self.signer_provider(private_key)?;
+        let address = signer.address();
+
+        let (nonce, cancellation) = match self
+            .cancellable_write(
+                &ct,
+                self.timeouts.for_tool("cancel_tx"),
+                &progress,
+                async {
+                    let (nonce, replaced) = match tx_hash {
+                        Some(tx_hash) => {
+                            tracing::info!("Cancelling transaction {}", encode_prefixed(tx_hash));
+                            let original = pending_transaction(&signer, tx_hash).await?;
+                            (original.nonce, ReplacedFees::from(&original))
+                        }
+                        None => {
+                            // The pending transaction itself can't be looked up by nonce, so
+                            // outbid the current fee estimate instead.
+                            let nonce = U256::from(nonce.unwrap_or_default());
+                            tracing::info!("Cancelling transaction with nonce {}", nonce);
+                            ensure_nonce_pending(&signer, nonce).await?;
+                            let (max_fee, priority_fee) = signer
+                                .estimate_eip1559_fees(None)
+                                .await
+                                .context("Failed to estimate fees")?;
+                            (
+                                nonce,
+                                ReplacedFees::Eip1559 {
+                                    max_fee,
+                                    priority_fee,
+                                },
+                            )
+                        }
+                    };
+                    let cancellation = send_replacement(
+                        &signer,
+                        nonce,
+                        replaced,
+                        Some(address),
+                        U256::zero(),
+                        Bytes::default(),
+                        U256::from(TRANSFER_GAS),
+                    );
+                    let cancellation = progress.track_send(cancellation, |hash| *hash).await?;
+                    anyhow::Ok((nonce, cancellation))
+                }
+                .instrument(tracing::info_span!("tool_call", tool = "cancel_tx")),
+            )
+            .await?
+        {
+            WriteOutcome::Finished(output) => {
+                output.map_err(|e| write_error("Cancellation failed", e, &self.native))?
+            }
+            WriteOutcome::Pending(tx_hash) => return self.pending_write("cancel_tx", tx_hash),
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Sent cancellation for nonce {}. Transaction hash: {}",
+            nonce,
+            encode_prefixed(cancellation)
+        ))]))
+    }
+
+    #[tool(
+        description = "Admin only. Drop cached protocol metadata such as decimals so it is read from the chain again, e.g. after a contract upgrade"
+    )]
+    async fn invalidate_cache(&self) -> Result<CallToolResult, McpError> {
+        let count = self.decimals.clear();
+        tracing::info!("Invalidated cached metadata of {} protocols", count);
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Cleared cached metadata of {} protocols",
+            count
+        ))]))
+    }
+
+    #[tool(
+        description = "Admin only. Re-sync the nonce writes from an address use with the chain, after its transactions were sent elsewhere or dropped: forgets the nonce the server would use next and reads the pending nonce from the chain, which the next write starts from"
+    )]
+    async fn reset_nonce(
+        &self,
+        #[tool(aggr)] ResetNonceRequest { address }: ResetNonceRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let address = address
+            .parse::<Address>()
+            .map_err(|e| McpServiceError::invalid_input(format!("Invalid address: {}", e)))?;
+
+        let (previous_nonce, nonce) = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("reset_nonce"),
+                reset_nonce(&self.provider, address)
+                    .instrument(tracing::info_span!("tool_call", tool = "reset_nonce")),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Failed to reset nonce", e))?;
+        tracing::info!(
+            "Reset nonce of {} to {} (was {:?})",
+            to_checksum(&address, None),
+            nonce,
+            previous_nonce
+        );
+
+        Ok(CallToolResult::success(vec![Content::json(
+            serde_json::json!({
+                "address": to_checksum(&address, None),
+                "previous_nonce": previous_nonce.map(DecimalU256),
+                "nonce": DecimalU256(nonce),
+            }),
+        )?]))
+    }
+
+    #[tool(
+        description = "Admin only. Run a battery of checks to triage failing tools: RPC reachability, chain id, each protocol's contract code and a sample totalAssets read, and whether an address's nonces are in sync. Returns a pass/fail report with the reason of each failure"
+    )]
+    async fn diagnostics(
+        &self,
+        #[tool(aggr)] DiagnosticsRequest { address }: DiagnosticsRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let address =
+            match address {
+                Some(address) => Some(address.parse::<Address>().map_err(|e| {
+                    McpServiceError::invalid_input(format!("Invalid address: {}", e))
+                })?),
+                None => self.diagnostics_address,
+            };
+
+        let report = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("diagnostics"),
+                self.diagnostics_report(address)
+                    .instrument(tracing::info_span!("tool_call", tool = "diagnostics")),
+            )
+            .await?;
+
+        Ok(CallToolResult::success(vec![Content::json(report)?]))
+    }
+
+    #[tool(
+        description = "Admin only. Re-read the cached TVL, APR and exchange rate of one protocol, or of all when none is given, instead of waiting for the background refresh, e.g. right after an oracle update. Returns when each protocol was read"
+    )]
+    async fn refresh_snapshots(
+        &self,
+        #[tool(aggr)] RefreshSnapshotsRequest { protocol }: RefreshSnapshotsRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(protocol) = protocol {
+            self.ensure_available(protocol)?;
+        }
+
+        let refreshed = self
+            .cancellable(
+                &ct,
+                self.timeouts.for_tool("refresh_snapshots"),
+                self.refreshed_snapshots(protocol)
+                    .instrument(tracing::info_span!("tool_call", tool = "refresh_snapshots")),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Failed to refresh snapshots", e))?;
+
+        Ok(CallToolResult::success(vec![Content::json(refreshed)?]))
+    }
+
+    #[tool(description = "Remove the signer stored for this connection")]
+    async fn clear_signer(&self) -> Result<CallToolResult, McpError> {
+        let message = if self.session.clear_signer() {
+            "Session signer cleared"
+        } else {
+            "No session signer was set"
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(message)]))
+    }
+
+    #[tool(description = "Stake LST tokens")]
+    async fn stake(
+        &self,
+        #[tool(aggr)] request: StakeRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let progress = Progress::new(self.peer.clone(), request.progress_token.clone());
+        self.stake_with(request, &progress, ct).await
+    }
+
+    /// Stakes as the `stake` tool does, reporting the write to `progress`.
+    async fn stake_with(
+        &self,
+        StakeRequest {
+            protocol,
+            private_key,
+            amount,
+            amount_wei,
+            receiver,
+            gas_limit_multiplier,
+            progress_token: _,
+        }: StakeRequest,
+        progress: &Progress,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let amount_warning = amount.as_ref().and_then(AmountArg::warning);
+        let currency = self.deposit_currency(protocol).await?;
+        let amount_u256 = parse_amount(
+            amount.map(String::from),
+            amount_wei,
+            u32::from(currency.decimals()),
+        )?;
+        let amount = currency.format(amount_u256);
+        let gas_multiplier = self.gas_multiplier(gas_limit_multiplier)?;
+        tracing::info!(
+            "Staking {} {} using protocol {}",
+            amount,
+            currency.symbol(),
+            protocol
+        );
+
+        self.ensure_chain_healthy()?;
+        self.ensure_available(protocol)?;
+        self.ensure_writable(protocol)?;
+        let decimals = protocol
+            .decimals(self.provider.clone(), &self.decimals)
+            .await
+            .map_err(|e| McpServiceError::from_anyhow("Failed to get decimals", e))?;
+
+        let signer = self.signer_provider(private_key)?;
+        let signer_address = signer.address();
+
+        let receiver = parse_address_or(receiver, "receiver", signer_address)?;
+
+        let (receipt, warnings) = match self
+            .cancellable_write(
+                &ct,
+                self.timeouts.for_tool("stake"),
+                progress,
+                async {
+                    let limits = protocol
+                        .deposit_limits(signer.inner().clone(), receiver)
+                        .await?;
+                    let receipt = protocol
+                        .stake(
+                            signer.clone(),
+                            receiver,
+                            amount_u256,
+                            &limits,
+                            &currency,
+                            &self.allowance_resets,
+                            gas_multiplier,
+                            progress,
+                        )
+                        .await?;
+                    anyhow::Ok((receipt, limits.warning(amount_u256, &currency)))
+                }
+                .instrument(tracing::info_span!("tool_call", tool = "stake")),
+            )
+            .await?
+        {
+            WriteOutcome::Finished(output) => {
+                output.map_err(|e| write_error("Staking failed", e, &self.native))?
+            }
+            WriteOutcome::Pending(tx_hash) => return self.pending_write("stake", tx_hash),
+        };
+        let receipt = receipt.ok_or_else(|| {
+            McpServiceError::Internal("Staking failed: no receipt returned".to_string())
+        })?;
+
+        let post_balance = self.post_balance(Some(protocol), receiver, &receipt).await;
+        let minted = protocol.minted_shares(&receipt, receiver);
+
+        let mut all_warnings = self.protocol_warnings(protocol);
+        all_warnings.extend(amount_warning);
+        all_warnings.extend(warnings);
+        all_warnings.extend(minted.warning(protocol, decimals, &self.native));
+
+        // Warnings join the minted shares in the second item, where other tools put them
+        let mut details = minted_json(protocol, &minted, decimals);
+        if !all_warnings.is_empty() {
+            details["warnings"] = warnings_json(&all_warnings);
+        }
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "Staked {} {} tokens successfully. Transaction hash: {}.{}",
+                currency.round(amount),
+                protocol,
+                encode_prefixed(receipt.transaction_hash),
+                post_balance
+            )),
+            Content::json(details)?,
+        ]))
+    }
+
+    #[tool(description = "Unstake LST tokens")]
+    async fn unstake(
+        &self,
+        #[tool(aggr)] request: UnstakeRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let progress = Progress::new(self.peer.clone(), request.progress_token.clone());
+        self.unstake_with(request, &progress, ct).await
+    }
+
+    /// Unstakes as the `unstake` tool does, reporting the write to `progress`.
+    async fn unstake_with(
+        &self,
+        UnstakeRequest {
+            protocol,
+            private_key,
+            amount,
+            amount_wei,
+            amount_unit,
+            controller,
+            owner,
+            gas_limit_multiplier,
+            progress_token: _,
+        }: UnstakeRequest,
+        progress: &Progress,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let amount_warning = amount.as_ref().and_then(AmountArg::warning);
+        let gas_multiplier = self.gas_multiplier(gas_limit_multiplier)?;
+
+        self.ensure_chain_healthy()?;
+        self.ensure_available(protocol)?;
+        self.ensure_writable(protocol)?;
+
+        // Shares and assets are parsed at their own decimals, which only the chain knows
+        let decimals = protocol
+            .decimals(self.provider.clone(), &self.decimals)
+            .await
+            .map_err(|e| McpServiceError::from_anyhow("Failed to get decimals", e))?;
+        let unit_decimals = u32::from(decimals.of(amount_unit));
+        let amount_u256 = parse_amount(amount.map(String::from), amount_wei, unit_decimals)?;
+        let amount = format_units(amount_u256, unit_decimals)
+            .map_err(|e| McpServiceError::Internal(format!("Failed to format amount: {}", e)))?;
+        tracing::info!(
+            "Unstaking {} {} using protocol {}",
+            amount,
+            amount_unit,
+            protocol
+        );
+
+        let signer = self.signer_provider(private_key)?;
+        let signer_address = signer.address();
+
+        let controller = parse_address_or(controller, "controller", signer_address)?;
+        let owner = parse_address_or(owner, "owner", signer_address)?;
+
+        let watch = progress.clone();
+        let provider = self.provider.clone();
+        let cache = self.decimals.clone();
+        let (shares, receipt) = match self
+            .cancellable_write(
+                &ct,
+                self.timeouts.for_tool("unstake"),
+                &watch,
+                async move {
+                    let shares = protocol
+                        .to_shares(provider, &cache, amount_u256, amount_unit)
+                        .await?;
+                    let receipt = protocol
+                        .unstake(
+                            signer.clone(),
+                            controller,
+                            owner,
+                            shares,
+                            gas_multiplier,
+                            progress,
+                        )
+                        .await?;
+                    anyhow::Ok((shares, receipt))
+                }
+                .instrument(tracing::info_span!("tool_call", tool = "unstake")),
+            )
+            .await?
+        {
+            WriteOutcome::Finished(output) => {
+                output.map_err(|e| write_error("Unstaking failed", e, &self.native))?
+            }
+            WriteOutcome::Pending(tx_hash) => return self.pending_write("unstake", tx_hash),
+        };
+        let receipt = receipt.ok_or_else(|| {
+            McpServiceError::Internal("Unstaking failed: no receipt returned".to_string())
+        })?;
+
+        let shares = decimals.format_shares(shares);
+
+        let post_balance = self.post_balance(Some(protocol), owner, &receipt).await;
+
+        let mut content = vec![Content::text(format!(
+            "Unstaked {} {} tokens ({} {}) successfully. Transaction hash: {}.{}",
+            self.native.round(shares),
+            protocol,
+            self.native.round(amount),
+            amount_unit,
+            encode_prefixed(receipt.transaction_hash),
+            post_balance
+        ))];
+        let mut warnings = self.protocol_warnings(protocol);
+        warnings.extend(amount_warning);
+        push_warnings(&mut content, &warnings)?;
+
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "Unstake the owner's entire LST balance, redeeming the exact redeemable share amount so no dust is left"
+    )]
+    async fn unstake_all(
+        &self,
+        #[tool(aggr)] UnstakeAllRequest {
+            protocol,
+            private_key,
+            controller,
+            owner,
+            gas_limit_multiplier,
+            progress_token,
+        }: UnstakeAllRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let gas_multiplier = self.gas_multiplier(gas_limit_multiplier)?;
+        let progress = Progress::new(self.peer.clone(), progress_token);
+        tracing::info!("Unstaking all LST tokens using protocol {}", protocol);
+
+        self.ensure_chain_healthy()?;
+        self.ensure_available(protocol)?;
+        self.ensure_writable(protocol)?;
+        let decimals = protocol
+            .decimals(self.provider.clone(), &self.decimals)
+            .await
+            .map_err(|e| McpServiceError::from_anyhow("Failed to get decimals", e))?;
+
+        let signer = self.signer_provider(private_key)?;
+        let signer_address = signer.address();
+
+        let controller = parse_address_or(controller, "controller", signer_address)?;
+        let owner = parse_address_or(owner, "owner", signer_address)?;
+
+        let watch = progress.clone();
+        let provider = self.provider.clone();
+        let (shares, receipt) = match self
+            .cancellable_write(
+                &ct,
+                self.timeouts.for_tool("unstake_all"),
+                &watch,
+                async move {
+                    let shares = protocol.redeemable_shares(provider, owner).await?;
+                    if shares.is_zero() {
+                        return Err(NothingToUnstake { owner, protocol }.into());
+                    }
+                    let receipt = protocol
+                        .unstake(
+                            signer.clone(),
+                            controller,
+                            owner,
+                            shares,
+                            gas_multiplier,
+                            &progress,
+                        )
+                        .await?;
+                    anyhow::Ok((shares, receipt))
+                }
+                .instrument(tracing::info_span!("tool_call", tool = "unstake_all")),
+            )
+            .await?
+        {
+            WriteOutcome::Finished(output) => {
+                output.map_err(|e| write_error("Unstaking failed", e, &self.native))?
+            }
+            WriteOutcome::Pending(tx_hash) => return self.pending_write("unstake_all", tx_hash),
+        };
+        let receipt = receipt.ok_or_else(|| {
+            McpServiceError::Internal("Unstaking failed: no receipt returned".to_string())
+        })?;
+
+        let shares = decimals.format_shares(shares);
+
+        let post_balance = self.post_balance(Some(protocol), owner, &receipt).await;
+
+        let mut content = vec![Content::text(format!(
+            "Unstaked all {} {} tokens successfully. Transaction hash: {}.{}",
+            self.native.round(shares),
+            protocol,
+            encode_prefixed(receipt.transaction_hash),
+            post_balance
+        ))];
+        push_warnings(&mut content, &self.protocol_warnings(protocol))?;
+
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "Redeem one or more matured redeem requests for MON. Fails with the remaining wait time if a request is not yet claimable"
+    )]
+    async fn claim_redeem(
+        &self,
+        #[tool(aggr)] ClaimRedeemRequest {
+            protocol,
+            private_key,
+            request_ids,
+            receiver,
+            gas_limit_multiplier,
+            progress_token,
+        }: ClaimRedeemRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let gas_multiplier = self.gas_multiplier(gas_limit_multiplier)?;
+        let progress = Progress::new(self.peer.clone(), progress_token);
+        tracing::info!(
+            "Claiming redeem requests {:?} using protocol {}",
+            request_ids,
+            protocol
+        );
+
+        self.ensure_chain_healthy()?;
+        self.ensure_available(protocol)?;
+        self.ensure_writable(protocol)?;
+
+        let signer = self.signer_provider(private_key)?;
+        let signer_address = signer.address();
+
+        if request_ids.is_empty() {
+            return Err(
+                McpServiceError::invalid_input("At least one request id is required").into(),
+            );
+        }
+        let request_ids = request_ids
+            .iter()
+            .map(|request_id| {
+                U256::from_dec_str(request_id).map_err(|e| {
+                    McpServiceError::invalid_input(format!(
+                        "Invalid request id '{}': {}",
+                        request_id, e
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let receiver = parse_address_or(receiver, "receiver", signer_address)?;
+
+        self.cancellable(
+            &ct,
+            self.timeouts.read(),
+            self.ensure_claimable(protocol, &request_ids),
+        )
+        .await?
+        .map_err(|e| write_error("Redeem failed", e, &self.native))?;
+
+        let receipt = match self
+            .cancellable_write(
+                &ct,
+                self.timeouts.for_tool("claim_redeem"),
+                &progress,
+                protocol
+                    .claim_redeem(
+                        signer.clone(),
+                        &request_ids,
+                        receiver,
+                        gas_multiplier,
+                        &progress,
+                    )
+                    .instrument(tracing::info_span!("tool_call", tool = "claim_redeem")),
+            )
+            .await?
+        {
+            WriteOutcome::Finished(output) => {
+                output.map_err(|e| write_error("Redeem failed", e, &self.native))?
+            }
+            WriteOutcome::Pending(tx_hash) => return self.pending_write("claim_redeem", tx_hash),
+        }
+        .ok_or_else(|| {
+            McpServiceError::Internal("Redeem failed: no receipt returned".to_string())
+        })?;
+
+        let request_ids = request_ids
+            .iter()
+            .map(U256::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let post_balance = self.post_balance(None, receiver, &receipt).await;
+
+        let mut content = vec![Content::text(format!(
+            "Redeemed request(s) {} of {} successfully. Transaction hash: {}.{}",
+            request_ids,
+            protocol,
+            encode_prefixed(receipt.transaction_hash),
+            post_balance
+        ))];
+        push_warnings(&mut content, &self.protocol_warnings(protocol))?;
+
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "Wait for a redeem request to become claimable, checking every 10 seconds for up to max_wait_secs, then redeem it for MON. If the wait runs out first, returns status pending with the remaining time, and can be called again to resume"
+    )]
+    async fn redeem_when_ready(
+        &self,
+        #[tool(aggr)] RedeemWhenReadyRequest {
+            protocol,
+            private_key,
+            request_id,
+            receiver,
+            max_wait_secs,
+            gas_limit_multiplier,
+            progress_token,
+        }: RedeemWhenReadyRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let gas_multiplier = self.gas_multiplier(gas_limit_multiplier)?;
+        let progress = Progress::new(self.peer.clone(), progress_token);
+        let request_id = U256::from_dec_str(&request_id).map_err(|e| {
+            McpServiceError::invalid_input(format!("Invalid request id '{}': {}", request_id, e))
+        })?;
+        let max_wait = max_wait_secs.map_or(REDEEM_MAX_WAIT, Duration::from_secs);
+        if max_wait.is_zero() || max_wait > REDEEM_MAX_WAIT_LIMIT {
+            return Err(McpServiceError::invalid_input(format!(
+                "max_wait_secs must be between 1 and {}",
+                REDEEM_MAX_WAIT_LIMIT.as_secs()
+            ))
+            .into());
+        }
+        if !protocol.capabilities().claim_redeem {
+            return Err(McpServiceError::Unsupported(format!(
+                "{} withdrawals are instant and have no redeem requests to wait for",
+                protocol
+            ))
+            .into());
+        }
+        tracing::info!(
+            "Waiting up to {}s to redeem request {} using protocol {}",
+            max_wait.as_secs(),
+            request_id,
+            protocol
+        );
+
+        // What the redeem needs is checked before the wait too, so it doesn't wait in vain
+        self.ensure_chain_healthy()?;
+        self.ensure_available(protocol)?;
+        self.ensure_writable(protocol)?;
+
+        let signer = self.signer_provider(private_key)?;
+        let receiver = parse_address_or(receiver, "receiver", signer.address())?;
+
+        // The last check may start just before the wait runs out, so it gets a read's time
+        let wait = self
+            .cancellable(
+                &ct,
+                max_wait + self.timeouts.read(),
+                self.wait_until_claimable(
+                    protocol,
+                    request_id,
+                    signer.address(),
+                    max_wait,
+                    &progress,
+                )
+                .instrument(tracing::info_span!("tool_call", tool = "redeem_when_ready")),
+            )
+            .await?
+            .map_err(|e| McpServiceError::from_anyhow("Failed to check redeem request", e))?
+            .ok_or_else(|| {
+                McpServiceError::NotFound(format!("Redeem request {} not found", request_id))
+            })?;
+        if let RedeemWait::Pending(status) = wait {
+            let mut content = vec![Content::json(status)?];
+            push_warnings(&mut content, &self.protocol_warnings(protocol))?;
+            return Ok(CallToolResult::success(content));
+        }
+
+        // The protocol may have been paused or deprecated during the wait
+        self.ensure_chain_healthy()?;
+        self.ensure_available(protocol)?;
+        self.ensure_writable(protocol)?;
+
+        let receipt = match self
+            .cancellable_write(
+                &ct,
+                self.timeouts.for_tool("redeem_when_ready"),
+                &progress,
+                protocol
+                    .claim_redeem(
+                        signer.clone(),
+                        &[request_id],
+                        receiver,
+                        gas_multiplier,
+                        &progress,
+                    )
+                    .instrument(tracing::info_span!("tool_call", tool = "redeem_when_ready")),
+            )
+            .await?
+        {
+            WriteOutcome::Finished(output) => {
+                output.map_err(|e| write_error("Redeem failed", e, &self.native))?
+            }
+            WriteOutcome::Pending(tx_hash) => {
+                return self.pending_write("redeem_when_ready", tx_hash);
+            }
+        }
+        .ok_or_else(|| {
+            McpServiceError::Internal("Redeem failed: no receipt returned".to_string())
+        })?;
+
+        let post_balance = self.post_balance(None, receiver, &receipt).await;
+
+        let mut content = vec![Content::text(format!(
+            "Redeemed request {} of {} successfully. Transaction hash: {}.{}",
+            request_id,
+            protocol,
+            encode_prefixed(receipt.transaction_hash),
+            post_balance
+        ))];
+        push_warnings(&mut content, &self.protocol_warnings(protocol))?;
+
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "Cancel a pending redeem request. No supported protocol allows it, so this always fails with the reason instead of sending a transaction that would revert"
+    )]
+    async fn cancel_redeem(
+        &self,
+        #[tool(aggr)] CancelRedeemRequest {
+            protocol,
+            request_id,
+        }: CancelRedeemRequest,
+    ) -> Result<CallToolResult, McpError> {
+        let protocol = self.resolve_protocol(protocol)?;
+        let request_id = U256::from_dec_str(&request_id).map_err(|e| {
+            McpServiceError::invalid_input(format!("Invalid request id '{}': {}", request_id, e))
+        })?;
+
+        let reason = if protocol.capabilities().claim_redeem {
+            format!(
+                "{} redeem requests are irreversible: the contract has no way to withdraw request {} once made. Wait until it is claimable and redeem it with claim_redeem",
+                protocol, request_id
+            )
+        } else {
+            format!(
+                "{} withdrawals are instant and leave no redeem requests to cancel",
+                protocol
+            )
+        };
+
+        Err(McpServiceError::Unsupported(reason).into())
+    }
+
+    #[tool(
+        description = "Stake MON with several protocols, one stake transaction per entry, in order. A failing entry doesn't stop the others. Returns a job_id and each entry's outcome; resume_job retries the entries that failed without sending the others again"
+    )]
+    async fn stake_split(
+        &self,
+        #[tool(aggr)] StakeSplitRequest {
+            private_key,
+            entries,
+            receiver,
+        }: StakeSplitRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let mut arguments = Vec::with_capacity(entries.len());
+        for SplitEntry {
+            protocol,
+            amount,
+            amount_wei,
+        } in entries
+        {
+            // Amounts are checked up front, so that no entry fails on every run
+            let currency = self.deposit_currency(protocol).await?;
+            parse_amount(
+                amount.clone().map(String::from),
+                amount_wei.clone(),
+                u32::from(currency.decimals()),
+            )?;
+            arguments.push(job_arguments::<StakeRequest>(serde_json::json!({
+                "protocol": protocol.to_string(),
+                "amount": amount.as_ref().map(AmountArg::as_str),
+                "amount_wei": amount_wei,
+                "receiver": receiver,
+            }))?);
+        }
+
+        self.start_job("stake", private_key, arguments, ct).await
+    }
+
+    #[tool(
+        description = "Unstake the signer's LST tokens from several protocols, one unstake transaction per entry, in order. A failing entry doesn't stop the others. Returns a job_id and each entry's outcome; resume_job retries the entries that failed without sending the others again"
+    )]
+    async fn unstake_split(
+        &self,
+        #[tool(aggr)] UnstakeSplitRequest {
+            private_key,
+            entries,
+            amount_unit,
+        }: UnstakeSplitRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let mut arguments = Vec::with_capacity(entries.len());
+        for SplitEntry {
+            protocol,
+            amount,
+            amount_wei,
+        } in entries
+        {
+            // Amounts are checked up front, so that no entry fails on every run
+            let decimals = protocol
+                .decimals(self.provider.clone(), &self.decimals)
+                .await
+                .map_err(|e| McpServiceError::from_anyhow("Failed to get decimals", e))?;
+            parse_amount(
+                amount.clone().map(String::from),
+                amount_wei.clone(),
+                u32::from(decimals.of(amount_unit)),
+            )?;
+            arguments.push(job_arguments::<UnstakeRequest>(serde_json::json!({
+                "protocol": protocol.to_string(),
+                "amount": amount.as_ref().map(AmountArg::as_str),
+                "amount_wei": amount_wei,
+                "amount_unit": amount_unit.to_string(),
+            }))?);
+        }
+
+        self.start_job("unstake", private_key, arguments, ct).await
+    }
+
+    #[tool(
+        description = "Resume a stake_split or unstake_split job by its job_id, running only the entries that failed or never ran; those already done are not sent again, and an entry whose transaction was sent but not confirmed has that transaction checked instead. Only the job's signer can resume it, and jobs are kept for an hour after they last ran"
+    )]
+    async fn resume_job(
+        &self,
+        #[tool(aggr)] ResumeJobRequest {
+            job_id,
+            private_key,
+        }: ResumeJobRequest,
+        ct: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        self.run_job(&job_id, private_key, ct).await
+    }
+}
+
+/// How a write run by [`Lst::cancellable_write`] ended.
+enum WriteOutcome<T> {
+    Finished(T),
+    /// Ran out of time after sending this transaction, which may still be mined.
+    Pending(H256),
+}
+
+/// Tools that send a transaction and wait for it to be confirmed, so they get the write timeout
+/// and report their steps under a `progress_token`. These are the tools taking
+/// `gas_limit_multiplier` and `progress_token`, which a test checks.
+pub(crate) const WRITE_TOOLS: [&str; 5] = [
+    "stake",
+    "unstake",
+    "unstake_all",
+    "claim_redeem",
+    "redeem_when_ready",
+];
+
+/// Tools gated by the server's admin token, which is checked on the `Authorization` header of
+/// the HTTP request carrying the call. A test checks these are the tools described as admin
+/// only.
+pub(crate) const ADMIN_TOOLS: [&str; 4] = [
+    "invalidate_cache",
+    "reset_nonce",
+    "diagnostics",
+    "refresh_snapshots",
+];
+
+/// Longest a tool may run before it fails with `TIMEOUT`. Writes wait for confirmation, so
+/// they default to far longer than reads; any tool can be given its own timeout. Resource
+/// reads use the read timeout.
+#[derive(Debug, Clone)]
+pub struct ToolTimeouts {
+    read: Duration,
+    write: Duration,
+    overrides: HashMap<String, Duration>,
+}
+
+impl ToolTimeouts {
+    pub fn new(
+        read: Duration,
+        write: Duration,
+        overrides: impl IntoIterator<Item = ToolTimeout>,
+    ) -> anyhow::Result<Self> {
+        if read.is_zero() || write.is_zero() {
+            bail!("Timeouts must be greater than zero");
+        }
+
+        let tools = Lst::tool_box().list();
+        let mut timeouts = ToolTimeouts {
+            read,
+            write,
+            overrides: HashMap::new(),
+        };
+        for ToolTimeout { tool, timeout } in overrides {
+            if !tools.iter().any(|t| t.name == tool) {
+                bail!("Unknown tool '{}' in tool timeouts", tool);
+            }
+            if timeout.is_zero() {
+                bail!("Timeout of '{}' must be greater than zero", tool);
+            }
+            timeouts.overrides.insert(tool, timeout);
+        }
+
+        Ok(timeouts)
+    }
+
+    pub fn read(&self) -> Duration {
+        self.read
+    }
+
+    pub fn write(&self) -> Duration {
+        self.write
+    }
+
+    pub fn for_tool(&self, tool: &str) -> Duration {
+        match self.overrides.get(tool) {
+            Some(timeout) => *timeout,
+            None if WRITE_TOOLS.contains(&tool) => self.write,
+            None => self.read,
+        }
+    }
+}
+
+impl Default for ToolTimeouts {
+    fn default() -> Self {
+        ToolTimeouts {
+            read: READ_TIMEOUT,
+            write: WRITE_TIMEOUT,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Timeout of a single tool, as given on the command line: `NAME=SECS`.
+#[derive(Debug, Clone)]
+pub struct ToolTimeout {
+    pub tool: String,
+    pub timeout: Duration,
+}
+
+impl std::str::FromStr for ToolTimeout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (tool, secs) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected NAME=SECS, got '{}'", s))?;
+        let secs = secs
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid seconds for '{}': {}", tool, e))?;
+
+        Ok(ToolTimeout {
+            tool: tool.trim().to_string(),
+            timeout: Duration::from_secs(secs),
+        })
+    }
+}
+
+/// Tools that sign transactions, or take the key to sign them with: those taking a
+/// `private_key`, which a test checks.
+const SIGNER_TOOLS: [&str; 13] = [
+    "stake",
+    "unstake",
+    "unstake_all",
+    "claim_redeem",
+    "redeem_when_ready",
+    "stake_split",
+    "unstake_split",
+    "resume_job",
+    "sign_message",
+    "sign_typed_data",
+    "speed_up",
+    "cancel_tx",
+    "set_signer",
+];
+
+/// Warns when the withdrawal fee exceeds [`HIGH_WITHDRAWAL_FEE_BPS`] of the gross amount.
+fn withdrawal_fee_warning(
+    gross_assets: U256,
+    withdrawal_fee: U256,
+    native: &NativeCurrency,
+) -> Option<Warning> {
+    let high = !gross_assets.is_zero()
+        && withdrawal_fee.saturating_mul(U256::from(10_000))
+            > gross_assets.saturating_mul(U256::from(HIGH_WITHDRAWAL_FEE_BPS));
+
+    high.then(|| {
+        Warning::new(
+            WarningCode::HighWithdrawalFee,
+            format!(
+                "Withdrawal fee of {} {} is more than {}% of the {} {} redeemed",
+                native.display(withdrawal_fee),
+                native.symbol(),
+                HIGH_WITHDRAWAL_FEE_BPS as f64 / 100.0,
+                native.display(gross_assets),
+                native.symbol()
+            ),
+        )
+    })
+}
+
+/// Per-item error of a batched read, with the same `kind` as a whole-request error.
+fn error_entry(error: &McpServiceError) -> serde_json::Value {
+    serde_json::json!({ "error": error.to_string(), "kind": error.kind() })
+}
+
+/// `_meta` object reporting how many RPC calls served a request.
+fn rpc_calls_meta(rpc_calls: u64) -> Meta {
+    Meta(serde_json::Map::from_iter([(
+        "rpc_calls".to_string(),
+        rpc_calls.into(),
+    )]))
+}
+
+/// Appends `warnings` to a tool result as a JSON `{ "warnings": [...] }` item, if there are any.
+fn push_warnings(content: &mut Vec<Content>, warnings: &[Warning]) -> Result<(), McpError> {
+    if !warnings.is_empty() {
+        content.push(Content::json(serde_json::json!({
+            "warnings": warnings_json(warnings),
+        }))?);
+    }
+
+    Ok(())
+}
+
+/// Checks that `value`, of the struct type `type_name`, has exactly the fields `types` declares
+/// for it, recursing into nested structs and arrays of them. `path` names `value` in errors.
+fn check_typed_fields(
+    types: &Types,
+    type_name: &str,
+    value: &serde_json::Value,
+    path: &str,
+) -> Result<(), String> {
+    // Arrays are declared as `T[]` or `T[n]`
+    if let Some(element) = type_name
+        .strip_suffix(']')
+        .and_then(|name| name.rsplit_once('['))
+        .map(|(element, _)| element)
+    {
+        let items = value
+            .as_array()
+            .ok_or_else(|| format!("'{}' must be an array of {}", path, element))?;
+        return items.iter().enumerate().try_for_each(|(i, item)| {
+            check_typed_fields(types, element, item, &format!("{}[{}]", path, i))
+        });
+    }
+    let Some(fields) = types.get(type_name) else {
+        // Atomic types are checked by the encoder
+        return Ok(());
+    };
+
+    let object = value
+        .as_object()
+        .ok_or_else(|| format!("'{}' must be an object of type {}", path, type_name))?;
+    if let Some(unknown) = object
+        .keys()
+        .find(|key| !fields.iter().any(|field| &field.name == *key))
+    {
+        return Err(format!(
+            "'{}' has field '{}', which type {} does not declare",
+            path, unknown, type_name
+        ));
+    }
+    fields.iter().try_for_each(|field| {
+        let field_path = format!("{}.{}", path, field.name);
+        let value = object
+            .get(&field.name)
+            .ok_or_else(|| format!("'{}' of type {} is missing", field_path, field.r#type))?;
+        check_typed_fields(types, &field.r#type, value, &field_path)
+    })
+}
+
+/// Parses an optional address argument, falling back to `default` when omitted.
+fn parse_address_or(
+    value: Option<String>,
+    field: &str,
+    default: Address,
+) -> Result<Address, McpServiceError> {
+    match value {
+        Some(value) => value.parse::<Address>().map_err(|e| {
+            McpServiceError::invalid_input(format!("Invalid {} address '{}': {}", field, value, e))
+        }),
+        None => Ok(default),
+    }
+}
+
+/// Canonical signature of an ABI item, e.g. `deposit(uint256,address)`, the form its selector
+/// is hashed from.
+fn canonical_signature<'a>(name: &str, kinds: impl Iterator<Item = &'a ParamType>) -> String {
+    let kinds = kinds.map(ToString::to_string).collect::<Vec<_>>();
+    format!("{}({})", name, kinds.join(","))
+}
+
+/// Renders a decoded ABI value as JSON. Integers become base-10 strings so 256-bit values
+/// survive JSON number parsing; addresses are checksummed and bytes hex-encoded.
+fn token_json(token: Token) -> serde_json::Value {
+    match token {
+        Token::Address(address) => to_checksum(&address, None).into(),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => encode_prefixed(bytes).into(),
+        Token::Int(value) => I256::from_raw(value).to_string().into(),
+        Token::Uint(value) => value.to_string().into(),
+        Token::Bool(value) => value.into(),
+        Token::String(value) => value.into(),
+        Token::FixedArray(tokens) | Token::Array(tokens) | Token::Tuple(tokens) => {
+            tokens.into_iter().map(token_json).collect()
+        }
+    }
+}
+
+/// Polls a redeem request's `status` for [`Lst::wait_until_claimable`].
+async fn wait_for_claimable<F, Fut>(
+    mut status: F,
+    protocol: LstProtocol,
+    controller: Address,
+    max_wait: Duration,
+    progress: &Progress,
+) -> anyhow::Result<Option<RedeemWait>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<Option<RedeemStatus>>>,
+{
+    let started = Instant::now();
+    loop {
+        let Some(status) = status().await? else {
+            return Ok(None);
+        };
+        let request_id = status.request.request_id;
+        if status.request.claimed {
+            bail!("Redeem request {} has already been claimed", request_id);
+        }
+        status.request.ensure_controller(controller)?;
+        if status.claimable_now() {
+            return Ok(Some(RedeemWait::Ready));
+        }
+        let RedeemStatus {
+            request,
+            queue,
+            processed_confirmed,
+            now,
+        } = status;
+
+        let remaining = max_wait.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            return Ok(Some(RedeemWait::Pending(serde_json::json!({
+                "status": "pending",
+                "protocol": protocol.to_string(),
+                "request_id": DecimalU256(request_id),
+                "waited_seconds": started.elapsed().as_secs(),
+                "claimable_at": request.claimable_at,
+                "claimable_at_iso": iso_timestamp(request.claimable_at),
+                "remaining_seconds": request.remaining_wait(now),
+                "processed": queue.is_processed(request_id),
+                "processed_confirmed": processed_confirmed,
+                "resume": "Call redeem_when_ready again with the same request_id to keep waiting",
+            }))));
+        }
+
+        progress.report(WriteStep::AwaitingClaimable).await;
+        tokio::time::sleep(REDEEM_POLL_INTERVAL.min(remaining)).await;
+    }
+}
+
+/// Arguments of one entry of a batch job, without the unset ones, checked to be valid
+/// arguments `T` of the job's tool.
+fn job_arguments<T: serde::de::DeserializeOwned>(
+    arguments: serde_json::Value,
+) -> Result<JsonObject, McpServiceError> {
+    let serde_json::Value::Object(mut arguments) = arguments else {
+        unreachable!("entry arguments are an object");
+    };
+    arguments.retain(|_, value| !value.is_null());
+    serde_json::from_value::<T>(arguments.clone().into())
+        .map_err(|e| McpServiceError::invalid_input(format!("Invalid entry: {}", e)))?;
+
+    Ok(arguments)
+}
+
+/// Whether `result` is that of a write left pending, see [`Lst::pending_write`].
+fn is_pending_write(result: &CallToolResult) -> bool {
+    result
+        .content
+        .get(1)
+        .and_then(|content| content.as_text())
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text.text).ok())
+        .is_some_and(|details| details["status"] == "pending")
+}
+
+/// Maps a failed write to a service error, surfacing known failure causes with a stable code.
+fn write_error(message: &str, e: anyhow::Error, native: &NativeCurrency) -> McpServiceError {
+    let invalid_input =
+        |err: &dyn fmt::Display, details: serde_json::Value| McpServiceError::InvalidInput {
+            message: format!("{}: {}", message, err),
+            details: Some(details),
+        };
+
+    if let Some(err) = e.downcast_ref::<InsufficientFundsForGas>() {
+        let amount = |amount: U256| format!("{} {}", native.format(amount), native.symbol());
+        return invalid_input(
+            &format!(
+                "Insufficient funds for gas: requires {} but balance is {} (short {})",
+                amount(err.required),
+                amount(err.balance),
+                amount(err.shortfall()),
+            ),
+            serde_json::json!({
+                "code": "INSUFFICIENT_FUNDS_FOR_GAS",
+                "required": DecimalU256(err.required),
+                "balance": DecimalU256(err.balance),
+                "shortfall": DecimalU256(err.shortfall()),
+            }),
+        );
+    }
+
+    if let Some(err) = e.downcast_ref::<RedeemNotMature>() {
+        return invalid_input(
+            err,
+            serde_json::json!({
+                "code": "REDEEM_NOT_MATURE",
+                "request_id": DecimalU256(err.request_id),
+                "claimable_at": err.claimable_at,
+                "claimable_at_iso": iso_timestamp(err.claimable_at),
+                "claimable_in_seconds": err.remaining,
+            }),
+        );
+    }
+
+    if let Some(err) = e.downcast_ref::<NothingToUnstake>() {
+        return McpServiceError::AmountZero(format!("{}: {}", message, err));
+    }
+
+    if let Some(err) = e.downcast_ref::<DepositOutOfBounds>() {
+        let data = match err {
+            DepositOutOfBounds::BelowMin { amount, min, .. } => serde_json::json!({
+                "code": err.code(),
+                "amount": DecimalU256(*amount),
+                "min": DecimalU256(*min),
+            }),
+            DepositOutOfBounds::AboveMax { amount, max, .. } => serde_json::json!({
+                "code": err.code(),
+                "amount": DecimalU256(*amount),
+                "max": DecimalU256(*max),
+            }),
+        };
+        return invalid_input(err, data);
+    }
+
+    McpServiceError::from_anyhow(message, e)
+}
+
+impl ServerHandler for Lst {
+    fn get_info(&self) -> ServerInfo {
+        let instructions = format!(
+            "This server provides a LST (Liquid Staking Token) tool that can be used by staking native token and receive a LST token. It serves the {} network (chain id {}) with the protocols {}. Admin tools are {}. The enabled features are listed under experimental.monad in the server capabilities.",
+            NETWORK,
+            self.chain_guard.expected_chain_id(),
+            self.available_protocols().join(", "),
+            if self.admin_token.is_some() {
+                "enabled"
+            } else {
+                "disabled"
+            },
+        );
+
+        ServerInfo {
+            instructions: Some(instructions),
+            capabilities: ServerCapabilities::builder()
+                .enable_experimental_with(ExperimentalCapabilities::from([(
+                    "monad".to_string(),
+                    self.server_features(),
+                )]))
+                .enable_tools()
+                .enable_resources()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleServer>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleServer>) {
+        self.peer = Some(peer);
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools: Self::tool_box().list(),
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        mut request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        Self::argument_patterns()
+            .map_err(|e| McpServiceError::Internal(format!("{:#}", e)))?
+            .check(&request.name, request.arguments.as_ref())?;
+        if ADMIN_TOOLS.contains(&request.name.as_ref()) {
+            self.ensure_admin(&context.extensions)?;
+        }
+        progress_token_from_meta(&mut request, &context.meta);
+
+        let tool = request.name.clone();
+        let (result, rpc_calls) =
+            count_rpc_calls(Self::tool_box().call(ToolCallContext::new(self, request, context)))
+                .await;
+        tracing::debug!(%tool, rpc_calls, ok = result.is_ok(), "Tool call finished");
+
+        let mut result = result?;
+        if self.report_rpc_calls {
+            result.meta = Some(rpc_calls_meta(rpc_calls));
+        }
+
+        Ok(result)
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        Ok(ListResourcesResult {
+            resources: self.resources(),
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        ReadResourceRequestParam { uri }: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let span = tracing::info_span!("read_resource", %uri);
+        let (result, rpc_calls) = count_rpc_calls(self.cancellable(
+            &context.ct,
+            self.timeouts.read(),
+            self.dispatch_resource(uri.clone()).instrument(span),
+        ))
+        .await;
+        tracing::debug!(%uri, rpc_calls, ok = matches!(result, Ok(Ok(_))), "Resource read finished");
+
+        let mut result = result??;
+        if self.report_rpc_calls {
+            result.contents = result
+                .contents
+                .into_iter()
+                .map(|contents| with_rpc_calls_meta(contents, rpc_calls))
+                .collect();
+        }
+
+        Ok(result)
+    }
+
+    async fn list_resource_templates(
         &self,
         _request: Option<PaginatedRequestParam>,
         _: RequestContext<RoleServer>,
     ) -> Result<ListResourceTemplatesResult, McpError> {
         Ok(ListResourceTemplatesResult {
             next_cursor: None,
-            resource_templates: vec![
-                ResourceTemplate {
-                    raw: RawResourceTemplate {
-                        uri_template: "evm://{network}/lsts".to_string(),
-                        name: "List of available LST protocols".to_string(),
-                        description: None,
-                        mime_type: Some("text".to_string()),
+            resource_templates: Self::resource_templates(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{providers::Provider, types::transaction::eip2718::TypedTransaction};
+
+    use super::*;
+    use crate::common::{
+        admin::AdminAccess,
+        health::DeprecatedProtocol,
+        rpc::{CallLogger, FailoverHttp},
+    };
+
+    pub(super) fn mon(amount: u64) -> U256 {
+        U256::from(amount) * U256::exp10(18)
+    }
+
+    /// Service whose RPC endpoint refuses connections, for logic that reads nothing.
+    pub(super) fn offline_lst() -> Lst {
+        let rpc = FailoverHttp::new(&["http://127.0.0.1:1".to_string()]).unwrap();
+        Lst::new(Arc::new(CallLogger::new(Provider::new(rpc), false)))
+    }
+
+    /// Service reading from a local JSON-RPC endpoint that answers each call with the result
+    /// `respond` gives for its method and params.
+    pub(super) async fn stub_provider<F>(respond: F) -> Arc<RpcProvider>
+    where
+        F: Fn(&str, &serde_json::Value) -> serde_json::Value + Send + Sync + 'static,
+    {
+        stub_rpc(move |method, params| Ok(respond(method, params))).await
+    }
+
+    /// Like [`stub_provider`], but `respond` can also answer with a JSON-RPC error object.
+    async fn stub_rpc<F>(respond: F) -> Arc<RpcProvider>
+    where
+        F: Fn(&str, &serde_json::Value) -> Result<serde_json::Value, serde_json::Value>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let respond = Arc::new(respond);
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(call): axum::Json<serde_json::Value>| {
+                let respond = respond.clone();
+                async move {
+                    let (key, value) =
+                        match respond(call["method"].as_str().unwrap_or_default(), &call["params"])
+                        {
+                            Ok(result) => ("result", result),
+                            Err(error) => ("error", error),
+                        };
+                    let mut response = serde_json::json!({ "jsonrpc": "2.0", "id": call["id"] });
+                    response[key] = value;
+                    axum::Json(response)
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Arc::new(CallLogger::new(
+            Provider::new(FailoverHttp::new(&[url]).unwrap()),
+            false,
+        ))
+    }
+
+    #[tokio::test]
+    async fn protocol_info_serves_what_it_could_read_and_reports_the_rest() {
+        let provider = stub_rpc(|method, params| {
+            let data = params[0]["data"].as_str().or(params[0]["input"].as_str());
+            match method {
+                "eth_chainId" => Ok(serde_json::json!(format!("{:#x}", MONAD_TESTNET_CHAIN_ID))),
+                "eth_blockNumber" => Ok(serde_json::json!("0x64")),
+                "eth_getBlockByNumber" => {
+                    Ok(serde_json::json!({ "number": "0x64", "timestamp": "0x6553f100" }))
+                }
+                // paused()
+                "eth_call" if data == Some("0x5c975abb") => {
+                    Err(serde_json::json!({ "code": -32603, "message": "internal error" }))
+                }
+                _ => Ok(serde_json::json!(format!("0x{:064x}", 18))),
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+
+        let info =
+            serde_json::to_value(lst.protocol_info(LstProtocol::AprMON, true).await).unwrap();
+        assert_eq!(info["paused"], serde_json::Value::Null);
+        assert_eq!(info["errors"]["paused"]["kind"], "RPC_ERROR");
+        let error = info["errors"]["paused"]["error"].as_str().unwrap();
+        assert!(error.starts_with("Failed to read pause state"), "{error}");
+        // The other reads are still served
+        assert_eq!(info["decimals"], 18);
+        assert!(info["errors"].get("stats").is_none(), "{info}");
+        assert!(info["tvl"].is_string(), "{info}");
+        assert!(info["rate"].is_string(), "{info}");
+        assert!(info["withdrawal_wait_seconds"].is_u64(), "{info}");
+    }
+
+    #[tokio::test]
+    async fn protocol_info_populates_every_field_from_its_reads() {
+        let word = |value: U256| serde_json::json!(format!("0x{:064x}", value));
+        let string = |value: &str| {
+            let encoded = ethers::abi::encode(&[ethers::abi::Token::String(value.to_string())]);
+            serde_json::json!(encode_prefixed(encoded))
+        };
+        let provider = stub_provider(move |method, params| {
+            let data = params[0]["data"].as_str().or(params[0]["input"].as_str());
+            match (method, data.and_then(|data| data.get(..10))) {
+                ("eth_chainId", _) => serde_json::json!(format!("{:#x}", MONAD_TESTNET_CHAIN_ID)),
+                ("eth_blockNumber", _) => serde_json::json!("0x64"),
+                ("eth_getBlockByNumber", _) => {
+                    serde_json::json!({ "number": "0x64", "timestamp": "0x6553f100" })
+                }
+                (_, Some("0x06fdde03")) => string("aPriori Monad LST"),
+                (_, Some("0x95d89b41")) => string("aprMON"),
+                (_, Some("0x313ce567")) => word(U256::from(18)),
+                // asset()
+                (_, Some("0x38d52e0f")) => word(U256::from(0xaa)),
+                // paused()
+                (_, Some("0x5c975abb")) => word(U256::zero()),
+                // maxDeposit(address)
+                (_, Some("0x402d267d")) => word(mon(1_000)),
+                // withdrawalWaitTime()
+                (_, Some("0x0b59bd0c")) => word(U256::from(86_400)),
+                // withdrawalFee() out of MAX_BASIS_POINTS()
+                (_, Some("0x8bc7e8c4")) => word(U256::from(10)),
+                (_, Some("0xf4ea93d8")) => word(U256::from(10_000)),
+                // totalAssets()
+                (_, Some("0x01e1d114")) => word(mon(5_000)),
+                // convertToAssets(uint256), one share worth 1.05 MON
+                (_, Some("0x07a2d13a")) => word(mon(105) / 100),
+                _ => word(mon(4_000)),
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+
+        let info =
+            serde_json::to_value(lst.protocol_info(LstProtocol::AprMON, true).await).unwrap();
+        assert_eq!(info["errors"], serde_json::json!({}));
+        assert_eq!(info["name"], "aPriori Monad LST");
+        assert_eq!(info["symbol"], "aprMON");
+        assert_eq!(info["decimals"], 18);
+        assert_eq!(info["asset"], "0x00000000000000000000000000000000000000AA");
+        assert_eq!(info["paused"], false);
+        assert_eq!(info["max_deposit"], "1000.000000000000000000");
+        assert_eq!(info["deposit_unit"], "MON");
+        assert_eq!(info["withdrawal_wait_seconds"], 86_400);
+        assert_eq!(info["fees"]["withdrawal_fee"], 0.1);
+        // The TVL and the rate are both native amounts, at the same decimals
+        assert_eq!(info["tvl"], "5000.000000000000000000");
+        assert_eq!(info["rate"], "1.050000000000000000");
+        assert!(info["apr"].is_f64(), "{info}");
+        assert!(info["effective_net_apr"].is_f64(), "{info}");
+        assert!(info["as_of"].is_string(), "{info}");
+    }
+
+    #[tokio::test]
+    async fn erc20_vault_amounts_are_counted_in_the_asset() {
+        let asset = Address::repeat_byte(0xaa);
+        let usdm = |amount: u64| U256::from(amount) * U256::exp10(6);
+        let word = |value: U256| serde_json::json!(format!("0x{:064x}", value));
+        let provider = stub_provider(move |method, params| {
+            let to = params[0]["to"]
+                .as_str()
+                .unwrap_or_default()
+                .parse::<Address>();
+            let data = params[0]["data"].as_str().or(params[0]["input"].as_str());
+            let string = |value: &str| {
+                let encoded = ethers::abi::encode(&[ethers::abi::Token::String(value.to_string())]);
+                serde_json::json!(encode_prefixed(encoded))
+            };
+            match (method, data.and_then(|data| data.get(..10))) {
+                ("eth_chainId", _) => serde_json::json!(format!("{:#x}", MONAD_TESTNET_CHAIN_ID)),
+                ("eth_blockNumber", _) => serde_json::json!("0x64"),
+                ("eth_getBlockByNumber", _) => {
+                    serde_json::json!({ "number": "0x64", "timestamp": "0x6553f100" })
+                }
+                (_, Some("0x06fdde03")) => string("shMONAD"),
+                (_, Some("0x95d89b41")) if to.ok() == Some(asset) => string("USDM"),
+                (_, Some("0x95d89b41")) => string("shMON"),
+                // decimals(), of a 6-decimal asset under an 18-decimal vault
+                (_, Some("0x313ce567")) if to.ok() == Some(asset) => word(U256::from(6)),
+                (_, Some("0x313ce567")) => word(U256::from(18)),
+                // asset()
+                (_, Some("0x38d52e0f")) => {
+                    serde_json::json!(encode_prefixed(ethers::abi::encode(&[
+                        ethers::abi::Token::Address(asset)
+                    ])))
+                }
+                // maxDeposit(address)
+                (_, Some("0x402d267d")) => word(usdm(1_000)),
+                // totalAssets()
+                (_, Some("0x01e1d114")) => word(usdm(5_000)),
+                // previewRedeem(uint256)
+                (_, Some("0x4cdad506")) => word(usdm(3) / 2),
+                _ => word(U256::zero()),
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+
+        let info = serde_json::to_value(lst.protocol_info(LstProtocol::SHMON, true).await).unwrap();
+        assert_eq!(info["deposit_unit"], "USDM", "{info}");
+        assert_eq!(info["tvl"], "5000.000000");
+        assert_eq!(info["max_deposit"], "1000.000000");
+
+        let request = serde_json::from_value(serde_json::json!({
+            "protocol": "shMON",
+            "amount": "1",
+            "amount_unit": "shares",
+        }))
+        .unwrap();
+        let result = lst
+            .preview_unstake(request, CancellationToken::new())
+            .await
+            .unwrap();
+        let preview: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(preview["gross_assets"], "1.500000");
+        assert_eq!(preview["net_assets"], "1.500000");
+        assert_eq!(preview["asset_unit"], "USDM");
+    }
+
+    #[tokio::test]
+    async fn oracles_trailing_past_the_threshold_warn_as_stale() {
+        // The oracle last processed block 4000 with the chain at 4096
+        let provider = stub_provider(|method, _| match method {
+            "eth_blockNumber" => serde_json::json!("0x1000"),
+            _ => serde_json::json!(format!("0x{:064x}", 4000)),
+        })
+        .await;
+
+        let stale = Lst::new(provider.clone())
+            .with_oracle_stale_blocks(50)
+            .oracle(LstProtocol::AprMON)
+            .await
+            .unwrap();
+        assert_eq!(stale["lag_blocks"], 96);
+        assert_eq!(stale["stale"], true);
+        assert_eq!(stale["warnings"][0]["code"], "ORACLE_STALE");
+        let message = stale["warnings"][0]["message"].as_str().unwrap();
+        assert!(
+            message.contains("trails the chain head by 96 blocks, more than the 50 block"),
+            "{message}"
+        );
+
+        let fresh = Lst::new(provider)
+            .with_oracle_stale_blocks(100)
+            .oracle(LstProtocol::AprMON)
+            .await
+            .unwrap();
+        assert_eq!(fresh["stale"], false);
+        assert_eq!(fresh["warnings"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn point_in_time_reads_forward_the_pinned_block() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let provider = stub_provider({
+            let calls = calls.clone();
+            move |method, params| match method {
+                "eth_getBlockByNumber" => {
+                    calls
+                        .lock()
+                        .unwrap()
+                        .push(("block".to_string(), params[0].clone()));
+                    serde_json::json!({ "number": "0x64", "timestamp": "0x6553f100" })
+                }
+                "eth_call" => {
+                    let data = params[0]["data"]
+                        .as_str()
+                        .or(params[0]["input"].as_str())
+                        .unwrap_or_default();
+                    calls
+                        .lock()
+                        .unwrap()
+                        .push((data[..10].to_string(), params[1].clone()));
+                    serde_json::json!(format!("0x{:064x}", 18))
+                }
+                _ => serde_json::Value::Null,
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+        let owner = "0x00000000000000000000000000000000000000aa";
+        let at_block = |selector: &str| {
+            calls
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(call, _)| call == selector)
+                .map(|(_, block)| block.clone())
+                .collect::<Vec<_>>()
+        };
+
+        for uri in [
+            "evm://monadTestnet/lsts/aprMON/rate?block=100".to_string(),
+            "evm://monadTestnet/lsts/aprMON/tvl?block=100".to_string(),
+            format!("evm://monadTestnet/address/{owner}/lsts/aprMON/balance?block=100"),
+        ] {
+            let result = lst.dispatch_resource(uri.clone()).await.unwrap();
+            let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+                panic!("{uri} is not text");
+            };
+            let body: serde_json::Value = serde_json::from_str(text).unwrap();
+            assert_eq!(body["block"], 100, "{uri}");
+        }
+
+        // The tag is resolved once, and every read of the state goes to that block
+        assert_eq!(at_block("block"), vec![serde_json::json!("0x64"); 3]);
+        // convertToAssets(uint256), totalAssets() and balanceOf(address)
+        for selector in ["0x07a2d13a", "0x01e1d114", "0x70a08231"] {
+            assert_eq!(
+                at_block(selector),
+                vec![serde_json::json!("0x64")],
+                "{selector}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn history_warns_when_from_block_is_past_the_lookback_limit() {
+        let provider = stub_provider(|method, _| match method {
+            "eth_blockNumber" => serde_json::json!(format!("{:#x}", 25_000)),
+            "eth_getLogs" => serde_json::json!([]),
+            _ => serde_json::json!(format!("0x{:064x}", 18)),
+        })
+        .await;
+        let lst = Lst::new(provider).with_event_scan(EventScanConfig::new(1_000, 10_000).unwrap());
+        let owner = Address::repeat_byte(0xaa);
+
+        let history = lst
+            .address_history(LstProtocol::AprMON, owner, Some(5_000), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(history["from_block"], 15_000);
+        assert_eq!(history["warnings"][0]["code"], "RANGE_CLAMPED");
+        let message = history["warnings"][0]["message"].as_str().unwrap();
+        assert!(
+            message.contains("starts at block 15000 rather than 5000"),
+            "{message}"
+        );
+
+        // Ranges within the limit, and the default range, are scanned as asked
+        for from_block in [Some(20_000), None] {
+            let history = lst
+                .address_history(LstProtocol::AprMON, owner, from_block, None, 10)
+                .await
+                .unwrap();
+            assert_eq!(history["from_block"], from_block.unwrap_or(15_000));
+            assert_eq!(history["warnings"], serde_json::json!([]));
+        }
+    }
+
+    #[tokio::test]
+    async fn history_parses_logs_into_activities_oldest_first() {
+        use ethers::contract::EthEvent;
+
+        use crate::bindings::gmonstakemanager::{DepositFilter, WithdrawFilter};
+
+        let owner = Address::repeat_byte(0xaa);
+        let word = |value: u64| format!("0x{value:064x}");
+        let log = |topics: Vec<String>, data: u64, block: u64, index: u64| {
+            serde_json::json!({
+                "address": LstProtocol::GMON.address(),
+                "topics": topics,
+                "data": word(data),
+                "blockNumber": format!("{block:#x}"),
+                "blockHash": H256::repeat_byte(block as u8),
+                "transactionHash": H256::repeat_byte((block + index) as u8),
+                "transactionIndex": "0x0",
+                "logIndex": format!("{index:#x}"),
+                "removed": false,
+            })
+        };
+        let owner_topic = format!("{:?}", H256::from(owner));
+        let deposit = format!("{:?}", DepositFilter::signature());
+        let withdraw = format!("{:?}", WithdrawFilter::signature());
+        // Returned newest first and interleaved across the two event kinds
+        let deposits = serde_json::json!([
+            log(
+                vec![deposit.clone(), owner_topic.clone(), word(3), word(0)],
+                2,
+                30,
+                1
+            ),
+            log(
+                vec![deposit.clone(), owner_topic.clone(), word(5), word(0)],
+                4,
+                10,
+                0
+            ),
+        ]);
+        let withdrawals = serde_json::json!([
+            log(
+                vec![withdraw.clone(), owner_topic.clone(), word(7)],
+                6,
+                30,
+                0
+            ),
+            log(
+                vec![withdraw.clone(), owner_topic.clone(), word(9)],
+                8,
+                20,
+                0
+            ),
+        ]);
+        let provider = stub_provider(move |method, params| match method {
+            "eth_blockNumber" => serde_json::json!("0x64"),
+            "eth_getLogs" if params[0]["topics"][0] == deposit => deposits.clone(),
+            "eth_getLogs" => withdrawals.clone(),
+            _ => serde_json::json!(word(0)),
+        })
+        .await;
+
+        let activities = LstProtocol::GMON
+            .history(provider, owner, 0, 100, 1_000)
+            .await
+            .unwrap();
+        let parsed: Vec<_> = activities
+            .iter()
+            .map(|a| {
+                (
+                    a.kind.to_string(),
+                    a.assets.as_u64(),
+                    a.shares.as_u64(),
+                    a.block_number,
+                    a.log_index.as_u64(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                ("deposit".to_string(), 5, 4, 10, 0),
+                ("redeem".to_string(), 9, 8, 20, 0),
+                ("redeem".to_string(), 7, 6, 30, 0),
+                ("deposit".to_string(), 3, 2, 30, 1),
+            ]
+        );
+        assert_eq!(activities[0].transaction_hash, H256::repeat_byte(10));
+        assert!(activities.iter().all(|a| a.addresses == [("owner", owner)]));
+    }
+
+    #[tokio::test]
+    async fn an_undeployed_protocol_is_listed_unavailable_and_refuses_reads() {
+        let aprmon = format!("{:?}", LstProtocol::AprMON.address());
+        let provider = stub_provider(move |method, params| match method {
+            "eth_getCode" if params[0] == aprmon => serde_json::json!("0x"),
+            "eth_getCode" => serde_json::json!("0x6080"),
+            _ => serde_json::json!(format!("0x{:064x}", 18)),
+        })
+        .await;
+        let lst = Lst::new(provider);
+        lst.verify_protocols().await.unwrap();
+
+        let listing = lst.protocol_list();
+        assert_eq!(listing["unavailable"][0]["protocol"], "aprMON", "{listing}");
+        let reason = listing["unavailable"][0]["reason"].as_str().unwrap();
+        assert!(
+            reason.starts_with("no contract deployed at token address"),
+            "{reason}"
+        );
+        assert_eq!(listing["unavailable"].as_array().unwrap().len(), 1);
+
+        // Reads are refused with the reason instead of failing to decode
+        let err = lst
+            .dispatch_resource("evm://monadTestnet/lsts/aprMON/tvl".to_string())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains(reason), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn an_omitted_protocol_falls_back_to_the_default() {
+        let protocol_of = |lst: Lst, segment: &'static str| async move {
+            let uri = format!("evm://monadTestnet/lsts/{segment}/actions");
+            let result = lst.dispatch_resource(uri).await?;
+            let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+                panic!("{segment} actions are not text");
+            };
+            let body: serde_json::Value = serde_json::from_str(text).unwrap();
+            Ok::<_, McpError>(body["protocol"].clone())
+        };
+
+        let lst = offline_lst().with_default_protocol(LstProtocol::GMON);
+        assert_eq!(lst.resolve_protocol(None).unwrap(), LstProtocol::GMON);
+        assert_eq!(protocol_of(lst.clone(), "default").await.unwrap(), "gMON");
+        // Explicit protocols win over the default
+        assert_eq!(
+            lst.resolve_protocol(Some(LstProtocol::SHMON)).unwrap(),
+            LstProtocol::SHMON
+        );
+        assert_eq!(protocol_of(lst, "aprMON").await.unwrap(), "aprMON");
+
+        // Without a default, the protocol must be given
+        let lst = offline_lst();
+        let err = lst.resolve_protocol(None).unwrap_err();
+        assert!(err.to_string().contains("no default protocol set"), "{err}");
+        assert!(protocol_of(lst, "default").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn the_list_protocols_tool_matches_the_lsts_resource() {
+        let deprecations = Deprecations::new(
+            [DeprecatedProtocol {
+                protocol: LstProtocol::AprMON,
+                notice: Some("Migrating to gMON".to_string()),
+            }],
+            false,
+        );
+        let lst = offline_lst().with_deprecations(deprecations);
+        let resource = lst
+            .dispatch_resource("evm://monadTestnet/lsts".to_string())
+            .await
+            .unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &resource.contents[0] else {
+            panic!("lsts is not text");
+        };
+        let resource: serde_json::Value = serde_json::from_str(text).unwrap();
+
+        for network in [None, Some("monadTestnet")] {
+            let request = ListProtocolsRequest {
+                network: network.map(str::to_string),
+            };
+            let result = lst.list_protocols(request).await.unwrap();
+            let text = &result.content[0].as_text().unwrap().text;
+            let tool: serde_json::Value = serde_json::from_str(text).unwrap();
+            assert_eq!(tool, resource, "{network:?}");
+        }
+        assert_eq!(resource["deprecated"][0]["protocol"], "aprMON");
+
+        let request = ListProtocolsRequest {
+            network: Some("ethereum".to_string()),
+        };
+        assert!(lst.list_protocols(request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn history_returns_the_most_recent_activities_up_to_the_limit() {
+        use ethers::contract::EthEvent;
+
+        use crate::bindings::gmonstakemanager::DepositFilter;
+
+        let owner = Address::repeat_byte(0xaa);
+        let word = |value: u64| format!("0x{value:064x}");
+        let deposit = format!("{:?}", DepositFilter::signature());
+        // One deposit of `block` MON wei in each of blocks 10 to 50
+        let deposits = (1..=5)
+            .map(|i| {
+                let block = i * 10;
+                let log = serde_json::json!({
+                    "address": LstProtocol::GMON.address(),
+                    "topics": [deposit, format!("{:?}", H256::from(owner)), word(block), word(0)],
+                    "data": word(block),
+                    "blockNumber": format!("{block:#x}"),
+                    "blockHash": H256::repeat_byte(block as u8),
+                    "transactionHash": H256::repeat_byte(block as u8),
+                    "transactionIndex": "0x0",
+                    "logIndex": "0x0",
+                    "removed": false,
+                });
+                (block, log)
+            })
+            .collect::<Vec<_>>();
+        let provider = stub_provider(move |method, params| match method {
+            "eth_blockNumber" => serde_json::json!("0x64"),
+            // Logs are scanned in chunks, each asking for its own range
+            "eth_getLogs" if params[0]["topics"][0] == deposit => {
+                let block = |key: &str| {
+                    let hex = params[0][key].as_str().unwrap().trim_start_matches("0x");
+                    u64::from_str_radix(hex, 16).unwrap()
+                };
+                let range = block("fromBlock")..=block("toBlock");
+                serde_json::json!(
+                    deposits
+                        .iter()
+                        .filter(|(block, _)| range.contains(block))
+                        .map(|(_, log)| log)
+                        .collect::<Vec<_>>()
+                )
+            }
+            "eth_getLogs" => serde_json::json!([]),
+            _ => serde_json::json!(word(18)),
+        })
+        .await;
+        let lst = Lst::new(provider);
+        let blocks = |history: &serde_json::Value| {
+            history["activities"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|a| a["block"].as_u64().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let history = lst
+            .address_history(LstProtocol::GMON, owner, None, None, 3)
+            .await
+            .unwrap();
+        assert_eq!(blocks(&history), [30, 40, 50]);
+        assert_eq!(history["truncated"], true);
+
+        // The cursor pages back to the older ones
+        let cursor = history["next_cursor"].as_str().unwrap().parse().unwrap();
+        let history = lst
+            .address_history(LstProtocol::GMON, owner, None, Some(cursor), 3)
+            .await
+            .unwrap();
+        assert_eq!(blocks(&history), [10, 20]);
+        assert_eq!(history["truncated"], false);
+        assert_eq!(history["next_cursor"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn addresses_decoded_from_events_are_checksummed() {
+        use ethers::contract::EthEvent;
+
+        use crate::bindings::gmonstakemanager::DepositFilter;
+
+        let owner = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf";
+        let owner_address = owner.parse::<Address>().unwrap();
+        // Logs carry addresses as lowercase topics
+        let log = serde_json::json!({
+            "address": LstProtocol::GMON.address(),
+            "topics": [
+                format!("{:?}", DepositFilter::signature()),
+                format!("{:?}", H256::from(owner_address)),
+                format!("0x{:064x}", 1),
+                format!("0x{:064x}", 0),
+            ],
+            "data": format!("0x{:064x}", 1),
+            "blockNumber": "0x10",
+            "blockHash": H256::repeat_byte(0x10),
+            "transactionHash": H256::repeat_byte(0x10),
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+            "removed": false,
+        });
+        let deposit = log["topics"][0].clone();
+        let provider = stub_provider(move |method, params| match method {
+            "eth_blockNumber" => serde_json::json!("0x64"),
+            // Returned once, by the first chunk of the scan
+            "eth_getLogs"
+                if params[0]["topics"][0] == deposit && params[0]["fromBlock"] == "0x0" =>
+            {
+                serde_json::json!([log])
+            }
+            "eth_getLogs" => serde_json::json!([]),
+            _ => serde_json::json!(format!("0x{:064x}", 18)),
+        })
+        .await;
+        let lst = Lst::new(provider);
+
+        let history = lst
+            .address_history(LstProtocol::GMON, owner_address, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(history["address"], owner);
+        assert_eq!(
+            history["activities"][0]["addresses"]["owner"], owner,
+            "{history}"
+        );
+    }
+
+    /// Progress of a write that has sent a transaction with hash `0x11…11`.
+    async fn sent_progress() -> Progress {
+        let progress = Progress::default();
+        progress
+            .track_send(async { Ok::<_, ()>(H256::repeat_byte(0x11)) }, |hash| *hash)
+            .await
+            .unwrap();
+        progress
+    }
+
+    #[tokio::test]
+    async fn writes_are_abandoned_only_before_sending() {
+        let lst = offline_lst();
+        let ct = CancellationToken::new();
+        ct.cancel();
+        let result = lst
+            .cancellable_write(
+                &ct,
+                Duration::from_secs(5),
+                &Progress::default(),
+                futures::future::pending::<()>(),
+            )
+            .await;
+        assert!(matches!(result, Err(McpServiceError::Internal(_))));
+
+        // Once sent, a cancellation lets the write finish
+        let progress = sent_progress().await;
+        let result = lst
+            .cancellable_write(&ct, Duration::from_secs(5), &progress, async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                "confirmed"
+            })
+            .await;
+        assert!(matches!(result, Ok(WriteOutcome::Finished("confirmed"))));
+    }
+
+    #[tokio::test]
+    async fn writes_out_of_time_after_sending_are_pending() {
+        let lst = offline_lst();
+        let ct = CancellationToken::new();
+        let result = lst
+            .cancellable_write(
+                &ct,
+                Duration::from_millis(20),
+                &Progress::default(),
+                futures::future::pending::<()>(),
+            )
+            .await;
+        assert!(matches!(result, Err(McpServiceError::Timeout(_))));
+
+        let progress = sent_progress().await;
+        let result = lst
+            .cancellable_write(
+                &ct,
+                Duration::from_millis(20),
+                &progress,
+                futures::future::pending::<()>(),
+            )
+            .await;
+        assert!(
+            matches!(result, Ok(WriteOutcome::Pending(hash)) if hash == H256::repeat_byte(0x11))
+        );
+
+        let pending = lst.pending_write("stake", H256::repeat_byte(0x11)).unwrap();
+        assert_eq!(
+            pending.content[1].as_text().map(|text| text.text.clone()),
+            Some(
+                serde_json::json!({
+                    "status": "pending",
+                    "tx_hash": encode_prefixed(H256::repeat_byte(0x11)),
+                })
+                .to_string()
+            )
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_write_outlasts_a_read_of_the_same_duration() {
+        let timeouts =
+            ToolTimeouts::new(Duration::from_secs(30), Duration::from_secs(300), []).unwrap();
+        let lst = offline_lst().with_timeouts(timeouts);
+        let ct = CancellationToken::new();
+        let work = || tokio::time::sleep(Duration::from_secs(60));
+
+        let read = lst
+            .cancellable(&ct, lst.timeouts.for_tool("quote_route"), work())
+            .await
+            .unwrap_err();
+        assert_eq!(read.kind(), "TIMEOUT");
+        assert_eq!(read.to_string(), "Timed out after 30 seconds");
+
+        let write = lst
+            .cancellable_write(
+                &ct,
+                lst.timeouts.for_tool("stake"),
+                &Progress::new(None, None),
+                work(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(write, WriteOutcome::Finished(())));
+    }
+
+    #[tokio::test]
+    async fn a_client_disconnect_cancels_a_read_in_progress() {
+        // The endpoint takes every request but never answers it
+        let requested = Arc::new(tokio::sync::Notify::new());
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post({
+                let requested = requested.clone();
+                move || {
+                    requested.notify_one();
+                    std::future::pending::<axum::Json<serde_json::Value>>()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let connection = CancellationToken::new();
+        let lst = Lst::new(Arc::new(CallLogger::new(
+            Provider::new(FailoverHttp::new(&[url]).unwrap()),
+            false,
+        )))
+        .for_connection(connection.clone());
+
+        let quote = serde_json::from_value(serde_json::json!({ "amount": "1" })).unwrap();
+        let read =
+            tokio::spawn(async move { lst.quote_route(quote, CancellationToken::new()).await });
+        requested.notified().await;
+        connection.cancel();
+
+        let err = tokio::time::timeout(Duration::from_secs(5), read)
+            .await
+            .expect("the read outlived the connection")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.message, "Client disconnected");
+    }
+
+    #[tokio::test]
+    async fn a_chain_id_mismatch_degrades_health_and_refuses_writes() {
+        let chain_id = Arc::new(std::sync::atomic::AtomicU64::new(MONAD_TESTNET_CHAIN_ID));
+        let provider = stub_provider({
+            let chain_id = chain_id.clone();
+            move |method, _| match method {
+                "eth_chainId" => serde_json::json!(format!(
+                    "{:#x}",
+                    chain_id.load(std::sync::atomic::Ordering::SeqCst)
+                )),
+                _ => serde_json::json!(format!("0x{:064x}", 0)),
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+        async fn health(lst: &Lst) -> serde_json::Value {
+            let result = lst
+                .dispatch_resource("evm://health".to_string())
+                .await
+                .unwrap();
+            let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+                panic!("health is not text");
+            };
+            serde_json::from_str(text).unwrap()
+        }
+
+        lst.verify_chain_id().await.unwrap();
+        assert_eq!(health(&lst).await["status"], "ok");
+
+        // The endpoint now serves another chain, as the periodic check finds
+        chain_id.store(1, std::sync::atomic::Ordering::SeqCst);
+        assert!(lst.verify_chain_id().await.is_err());
+        let body = health(&lst).await;
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["detected_chain_id"], 1);
+        assert!(
+            body["error"].as_str().unwrap().contains("does not match"),
+            "{body}"
+        );
+
+        let stake =
+            serde_json::from_value(serde_json::json!({ "protocol": "aprMON", "amount": "1" }))
+                .unwrap();
+        let err = lst
+            .stake(stake, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "RPC_ERROR");
+        assert!(
+            err.message.contains("Refusing to send transaction"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[tokio::test]
+    async fn writes_of_nothing_are_rejected_as_amount_zero() {
+        let provider = stub_provider(|method, params| {
+            let data = params[0]["data"].as_str().or(params[0]["input"].as_str());
+            match method {
+                "eth_chainId" => serde_json::json!(format!("{:#x}", MONAD_TESTNET_CHAIN_ID)),
+                // decimals()
+                "eth_call" if data == Some("0x313ce567") => {
+                    serde_json::json!(format!("0x{:064x}", 18))
+                }
+                // Every balance, including maxRedeem, is empty
+                _ => serde_json::json!(format!("0x{:064x}", 0)),
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+        lst.verify_chain_id().await.unwrap();
+        let private_key = format!("{:064x}", 1);
+        let kind = |err: McpError| err.data.unwrap()["kind"].clone();
+
+        let stake =
+            serde_json::from_value(serde_json::json!({ "protocol": "aprMON", "amount": "0" }))
+                .unwrap();
+        let err = lst
+            .stake(stake, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert_eq!(kind(err), "AMOUNT_ZERO");
+
+        let unstake =
+            serde_json::from_value(serde_json::json!({ "protocol": "aprMON", "amount_wei": "0" }))
+                .unwrap();
+        let err = lst
+            .unstake(unstake, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert_eq!(kind(err), "AMOUNT_ZERO");
+
+        let unstake_all = serde_json::from_value(serde_json::json!({
+            "protocol": "aprMON",
+            "private_key": private_key,
+        }))
+        .unwrap();
+        let err = lst
+            .unstake_all(unstake_all, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("has no aprMON to unstake"),
+            "{}",
+            err.message
+        );
+        assert_eq!(kind(err), "AMOUNT_ZERO");
+    }
+
+    #[tokio::test]
+    async fn a_signed_message_recovers_to_the_signer() {
+        let lst = offline_lst();
+        // The address of private key 1
+        let signer = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf";
+        let request = SignMessageRequest {
+            private_key: Some(format!("{:064x}", 1)),
+            message: "Sign in to example.org".to_string(),
+        };
+        let result = lst.sign_message(request).await.unwrap();
+        let body: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(body["address"], signer);
+
+        let signature = body["signature"].as_str().unwrap();
+        let recovered = signature
+            .parse::<Signature>()
+            .unwrap()
+            .recover("Sign in to example.org")
+            .unwrap();
+        assert_eq!(to_checksum(&recovered, None), signer);
+
+        // The verify tool agrees, and only for the message that was signed
+        for (message, valid) in [("Sign in to example.org", true), ("Sign in", false)] {
+            let request = VerifySignatureRequest {
+                address: signer.to_string(),
+                message: message.to_string(),
+                signature: signature.to_string(),
+            };
+            let result = lst.verify_signature(request).await.unwrap();
+            let body: serde_json::Value =
+                serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+            assert_eq!(body["valid"], valid, "{message}");
+        }
+    }
+
+    #[tokio::test]
+    async fn signed_typed_data_recovers_to_the_signer() {
+        // The example mail of EIP-712, whose digest is given by the spec
+        let lst = offline_lst().with_expected_chain_id(1);
+        let signer = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf";
+        let mut request = serde_json::json!({
+            "private_key": format!("{:064x}", 1),
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC",
+            },
+            "types": {
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallet", "type": "address" },
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person" },
+                    { "name": "contents", "type": "string" },
+                ],
+            },
+            "primary_type": "Mail",
+            "message": {
+                "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+                "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+                "contents": "Hello, Bob!",
+            },
+        });
+        let result = lst
+            .sign_typed_data(serde_json::from_value(request.clone()).unwrap())
+            .await
+            .unwrap();
+        let body: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(body["address"], signer);
+        assert_eq!(
+            body["digest"],
+            "0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+
+        let digest = body["digest"].as_str().unwrap().parse::<H256>().unwrap();
+        let recovered = body["signature"]
+            .as_str()
+            .unwrap()
+            .parse::<Signature>()
+            .unwrap()
+            .recover(digest)
+            .unwrap();
+        assert_eq!(to_checksum(&recovered, None), signer);
+
+        // A message missing a field of its type is rejected instead of signed as zero
+        request["message"]["to"]
+            .as_object_mut()
+            .unwrap()
+            .remove("wallet");
+        let err = lst
+            .sign_typed_data(serde_json::from_value(request).unwrap())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("wallet"), "{}", err.message);
+    }
+
+    /// Service over a chain where the key `1` signer has a pending EIP-1559 transaction
+    /// `0x22…22` at nonce 5, with a 100 gwei max fee and a 2 gwei tip, and transaction counts
+    /// of 5 latest and 6 pending. Records every transaction sent.
+    async fn pending_tx_lst() -> (Lst, Arc<std::sync::Mutex<Vec<TypedTransaction>>>) {
+        let signer = "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+        let gwei = |value: u64| format!("{:#x}", U256::from(value) * U256::exp10(9));
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let provider = stub_provider({
+            let sent = sent.clone();
+            move |method, params| match method {
+                "eth_chainId" => serde_json::json!(format!("{:#x}", MONAD_TESTNET_CHAIN_ID)),
+                "eth_getTransactionByHash" => serde_json::json!({
+                    "hash": H256::repeat_byte(0x22),
+                    "nonce": "0x5",
+                    "blockHash": null,
+                    "blockNumber": null,
+                    "transactionIndex": null,
+                    "from": signer,
+                    "to": Address::repeat_byte(0xbb),
+                    "value": "0x3",
+                    "gas": "0x30000",
+                    "input": "0xabcdef",
+                    "type": "0x2",
+                    "maxFeePerGas": gwei(100),
+                    "maxPriorityFeePerGas": gwei(2),
+                    "chainId": format!("{:#x}", MONAD_TESTNET_CHAIN_ID),
+                    "accessList": [],
+                    "v": "0x0",
+                    "r": "0x1",
+                    "s": "0x1",
+                }),
+                "eth_getTransactionCount" if params[1] == "pending" => serde_json::json!("0x6"),
+                "eth_getTransactionCount" => serde_json::json!("0x5"),
+                "eth_getBlockByNumber" => {
+                    serde_json::json!({ "number": "0x64", "baseFeePerGas": gwei(1) })
+                }
+                "eth_feeHistory" => serde_json::json!({
+                    "oldestBlock": "0x63",
+                    "baseFeePerGas": [gwei(1), gwei(1)],
+                    "gasUsedRatio": [0.5],
+                    "reward": [[gwei(1)]],
+                }),
+                "eth_getBalance" => serde_json::json!(format!("{:#x}", mon(100))),
+                "eth_sendRawTransaction" => {
+                    let raw = ethers::utils::hex::decode(params[0].as_str().unwrap()).unwrap();
+                    let (tx, _) =
+                        TypedTransaction::decode_signed(&ethers::utils::rlp::Rlp::new(&raw))
+                            .unwrap();
+                    sent.lock().unwrap().push(tx);
+                    serde_json::json!(H256::from(ethers::utils::keccak256(&raw)))
+                }
+                _ => serde_json::Value::Null,
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+        lst.verify_chain_id().await.unwrap();
+
+        (lst, sent)
+    }
+
+    #[tokio::test]
+    async fn speed_up_resends_at_the_same_nonce_with_higher_fees() {
+        let (lst, sent) = pending_tx_lst().await;
+        let request = serde_json::from_value(serde_json::json!({
+            "private_key": format!("{:064x}", 1),
+            "tx_hash": format!("{:?}", H256::repeat_byte(0x22)),
+        }))
+        .unwrap();
+        lst.speed_up(request, CancellationToken::new())
+            .await
+            .unwrap();
+
+        let sent = sent.lock().unwrap();
+        let [TypedTransaction::Eip1559(replacement)] = &sent[..] else {
+            panic!("expected one EIP-1559 replacement, sent {sent:?}");
+        };
+        assert_eq!(replacement.nonce, Some(U256::from(5)));
+        // Fees rise by at least 10%, and the call itself is unchanged
+        let gwei = |value: u64| U256::from(value) * U256::exp10(9);
+        assert!(replacement.max_fee_per_gas.unwrap() >= gwei(110));
+        assert!(replacement.max_priority_fee_per_gas.unwrap() >= gwei(2) * 11 / 10);
+        assert_eq!(replacement.to, Some(Address::repeat_byte(0xbb).into()));
+        assert_eq!(replacement.value, Some(U256::from(3)));
+        assert_eq!(replacement.data.as_deref(), Some(&[0xab, 0xcd, 0xef][..]));
+        assert_eq!(replacement.gas, Some(U256::from(0x30000)));
+    }
+
+    #[tokio::test]
+    async fn cancel_tx_sends_a_zero_value_self_transfer_at_the_nonce() {
+        let signer = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+            .parse::<Address>()
+            .unwrap();
+        let (lst, sent) = pending_tx_lst().await;
+
+        let by_hash = serde_json::json!({ "tx_hash": format!("{:?}", H256::repeat_byte(0x22)) });
+        let by_nonce = serde_json::json!({ "nonce": 5 });
+        for mut target in [by_hash, by_nonce] {
+            target["private_key"] = serde_json::json!(format!("{:064x}", 1));
+            let request = serde_json::from_value(target.clone()).unwrap();
+            lst.cancel_tx(request, CancellationToken::new())
+                .await
+                .unwrap();
+
+            let Some(TypedTransaction::Eip1559(cancellation)) = sent.lock().unwrap().pop() else {
+                panic!("no EIP-1559 cancellation sent for {target}");
+            };
+            assert_eq!(cancellation.nonce, Some(U256::from(5)), "{target}");
+            assert_eq!(cancellation.to, Some(signer.into()));
+            assert_eq!(cancellation.value, Some(U256::zero()));
+            assert_eq!(cancellation.data.unwrap_or_default().len(), 0);
+            assert_eq!(cancellation.gas, Some(U256::from(TRANSFER_GAS)));
+        }
+
+        // A nonce with nothing pending at it is refused
+        let request = serde_json::from_value(serde_json::json!({
+            "private_key": format!("{:064x}", 1),
+            "nonce": 6,
+        }))
+        .unwrap();
+        let err = lst
+            .cancel_tx(request, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(
+            err.message.contains("No pending transaction with nonce 6"),
+            "{}",
+            err.message
+        );
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn write_signers_are_bound_to_the_expected_chain_id() {
+        let lst = offline_lst().with_expected_chain_id(4321);
+        let key = format!("{:064x}", 1);
+        let address = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+            .parse::<Address>()
+            .unwrap();
+
+        let explicit = lst.signer_provider(Some(key.clone())).unwrap();
+        assert_eq!(explicit.signer().chain_id(), 4321);
+        assert_eq!(explicit.address(), address);
+
+        // A session signer parsed without a chain id is bound too
+        lst.session.set_signer(key.parse::<LocalWallet>().unwrap());
+        let session = lst.signer_provider(None).unwrap();
+        assert_eq!(session.signer().chain_id(), 4321);
+        assert_eq!(session.address(), address);
+    }
+
+    #[tokio::test]
+    async fn sent_transactions_are_signed_for_the_configured_chain() {
+        let (lst, sent) = pending_tx_lst().await;
+        let request = serde_json::from_value(serde_json::json!({
+            "private_key": format!("{:064x}", 1),
+            "nonce": 5,
+        }))
+        .unwrap();
+        lst.cancel_tx(request, CancellationToken::new())
+            .await
+            .unwrap();
+
+        let sent = sent.lock().unwrap();
+        let [tx] = &sent[..] else {
+            panic!("expected one transaction, sent {sent:?}");
+        };
+        assert_eq!(tx.chain_id(), Some(MONAD_TESTNET_CHAIN_ID.into()));
+    }
+
+    #[tokio::test]
+    async fn balances_of_several_addresses_isolate_each_error() {
+        // Each holder has as many tokens as its last byte, and reads for 0x…cc fail
+        let provider = stub_rpc(|_, params| {
+            let data = params[0]["data"]
+                .as_str()
+                .or(params[0]["input"].as_str())
+                .unwrap();
+            match &data[..10] {
+                // decimals()
+                "0x313ce567" => Ok(serde_json::json!(format!("0x{:064x}", 18))),
+                _ if data.ends_with("cc") => Err(serde_json::json!({
+                    "code": -32000,
+                    "message": "upstream unavailable",
+                })),
+                _ => {
+                    let holder = u64::from_str_radix(&data[data.len() - 2..], 16).unwrap();
+                    Ok(serde_json::json!(format!("0x{:064x}", mon(holder))))
+                }
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+        let holder = |byte: u8| to_checksum(&Address::repeat_byte(byte), None);
+
+        let addresses = [
+            holder(0x0a),
+            "not-an-address".to_string(),
+            holder(0x0b),
+            holder(0xcc),
+        ];
+        let body = lst
+            .balances(LstProtocol::AprMON, &addresses.join(","), None)
+            .await
+            .unwrap();
+        let balances = &body["balances"];
+        assert_eq!(balances.as_object().unwrap().len(), 4, "{body}");
+        assert_eq!(balances[holder(0x0a)]["balance"], "10.000000000000000000");
+        assert_eq!(balances[holder(0x0b)]["balance"], "11.000000000000000000");
+        assert_eq!(balances["not-an-address"]["kind"], "INVALID_INPUT");
+        assert_eq!(balances[holder(0xcc)]["kind"], "RPC_ERROR", "{body}");
+
+        // The list is capped
+        let too_many = vec![holder(0x0a); BALANCES_MAX_ADDRESSES + 1].join(",");
+        let err = lst
+            .balances(LstProtocol::AprMON, &too_many, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("At most"), "{err}");
+    }
+    #[tokio::test]
+    async fn post_balances_are_read_at_the_receipt_block() {
+        let blocks = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let provider = stub_provider({
+            let blocks = blocks.clone();
+            move |method, params| {
+                // Both eth_call and eth_getBalance take the block second
+                blocks
+                    .lock()
+                    .unwrap()
+                    .push((method.to_string(), params[1].clone()));
+                serde_json::json!(format!("0x{:064x}", mon(2)))
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+        lst.decimals.store(
+            LstProtocol::AprMON,
+            Decimals {
+                shares: 18,
+                assets: 18,
+            },
+        );
+        let receipt = TransactionReceipt {
+            block_number: Some(0x42.into()),
+            ..Default::default()
+        };
+        let owner = Address::repeat_byte(0xaa);
+
+        let lst_balance = lst
+            .post_balance(Some(LstProtocol::AprMON), owner, &receipt)
+            .await;
+        assert!(
+            lst_balance.contains(": 2.000000000000000000 aprMON at block 66"),
+            "{lst_balance}"
+        );
+        let native_balance = lst.post_balance(None, owner, &receipt).await;
+        assert!(
+            native_balance.contains(": 2.000000000000000000 MON at block 66"),
+            "{native_balance}"
+        );
+
+        assert_eq!(
+            *blocks.lock().unwrap(),
+            [
+                ("eth_call".to_string(), serde_json::json!("0x42")),
+                ("eth_getBalance".to_string(), serde_json::json!("0x42")),
+            ]
+        );
+    }
+
+    pub(super) fn redeem_request(controller: Address) -> RedeemRequestInfo {
+        RedeemRequestInfo {
+            request_id: U256::from(7),
+            shares: mon(1),
+            controller,
+            assets: mon(1),
+            claimed: false,
+            timestamp: 1_000,
+            claimable_at: 2_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn redeem_requests_report_their_position_in_the_queue() {
+        let selector = |signature: &str| encode_prefixed(&ethers::utils::id(signature)[..4]);
+        let (last_processed, next) = (
+            selector("lastProcessedRequestId()"),
+            selector("nextRequestId()"),
+        );
+        let request = selector("redeemRequests(uint256)");
+        let provider = stub_provider(move |method, params| match method {
+            "eth_getBlockByNumber" => {
+                serde_json::json!({ "number": "0x64", "timestamp": "0x6553f100" })
+            }
+            _ => {
+                let data = params[0]["data"]
+                    .as_str()
+                    .or(params[0]["input"].as_str())
+                    .unwrap();
+                let tokens = match &data[..10] {
+                    s if s == last_processed => vec![Token::Uint(U256::from(4))],
+                    s if s == next => vec![Token::Uint(U256::from(10))],
+                    s if s == request => vec![
+                        Token::Uint(mon(1)),
+                        Token::Address(Address::repeat_byte(0xaa)),
+                        Token::Uint(mon(1)),
+                        Token::Bool(false),
+                        Token::Uint(U256::from(1_000)),
+                    ],
+                    // decimals() and withdrawalWaitTime()
+                    _ => vec![Token::Uint(U256::from(18))],
+                };
+                serde_json::json!(encode_prefixed(ethers::abi::encode(&tokens)))
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+        let status = |request_id: u64| {
+            let lst = lst.clone();
+            async move {
+                lst.redeem_request(LstProtocol::AprMON, U256::from(request_id))
+                    .await
+                    .unwrap()
+                    .unwrap()
+            }
+        };
+
+        // Requests 5 to 7 are still to be processed
+        let pending = status(7).await;
+        assert_eq!(pending["queue_position"], "3");
+        assert_eq!(pending["processed"], false);
+        assert_eq!(pending["last_processed_request_id"], "4");
+        assert_eq!(pending["next_request_id"], "10");
+
+        let processed = status(4).await;
+        assert_eq!(processed["queue_position"], "0");
+        assert_eq!(processed["processed"], true);
+    }
+    #[tokio::test(start_paused = true)]
+    async fn redeem_waits_until_the_request_becomes_claimable() {
+        let controller = Address::repeat_byte(0xaa);
+        let checks = std::cell::Cell::new(0);
+        // The request matures, and is then processed, over successive checks
+        let status = || {
+            checks.set(checks.get() + 1);
+            let check = checks.get();
+            async move {
+                Ok(Some(RedeemStatus {
+                    request: redeem_request(controller),
+                    queue: RedeemQueue {
+                        last_processed_request_id: U256::from(6 + check / 3),
+                        next_request_id: U256::from(8),
                     },
-                    annotations: None,
-                },
-                ResourceTemplate {
-                    raw: RawResourceTemplate {
-                        uri_template: "evm://{network}/lsts/{lst}".to_string(),
-                        name: "Details of a specific LST protocol".to_string(),
-                        description: None,
-                        mime_type: Some("text".to_string()),
+                    processed_confirmed: check >= 3,
+                    now: 1_000 + check * 500,
+                }))
+            }
+        };
+
+        let wait = wait_for_claimable(
+            status,
+            LstProtocol::AprMON,
+            controller,
+            Duration::from_secs(3_600),
+            &Progress::default(),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(wait, Some(RedeemWait::Ready)));
+        assert_eq!(checks.get(), 3);
+
+        // Another signer is turned away on the first check instead of waiting
+        checks.set(0);
+        let err = wait_for_claimable(
+            status,
+            LstProtocol::AprMON,
+            Address::repeat_byte(0xbb),
+            Duration::from_secs(3_600),
+            &Progress::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("belongs to controller"), "{err}");
+        assert_eq!(checks.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn metadata_ttl_is_per_service_and_only_admins_invalidate_it() {
+        let decimals = Decimals {
+            shares: 18,
+            assets: 6,
+        };
+        let expiring = offline_lst()
+            .with_metadata_ttl(Duration::ZERO)
+            .with_admin_token(AdminToken::new("secret").unwrap());
+        let lasting = offline_lst();
+        expiring.decimals.store(LstProtocol::AprMON, decimals);
+        lasting.decimals.store(LstProtocol::AprMON, decimals);
+
+        assert_eq!(expiring.decimals.get(LstProtocol::AprMON), None);
+        assert_eq!(lasting.decimals.get(LstProtocol::AprMON), Some(decimals));
+
+        // As marked by the admin middleware on the call's HTTP request
+        let admin = || {
+            let (mut parts, _) = axum::http::Request::new(()).into_parts();
+            parts.extensions.insert(AdminAccess);
+            let mut extensions = Extensions::new();
+            extensions.insert(parts);
+            extensions
+        };
+        let disabled = lasting.ensure_admin(&admin()).unwrap_err();
+        assert_eq!(disabled.kind(), "UNSUPPORTED");
+        let missing = expiring.ensure_admin(&Extensions::new()).unwrap_err();
+        assert_eq!(missing.kind(), "UNAUTHORIZED");
+        assert!(expiring.ensure_admin(&admin()).is_ok());
+        assert_eq!(lasting.decimals.get(LstProtocol::AprMON), Some(decimals));
+
+        let shared = lasting
+            .clone()
+            .with_admin_token(AdminToken::new("secret").unwrap());
+        shared.invalidate_cache().await.unwrap();
+        assert_eq!(lasting.decimals.get(LstProtocol::AprMON), None);
+    }
+
+    /// Client speaking raw JSON-RPC lines to a service over an in-memory pipe.
+    struct RawClient {
+        lines: tokio::io::Lines<tokio::io::BufReader<tokio::io::ReadHalf<tokio::io::DuplexStream>>>,
+        write: tokio::io::WriteHalf<tokio::io::DuplexStream>,
+    }
+
+    impl RawClient {
+        /// Serves `lst` to a new client, returning once the handshake is done.
+        async fn connect(lst: Lst) -> (rmcp::service::RunningService<RoleServer, Lst>, Self) {
+            use rmcp::ServiceExt;
+            use tokio::io::AsyncBufReadExt;
+
+            let (client, server) = tokio::io::duplex(64 * 1024);
+            let (read, write) = tokio::io::split(client);
+            let mut client = RawClient {
+                lines: tokio::io::BufReader::new(read).lines(),
+                write,
+            };
+            let server = tokio::spawn(lst.serve(server));
+            client
+                .send(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": "initialize",
+                    "params": {
+                        "protocolVersion": "2024-11-05",
+                        "capabilities": {},
+                        "clientInfo": { "name": "test", "version": "0" },
                     },
-                    annotations: None,
-                },
-                ResourceTemplate {
-                    raw: RawResourceTemplate {
-                        uri_template: "evm://{network}/address/{address}/lsts/{lst}/balance"
-                            .to_string(),
-                        name: "Get balance of LST token for a given address".to_string(),
-                        description: None,
-                        mime_type: Some("text".to_string()),
+                }))
+                .await;
+            client.receive().await;
+            client
+                .send(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/initialized",
+                }))
+                .await;
+
+            (server.await.unwrap().unwrap(), client)
+        }
+
+        async fn send(&mut self, message: serde_json::Value) {
+            use tokio::io::AsyncWriteExt;
+
+            self.write
+                .write_all(format!("{}\n", message).as_bytes())
+                .await
+                .unwrap();
+        }
+
+        async fn receive(&mut self) -> serde_json::Value {
+            let line = self.lines.next_line().await.unwrap().unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn a_call_missing_a_required_argument_is_rejected_naming_it() {
+        let tools = Lst::tool_box().list();
+        let verify = tools
+            .iter()
+            .find(|tool| tool.name == "verify_signature")
+            .unwrap();
+        assert_eq!(
+            verify.input_schema["required"],
+            serde_json::json!(["address", "message", "signature"])
+        );
+
+        let (_server, mut client) = RawClient::connect(offline_lst()).await;
+        client
+            .send(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": {
+                    "name": "verify_signature",
+                    "arguments": {
+                        "address": "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf",
+                        "message": "hello",
                     },
-                    annotations: None,
                 },
+            }))
+            .await;
+        let response = client.receive().await;
+        let message = response["error"]["message"].as_str().unwrap();
+        assert!(message.contains("missing field `signature`"), "{response}");
+    }
+    #[tokio::test]
+    async fn admin_tools_refuse_calls_without_the_admin_header() {
+        let lst = offline_lst().with_admin_token(AdminToken::new("secret").unwrap());
+        let (_server, mut client) = RawClient::connect(lst).await;
+
+        for (id, tool) in (2..).zip(ADMIN_TOOLS) {
+            client
+                .send(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "tools/call",
+                    "params": { "name": tool, "arguments": {} },
+                }))
+                .await;
+            let response = client.receive().await;
+            assert_eq!(response["id"], id);
+            assert_eq!(
+                response["error"]["data"]["kind"], "UNAUTHORIZED",
+                "{tool}: {response}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn progress_reaches_the_client_in_order_under_the_meta_token() {
+        let (server, mut client) = RawClient::connect(offline_lst()).await;
+
+        let mut request = CallToolRequestParam {
+            name: "stake".into(),
+            arguments: serde_json::json!({ "protocol": "aprMON", "amount": "1" })
+                .as_object()
+                .cloned(),
+        };
+        let meta: Meta =
+            serde_json::from_value(serde_json::json!({ "progressToken": "stake-1" })).unwrap();
+        progress_token_from_meta(&mut request, &meta);
+        let stake: StakeRequest =
+            serde_json::from_value(serde_json::Value::Object(request.arguments.unwrap())).unwrap();
+
+        let progress = Progress::new(Some(server.peer().clone()), stake.progress_token);
+        for step in [
+            WriteStep::Approving,
+            WriteStep::Depositing,
+            WriteStep::AwaitingConfirmation,
+        ] {
+            progress.report(step).await;
+        }
+        for expected in 1..=3 {
+            let message = client.receive().await;
+            assert_eq!(message["method"], "notifications/progress");
+            assert_eq!(message["params"]["progressToken"], "stake-1");
+            assert_eq!(message["params"]["progress"], expected);
+        }
+    }
+
+    #[test]
+    fn signer_and_write_tool_lists_match_the_tool_schemas() {
+        let tools = Lst::tool_box().list();
+        let takes = |tool: &Tool, argument: &str| {
+            tool.input_schema
+                .get("properties")
+                .and_then(|properties| properties.get(argument))
+                .is_some()
+        };
+        let names = |keep: &dyn Fn(&Tool) -> bool| {
+            let mut names = tools
+                .iter()
+                .filter(|tool| keep(tool))
+                .map(|tool| tool.name.to_string())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        let sorted = |list: &[&str]| {
+            let mut list = list.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+            list.sort();
+            list
+        };
+
+        // Every tool that signs takes the key to sign with
+        assert_eq!(
+            names(&|tool| takes(tool, "private_key")),
+            sorted(&SIGNER_TOOLS)
+        );
+        // and those that wait for a confirmation take the gas limit multiplier
+        assert_eq!(
+            names(&|tool| takes(tool, "gas_limit_multiplier")),
+            sorted(&WRITE_TOOLS)
+        );
+        // and a progress token to report their steps under
+        assert_eq!(
+            names(&|tool| takes(tool, "progress_token")),
+            sorted(&WRITE_TOOLS)
+        );
+    }
+
+    #[test]
+    fn admin_tools_are_the_admin_only_ones_and_never_advertise_the_token() {
+        let tools = Lst::tool_box().list();
+        let mut admin = tools
+            .iter()
+            .filter(|tool| tool.description.starts_with("Admin only."))
+            .map(|tool| tool.name.to_string())
+            .collect::<Vec<_>>();
+        admin.sort();
+        let mut expected = ADMIN_TOOLS.map(String::from).to_vec();
+        expected.sort();
+        assert_eq!(admin, expected);
+
+        for tool in &tools {
+            let schema = serde_json::Value::Object(tool.input_schema.as_ref().clone());
+            assert!(
+                schema["properties"].get("admin_token").is_none(),
+                "{} advertises admin_token",
+                tool.name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn diagnostics_against_a_down_rpc_fail_every_check_with_a_reason() {
+        let lst = offline_lst();
+
+        let report = lst
+            .diagnostics_report(Some(Address::repeat_byte(0xaa)))
+            .await;
+        assert_eq!(report["ok"], false);
+        let checks = report["checks"].as_array().unwrap();
+        // RPC, chain id, the code and a TVL read of each protocol, and the nonce check
+        assert_eq!(checks.len(), 3 + 2 * LstProtocol::ALL.len());
+        for check in checks {
+            assert_eq!(check["ok"], false, "{check}");
+            let detail = check["detail"].as_str().unwrap();
+            assert!(!detail.is_empty(), "{check}");
+        }
+        assert_eq!(checks[0]["name"], "rpc");
+        let detail = checks[0]["detail"].as_str().unwrap();
+        assert!(detail.starts_with("Failed to get block number"), "{detail}");
+    }
+
+    #[test]
+    fn tool_arguments_are_checked_against_the_advertised_patterns() {
+        let patterns = Lst::argument_patterns().unwrap();
+        let arguments = |value: serde_json::Value| value.as_object().unwrap().clone();
+
+        let stake = arguments(serde_json::json!({
+            "amount": "1.5",
+            "receiver": "0x00000000000000000000000000000000000000aa",
+        }));
+        assert!(patterns.check("stake", Some(&stake)).is_ok());
+
+        let stake = arguments(serde_json::json!({ "amount": "1.5", "receiver": "0xaa" }));
+        let err = patterns.check("stake", Some(&stake)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid receiver: must match {}", ADDRESS_PATTERN)
+        );
+        let unstake = arguments(serde_json::json!({ "amount": "1,5" }));
+        let err = patterns.check("unstake", Some(&unstake)).unwrap_err();
+        assert!(err.to_string().contains("Invalid amount"), "{err}");
+        let claim = arguments(serde_json::json!({ "request_ids": ["7", "0x7"] }));
+        let err = patterns.check("claim_redeem", Some(&claim)).unwrap_err();
+        assert!(err.to_string().contains("Invalid request_ids"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn deprecated_protocols_warn_on_every_tool_and_block_writes_when_asked() {
+        let provider = stub_provider(|method, _| match method {
+            "eth_chainId" => serde_json::json!(format!("{:#x}", MONAD_TESTNET_CHAIN_ID)),
+            _ => serde_json::json!(format!("0x{:064x}", 5)),
+        })
+        .await;
+        let deprecated = || {
+            [DeprecatedProtocol {
+                protocol: LstProtocol::AprMON,
+                notice: Some("Migrating to gMON".to_string()),
+            }]
+        };
+        let warning = |lst: Lst| async move {
+            let request = serde_json::from_value(serde_json::json!({
+                "protocol": "aprMON",
+                "signature": "function totalSupply() view returns (uint256)",
+            }))
+            .unwrap();
+            let result = lst
+                .raw_view(request, CancellationToken::new())
+                .await
+                .unwrap();
+            serde_json::to_string(&result.content).unwrap()
+        };
+
+        let warning_lst =
+            Lst::new(provider.clone()).with_deprecations(Deprecations::new(deprecated(), false));
+        let content = warning(warning_lst).await;
+        assert!(content.contains("PROTOCOL_DEPRECATED"), "{content}");
+        assert!(
+            content.contains("aprMON is deprecated: Migrating to gMON"),
+            "{content}"
+        );
+        let content = warning(Lst::new(provider.clone())).await;
+        assert!(!content.contains("warnings"), "{content}");
+
+        let blocking = Lst::new(provider).with_deprecations(Deprecations::new(deprecated(), true));
+        blocking.verify_chain_id().await.unwrap();
+        let stake = serde_json::from_value(serde_json::json!({
+            "protocol": "aprMON",
+            "amount": "1",
+        }))
+        .unwrap();
+        let err = blocking
+            .stake(stake, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "UNSUPPORTED");
+        assert!(
+            err.message.contains("writes against it are blocked"),
+            "{}",
+            err.message
+        );
+
+        // Reads of a blocked protocol still go through, with the warning
+        let content = warning(blocking).await;
+        assert!(content.contains("PROTOCOL_DEPRECATED"), "{content}");
+    }
+
+    /// Serves stats reads over HTTP, answering batches, and records every request body it gets.
+    async fn stats_rpc_server() -> (String, Arc<std::sync::Mutex<Vec<serde_json::Value>>>) {
+        stats_rpc_server_failing(None).await
+    }
+
+    /// Like [`stats_rpc_server`], but calls to the `failing` contract revert.
+    async fn stats_rpc_server_failing(
+        failing: Option<Address>,
+    ) -> (String, Arc<std::sync::Mutex<Vec<serde_json::Value>>>) {
+        let selector = |signature: &str| encode_prefixed(&ethers::utils::id(signature)[..4]);
+        let (fee, max) = (selector("withdrawalFee()"), selector("MAX_BASIS_POINTS()"));
+        let answer = move |call: &serde_json::Value| {
+            let to = call["params"][0]["to"].as_str();
+            if to.is_some_and(|to| Some(to) == failing.map(|a| format!("{a:?}")).as_deref()) {
+                return serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": call["id"],
+                    "error": { "code": 3, "message": "execution reverted" },
+                });
+            }
+            let result = match call["method"].as_str().unwrap_or_default() {
+                "eth_getBlockByNumber" => {
+                    serde_json::json!({ "number": "0x2710", "timestamp": "0x6553f100" })
+                }
+                "eth_call" => {
+                    let data = call["params"][0]["data"]
+                        .as_str()
+                        .or(call["params"][0]["input"].as_str())
+                        .unwrap_or_default();
+                    let value = match &data[..10] {
+                        s if s == fee => U256::from(50),
+                        s if s == max => U256::from(10_000),
+                        _ => mon(1),
+                    };
+                    serde_json::json!(format!("0x{:064x}", value))
+                }
+                _ => serde_json::Value::Null,
+            };
+            serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+        };
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post({
+                let requests = requests.clone();
+                move |axum::Json(body): axum::Json<serde_json::Value>| {
+                    requests.lock().unwrap().push(body.clone());
+                    let response = match &body {
+                        serde_json::Value::Array(calls) => calls.iter().map(answer).collect(),
+                        call => answer(call),
+                    };
+                    async move { axum::Json(response) }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        (url, requests)
+    }
+
+    /// Service over `url` with every protocol's decimals cached, so a read only fetches stats.
+    fn warm_lst(url: String) -> Lst {
+        let lst = Lst::new(Arc::new(CallLogger::new(
+            Provider::new(FailoverHttp::new(&[url]).unwrap()),
+            false,
+        )));
+        for protocol in LstProtocol::ALL {
+            lst.decimals.store(
+                protocol,
+                Decimals {
+                    shares: 18,
+                    assets: 18,
+                },
+            );
+        }
+        lst
+    }
+
+    #[tokio::test]
+    async fn a_fresh_stats_read_sends_its_calls_as_one_batch() {
+        let (url, requests) = stats_rpc_server().await;
+        let lst = warm_lst(url);
+
+        let result = lst
+            .dispatch_resource("evm://monadTestnet/lsts/compare?fresh=true".to_string())
+            .await
+            .unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+            panic!("compare is not text");
+        };
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(body["errors"], serde_json::json!([]), "{body}");
+        let fees = body["protocols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                (
+                    p["protocol"].as_str().unwrap(),
+                    p["withdrawal_fee"].as_f64().unwrap(),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(fees["aprMON"], 0.5);
+
+        // The two block headers, then every stats call of every protocol in a single batch
+        let requests = requests.lock().unwrap();
+        let batches = requests
+            .iter()
+            .filter_map(serde_json::Value::as_array)
+            .collect::<Vec<_>>();
+        assert_eq!(batches.len(), 1, "{requests:?}");
+        assert_eq!(batches[0].len(), 5 + 4 + 3);
+        assert!(batches[0].iter().all(|call| call["method"] == "eth_call"));
+        let singles = requests
+            .iter()
+            .filter(|request| !request.is_array())
+            .map(|request| request["method"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(singles, ["eth_getBlockByNumber"; 2]);
+    }
+
+    #[tokio::test]
+    async fn stats_reads_hit_the_snapshot_unless_fresh() {
+        let (url, requests) = stats_rpc_server().await;
+        let lst = warm_lst(url);
+        let read = |uri: &'static str| {
+            let lst = lst.clone();
+            async move {
+                let result = lst.dispatch_resource(uri.to_string()).await.unwrap();
+                let ResourceContents::TextResourceContents { text, .. } = &result.contents[0]
+                else {
+                    panic!("{uri} is not text");
+                };
+                serde_json::from_str::<serde_json::Value>(text).unwrap()
+            }
+        };
+        let sent = || requests.lock().unwrap().len();
+
+        // The first read fills the snapshot, which the next ones are served from
+        let first = read("evm://monadTestnet/lsts/aprMON/tvl").await;
+        let filled = sent();
+        assert!(filled > 0);
+        let cached = read("evm://monadTestnet/lsts/aprMON/tvl").await;
+        read("evm://monadTestnet/lsts/aprMON/rate").await;
+        assert_eq!(sent(), filled);
+        assert!(first["as_of"].is_string(), "{first}");
+        assert_eq!(cached["as_of"], first["as_of"]);
+
+        read("evm://monadTestnet/lsts/aprMON/tvl?fresh=true").await;
+        assert_eq!(sent(), 2 * filled);
+    }
+    #[tokio::test]
+    async fn a_failing_protocol_leaves_the_others_in_the_comparison() {
+        let (url, _) = stats_rpc_server_failing(Some(LstProtocol::SHMON.address())).await;
+        let lst = warm_lst(url);
+
+        let result = lst
+            .dispatch_resource("evm://monadTestnet/lsts/compare?fresh=true".to_string())
+            .await
+            .unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+            panic!("compare is not text");
+        };
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+        let protocols = body["protocols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["protocol"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(protocols.len(), 2, "{body}");
+        assert!(protocols.contains(&"aprMON") && protocols.contains(&"gMON"));
+        assert_eq!(body["errors"].as_array().unwrap().len(), 1, "{body}");
+        assert_eq!(body["errors"][0]["protocol"], "shMON");
+        assert!(body["errors"][0]["error"].is_string(), "{body}");
+    }
+
+    #[tokio::test]
+    async fn quotes_rank_protocols_by_their_previewed_round_trip() {
+        use crate::common::snapshot::ProtocolSnapshot;
+
+        let selector = |signature: &str| encode_prefixed(&ethers::utils::id(signature)[..4]);
+        let (deposit, redeem, max_bps) = (
+            selector("previewDeposit(uint256)"),
+            selector("previewRedeem(uint256)"),
+            selector("MAX_BASIS_POINTS()"),
+        );
+        let aprmon = format!("{:?}", LstProtocol::AprMON.address());
+        // aprMON mints 90% of the MON staked in shares, redeemed 1:1, so a round trip loses
+        // 10%; shMON mints two shares per MON and redeems them at half a MON, losing nothing
+        let provider = stub_provider(move |_, params| {
+            let data = params[0]["data"]
+                .as_str()
+                .or(params[0]["input"].as_str())
+                .unwrap();
+            let is_aprmon = params[0]["to"] == aprmon;
+            let amount = || U256::from_str_radix(&data[10..], 16).unwrap();
+            let value = match &data[..10] {
+                s if s == deposit && is_aprmon => amount() * 9 / 10,
+                s if s == deposit => amount() * 2,
+                s if s == redeem && is_aprmon => amount(),
+                s if s == redeem => amount() / 2,
+                s if s == max_bps => U256::from(10_000),
+                // decimals(), and no withdrawal fee
+                "0x313ce567" => U256::from(18),
+                _ => U256::zero(),
+            };
+            serde_json::json!(format!("0x{:064x}", value))
+        })
+        .await;
+        let lst = Lst::new(provider);
+        // Equal APRs and TVLs, so only the previews tell the protocols apart
+        let read = |protocol| ProtocolSnapshot {
+            stats: ProtocolStats {
+                protocol,
+                tvl: mon(MIN_RECOMMENDED_TVL),
+                apr: 5.0,
+                withdrawal_fee: 0.0,
+            },
+            exchange_rate: U256::exp10(18),
+            as_of: 0,
+        };
+        lst.snapshot.update(
+            None,
+            Snapshot {
+                as_of: 0,
+                protocols: vec![read(LstProtocol::AprMON), read(LstProtocol::SHMON)],
+                errors: Vec::new(),
+            },
+        );
+
+        let quote = lst.quote_routes(mon(10)).await.unwrap();
+        let routes = quote["protocols"].as_array().unwrap();
+        assert_eq!(routes.len(), 2, "{quote}");
+        assert_eq!(routes[0]["protocol"], "shMON");
+        assert_eq!(routes[0]["expected_shares"], "20.000000000000000000");
+        assert_eq!(routes[0]["value_after_fees"], "10.000000000000000000");
+        assert_eq!(routes[0]["score"], 5.0);
+        assert_eq!(routes[1]["protocol"], "aprMON");
+        assert_eq!(routes[1]["expected_shares"], "9.000000000000000000");
+        assert_eq!(routes[1]["value_after_fees"], "9.000000000000000000");
+        assert_eq!(routes[1]["score"], -5.0);
+    }
+    #[tokio::test]
+    async fn tool_results_report_every_call_of_a_batched_stats_read_in_meta() {
+        let (url, requests) = stats_rpc_server().await;
+        let lst = warm_lst(url).with_rpc_call_reporting(true);
+        let (_server, mut client) = RawClient::connect(lst).await;
+
+        // A cold snapshot is read with one batch of stats calls
+        client
+            .send(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": {
+                    "name": "estimate_apr_after_deposit",
+                    "arguments": { "protocol": "aprMON", "amount": "1" },
+                },
+            }))
+            .await;
+        let response = client.receive().await;
+        let result = &response["result"];
+        assert_eq!(result["isError"], false, "{response}");
+
+        let requests = requests.lock().unwrap();
+        assert!(requests.iter().any(|request| request.is_array()));
+        let calls = requests
+            .iter()
+            .map(|request| request.as_array().map_or(1, Vec::len))
+            .sum::<usize>();
+        assert_eq!(calls, 2 + 5 + 4 + 3);
+        assert_eq!(result["_meta"]["rpc_calls"], calls, "{response}");
+        // The count is not mixed into the tool's own content
+        assert!(
+            result["content"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|item| !item["text"].as_str().unwrap().contains("rpc_calls")),
+            "{response}"
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelling_a_redeem_request_is_rejected_with_the_reason() {
+        // Nothing is read or sent, so the offline service answers too
+        let lst = offline_lst();
+        let cancel = |protocol: &str| {
+            serde_json::from_value::<CancelRedeemRequest>(serde_json::json!({
+                "protocol": protocol,
+                "request_id": "7",
+            }))
+            .unwrap()
+        };
+
+        let err = lst.cancel_redeem(cancel("aprMON")).await.unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "UNSUPPORTED");
+        assert!(
+            err.message.contains("irreversible") && err.message.contains("request 7"),
+            "{}",
+            err.message
+        );
+
+        let err = lst.cancel_redeem(cancel("gMON")).await.unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "UNSUPPORTED");
+        assert!(err.message.contains("instant"), "{}", err.message);
+
+        // The protocol details say so before any attempt
+        let info = lst.supported_actions(LstProtocol::AprMON);
+        assert_eq!(info["actions"]["cancel_redeem"], false);
+    }
+
+    #[test]
+    fn server_info_advertises_the_configured_features() {
+        let features = |lst: &Lst| {
+            let info = lst.get_info();
+            let experimental = info.capabilities.experimental.unwrap();
+            (info.instructions.unwrap(), experimental["monad"].clone())
+        };
+
+        let (instructions, defaults) = features(&offline_lst());
+        assert!(
+            instructions.contains("Admin tools are disabled"),
+            "{instructions}"
+        );
+        assert_eq!(defaults["networks"], serde_json::json!([NETWORK]));
+        assert_eq!(defaults["chain_id"], MONAD_TESTNET_CHAIN_ID);
+        assert_eq!(defaults["default_protocol"], serde_json::Value::Null);
+        assert_eq!(defaults["deprecated_protocols"], serde_json::json!([]));
+        assert_eq!(defaults["admin_tools"], false);
+        assert_eq!(defaults["rpc_call_reporting"], false);
+
+        let lst = offline_lst()
+            .with_expected_chain_id(4321)
+            .with_default_protocol(LstProtocol::GMON)
+            .with_deprecations(Deprecations::new(
+                [DeprecatedProtocol {
+                    protocol: LstProtocol::AprMON,
+                    notice: None,
+                }],
+                false,
+            ))
+            .with_admin_token(AdminToken::new("secret").unwrap())
+            .with_rpc_call_reporting(true);
+        let (instructions, configured) = features(&lst);
+        assert!(
+            instructions.contains("chain id 4321")
+                && instructions.contains("Admin tools are enabled"),
+            "{instructions}"
+        );
+        assert_eq!(configured["chain_id"], 4321);
+        assert_eq!(configured["default_protocol"], "gMON");
+        assert_eq!(
+            configured["deprecated_protocols"],
+            serde_json::json!(["aprMON"])
+        );
+        assert_eq!(configured["admin_tools"], true);
+        assert_eq!(configured["rpc_call_reporting"], true);
+        assert_eq!(configured["protocols"], defaults["protocols"]);
+    }
+
+    #[tokio::test]
+    async fn a_null_receipt_reads_as_pending_and_a_missing_effective_price_as_the_gas_price() {
+        let pending = H256::repeat_byte(0x11);
+        let provider = stub_provider(move |method, params| match method {
+            "eth_blockNumber" => serde_json::json!("0x64"),
+            "eth_getTransactionByHash" => serde_json::json!({ "gasPrice": "0x3b9aca00" }),
+            "eth_getTransactionReceipt" if params[0] == format!("{:?}", pending) => {
+                serde_json::Value::Null
+            }
+            "eth_getTransactionReceipt" => serde_json::json!({
+                "blockNumber": "0x60",
+                "status": "0x1",
+                "gasUsed": "0x5208",
+                "effectiveGasPrice": null,
+            }),
+            _ => serde_json::Value::Null,
+        })
+        .await;
+        let lst = Lst::new(provider);
+        let status = async |tx_hash: H256| {
+            let request = serde_json::from_value(serde_json::json!({
+                "tx_hash": format!("{:?}", tx_hash),
+            }))
+            .unwrap();
+            let result = lst
+                .tx_status(request, CancellationToken::new())
+                .await
+                .unwrap();
+            serde_json::from_str::<serde_json::Value>(&result.content[0].as_text().unwrap().text)
+                .unwrap()
+        };
+
+        let body = status(pending).await;
+        assert_eq!(body["status"], "pending", "{body}");
+        assert_eq!(body["confirmations"], 0);
+
+        let body = status(H256::repeat_byte(0x22)).await;
+        assert_eq!(body["status"], "success", "{body}");
+        assert_eq!(body["confirmations"], 5);
+        assert_eq!(body["effective_gas_price"], "1000000000");
+        assert_eq!(body["fee"], "0.000021000000000000");
+    }
+
+    #[tokio::test]
+    async fn stake_split_records_failed_entries_for_their_signer_to_resume() {
+        // Nothing can be sent offline, so every entry fails and stays to be resumed
+        let lst = offline_lst();
+        let key = |byte: u8| format!("{:064x}", byte);
+        let request = serde_json::from_value(serde_json::json!({
+            "private_key": key(1),
+            "entries": [
+                { "protocol": "aprMON", "amount": "1" },
+                { "protocol": "gMON", "amount_wei": "5" },
             ],
+        }))
+        .unwrap();
+        let result = lst
+            .stake_split(request, CancellationToken::new())
+            .await
+            .unwrap();
+        let job: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(job["tool"], "stake");
+        assert_eq!(job["signer"], "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+        assert_eq!(job["unfinished"], 2, "{job}");
+        assert_eq!(job["entries"][0]["status"], "failed");
+        // The key is not kept with the entries
+        assert_eq!(
+            job["entries"][1]["arguments"],
+            serde_json::json!({ "protocol": "gMON", "amount_wei": "5" })
+        );
+
+        let resume = |private_key: String| {
+            serde_json::from_value(serde_json::json!({
+                "job_id": job["job_id"],
+                "private_key": private_key,
+            }))
+            .unwrap()
+        };
+        let err = lst
+            .resume_job(resume(key(2)), CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "UNAUTHORIZED");
+        let result = lst
+            .resume_job(resume(key(1)), CancellationToken::new())
+            .await
+            .unwrap();
+        let resumed: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(resumed["job_id"], job["job_id"]);
+        assert_eq!(resumed["unfinished"], 2);
+
+        // An entry that can never succeed is refused before a job is made
+        let request = serde_json::from_value(serde_json::json!({
+            "private_key": key(1),
+            "entries": [{ "protocol": "aprMON", "amount": "0" }],
+        }))
+        .unwrap();
+        let err = lst
+            .stake_split(request, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "AMOUNT_ZERO");
+    }
+
+    #[test]
+    fn insufficient_funds_are_described_in_the_configured_currency() {
+        let native = NativeCurrency::new("TMON", 6).unwrap();
+        let e = crate::common::tx::check_gas_funds(
+            U256::from(1_500_000),
+            U256::from(2_000_000),
+            U256::from(250_000),
+        )
+        .unwrap_err();
+        let McpServiceError::InvalidInput { message, details } =
+            write_error("Staking failed", e, &native)
+        else {
+            panic!("insufficient funds are invalid input");
+        };
+        assert_eq!(
+            message,
+            "Staking failed: Insufficient funds for gas: requires 2.250000 TMON but balance is 1.500000 TMON (short 0.750000 TMON)"
+        );
+        let details = details.unwrap();
+        assert_eq!(details["code"], "INSUFFICIENT_FUNDS_FOR_GAS");
+        assert_eq!(details["shortfall"], "750000");
+    }
+
+    #[tokio::test]
+    async fn erc20_deposits_are_parsed_at_the_asset_decimals() {
+        let asset = Address::repeat_byte(0xaa);
+        let provider = stub_provider(move |method, params| {
+            let to = params[0]["to"]
+                .as_str()
+                .unwrap_or_default()
+                .parse::<Address>();
+            let data = params[0]["data"].as_str().or(params[0]["input"].as_str());
+            let word = |value: u64| serde_json::json!(format!("0x{:064x}", value));
+            match (method, data.and_then(|data| data.get(..10))) {
+                ("eth_chainId", _) => serde_json::json!(format!("{:#x}", MONAD_TESTNET_CHAIN_ID)),
+                // asset()
+                (_, Some("0x38d52e0f")) => {
+                    serde_json::json!(encode_prefixed(ethers::abi::encode(&[
+                        ethers::abi::Token::Address(asset)
+                    ])))
+                }
+                // decimals(), of a 6-decimal asset under an 18-decimal vault
+                (_, Some("0x313ce567")) if to.ok() == Some(asset) => word(6),
+                (_, Some("0x313ce567")) => word(18),
+                // symbol()
+                (_, Some("0x95d89b41")) => {
+                    serde_json::json!(encode_prefixed(ethers::abi::encode(&[
+                        ethers::abi::Token::String("USDM".to_string())
+                    ])))
+                }
+                _ => serde_json::Value::Null,
+            }
+        })
+        .await;
+        let lst = Lst::new(provider);
+
+        let currency = lst.deposit_currency(LstProtocol::SHMON).await.unwrap();
+        assert_eq!(currency.symbol(), "USDM");
+        assert_eq!(currency.decimals(), 6);
+        // Native deposits keep the native currency without reading anything
+        let currency = lst.deposit_currency(LstProtocol::GMON).await.unwrap();
+        assert_eq!(currency, lst.native);
+
+        // Below one unit of the asset, though not of the native currency
+        let request = serde_json::from_value(serde_json::json!({
+            "private_key": format!("{:064x}", 1),
+            "entries": [{ "protocol": "shMON", "amount": "0.0000001" }],
+        }))
+        .unwrap();
+        let err = lst
+            .stake_split(request, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "AMOUNT_ZERO", "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn unstake_split_refuses_unparsable_amounts_before_making_a_job() {
+        let provider = stub_provider(|_, _| serde_json::json!(format!("0x{:064x}", 18))).await;
+        let lst = Lst::new(provider);
+        let request = |entry: serde_json::Value| {
+            serde_json::from_value(serde_json::json!({
+                "private_key": format!("{:064x}", 1),
+                "entries": [{ "protocol": "aprMON", "amount": "1" }, entry],
+            }))
+            .unwrap()
+        };
+
+        let err = lst
+            .unstake_split(
+                request(serde_json::json!({ "protocol": "gMON", "amount_wei": "0" })),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "AMOUNT_ZERO");
+        let err = lst
+            .unstake_split(
+                request(
+                    serde_json::json!({ "protocol": "gMON", "amount": "1", "amount_wei": "1" }),
+                ),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.data.unwrap()["kind"], "INVALID_INPUT");
+    }
+
+    #[tokio::test]
+    async fn a_stake_left_unconfirmed_is_checked_not_sent_again_on_resume() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let gwei = |value: u64| format!("{:#x}", U256::from(value) * U256::exp10(9));
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let confirmed = Arc::new(AtomicBool::new(false));
+        let provider = stub_provider({
+            let (sent, confirmed) = (sent.clone(), confirmed.clone());
+            move |method, params| match method {
+                "eth_chainId" => serde_json::json!(format!("{:#x}", MONAD_TESTNET_CHAIN_ID)),
+                // Every call reads zero, so the stake manager has no TVL cap
+                "eth_call" => serde_json::json!(format!("0x{:064x}", 0)),
+                "eth_estimateGas" => serde_json::json!("0x30000"),
+                "eth_getBalance" => serde_json::json!(format!("{:#x}", mon(100))),
+                "eth_getTransactionCount" => serde_json::json!("0x5"),
+                "eth_getBlockByNumber" => {
+                    serde_json::json!({ "number": "0x64", "baseFeePerGas": gwei(1) })
+                }
+                "eth_feeHistory" => serde_json::json!({
+                    "oldestBlock": "0x63",
+                    "baseFeePerGas": [gwei(1), gwei(1)],
+                    "gasUsedRatio": [0.5],
+                    "reward": [[gwei(1)]],
+                }),
+                "eth_sendRawTransaction" => {
+                    let raw = ethers::utils::hex::decode(params[0].as_str().unwrap()).unwrap();
+                    sent.lock().unwrap().push(raw.clone());
+                    serde_json::json!(H256::from(ethers::utils::keccak256(&raw)))
+                }
+                "eth_getTransactionReceipt" if confirmed.load(Ordering::SeqCst) => {
+                    serde_json::json!({
+                        "blockNumber": "0x64",
+                        "status": "0x1",
+                        "gasUsed": "0x5208",
+                        "effectiveGasPrice": null,
+                    })
+                }
+                _ => serde_json::Value::Null,
+            }
         })
+        .await;
+        // The deposit goes out but is not confirmed before the stake times out
+        let timeouts =
+            ToolTimeouts::new(Duration::from_secs(5), Duration::from_secs(2), []).unwrap();
+        let lst = Lst::new(provider).with_timeouts(timeouts);
+        lst.verify_chain_id().await.unwrap();
+        let key = format!("{:064x}", 1);
+
+        let request = serde_json::from_value(serde_json::json!({
+            "private_key": key,
+            "entries": [{ "protocol": "gMON", "amount": "1" }],
+        }))
+        .unwrap();
+        let result = lst
+            .stake_split(request, CancellationToken::new())
+            .await
+            .unwrap();
+        let job: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(job["entries"][0]["status"], "sent", "{job}");
+        assert_eq!(job["unfinished"], 1);
+        let tx_hash = H256::from(ethers::utils::keccak256(&sent.lock().unwrap()[0]));
+        assert_eq!(job["entries"][0]["tx_hash"], encode_prefixed(tx_hash));
+
+        // Once it is mined, resuming finds it confirmed rather than staking again
+        confirmed.store(true, Ordering::SeqCst);
+        let request = serde_json::from_value(serde_json::json!({
+            "job_id": job["job_id"],
+            "private_key": key,
+        }))
+        .unwrap();
+        let result = lst
+            .resume_job(request, CancellationToken::new())
+            .await
+            .unwrap();
+        let job: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(job["entries"][0]["status"], "done", "{job}");
+        assert_eq!(job["unfinished"], 0);
+        assert_eq!(sent.lock().unwrap().len(), 1);
     }
 }
@@ -0,0 +1,450 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use ethers::{
+    types::{Address, H256},
+    utils::{
+        hex::{encode, encode_prefixed},
+        to_checksum,
+    },
+};
+use rmcp::{
+    Error as McpError,
+    model::{CallToolResult, JsonObject},
+};
+
+use crate::{common::error::McpServiceError, services::constants::JOB_TTL};
+
+/// Where an entry of a batch job stands.
+#[derive(Debug, Clone)]
+enum EntryStatus {
+    /// Not run yet.
+    Unfinished,
+    /// Its tool succeeded, returning this text.
+    Done(String),
+    /// Its tool sent transaction `tx_hash`, which moves the entry's funds, but returned before
+    /// it was confirmed, with this text or error. Running the tool again would send a second
+    /// transaction, so resuming checks this one instead.
+    Sent { tx_hash: H256, message: String },
+    /// Its tool failed with this error before sending anything that moves funds, or the
+    /// transaction it sent reverted, so it runs again when the job is resumed.
+    Failed { kind: String, message: String },
+}
+
+impl EntryStatus {
+    /// Status of an entry from what running it returned, and the transaction it sent that is
+    /// not confirmed yet, if any.
+    fn of(result: Result<CallToolResult, McpError>, sent: Option<H256>) -> Self {
+        match (result, sent) {
+            (Ok(result), None) => EntryStatus::Done(text(&result)),
+            (Ok(result), Some(tx_hash)) => EntryStatus::Sent {
+                tx_hash,
+                message: text(&result),
+            },
+            (Err(e), Some(tx_hash)) => EntryStatus::Sent {
+                tx_hash,
+                message: e.message.to_string(),
+            },
+            (Err(e), None) => EntryStatus::Failed {
+                kind: e
+                    .data
+                    .as_ref()
+                    .and_then(|data| data["kind"].as_str())
+                    .unwrap_or("INTERNAL")
+                    .to_string(),
+                message: e.message.to_string(),
+            },
+        }
+    }
+}
+
+/// First text item of a tool result.
+fn text(result: &CallToolResult) -> String {
+    result
+        .content
+        .first()
+        .and_then(|content| content.as_text())
+        .map(|text| text.text.clone())
+        .unwrap_or_default()
+}
+
+/// One tool call of a batch job: its arguments, without the signer's key, and how it went.
+#[derive(Debug, Clone)]
+struct JobEntry {
+    arguments: JsonObject,
+    status: EntryStatus,
+}
+
+#[derive(Debug)]
+struct Job {
+    /// Tool each entry is a call of.
+    tool: &'static str,
+    signer: Address,
+    entries: Vec<JobEntry>,
+    running: bool,
+    updated_at: Instant,
+}
+
+/// Batch jobs of `stake_split` and `unstake_split` by id, each kept in memory until `ttl` after
+/// it last ran so that `resume_job` can retry its unfinished entries. Jobs are shared by every
+/// connection, so a job outlives the connection that started it.
+#[derive(Debug)]
+pub(super) struct JobStore {
+    jobs: RwLock<HashMap<String, Job>>,
+    ttl: Duration,
+}
+
+impl Default for JobStore {
+    fn default() -> Self {
+        JobStore::new(JOB_TTL)
+    }
+}
+
+impl JobStore {
+    pub(super) fn new(ttl: Duration) -> Self {
+        JobStore {
+            jobs: RwLock::default(),
+            ttl,
+        }
+    }
+
+    /// Stores a job of one `tool` call per entry of `arguments` for `signer`, none of them run
+    /// yet, and returns its id. Expired jobs are dropped on the way.
+    pub(super) fn create(
+        &self,
+        tool: &'static str,
+        signer: Address,
+        arguments: Vec<JsonObject>,
+    ) -> String {
+        let job_id = encode(ethers::core::rand::random::<[u8; 16]>());
+        let entries = arguments
+            .into_iter()
+            .map(|arguments| JobEntry {
+                arguments,
+                status: EntryStatus::Unfinished,
+            })
+            .collect();
+
+        let mut jobs = self.jobs.write().unwrap();
+        jobs.retain(|_, job| self.is_live(job));
+        jobs.insert(
+            job_id.clone(),
+            Job {
+                tool,
+                signer,
+                entries,
+                running: false,
+                updated_at: Instant::now(),
+            },
+        );
+
+        job_id
+    }
+
+    /// Runs the entries of job `job_id` that are not done, in order, each through `run_entry`
+    /// with the job's tool, the entry's arguments and the unconfirmed transaction an earlier
+    /// run sent for it, and returns the job's summary. `run_entry` checks that transaction
+    /// rather than sending another, and returns its result along with the transaction it
+    /// leaves unconfirmed, if any. Each entry is recorded as soon as it returns, so an
+    /// interrupted run keeps what it got done. Only the job's `signer` may run it, and only
+    /// one run at a time.
+    pub(super) async fn run<F, Fut>(
+        &self,
+        job_id: &str,
+        signer: Address,
+        mut run_entry: F,
+    ) -> Result<serde_json::Value, McpServiceError>
+    where
+        F: FnMut(&'static str, JsonObject, Option<H256>) -> Fut,
+        Fut: Future<Output = (Result<CallToolResult, McpError>, Option<H256>)>,
+    {
+        let (tool, unfinished) = {
+            let mut jobs = self.jobs.write().unwrap();
+            let job = jobs
+                .get_mut(job_id)
+                .filter(|job| self.is_live(job))
+                .ok_or_else(|| {
+                    McpServiceError::NotFound(format!("Job {} not found or expired", job_id))
+                })?;
+            if job.signer != signer {
+                return Err(McpServiceError::Unauthorized(format!(
+                    "Job {} belongs to {}, not {}",
+                    job_id,
+                    to_checksum(&job.signer, None),
+                    to_checksum(&signer, None)
+                )));
+            }
+            if job.running {
+                return Err(McpServiceError::invalid_input(format!(
+                    "Job {} is already running",
+                    job_id
+                )));
+            }
+            job.running = true;
+
+            let unfinished = job
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| !matches!(entry.status, EntryStatus::Done(_)))
+                .map(|(index, entry)| {
+                    let sent = match entry.status {
+                        EntryStatus::Sent { tx_hash, .. } => Some(tx_hash),
+                        _ => None,
+                    };
+                    (index, entry.arguments.clone(), sent)
+                })
+                .collect::<Vec<_>>();
+            (job.tool, unfinished)
+        };
+        let running = Running {
+            store: self,
+            job_id,
+        };
+
+        for (index, arguments, sent) in unfinished {
+            let (result, sent) = run_entry(tool, arguments, sent).await;
+            let status = EntryStatus::of(result, sent);
+            self.update(job_id, |job| job.entries[index].status = status);
+        }
+        drop(running);
+
+        let jobs = self.jobs.read().unwrap();
+        let job = jobs.get(job_id).ok_or_else(|| {
+            McpServiceError::NotFound(format!("Job {} not found or expired", job_id))
+        })?;
+        Ok(self.summary(job_id, job))
+    }
+
+    /// A job is kept while it runs and until `ttl` after it last ran.
+    fn is_live(&self, job: &Job) -> bool {
+        job.running || job.updated_at.elapsed() < self.ttl
+    }
+
+    fn update(&self, job_id: &str, update: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.write().unwrap().get_mut(job_id) {
+            update(job);
+            job.updated_at = Instant::now();
+        }
+    }
+
+    fn summary(&self, job_id: &str, job: &Job) -> serde_json::Value {
+        let entries = job
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut summary = serde_json::json!({ "arguments": entry.arguments });
+                match &entry.status {
+                    EntryStatus::Unfinished => summary["status"] = "unfinished".into(),
+                    EntryStatus::Done(result) => {
+                        summary["status"] = "done".into();
+                        summary["result"] = result.clone().into();
+                    }
+                    EntryStatus::Sent { tx_hash, message } => {
+                        summary["status"] = "sent".into();
+                        summary["tx_hash"] = encode_prefixed(tx_hash).into();
+                        summary["result"] = message.clone().into();
+                    }
+                    EntryStatus::Failed { kind, message } => {
+                        summary["status"] = "failed".into();
+                        summary["error"] = serde_json::json!({ "kind": kind, "message": message });
+                    }
+                }
+                summary
+            })
+            .collect::<Vec<_>>();
+        let unfinished = job
+            .entries
+            .iter()
+            .filter(|entry| !matches!(entry.status, EntryStatus::Done(_)))
+            .count();
+
+        serde_json::json!({
+            "job_id": job_id,
+            "tool": job.tool,
+            "signer": to_checksum(&job.signer, None),
+            "entries": entries,
+            "unfinished": unfinished,
+            "expires_in_secs": self.ttl.as_secs(),
+        })
+    }
+}
+
+/// Marks a job as no longer running when its run ends, even if the run is dropped midway.
+struct Running<'a> {
+    store: &'a JobStore,
+    job_id: &'a str,
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.store.update(self.job_id, |job| job.running = false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use rmcp::model::Content;
+
+    use super::*;
+
+    fn arguments(protocol: &str) -> JsonObject {
+        let serde_json::Value::Object(arguments) =
+            serde_json::json!({ "protocol": protocol, "amount": "1" })
+        else {
+            unreachable!("arguments are an object");
+        };
+        arguments
+    }
+
+    #[tokio::test]
+    async fn resuming_a_partially_failed_job_retries_only_the_failed_entries() {
+        let jobs = JobStore::default();
+        let signer = Address::repeat_byte(0x11);
+        let job_id = jobs.create(
+            "stake",
+            signer,
+            vec![arguments("aprMON"), arguments("gMON"), arguments("shMON")],
+        );
+
+        // gMON fails on the first run only
+        let calls = Mutex::new(Vec::new());
+        let run = |tool: &'static str, arguments: JsonObject, sent: Option<H256>| {
+            let protocol = arguments["protocol"].as_str().unwrap().to_string();
+            let attempt = {
+                let mut calls = calls.lock().unwrap();
+                calls.push(protocol.clone());
+                calls.iter().filter(|called| **called == protocol).count()
+            };
+            async move {
+                assert_eq!(tool, "stake");
+                assert_eq!(sent, None);
+                if protocol == "gMON" && attempt == 1 {
+                    let e = McpServiceError::RpcError("Staking failed".to_string());
+                    return (Err(e.into()), None);
+                }
+                let result =
+                    CallToolResult::success(vec![Content::text(format!("Staked 1 {}", protocol))]);
+                (Ok(result), None)
+            }
+        };
+
+        let summary = jobs.run(&job_id, signer, run).await.unwrap();
+        assert_eq!(summary["job_id"], job_id);
+        assert_eq!(summary["unfinished"], 1);
+        assert_eq!(summary["entries"][0]["status"], "done");
+        assert_eq!(summary["entries"][1]["status"], "failed");
+        assert_eq!(summary["entries"][1]["error"]["kind"], "RPC_ERROR");
+        assert_eq!(summary["entries"][2]["status"], "done");
+
+        let summary = jobs.run(&job_id, signer, run).await.unwrap();
+        assert_eq!(*calls.lock().unwrap(), ["aprMON", "gMON", "shMON", "gMON"]);
+        assert_eq!(summary["unfinished"], 0);
+        assert_eq!(summary["entries"][1]["status"], "done");
+        assert_eq!(summary["entries"][1]["result"], "Staked 1 gMON");
+        // The entries done on the first run keep their results
+        assert_eq!(summary["entries"][0]["result"], "Staked 1 aprMON");
+
+        // Once everything is done, resuming runs nothing
+        jobs.run(&job_id, signer, run).await.unwrap();
+        assert_eq!(calls.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn jobs_run_only_for_their_signer_and_until_they_expire() {
+        async fn never(
+            _: &'static str,
+            _: JsonObject,
+            _: Option<H256>,
+        ) -> (Result<CallToolResult, McpError>, Option<H256>) {
+            panic!("no entry should run");
+        }
+        let signer = Address::repeat_byte(0x11);
+
+        let jobs = JobStore::default();
+        let job_id = jobs.create("unstake", signer, vec![arguments("aprMON")]);
+        let err = jobs
+            .run(&job_id, Address::repeat_byte(0x22), never)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "UNAUTHORIZED");
+        let err = jobs.run("unknown", signer, never).await.unwrap_err();
+        assert_eq!(err.kind(), "NOT_FOUND");
+
+        let jobs = JobStore::new(Duration::ZERO);
+        let job_id = jobs.create("unstake", signer, vec![arguments("aprMON")]);
+        let err = jobs.run(&job_id, signer, never).await.unwrap_err();
+        assert_eq!(err.kind(), "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn entries_failing_after_their_transfer_is_sent_are_checked_not_sent_again() {
+        let jobs = JobStore::default();
+        let signer = Address::repeat_byte(0x11);
+        let job_id = jobs.create(
+            "unstake",
+            signer,
+            vec![arguments("aprMON"), arguments("gMON")],
+        );
+        let tx_hash = H256::repeat_byte(0xab);
+
+        // gMON's redeem goes out, then waiting for its receipt fails
+        let sends = Mutex::new(Vec::new());
+        let first = |_: &'static str, arguments: JsonObject, sent: Option<H256>| {
+            assert_eq!(sent, None);
+            let protocol = arguments["protocol"].as_str().unwrap().to_string();
+            sends.lock().unwrap().push(protocol.clone());
+            async move {
+                if protocol == "gMON" {
+                    let e = McpServiceError::Timeout("Receipt not found".to_string());
+                    return (Err(e.into()), Some(tx_hash));
+                }
+                (
+                    Ok(CallToolResult::success(vec![Content::text("Unstaked")])),
+                    None,
+                )
+            }
+        };
+        let summary = jobs.run(&job_id, signer, first).await.unwrap();
+        assert_eq!(summary["unfinished"], 1);
+        assert_eq!(summary["entries"][1]["status"], "sent");
+        assert_eq!(summary["entries"][1]["tx_hash"], encode_prefixed(tx_hash));
+        assert_eq!(summary["entries"][1]["result"], "Receipt not found");
+
+        // Resuming checks the transaction, still pending, then confirmed
+        let checks = Mutex::new(0);
+        let check = |_: &'static str, _: JsonObject, sent: Option<H256>| {
+            assert_eq!(sent, Some(tx_hash), "only the sent entry is resumed");
+            let check = {
+                let mut checks = checks.lock().unwrap();
+                *checks += 1;
+                *checks
+            };
+            async move {
+                if check == 1 {
+                    let result = CallToolResult::success(vec![Content::text("Still pending")]);
+                    return (Ok(result), sent);
+                }
+                (
+                    Ok(CallToolResult::success(vec![Content::text("Confirmed")])),
+                    None,
+                )
+            }
+        };
+        let summary = jobs.run(&job_id, signer, check).await.unwrap();
+        assert_eq!(summary["unfinished"], 1);
+        assert_eq!(summary["entries"][1]["status"], "sent");
+        assert_eq!(summary["entries"][1]["result"], "Still pending");
+        let summary = jobs.run(&job_id, signer, check).await.unwrap();
+        assert_eq!(summary["unfinished"], 0);
+        assert_eq!(summary["entries"][1]["status"], "done");
+        assert_eq!(summary["entries"][1]["result"], "Confirmed");
+        assert_eq!(*sends.lock().unwrap(), ["aprMON", "gMON"]);
+    }
+}
@@ -0,0 +1,2814 @@
+//! The supported liquid staking protocols and their contract bindings: what each can do, and
+//! the reads and writes against its contracts.
+
+use core::fmt;
+use std::sync::Arc;
+
+use anyhow::{Context, bail};
+use ethers::{
+    abi::Abi,
+    contract::{LogMeta, builders::ContractCall, parse_log},
+    providers::Middleware,
+    types::{Address, BlockId, BlockNumber, Filter, H256, TransactionReceipt, U256},
+    utils::to_checksum,
+};
+use rmcp::schemars;
+
+use super::{
+    stats::{
+        ProtocolStats, UnstakePreview, annualize, deduct_withdrawal_fee, fee_percent,
+        gmon_assets_for, gmon_exchange_rate,
+    },
+    units::{AmountUnit, Decimals, DecimalsCache, NativeCurrency},
+};
+#[cfg(feature = "mock")]
+use crate::common::mock;
+use crate::{
+    bindings::{aprmon, erc20, gmon, gmonstakemanager, shmon},
+    common::{
+        batch::call_all,
+        events::get_logs_chunked,
+        progress::{Progress, WriteStep},
+        registry,
+        rpc::RpcProvider,
+        snapshot::ProtocolSnapshot,
+        tx::{AllowanceResets, GasMultiplier, WalletSigner, send_checked},
+        warning::{Warning, WarningCode},
+    },
+    services::constants::{
+        APR_LOOKBACK_BLOCKS, APRMON_ADDRESS, GMON_ADDRESS, GMON_STAKEMANAGER_ADDRESS,
+        NEAR_MAX_DEPOSIT_PERCENT, SHMON_ADDRESS,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub enum LstProtocol {
+    #[serde(rename = "aprMON")]
+    AprMON,
+    #[serde(rename = "gMON")]
+    GMON,
+    #[serde(rename = "shMON")]
+    SHMON,
+    /// In-memory LST for demos, see [`crate::common::mock`].
+    #[cfg(feature = "mock")]
+    #[serde(rename = "mock")]
+    Mock,
+}
+
+impl fmt::Display for LstProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LstProtocol::AprMON => write!(f, "aprMON"),
+            LstProtocol::GMON => write!(f, "gMON"),
+            LstProtocol::SHMON => write!(f, "shMON"),
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => write!(f, "mock"),
+        }
+    }
+}
+
+/// Most edits a misspelt protocol name may be from a protocol's for it to be suggested.
+const PROTOCOL_SUGGESTION_DISTANCE: usize = 2;
+
+impl TryFrom<&str> for LstProtocol {
+    type Error = String;
+
+    /// Matches protocol names case-insensitively, so `shmon` and `SHMON` both name shMON. Near
+    /// misses fail with a suggestion of the closest name.
+    fn try_from(value: &str) -> Result<Self, String> {
+        let value = value.to_lowercase();
+        let closest = LstProtocol::ALL
+            .into_iter()
+            .map(|protocol| {
+                let name = protocol.to_string().to_lowercase();
+                (edit_distance(&name, &value), protocol)
+            })
+            .min_by_key(|(distance, _)| *distance);
+
+        match closest {
+            Some((0, protocol)) => Ok(protocol),
+            Some((distance, protocol)) if distance <= PROTOCOL_SUGGESTION_DISTANCE => {
+                Err(format!("Invalid LST protocol, did you mean {}?", protocol))
+            }
+            _ => Err(format!(
+                "Invalid LST protocol, expected one of {}",
+                LstProtocol::ALL
+                    .map(|protocol| protocol.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for LstProtocol {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        value.as_str().try_into()
+    }
+}
+
+impl std::str::FromStr for LstProtocol {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        value.try_into()
+    }
+}
+
+/// Describes the names [`LstProtocol`] deserializes from rather than its variants: schemars
+/// ignores `try_from`, and its derived schema would reject the other spellings the names are
+/// matched case-insensitively in.
+impl schemars::JsonSchema for LstProtocol {
+    fn schema_name() -> String {
+        "LstProtocol".to_string()
+    }
+
+    fn json_schema(_: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{InstanceType, Metadata, SchemaObject, StringValidation};
+
+        let names = LstProtocol::ALL.map(|protocol| protocol.to_string());
+        let pattern = names
+            .iter()
+            .map(|name| {
+                name.chars()
+                    .map(|c| format!("[{}{}]", c.to_ascii_lowercase(), c.to_ascii_uppercase()))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(format!("^({})$", pattern)),
+                ..Default::default()
+            })),
+            metadata: Some(Box::new(Metadata {
+                description: Some(format!(
+                    "LST protocol, one of {}, in any letter case",
+                    names.join(", ")
+                )),
+                examples: names.into_iter().map(serde_json::Value::from).collect(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+impl LstProtocol {
+    #[cfg(not(feature = "mock"))]
+    pub const ALL: [LstProtocol; 3] = [LstProtocol::AprMON, LstProtocol::GMON, LstProtocol::SHMON];
+    #[cfg(feature = "mock")]
+    pub const ALL: [LstProtocol; 4] = [
+        LstProtocol::AprMON,
+        LstProtocol::GMON,
+        LstProtocol::SHMON,
+        LstProtocol::Mock,
+    ];
+
+    /// Contract staked into and redeemed from, as overridden by the protocols file, if set.
+    pub(super) fn address(&self) -> Address {
+        if let Some(addresses) = registry::overridden(*self) {
+            return addresses.address;
+        }
+        match self {
+            LstProtocol::AprMON => *APRMON_ADDRESS,
+            LstProtocol::GMON => *GMON_STAKEMANAGER_ADDRESS,
+            LstProtocol::SHMON => *SHMON_ADDRESS,
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => mock::MOCK_ADDRESS,
+        }
+    }
+
+    pub(super) fn description(&self) -> &'static str {
+        match self {
+            LstProtocol::AprMON => {
+                "aPriori is the leading MEV-powered liquid staking platform on Monad."
+            }
+            LstProtocol::GMON => {
+                "Magma enables Monad token holders to earn staking rewards while remaining liquid through Magma's Liquid Staking token, gMON."
+            }
+            LstProtocol::SHMON => {
+                "shMONAD is an innovative Liquid Staking Token (LST) built on top of MON (Monad). Designed for users who wish to stake their MON while retaining liquidity, shMONAD allows holders to convert MON into shMON, bond their tokens within distinct policies, and later unbond them after an escrow period."
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => {
+                "In-memory mock LST for demos. Balances live in the server process and no real MON is moved."
+            }
+        }
+    }
+
+    /// Which operations the protocol supports, used to advertise and gate tools.
+    pub fn capabilities(&self) -> Capabilities {
+        match self {
+            LstProtocol::AprMON => Capabilities {
+                withdrawal: WithdrawalKind::Delayed,
+                deposit: DepositPath::Native,
+                stake_for_receiver: true,
+                unstake_for_owner: true,
+                claim_redeem: true,
+            },
+            LstProtocol::GMON => Capabilities {
+                withdrawal: WithdrawalKind::Instant,
+                deposit: DepositPath::Native,
+                stake_for_receiver: false,
+                unstake_for_owner: false,
+                claim_redeem: false,
+            },
+            // shMON is a plain ERC-4626 vault whose `deposit` is not payable
+            LstProtocol::SHMON => Capabilities {
+                withdrawal: WithdrawalKind::Instant,
+                deposit: DepositPath::Erc20,
+                stake_for_receiver: true,
+                unstake_for_owner: true,
+                claim_redeem: false,
+            },
+            // The mock has no allowances, so only owners redeem their own shares
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => Capabilities {
+                withdrawal: WithdrawalKind::Instant,
+                deposit: DepositPath::Native,
+                stake_for_receiver: true,
+                unstake_for_owner: false,
+                claim_redeem: false,
+            },
+        }
+    }
+
+    pub(super) fn token_address(&self) -> Address {
+        if let Some(addresses) = registry::overridden(*self) {
+            return addresses.token_address.unwrap_or(addresses.address);
+        }
+        match self {
+            LstProtocol::AprMON => *APRMON_ADDRESS,
+            LstProtocol::GMON => *GMON_ADDRESS,
+            LstProtocol::SHMON => *SHMON_ADDRESS,
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => mock::MOCK_ADDRESS,
+        }
+    }
+
+    /// Reads the decimals of the LST token and of the asset it is redeemable for. They only
+    /// change on a contract upgrade, so they are kept in `cache` for its TTL.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn decimals(
+        &self,
+        provider: Arc<RpcProvider>,
+        cache: &DecimalsCache,
+    ) -> anyhow::Result<Decimals> {
+        if let Some(decimals) = cache.get(*self) {
+            return Ok(decimals);
+        }
+
+        let decimals = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                Decimals {
+                    shares: contract
+                        .decimals()
+                        .call()
+                        .await
+                        .context("Failed to get decimals")?,
+                    assets: asset_decimals(provider.clone(), contract.asset(), cache).await?,
+                }
+            }
+            // gMON is minted for native MON only
+            LstProtocol::GMON => {
+                let contract = gmon::g_mon::gMON::new(self.token_address(), provider.clone());
+                Decimals {
+                    shares: contract
+                        .decimals()
+                        .call()
+                        .await
+                        .context("Failed to get decimals")?,
+                    assets: cache.native_decimals(),
+                }
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.token_address(), provider.clone());
+                Decimals {
+                    shares: contract
+                        .decimals()
+                        .call()
+                        .await
+                        .context("Failed to get decimals")?,
+                    assets: asset_decimals(provider.clone(), contract.asset(), cache).await?,
+                }
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => Decimals {
+                shares: cache.native_decimals(),
+                assets: cache.native_decimals(),
+            },
+        };
+
+        cache.store(*self, decimals);
+        Ok(decimals)
+    }
+
+    /// Contracts the protocol is read from and written to, which must be deployed.
+    pub(super) fn contracts(&self) -> Vec<(&'static str, Address)> {
+        match self {
+            LstProtocol::AprMON | LstProtocol::SHMON => vec![("token", self.token_address())],
+            LstProtocol::GMON => vec![
+                ("token", self.token_address()),
+                ("stake_manager", self.address()),
+            ],
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => vec![],
+        }
+    }
+
+    /// Generated ABIs the adapter reads and writes the protocol's contracts with, by the same
+    /// roles as [`Self::contracts`].
+    pub fn abis(&self) -> Vec<(&'static str, &'static Abi)> {
+        match self {
+            LstProtocol::AprMON => vec![("token", &aprmon::APRMON_ABI)],
+            LstProtocol::GMON => vec![
+                ("token", &gmon::GMON_ABI),
+                ("stake_manager", &gmonstakemanager::GMONSTAKEMANAGER_ABI),
+            ],
+            LstProtocol::SHMON => vec![("token", &shmon::SHMON_ABI)],
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => vec![],
+        }
+    }
+
+    /// Checks that every contract of the protocol is deployed, returning why the protocol is
+    /// unusable if not. Fails only when the RPC cannot answer.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn check_deployed(
+        &self,
+        provider: Arc<RpcProvider>,
+    ) -> anyhow::Result<Option<String>> {
+        self.check_contracts(provider, self.contracts()).await
+    }
+
+    /// Like [`LstProtocol::check_deployed`], over the given labeled contracts. Zero addresses
+    /// are reported without reading the chain.
+    async fn check_contracts(
+        &self,
+        provider: Arc<RpcProvider>,
+        contracts: Vec<(&'static str, Address)>,
+    ) -> anyhow::Result<Option<String>> {
+        for (label, address) in contracts {
+            if address.is_zero() {
+                return Ok(Some(format!("{} address is unset", label)));
+            }
+
+            let code = provider
+                .get_code(address, None)
+                .await
+                .with_context(|| format!("Failed to get code of {} {}", self, label))?;
+            if code.is_empty() {
+                return Ok(Some(format!(
+                    "no contract deployed at {} address {}",
+                    label,
+                    to_checksum(&address, None)
+                )));
+            }
+        }
+
+        Ok(None)
+    }
+
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn read_balance(
+        &self,
+        provider: Arc<RpcProvider>,
+        owner: Address,
+        block: Option<BlockId>,
+    ) -> anyhow::Result<U256> {
+        let block = block.unwrap_or(BlockId::Number(BlockNumber::Latest));
+
+        Ok(match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                contract
+                    .balance_of(owner)
+                    .block(block)
+                    .call()
+                    .await
+                    .context("Failed to get balance")?
+            }
+            LstProtocol::GMON => {
+                let contract = gmon::g_mon::gMON::new(self.token_address(), provider.clone());
+                contract
+                    .balance_of(owner)
+                    .block(block)
+                    .call()
+                    .await
+                    .context("Failed to get balance")?
+            }
+            LstProtocol::SHMON => {
+                let contract = erc20::erc20::new(self.token_address(), provider.clone());
+                contract
+                    .balance_of(owner)
+                    .block(block)
+                    .call()
+                    .await
+                    .context("Failed to get balance")?
+            }
+            // The mock keeps no history, so every block reads the current balance
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => mock::MOCK_LST.balance_of(owner),
+        })
+    }
+
+    /// Reads the bounds on a single deposit minting to `receiver`.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn deposit_limits(
+        &self,
+        provider: Arc<RpcProvider>,
+        receiver: Address,
+    ) -> anyhow::Result<DepositLimits> {
+        // ERC-4626 vaults report `type(uint256).max` when deposits are unlimited
+        let max = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                let max = contract
+                    .max_deposit(receiver)
+                    .call()
+                    .await
+                    .context("Failed to get max deposit")?;
+                (max != U256::MAX).then_some(max)
+            }
+            // The stake manager caps TVL rather than single deposits; a zero cap means none
+            LstProtocol::GMON => {
+                let contract = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+                    self.address(),
+                    provider.clone(),
+                );
+                let max_tvl = contract.max_deposit_tvl();
+                let tvl = contract.calculate_tvl();
+                let (max_tvl, tvl) = tokio::try_join!(max_tvl.call(), tvl.call())
+                    .context("Failed to get max deposit TVL")?;
+                (!max_tvl.is_zero()).then(|| max_tvl.saturating_sub(tvl))
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.token_address(), provider.clone());
+                let max = contract
+                    .max_deposit(receiver)
+                    .call()
+                    .await
+                    .context("Failed to get max deposit")?;
+                (max != U256::MAX).then_some(max)
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => None,
+        };
+
+        // None of the protocols enforce a minimum deposit
+        Ok(DepositLimits { min: None, max })
+    }
+
+    /// Reads the name and symbol of the LST token in one batch.
+    pub async fn token_metadata(
+        &self,
+        provider: Arc<RpcProvider>,
+    ) -> anyhow::Result<(String, String)> {
+        #[cfg(feature = "mock")]
+        if *self == LstProtocol::Mock {
+            return Ok(("Mock LST".to_string(), "mockLST".to_string()));
+        }
+
+        let token = erc20::erc20::new(self.token_address(), provider.clone());
+        let [name, symbol] = <[String; 2]>::try_from(
+            call_all(&provider, &[token.name(), token.symbol()])
+                .await
+                .context("Failed to get token name and symbol")?,
+        )
+        .map_err(|_| anyhow::anyhow!("Unexpected number of token metadata values"))?;
+
+        Ok((name, symbol))
+    }
+
+    /// Reads the ERC-20 token deposits are made in, or `None` when they are made in native MON.
+    pub async fn deposit_asset(
+        &self,
+        provider: Arc<RpcProvider>,
+    ) -> anyhow::Result<Option<Address>> {
+        let asset = match self {
+            LstProtocol::AprMON => aprmon::aprMON::new(self.token_address(), provider)
+                .asset()
+                .call()
+                .await
+                .context("Failed to get vault asset")?,
+            LstProtocol::SHMON => shmon::shMON::new(self.token_address(), provider)
+                .asset()
+                .call()
+                .await
+                .context("Failed to get vault asset")?,
+            // The stake manager only takes native MON
+            LstProtocol::GMON => return Ok(None),
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => return Ok(None),
+        };
+
+        Ok(erc20_asset(asset))
+    }
+
+    /// Currency stakes into the protocol are counted in: the native currency, or for ERC-20
+    /// deposits the vault's asset, at its own symbol and decimals. It formats amounts like the
+    /// native currency does, down to the configured display decimals.
+    pub async fn deposit_currency(
+        &self,
+        provider: Arc<RpcProvider>,
+        native: &NativeCurrency,
+        cache: &DecimalsCache,
+    ) -> anyhow::Result<NativeCurrency> {
+        if self.capabilities().deposit == DepositPath::Native {
+            return Ok(native.clone());
+        }
+        let Some(asset) = self.deposit_asset(provider.clone()).await? else {
+            return Ok(native.clone());
+        };
+
+        let decimals = self.decimals(provider.clone(), cache).await?;
+        let symbol = erc20::erc20::new(asset, provider)
+            .symbol()
+            .call()
+            .await
+            .context("Failed to get asset symbol")?;
+        let currency = NativeCurrency::new(symbol, decimals.assets)?;
+        Ok(match native.display_decimals() {
+            Some(display_decimals) => currency.with_display_decimals(display_decimals),
+            None => currency,
+        })
+    }
+
+    /// Reads whether the protocol's deposit contract is paused. shMON exposes no pause view, so
+    /// it is never reported paused.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn paused(&self, provider: Arc<RpcProvider>) -> anyhow::Result<bool> {
+        let paused = match self {
+            LstProtocol::AprMON => aprmon::aprMON::new(self.address(), provider)
+                .paused()
+                .call()
+                .await
+                .context("Failed to get paused state")?,
+            LstProtocol::GMON => gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+                self.address(),
+                provider,
+            )
+            .paused()
+            .call()
+            .await
+            .context("Failed to get paused state")?,
+            LstProtocol::SHMON => false,
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => false,
+        };
+
+        Ok(paused)
+    }
+
+    /// Checks that `spender`, the deposit contract the registry names for this protocol, is
+    /// deployed and not paused, so an approval never goes to a zero address, an EOA or a vault
+    /// that can't take the deposit.
+    async fn ensure_spender(
+        &self,
+        provider: Arc<RpcProvider>,
+        spender: Address,
+    ) -> anyhow::Result<()> {
+        if spender.is_zero() {
+            bail!("Refusing to approve the zero address as {} spender", self);
+        }
+
+        let code = provider
+            .get_code(spender, None)
+            .await
+            .context("Failed to get spender code")?;
+        if code.is_empty() {
+            bail!(
+                "Refusing to approve {}: no contract is deployed there",
+                to_checksum(&spender, None)
+            );
+        }
+        if self.paused(provider).await? {
+            bail!("Refusing to approve {}: deposits are paused", self);
+        }
+
+        Ok(())
+    }
+
+    /// Approves the protocol to pull `amount` of the ERC-20 `asset` from the signer, unless its
+    /// allowance already covers it. The spender is checked with [`Self::ensure_spender`] first.
+    /// For tokens in `allowance_resets`, a non-zero allowance is first set to zero in its own
+    /// transaction.
+    async fn approve_deposit(
+        &self,
+        signer: &Arc<WalletSigner>,
+        asset: Address,
+        amount: U256,
+        allowance_resets: &AllowanceResets,
+        gas_multiplier: GasMultiplier,
+        progress: &Progress,
+    ) -> anyhow::Result<()> {
+        let signer_address = signer.address();
+        let spender = self.address();
+        let token = erc20::erc20::new(asset, signer.clone());
+        let allowance = token
+            .allowance(signer_address, spender)
+            .call()
+            .await
+            .context("Failed to get allowance")?;
+        let approvals = allowance_resets.approvals(asset, allowance, amount);
+        if approvals.is_empty() {
+            return Ok(());
+        }
+
+        self.ensure_spender(signer.inner().clone(), spender).await?;
+        for approval in approvals {
+            let (step, unconfirmed) = if approval.is_zero() {
+                (
+                    WriteStep::ResettingAllowance,
+                    "Allowance reset was not confirmed",
+                )
+            } else {
+                (WriteStep::Approving, "Approval was not confirmed")
+            };
+            progress.report(step).await;
+            send_checked(
+                token.approve(spender, approval),
+                signer,
+                signer_address,
+                "approve",
+                gas_multiplier,
+                progress,
+            )
+            .await?
+            .context(unconfirmed)?;
+        }
+
+        Ok(())
+    }
+
+    /// Stakes `amount` along the protocol's [`DepositPath`]: as the transaction value for native
+    /// deposits, or after approving the vault's ERC-20 asset. The path is checked against the
+    /// asset the vault reports, and a mismatch fails before anything is sent rather than paying
+    /// MON to a vault that expects a token or the other way round. The amount is checked
+    /// against the receiver's deposit `limits` first, which the caller reads to also warn on.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn stake(
+        &self,
+        signer: Arc<WalletSigner>,
+        receiver: Address,
+        amount: U256,
+        limits: &DepositLimits,
+        native: &NativeCurrency,
+        allowance_resets: &AllowanceResets,
+        gas_multiplier: GasMultiplier,
+        progress: &Progress,
+    ) -> anyhow::Result<Option<TransactionReceipt>> {
+        let signer_address = signer.address();
+        limits.check(amount, native)?;
+
+        let asset = self.deposit_asset(signer.inner().clone()).await?;
+        let value = match self.capabilities().deposit.token_to_approve(*self, asset)? {
+            None => amount,
+            Some(asset) => {
+                self.approve_deposit(
+                    &signer,
+                    asset,
+                    amount,
+                    allowance_resets,
+                    gas_multiplier,
+                    progress,
+                )
+                .await?;
+                U256::zero()
+            }
+        };
+
+        progress.report(WriteStep::Depositing).await;
+        let receipt = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), signer.clone());
+                let call = contract.deposit(amount, receiver).value(value);
+                send_checked(
+                    call,
+                    &signer,
+                    signer_address,
+                    "deposit",
+                    gas_multiplier,
+                    progress,
+                )
+                .await?
+            }
+            LstProtocol::GMON => {
+                if receiver != signer_address {
+                    bail!("gMON deposits can only be received by the signer");
+                }
+
+                let contract = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+                    self.address(),
+                    signer.clone(),
+                );
+                let call = contract.deposit_mon().value(value);
+                send_checked(
+                    call,
+                    &signer,
+                    signer_address,
+                    "deposit",
+                    gas_multiplier,
+                    progress,
+                )
+                .await?
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.address(), signer.clone());
+                let call = contract.deposit(amount, receiver).value(value);
+                send_checked(
+                    call,
+                    &signer,
+                    signer_address,
+                    "deposit",
+                    gas_multiplier,
+                    progress,
+                )
+                .await?
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => Some(mock::MOCK_LST.deposit(signer_address, receiver, amount)),
+        };
+
+        Ok(receipt)
+    }
+
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn unstake(
+        &self,
+        signer: Arc<WalletSigner>,
+        controller: Address,
+        owner: Address,
+        amount: U256,
+        gas_multiplier: GasMultiplier,
+        progress: &Progress,
+    ) -> anyhow::Result<Option<TransactionReceipt>> {
+        let signer_address = signer.address();
+        progress.report(WriteStep::Redeeming).await;
+        let receipt = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), signer.clone());
+                let call = contract.request_redeem(amount, controller, owner);
+                send_checked(
+                    call,
+                    &signer,
+                    signer_address,
+                    "request redeem",
+                    gas_multiplier,
+                    progress,
+                )
+                .await?
+            }
+            LstProtocol::GMON => {
+                if controller != signer_address || owner != signer_address {
+                    bail!("gMON withdrawals can only be made by and to the signer");
+                }
+
+                let assets = self.gmon_assets(signer.inner().clone(), amount).await?;
+                if assets.is_zero() {
+                    bail!("{} gMON shares are worth less than 1 wei of MON", amount);
+                }
+                let contract = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+                    self.address(),
+                    signer.clone(),
+                );
+                let call = contract.withdraw_mon(assets);
+                send_checked(
+                    call,
+                    &signer,
+                    signer_address,
+                    "withdraw",
+                    gas_multiplier,
+                    progress,
+                )
+                .await?
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.address(), signer.clone());
+                let call = contract.redeem(amount, controller, owner);
+                send_checked(
+                    call,
+                    &signer,
+                    signer_address,
+                    "redeem",
+                    gas_multiplier,
+                    progress,
+                )
+                .await?
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => Some(mock::MOCK_LST.redeem(signer_address, owner, amount)?),
+        };
+
+        Ok(receipt)
+    }
+
+    /// Exact number of shares `owner` can redeem, so unstaking everything leaves no dust and
+    /// never requests more than is owned. Uses `maxRedeem` where the vault exposes it and the
+    /// token balance otherwise; no share/asset conversion is involved, so nothing is rounded.
+    /// gMON's withdrawal converts them to MON though, rounding down, which can leave gMON
+    /// worth less than 1 wei behind.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn redeemable_shares(
+        &self,
+        provider: Arc<RpcProvider>,
+        owner: Address,
+    ) -> anyhow::Result<U256> {
+        match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                contract
+                    .max_redeem(owner)
+                    .call()
+                    .await
+                    .context("Failed to get max redeem")
+            }
+            LstProtocol::GMON | LstProtocol::SHMON => {
+                self.read_balance(provider, owner, None).await
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => self.read_balance(provider, owner, None).await,
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn tvl(
+        &self,
+        provider: Arc<RpcProvider>,
+        block: Option<BlockId>,
+    ) -> anyhow::Result<U256> {
+        let block = block.unwrap_or(BlockId::Number(BlockNumber::Latest));
+
+        let tvl = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                contract
+                    .total_assets()
+                    .block(block)
+                    .call()
+                    .await
+                    .context("Failed to get total assets")?
+            }
+            LstProtocol::GMON => {
+                let contract = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+                    self.address(),
+                    provider.clone(),
+                );
+                contract
+                    .calculate_tvl()
+                    .block(block)
+                    .call()
+                    .await
+                    .context("Failed to get total supply")?
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.token_address(), provider.clone());
+                contract
+                    .total_assets()
+                    .block(block)
+                    .call()
+                    .await
+                    .context("Failed to get total supply")?
+            }
+            // The mock keeps no history, so every block reads the current TVL
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => mock::MOCK_LST.total_assets(),
+        };
+
+        Ok(tvl)
+    }
+
+    /// Previews the LST shares minted for staking `assets` MON.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn preview_stake(
+        &self,
+        provider: Arc<RpcProvider>,
+        cache: &DecimalsCache,
+        assets: U256,
+    ) -> anyhow::Result<U256> {
+        let shares = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                contract
+                    .preview_deposit(assets)
+                    .call()
+                    .await
+                    .context("Failed to preview deposit")?
+            }
+            // The gMON stake manager has no preview, so mint at the current exchange rate
+            LstProtocol::GMON => {
+                let decimals = self.decimals(provider.clone(), cache).await?;
+                let rate = self.exchange_rate(provider.clone(), cache, None).await?;
+                if rate.is_zero() {
+                    bail!("gMON exchange rate is zero");
+                }
+                decimals.shares_for(assets, rate)?
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.token_address(), provider.clone());
+                contract
+                    .preview_deposit(assets)
+                    .call()
+                    .await
+                    .context("Failed to preview deposit")?
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => mock::MOCK_LST.convert_to_shares(assets),
+        };
+
+        Ok(shares)
+    }
+
+    /// Previews the MON paid out for redeeming `shares`, before and after the withdrawal fee.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn preview_unstake(
+        &self,
+        provider: Arc<RpcProvider>,
+        shares: U256,
+    ) -> anyhow::Result<UnstakePreview> {
+        let (gross_assets, withdrawal_fee) = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), provider.clone());
+                let gross_assets = contract
+                    .preview_redeem(shares)
+                    .call()
+                    .await
+                    .context("Failed to preview redeem")?;
+                let fee_bps = contract
+                    .withdrawal_fee()
+                    .call()
+                    .await
+                    .context("Failed to get withdrawal fee")?;
+                let max_bps = contract
+                    .max_basis_points()
+                    .call()
+                    .await
+                    .context("Failed to get max basis points")?;
+                let (fee, _) = deduct_withdrawal_fee(gross_assets, fee_bps, max_bps);
+                (gross_assets, fee)
+            }
+            // gMON is withdrawn at the stake manager's backing, without a withdrawal fee
+            LstProtocol::GMON => (
+                self.gmon_assets(provider.clone(), shares).await?,
+                U256::zero(),
+            ),
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.address(), provider.clone());
+                let gross_assets = contract
+                    .preview_redeem(shares)
+                    .call()
+                    .await
+                    .context("Failed to preview redeem")?;
+                (gross_assets, U256::zero())
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => (mock::MOCK_LST.convert_to_assets(shares), U256::zero()),
+        };
+
+        Ok(UnstakePreview {
+            shares,
+            gross_assets,
+            withdrawal_fee,
+            net_assets: gross_assets.saturating_sub(withdrawal_fee),
+        })
+    }
+
+    /// Converts a MON amount to LST shares, rounding down so that no more shares than the
+    /// amount is worth are ever requested.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn convert_to_shares(
+        &self,
+        provider: Arc<RpcProvider>,
+        cache: &DecimalsCache,
+        assets: U256,
+    ) -> anyhow::Result<U256> {
+        let shares = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                contract
+                    .convert_to_shares(assets)
+                    .call()
+                    .await
+                    .context("Failed to convert to shares")?
+            }
+            LstProtocol::GMON => {
+                let decimals = self.decimals(provider.clone(), cache).await?;
+                let rate = self.exchange_rate(provider.clone(), cache, None).await?;
+                if rate.is_zero() {
+                    bail!("gMON exchange rate is zero");
+                }
+                decimals.shares_for(assets, rate)?
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.token_address(), provider.clone());
+                contract
+                    .convert_to_shares(assets)
+                    .call()
+                    .await
+                    .context("Failed to convert to shares")?
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => mock::MOCK_LST.convert_to_shares(assets),
+        };
+
+        Ok(shares)
+    }
+
+    /// Reads the MON backing gMON, the stake manager's TVL, and the gMON supply, which together
+    /// price gMON as it has no ERC-4626 conversions.
+    async fn gmon_backing(
+        &self,
+        provider: Arc<RpcProvider>,
+        block: BlockId,
+    ) -> anyhow::Result<(U256, U256)> {
+        let manager = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+            self.address(),
+            provider.clone(),
+        );
+        let token = gmon::g_mon::gMON::new(self.token_address(), provider);
+        let tvl = manager
+            .calculate_tvl()
+            .block(block)
+            .call()
+            .await
+            .context("Failed to get TVL")?;
+        let supply = token
+            .total_supply()
+            .block(block)
+            .call()
+            .await
+            .context("Failed to get total supply")?;
+
+        Ok((tvl, supply))
+    }
+
+    /// MON withdrawing `shares` gMON pays out at the stake manager's current backing, rounded
+    /// down. `withdrawMon` takes this MON amount rather than the gMON to burn.
+    async fn gmon_assets(&self, provider: Arc<RpcProvider>, shares: U256) -> anyhow::Result<U256> {
+        let (tvl, supply) = self
+            .gmon_backing(provider, BlockId::Number(BlockNumber::Latest))
+            .await?;
+        Ok(gmon_assets_for(shares, tvl, supply))
+    }
+
+    /// Interprets `amount` in the given unit and returns the number of shares it represents.
+    pub async fn to_shares(
+        &self,
+        provider: Arc<RpcProvider>,
+        cache: &DecimalsCache,
+        amount: U256,
+        unit: AmountUnit,
+    ) -> anyhow::Result<U256> {
+        match unit {
+            AmountUnit::Shares => Ok(amount),
+            AmountUnit::Assets => self.convert_to_shares(provider, cache, amount).await,
+        }
+    }
+
+    /// Reads the MON value of one LST token, scaled by 1e18 whatever the token and asset
+    /// decimals, optionally at a historical block.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn exchange_rate(
+        &self,
+        provider: Arc<RpcProvider>,
+        cache: &DecimalsCache,
+        block: Option<BlockId>,
+    ) -> anyhow::Result<U256> {
+        let decimals = self.decimals(provider.clone(), cache).await?;
+        let one = decimals.one_share();
+        let block = block.unwrap_or(BlockId::Number(BlockNumber::Latest));
+
+        let rate = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                let assets = contract
+                    .convert_to_assets(one)
+                    .block(block)
+                    .call()
+                    .await
+                    .context("Failed to convert to assets")?;
+                decimals.rate(assets)?
+            }
+            LstProtocol::GMON => {
+                let (tvl, supply) = self.gmon_backing(provider.clone(), block).await?;
+                gmon_exchange_rate(tvl, supply, decimals)?
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.token_address(), provider.clone());
+                let assets = contract
+                    .convert_to_assets(one)
+                    .block(block)
+                    .call()
+                    .await
+                    .context("Failed to convert to assets")?;
+                decimals.rate(assets)?
+            }
+            // The mock keeps no history, so historical blocks read the current rate
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => decimals.rate(mock::MOCK_LST.convert_to_assets(one))?,
+        };
+
+        Ok(rate)
+    }
+
+    /// Annualised yield, in percent, derived from the exchange-rate growth over the last
+    /// `APR_LOOKBACK_BLOCKS` blocks.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn apr(
+        &self,
+        provider: Arc<RpcProvider>,
+        cache: &DecimalsCache,
+    ) -> anyhow::Result<f64> {
+        let latest = provider
+            .get_block(BlockNumber::Latest)
+            .await
+            .context("Failed to get latest block")?
+            .context("Latest block not found")?;
+        let latest_number = latest.number.context("Latest block is pending")?;
+        let past_number = latest_number.saturating_sub(APR_LOOKBACK_BLOCKS.into());
+        let past = provider
+            .get_block(past_number)
+            .await
+            .context("Failed to get lookback block")?
+            .context("Lookback block not found")?;
+
+        let (rate_now, rate_then) = tokio::try_join!(
+            self.exchange_rate(provider.clone(), cache, Some(latest_number.into())),
+            self.exchange_rate(provider.clone(), cache, Some(past_number.into())),
+        )?;
+
+        let elapsed = latest.timestamp.saturating_sub(past.timestamp).as_u64();
+        Ok(annualize(rate_then, rate_now, elapsed))
+    }
+
+    /// Withdrawal fee charged on unstake, in percent.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn withdrawal_fee_percent(&self, provider: Arc<RpcProvider>) -> anyhow::Result<f64> {
+        match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), provider.clone());
+                let fee_bps = contract
+                    .withdrawal_fee()
+                    .call()
+                    .await
+                    .context("Failed to get withdrawal fee")?;
+                let max_bps = contract
+                    .max_basis_points()
+                    .call()
+                    .await
+                    .context("Failed to get max basis points")?;
+
+                Ok(fee_percent(fee_bps, max_bps))
+            }
+            LstProtocol::GMON | LstProtocol::SHMON => Ok(0.0),
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => Ok(0.0),
+        }
+    }
+
+    /// Seconds between requesting a withdrawal and being able to claim it, zero for protocols
+    /// that redeem instantly.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn withdrawal_wait_seconds(&self, provider: Arc<RpcProvider>) -> anyhow::Result<u64> {
+        match self {
+            LstProtocol::AprMON => {
+                let wait_time = aprmon::aprMON::new(self.address(), provider)
+                    .withdrawal_wait_time()
+                    .call()
+                    .await
+                    .context("Failed to get withdrawal wait time")?;
+                Ok(wait_time.low_u64())
+            }
+            LstProtocol::GMON | LstProtocol::SHMON => Ok(0),
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => Ok(0),
+        }
+    }
+
+    pub async fn stats(
+        &self,
+        provider: Arc<RpcProvider>,
+        cache: &DecimalsCache,
+    ) -> anyhow::Result<ProtocolStats> {
+        let (tvl, apr, withdrawal_fee) = tokio::try_join!(
+            self.tvl(provider.clone(), None),
+            self.apr(provider.clone(), cache),
+            self.withdrawal_fee_percent(provider.clone()),
+        )?;
+
+        Ok(ProtocolStats {
+            protocol: *self,
+            tvl,
+            apr,
+            withdrawal_fee,
+        })
+    }
+
+    /// Read-only calls behind a snapshot of this protocol: TVL, withdrawal fee and exchange rate
+    /// at block `now`, and the exchange rate at block `then` for the APR. Decoded by
+    /// [`LstProtocol::snapshot_from`].
+    pub fn snapshot_calls(
+        &self,
+        provider: Arc<RpcProvider>,
+        decimals: Decimals,
+        now: BlockId,
+        then: BlockId,
+    ) -> Vec<ContractCall<RpcProvider, U256>> {
+        let one = decimals.one_share();
+        match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.token_address(), provider.clone());
+                vec![
+                    contract.total_assets().block(now),
+                    contract.withdrawal_fee().block(now),
+                    contract.max_basis_points().block(now),
+                    contract.convert_to_assets(one).block(now),
+                    contract.convert_to_assets(one).block(then),
+                ]
+            }
+            LstProtocol::GMON => {
+                let manager = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+                    self.address(),
+                    provider.clone(),
+                );
+                let token = gmon::g_mon::gMON::new(self.token_address(), provider.clone());
+                vec![
+                    manager.calculate_tvl().block(now),
+                    token.total_supply().block(now),
+                    manager.calculate_tvl().block(then),
+                    token.total_supply().block(then),
+                ]
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.token_address(), provider.clone());
+                vec![
+                    contract.total_assets().block(now),
+                    contract.convert_to_assets(one).block(now),
+                    contract.convert_to_assets(one).block(then),
+                ]
+            }
+            // Read from memory by `snapshot_from`
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => vec![],
+        }
+    }
+
+    /// Builds a snapshot from the outputs of [`LstProtocol::snapshot_calls`], `elapsed` being
+    /// the seconds between the two blocks and `as_of` the unix time they were read.
+    pub fn snapshot_from(
+        &self,
+        values: &[U256],
+        decimals: Decimals,
+        elapsed: u64,
+        as_of: u64,
+    ) -> anyhow::Result<ProtocolSnapshot> {
+        let (tvl, withdrawal_fee, rate_now, rate_then) = match (self, values) {
+            (LstProtocol::AprMON, &[tvl, fee_bps, max_bps, rate_now, rate_then]) => (
+                tvl,
+                fee_percent(fee_bps, max_bps),
+                decimals.rate(rate_now)?,
+                decimals.rate(rate_then)?,
+            ),
+            (LstProtocol::GMON, &[tvl, supply, tvl_then, supply_then]) => (
+                tvl,
+                0.0,
+                gmon_exchange_rate(tvl, supply, decimals)?,
+                gmon_exchange_rate(tvl_then, supply_then, decimals)?,
+            ),
+            (LstProtocol::SHMON, &[tvl, rate_now, rate_then]) => (
+                tvl,
+                0.0,
+                decimals.rate(rate_now)?,
+                decimals.rate(rate_then)?,
+            ),
+            #[cfg(feature = "mock")]
+            (LstProtocol::Mock, &[]) => {
+                let rate = decimals.rate(mock::MOCK_LST.convert_to_assets(decimals.one_share()))?;
+                (mock::MOCK_LST.total_assets(), 0.0, rate, rate)
+            }
+            _ => bail!("Unexpected number of snapshot values for {}", self),
+        };
+
+        Ok(ProtocolSnapshot {
+            stats: ProtocolStats {
+                protocol: *self,
+                tvl,
+                apr: annualize(rate_then, rate_now, elapsed),
+                withdrawal_fee,
+            },
+            exchange_rate: rate_now,
+            as_of,
+        })
+    }
+
+    /// Reads a redeem request by id, returning `None` when no such request exists.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn redeem_request(
+        &self,
+        provider: Arc<RpcProvider>,
+        request_id: U256,
+    ) -> anyhow::Result<Option<RedeemRequestInfo>> {
+        match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), provider.clone());
+                let (shares, controller, assets, claimed, timestamp) = contract
+                    .redeem_requests(request_id)
+                    .call()
+                    .await
+                    .context("Failed to get redeem request")?;
+                let wait_time = contract
+                    .withdrawal_wait_time()
+                    .call()
+                    .await
+                    .context("Failed to get withdrawal wait time")?;
+
+                // Unknown ids decode to an all-zero struct
+                if controller.is_zero() && shares.is_zero() && timestamp.is_zero() {
+                    return Ok(None);
+                }
+
+                // Timestamps past u64 can only come from a broken contract; they saturate
+                // rather than panic, reading as never claimable
+                let seconds = |value: U256| u64::try_from(value).unwrap_or(u64::MAX);
+                Ok(Some(RedeemRequestInfo {
+                    request_id,
+                    shares,
+                    controller,
+                    assets,
+                    claimed,
+                    timestamp: seconds(timestamp),
+                    claimable_at: seconds(timestamp.saturating_add(wait_time)),
+                }))
+            }
+            LstProtocol::GMON | LstProtocol::SHMON => {
+                bail!("{} does not support redeem requests", self)
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => bail!("{} does not support redeem requests", self),
+        }
+    }
+
+    /// Labels the protocol's contracts and the privileged addresses its contracts expose.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn labeled_addresses(
+        &self,
+        provider: Arc<RpcProvider>,
+    ) -> anyhow::Result<Vec<(&'static str, Address)>> {
+        let labels = match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), provider.clone());
+                let owner = contract.owner();
+                let oracle_operator = contract.oracle_operator();
+                let fee_vault = contract.fee_vault();
+                let stake_pool = contract.stake_pool();
+                let (owner, oracle_operator, fee_vault, stake_pool) = tokio::try_join!(
+                    owner.call(),
+                    oracle_operator.call(),
+                    fee_vault.call(),
+                    stake_pool.call(),
+                )
+                .context("Failed to get aprMON roles")?;
+
+                vec![
+                    ("token", self.token_address()),
+                    ("owner", owner),
+                    ("oracle_operator", oracle_operator),
+                    ("fee_vault", fee_vault),
+                    ("stake_pool", stake_pool),
+                ]
+            }
+            LstProtocol::GMON => {
+                let stake_manager = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+                    self.address(),
+                    provider.clone(),
+                );
+                let token = gmon::g_mon::gMON::new(self.token_address(), provider.clone());
+                let stake_manager_roles = stake_manager.role_manager();
+                let token_roles = token.role_manager();
+                let (stake_manager_roles, token_roles) =
+                    tokio::try_join!(stake_manager_roles.call(), token_roles.call())
+                        .context("Failed to get gMON roles")?;
+
+                vec![
+                    ("token", self.token_address()),
+                    ("stake_manager", self.address()),
+                    ("stake_manager_role_manager", stake_manager_roles),
+                    ("token_role_manager", token_roles),
+                ]
+            }
+            // shMON exposes no governance views in its ABI
+            LstProtocol::SHMON => vec![("token", self.token_address())],
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => vec![("token", self.token_address())],
+        };
+
+        Ok(labels)
+    }
+
+    /// Reads the bounds of the redeem request queue.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn redeem_queue(
+        &self,
+        provider: Arc<RpcProvider>,
+        block: BlockId,
+    ) -> anyhow::Result<RedeemQueue> {
+        match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), provider.clone());
+                let last_processed = contract.last_processed_request_id().block(block);
+                let next = contract.next_request_id().block(block);
+                let (last_processed_request_id, next_request_id) =
+                    tokio::try_join!(last_processed.call(), next.call())
+                        .context("Failed to get redeem queue")?;
+
+                Ok(RedeemQueue {
+                    last_processed_request_id,
+                    next_request_id,
+                })
+            }
+            LstProtocol::GMON | LstProtocol::SHMON => {
+                bail!("{} does not support redeem requests", self)
+            }
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => bail!("{} does not support redeem requests", self),
+        }
+    }
+
+    /// Reads the last block processed by the protocol's oracle against the chain head.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn oracle_status(&self, provider: Arc<RpcProvider>) -> anyhow::Result<OracleStatus> {
+        match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), provider.clone());
+                let last_processed = contract.last_processed_block_number();
+                let (last_processed_block, current_block) = tokio::try_join!(
+                    async {
+                        last_processed
+                            .call()
+                            .await
+                            .context("Failed to get last processed block number")
+                    },
+                    async {
+                        provider
+                            .get_block_number()
+                            .await
+                            .context("Failed to get block number")
+                    },
+                )?;
+
+                let last_processed_block = u64::try_from(last_processed_block).map_err(|_| {
+                    anyhow::anyhow!(
+                        "Last processed block number {} is not a valid block number",
+                        last_processed_block
+                    )
+                })?;
+
+                Ok(OracleStatus {
+                    last_processed_block,
+                    current_block: current_block.as_u64(),
+                })
+            }
+            LstProtocol::GMON | LstProtocol::SHMON => bail!("{} has no oracle", self),
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => bail!("{} has no oracle", self),
+        }
+    }
+
+    /// Redeems matured redeem requests, sending the MON to `receiver`. A single id uses the
+    /// scalar `redeem(uint256,address)` overload and several ids the batched
+    /// `redeem(uint256[],address)` one. Requests still within the withdrawal wait time fail with
+    /// [`RedeemNotMature`], and requests controlled by another address fail too, before any
+    /// transaction is sent.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn claim_redeem(
+        &self,
+        signer: Arc<WalletSigner>,
+        request_ids: &[U256],
+        receiver: Address,
+        gas_multiplier: GasMultiplier,
+        progress: &Progress,
+    ) -> anyhow::Result<Option<TransactionReceipt>> {
+        if !self.capabilities().claim_redeem {
+            bail!(
+                "{} withdrawals are instant and have no redeem requests to claim",
+                self
+            );
+        }
+        if request_ids.is_empty() {
+            bail!("No redeem request ids given");
+        }
+
+        let provider = signer.inner().clone();
+        let (requests, latest) = tokio::try_join!(
+            futures::future::try_join_all(
+                request_ids
+                    .iter()
+                    .map(|&request_id| self.redeem_request(provider.clone(), request_id)),
+            ),
+            async {
+                provider
+                    .get_block(BlockNumber::Latest)
+                    .await
+                    .context("Failed to get latest block")?
+                    .context("Latest block not found")
+            },
+        )?;
+
+        let now = latest.timestamp.as_u64();
+        let signer_address = signer.address();
+        for (&request_id, request) in request_ids.iter().zip(requests) {
+            let Some(request) = request else {
+                bail!("Redeem request {} not found", request_id);
+            };
+            if request.claimed {
+                bail!("Redeem request {} has already been claimed", request_id);
+            }
+            request.ensure_controller(signer_address)?;
+            if !request.is_claimable(now) {
+                return Err(RedeemNotMature {
+                    request_id,
+                    claimable_at: request.claimable_at,
+                    remaining: request.remaining_wait(now),
+                }
+                .into());
+            }
+        }
+
+        let contract = aprmon::aprMON::new(self.address(), signer.clone());
+        progress.report(WriteStep::Redeeming).await;
+        match request_ids {
+            [request_id] => {
+                let call = contract.redeem_with_request_id(*request_id, receiver);
+                send_checked(
+                    call,
+                    &signer,
+                    signer_address,
+                    "redeem",
+                    gas_multiplier,
+                    progress,
+                )
+                .await
+            }
+            _ => {
+                let call = contract.redeem(request_ids.to_vec(), receiver);
+                send_checked(
+                    call,
+                    &signer,
+                    signer_address,
+                    "redeem",
+                    gas_multiplier,
+                    progress,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Collects the deposit and redeem activity of `owner` between the given blocks, oldest first,
+    /// querying logs `chunk_size` blocks at a time.
+    #[tracing::instrument(skip_all, fields(protocol = %self))]
+    pub async fn history(
+        &self,
+        provider: Arc<RpcProvider>,
+        owner: Address,
+        from_block: u64,
+        to_block: u64,
+        chunk_size: u64,
+    ) -> anyhow::Result<Vec<Activity>> {
+        let fetch = |filter: Filter| {
+            let provider = provider.clone();
+            async move {
+                get_logs_chunked(provider.as_ref(), &filter, from_block, to_block, chunk_size).await
+            }
+        };
+
+        let mut activities = Vec::new();
+        match self {
+            LstProtocol::AprMON => {
+                let contract = aprmon::aprMON::new(self.address(), provider.clone());
+                let (deposits, requests, redeems) = tokio::try_join!(
+                    fetch(contract.deposit_filter().topic2(owner).filter),
+                    fetch(contract.redeem_request_filter().topic2(owner).filter),
+                    fetch(contract.redeem_filter().topic2(owner).filter),
+                )?;
+
+                for log in deposits {
+                    let meta = LogMeta::from(&log);
+                    let event: aprmon::DepositFilter = parse_log(log)?;
+                    activities.push(
+                        Activity::new(ActivityKind::Deposit, event.assets, event.shares, meta)
+                            .with_address("sender", event.sender)
+                            .with_address("owner", event.owner),
+                    );
+                }
+                for log in requests {
+                    let meta = LogMeta::from(&log);
+                    let event: aprmon::RedeemRequestFilter = parse_log(log)?;
+                    activities.push(
+                        Activity::new(
+                            ActivityKind::RedeemRequest,
+                            event.assets,
+                            event.shares,
+                            meta,
+                        )
+                        .with_address("sender", event.sender)
+                        .with_address("owner", event.owner)
+                        .with_address("controller", event.controller),
+                    );
+                }
+                for log in redeems {
+                    let meta = LogMeta::from(&log);
+                    let event: aprmon::RedeemFilter = parse_log(log)?;
+                    activities.push(
+                        Activity::new(ActivityKind::Redeem, event.assets, event.shares, meta)
+                            .with_address("controller", event.controller)
+                            .with_address("receiver", event.receiver),
+                    );
+                }
+            }
+            LstProtocol::GMON => {
+                let contract = gmonstakemanager::g_mon_stake_manager::gMONStakeManager::new(
+                    self.address(),
+                    provider.clone(),
+                );
+                let (deposits, withdrawals) = tokio::try_join!(
+                    fetch(contract.deposit_filter().topic1(owner).filter),
+                    fetch(contract.withdraw_filter().topic1(owner).filter),
+                )?;
+
+                for log in deposits {
+                    let meta = LogMeta::from(&log);
+                    let event: gmonstakemanager::DepositFilter = parse_log(log)?;
+                    activities.push(
+                        Activity::new(
+                            ActivityKind::Deposit,
+                            event.amount,
+                            event.g_mon_minted,
+                            meta,
+                        )
+                        .with_address("owner", event.depositor),
+                    );
+                }
+                for log in withdrawals {
+                    let meta = LogMeta::from(&log);
+                    let event: gmonstakemanager::WithdrawFilter = parse_log(log)?;
+                    activities.push(
+                        Activity::new(ActivityKind::Redeem, event.amount, event.g_mon_burned, meta)
+                            .with_address("owner", event.withdrawer),
+                    );
+                }
+            }
+            LstProtocol::SHMON => {
+                let contract = shmon::shMON::new(self.address(), provider.clone());
+                let (deposits, withdrawals) = tokio::try_join!(
+                    fetch(contract.deposit_filter().topic2(owner).filter),
+                    fetch(contract.withdraw_filter().topic3(owner).filter),
+                )?;
+
+                for log in deposits {
+                    let meta = LogMeta::from(&log);
+                    let event: shmon::DepositFilter = parse_log(log)?;
+                    activities.push(
+                        Activity::new(ActivityKind::Deposit, event.assets, event.shares, meta)
+                            .with_address("sender", event.caller)
+                            .with_address("owner", event.owner),
+                    );
+                }
+                for log in withdrawals {
+                    let meta = LogMeta::from(&log);
+                    let event: shmon::WithdrawFilter = parse_log(log)?;
+                    activities.push(
+                        Activity::new(ActivityKind::Redeem, event.assets, event.shares, meta)
+                            .with_address("sender", event.caller)
+                            .with_address("owner", event.owner)
+                            .with_address("receiver", event.receiver),
+                    );
+                }
+            }
+            // The mock emits no events
+            #[cfg(feature = "mock")]
+            LstProtocol::Mock => {}
+        }
+
+        activities.sort_by_key(|a| (a.block_number, a.log_index));
+        Ok(activities)
+    }
+
+    /// Reads the LST shares a stake minted to `receiver` from its receipt, both from the
+    /// protocol's deposit event and from the LST's ERC-20 `Transfer` out of the zero address,
+    /// so a deposit event whose shape was misread shows up as a mismatch.
+    pub fn minted_shares(&self, receipt: &TransactionReceipt, receiver: Address) -> MintedShares {
+        let add = |total: Option<U256>, shares: U256| {
+            Some(total.unwrap_or_default().saturating_add(shares))
+        };
+
+        let mut minted = MintedShares::default();
+        for log in &receipt.logs {
+            if log.address == self.token_address() {
+                let mint = parse_log::<erc20::TransferFilter>(log.clone())
+                    .ok()
+                    .filter(|event| event.from == Address::zero() && event.to == receiver);
+                if let Some(mint) = mint {
+                    minted.transfer = add(minted.transfer, mint.value);
+                }
+            }
+            if log.address != self.address() {
+                continue;
+            }
+
+            let shares = match self {
+                LstProtocol::AprMON => parse_log::<aprmon::DepositFilter>(log.clone())
+                    .ok()
+                    .filter(|event| event.owner == receiver)
+                    .map(|event| event.shares),
+                // gMON's event names the depositor rather than the receiver
+                LstProtocol::GMON => parse_log::<gmonstakemanager::DepositFilter>(log.clone())
+                    .ok()
+                    .map(|event| event.g_mon_minted),
+                LstProtocol::SHMON => parse_log::<shmon::DepositFilter>(log.clone())
+                    .ok()
+                    .filter(|event| event.owner == receiver)
+                    .map(|event| event.shares),
+                #[cfg(feature = "mock")]
+                LstProtocol::Mock => None,
+            };
+            if let Some(shares) = shares {
+                minted.deposit_event = add(minted.deposit_event, shares);
+            }
+        }
+
+        minted
+    }
+}
+
+/// How a protocol takes the MON being staked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositPath {
+    /// Native MON is sent as the deposit transaction's value.
+    Native,
+    /// The vault's ERC-20 asset is approved for the vault, which pulls it in on deposit; no
+    /// value is sent.
+    Erc20,
+}
+
+impl DepositPath {
+    /// Checks the path against the `asset` the vault of `protocol` reports, returning the
+    /// ERC-20 token to approve, or `None` when the amount goes as the transaction value.
+    pub fn token_to_approve(
+        self,
+        protocol: LstProtocol,
+        asset: Option<Address>,
+    ) -> anyhow::Result<Option<Address>> {
+        match (self, asset) {
+            (DepositPath::Native, None) => Ok(None),
+            (DepositPath::Erc20, Some(asset)) => Ok(Some(asset)),
+            (DepositPath::Native, Some(asset)) => bail!(
+                "{} deposits the ERC-20 token {}, not native MON; refusing to send MON",
+                protocol,
+                to_checksum(&asset, None)
+            ),
+            (DepositPath::Erc20, None) => bail!(
+                "{} deposits native MON, not an ERC-20 token; refusing to approve",
+                protocol
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DepositPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepositPath::Native => write!(f, "native"),
+            DepositPath::Erc20 => write!(f, "erc20"),
+        }
+    }
+}
+
+/// How unstaked MON reaches the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WithdrawalKind {
+    /// MON is paid out in the unstake transaction.
+    Instant,
+    /// Unstaking creates a redeem request that is claimed once the wait time has passed.
+    Delayed,
+}
+
+impl fmt::Display for WithdrawalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WithdrawalKind::Instant => write!(f, "instant"),
+            WithdrawalKind::Delayed => write!(f, "delayed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    pub withdrawal: WithdrawalKind,
+    pub deposit: DepositPath,
+    /// Whether `stake` can mint to an address other than the signer.
+    pub stake_for_receiver: bool,
+    /// Whether `unstake` can redeem on behalf of another owner or controller.
+    pub unstake_for_owner: bool,
+    /// Whether redeem requests must be claimed with `claim_redeem`.
+    pub claim_redeem: bool,
+}
+
+/// A withdrawal ticket created by `requestRedeem`.
+#[derive(Debug, Clone)]
+pub struct RedeemRequestInfo {
+    pub request_id: U256,
+    pub shares: U256,
+    pub controller: Address,
+    pub assets: U256,
+    pub claimed: bool,
+    /// Unix timestamp at which the request was made.
+    pub timestamp: u64,
+    /// Unix timestamp from which the request can be redeemed.
+    pub claimable_at: u64,
+}
+
+impl RedeemRequestInfo {
+    pub fn is_claimable(&self, now: u64) -> bool {
+        !self.claimed && now >= self.claimable_at
+    }
+
+    /// Seconds left until the request can be redeemed; zero once it is claimable.
+    pub fn remaining_wait(&self, now: u64) -> u64 {
+        self.claimable_at.saturating_sub(now)
+    }
+
+    /// Only the request's controller can redeem it; anyone else's redeem reverts.
+    pub fn ensure_controller(&self, signer: Address) -> anyhow::Result<()> {
+        if self.controller != signer {
+            bail!(
+                "Redeem request {} belongs to controller {}, not the signer {}",
+                self.request_id,
+                to_checksum(&self.controller, None),
+                to_checksum(&signer, None)
+            );
+        }
+        Ok(())
+    }
+}
+
+/// A redeem request and the queue, read together by the redeem resources.
+pub(super) struct RedeemStatus {
+    pub(super) request: RedeemRequestInfo,
+    pub(super) queue: RedeemQueue,
+    /// Whether the queue had already processed the request `claim_confirmations` blocks ago.
+    pub(super) processed_confirmed: bool,
+    /// Timestamp of the latest block.
+    pub(super) now: u64,
+}
+
+impl RedeemStatus {
+    /// Whether the request can be claimed now: its wait time has passed and the queue had
+    /// processed it `claim_confirmations` blocks ago.
+    pub(super) fn claimable_now(&self) -> bool {
+        self.processed_confirmed && self.request.is_claimable(self.now)
+    }
+
+    /// Fails with why the request can't be claimed yet, if it can't.
+    pub(super) fn ensure_claimable(&self, confirmations: u64) -> anyhow::Result<()> {
+        let request_id = self.request.request_id;
+        if self.request.claimed {
+            bail!("Redeem request {} has already been claimed", request_id);
+        }
+        if !self.request.is_claimable(self.now) {
+            return Err(RedeemNotMature {
+                request_id,
+                claimable_at: self.request.claimable_at,
+                remaining: self.request.remaining_wait(self.now),
+            }
+            .into());
+        }
+        if !self.processed_confirmed {
+            bail!(
+                "Redeem request {} has not been processed for {} blocks yet",
+                request_id,
+                confirmations
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of waiting for a redeem request to become claimable.
+#[derive(Debug)]
+pub(super) enum RedeemWait {
+    Ready,
+    /// The wait ran out first; holds where the request stands.
+    Pending(serde_json::Value),
+}
+
+/// Bounds of the redeem request queue: requests up to `last_processed_request_id` have been
+/// processed and `next_request_id` is the id the next request will get.
+#[derive(Debug, Clone, Copy)]
+pub struct RedeemQueue {
+    pub last_processed_request_id: U256,
+    pub next_request_id: U256,
+}
+
+impl RedeemQueue {
+    pub fn is_processed(&self, request_id: U256) -> bool {
+        request_id <= self.last_processed_request_id
+    }
+
+    /// Number of requests, including this one, still to be processed up to `request_id`;
+    /// zero once it has been processed.
+    pub fn position(&self, request_id: U256) -> U256 {
+        request_id.saturating_sub(self.last_processed_request_id)
+    }
+}
+
+/// How far the protocol's oracle trails the chain head.
+#[derive(Debug, Clone, Copy)]
+pub struct OracleStatus {
+    pub last_processed_block: u64,
+    pub current_block: u64,
+}
+
+impl OracleStatus {
+    pub fn lag(&self) -> u64 {
+        self.current_block.saturating_sub(self.last_processed_block)
+    }
+
+    /// Whether the oracle trails the chain head by more than `threshold` blocks.
+    pub fn is_stale(&self, threshold: u64) -> bool {
+        self.lag() > threshold
+    }
+}
+
+/// Bounds on a single deposit, in MON; `None` where the protocol sets none.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DepositLimits {
+    pub min: Option<U256>,
+    pub max: Option<U256>,
+}
+
+impl DepositLimits {
+    /// Fails when `amount` is out of bounds, with a message in `native` units.
+    pub fn check(&self, amount: U256, native: &NativeCurrency) -> Result<(), DepositOutOfBounds> {
+        let native = native.clone();
+        if let Some(min) = self.min.filter(|min| amount < *min) {
+            return Err(DepositOutOfBounds::BelowMin {
+                amount,
+                min,
+                native,
+            });
+        }
+        if let Some(max) = self.max.filter(|max| amount > *max) {
+            return Err(DepositOutOfBounds::AboveMax {
+                amount,
+                max,
+                native,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Warns when `amount` is within bounds but uses at least [`NEAR_MAX_DEPOSIT_PERCENT`] of
+    /// the maximum, as the next deposit is likely to be rejected.
+    pub fn warning(&self, amount: U256, native: &NativeCurrency) -> Option<Warning> {
+        let max = self.max.filter(|max| !max.is_zero() && amount <= *max)?;
+        let near = amount.saturating_mul(U256::from(100))
+            >= max.saturating_mul(U256::from(NEAR_MAX_DEPOSIT_PERCENT));
+
+        near.then(|| {
+            Warning::new(
+                WarningCode::NearMaxDeposit,
+                format!(
+                    "Deposit of {} {} uses at least {}% of the {} {} maximum",
+                    native.display(amount),
+                    native.symbol(),
+                    NEAR_MAX_DEPOSIT_PERCENT,
+                    native.display(max),
+                    native.symbol()
+                ),
+            )
+        })
+    }
+}
+
+/// LST shares minted by a stake, as read from its receipt; `None` where no matching log was
+/// found.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MintedShares {
+    /// From the protocol's deposit event.
+    pub deposit_event: Option<U256>,
+    /// From the LST's ERC-20 `Transfer` out of the zero address to the receiver.
+    pub transfer: Option<U256>,
+}
+
+impl MintedShares {
+    /// Warns when both logs were found but disagree, in which case the transfer is the one
+    /// to trust: it is what the receiver's balance actually grew by.
+    pub fn warning(
+        &self,
+        protocol: LstProtocol,
+        decimals: Decimals,
+        native: &NativeCurrency,
+    ) -> Option<Warning> {
+        let (Some(deposit_event), Some(transfer)) = (self.deposit_event, self.transfer) else {
+            return None;
+        };
+
+        (deposit_event != transfer).then(|| {
+            Warning::new(
+                WarningCode::SharesMismatch,
+                format!(
+                    "The {} deposit event reports {} shares minted but the token transfer {}; lst_token_received is the amount received",
+                    protocol,
+                    native.round(decimals.format_shares(deposit_event)),
+                    native.round(decimals.format_shares(transfer))
+                ),
+            )
+        })
+    }
+}
+
+/// A stake amount outside the protocol's deposit limits, with the currency to word it in.
+#[derive(Debug)]
+pub enum DepositOutOfBounds {
+    BelowMin {
+        amount: U256,
+        min: U256,
+        native: NativeCurrency,
+    },
+    AboveMax {
+        amount: U256,
+        max: U256,
+        native: NativeCurrency,
+    },
+}
+
+impl DepositOutOfBounds {
+    pub fn code(&self) -> &'static str {
+        match self {
+            DepositOutOfBounds::BelowMin { .. } => "BELOW_MIN_DEPOSIT",
+            DepositOutOfBounds::AboveMax { .. } => "ABOVE_MAX_DEPOSIT",
+        }
+    }
+}
+
+impl fmt::Display for DepositOutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepositOutOfBounds::BelowMin {
+                amount,
+                min,
+                native,
+            } => write!(
+                f,
+                "Deposit of {} {} is below the minimum of {} {}",
+                native.format(*amount),
+                native.symbol(),
+                native.format(*min),
+                native.symbol()
+            ),
+            DepositOutOfBounds::AboveMax {
+                amount,
+                max,
+                native,
+            } => write!(
+                f,
+                "Deposit of {} {} is above the maximum of {} {}",
+                native.format(*amount),
+                native.symbol(),
+                native.format(*max),
+                native.symbol()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DepositOutOfBounds {}
+
+/// A redeem was attempted before the request's withdrawal wait time elapsed.
+#[derive(Debug)]
+pub struct RedeemNotMature {
+    pub request_id: U256,
+    pub claimable_at: u64,
+    pub remaining: u64,
+}
+
+impl fmt::Display for RedeemNotMature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Redeem request {} is claimable in {}s at {}",
+            self.request_id,
+            self.remaining,
+            iso_timestamp(self.claimable_at)
+        )
+    }
+}
+
+impl std::error::Error for RedeemNotMature {}
+
+/// An `unstake_all` found no redeemable shares for the owner.
+#[derive(Debug)]
+pub struct NothingToUnstake {
+    pub owner: Address,
+    pub protocol: LstProtocol,
+}
+
+impl fmt::Display for NothingToUnstake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} has no {} to unstake",
+            to_checksum(&self.owner, None),
+            self.protocol
+        )
+    }
+}
+
+impl std::error::Error for NothingToUnstake {}
+
+/// Formats a unix timestamp as an ISO 8601 UTC date-time.
+pub fn iso_timestamp(timestamp: u64) -> String {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+        .map(|datetime| datetime.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityKind {
+    Deposit,
+    RedeemRequest,
+    Redeem,
+}
+
+impl fmt::Display for ActivityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActivityKind::Deposit => write!(f, "deposit"),
+            ActivityKind::RedeemRequest => write!(f, "redeem_request"),
+            ActivityKind::Redeem => write!(f, "redeem"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Activity {
+    pub kind: ActivityKind,
+    pub assets: U256,
+    pub shares: U256,
+    pub block_number: u64,
+    pub log_index: U256,
+    pub transaction_hash: H256,
+    /// Addresses the event names, by role: `sender`, `owner`, `receiver` or `controller`.
+    pub addresses: Vec<(&'static str, Address)>,
+}
+
+impl Activity {
+    fn new(kind: ActivityKind, assets: U256, shares: U256, meta: LogMeta) -> Self {
+        Activity {
+            kind,
+            assets,
+            shares,
+            block_number: meta.block_number.as_u64(),
+            log_index: meta.log_index,
+            transaction_hash: meta.transaction_hash,
+            addresses: Vec::new(),
+        }
+    }
+
+    fn with_address(mut self, role: &'static str, address: Address) -> Self {
+        self.addresses.push((role, address));
+        self
+    }
+
+    /// The event's addresses keyed by role, checksummed like every other address in
+    /// responses, as decoded logs carry them in lowercase.
+    pub fn addresses_json(&self) -> serde_json::Value {
+        self.addresses
+            .iter()
+            .map(|(role, address)| (role.to_string(), to_checksum(address, None).into()))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+/// Position in an address history, pointing at the oldest activity already returned. The next
+/// page holds the activities before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryCursor {
+    pub block_number: u64,
+    pub log_index: U256,
+}
+
+impl HistoryCursor {
+    pub(super) fn of(activity: &Activity) -> Self {
+        HistoryCursor {
+            block_number: activity.block_number,
+            log_index: activity.log_index,
+        }
+    }
+
+    /// Whether `activity` comes before the cursor.
+    pub(super) fn precedes(&self, activity: &Activity) -> bool {
+        (activity.block_number, activity.log_index) < (self.block_number, self.log_index)
+    }
+}
+
+impl fmt::Display for HistoryCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.block_number, self.log_index)
+    }
+}
+
+impl std::str::FromStr for HistoryCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        let (block_number, log_index) = value
+            .split_once('-')
+            .context("Expected {block}-{log_index}")?;
+
+        Ok(HistoryCursor {
+            block_number: block_number.parse().context("Invalid block number")?,
+            log_index: U256::from_dec_str(log_index).context("Invalid log index")?,
+        })
+    }
+}
+
+/// Parses the `block` query parameter of point-in-time reads: a decimal block number or one of
+/// `latest`, `pending` and `finalized`.
+pub(super) fn parse_block_tag(value: &str) -> anyhow::Result<BlockNumber> {
+    match value {
+        "latest" => Ok(BlockNumber::Latest),
+        "pending" => Ok(BlockNumber::Pending),
+        "finalized" => Ok(BlockNumber::Finalized),
+        number => Ok(BlockNumber::Number(
+            number
+                .parse::<u64>()
+                .context("Expected a block number, latest, pending or finalized")?
+                .into(),
+        )),
+    }
+}
+
+/// Block that a point-in-time read is pinned to, resolved from a block tag.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PinnedBlock {
+    pub(super) id: BlockId,
+    pub(super) number: Option<u64>,
+    pub(super) timestamp: u64,
+}
+
+impl PinnedBlock {
+    /// Error context for a failed read at this block, pointing at the likely cause.
+    pub(super) fn read_context(&self) -> String {
+        match self.number {
+            Some(number) => format!(
+                "Failed to read state at block {}; blocks older than the RPC node keeps state for require an archive node",
+                number
+            ),
+            None => "Failed to read state at the pending block".to_string(),
+        }
+    }
+}
+
+/// The ERC-20 token an ERC-4626 vault reports as its `asset`, or `None` for native MON, which
+/// vaults report as the zero address or the `0xEeee…` placeholder.
+fn erc20_asset(asset: Address) -> Option<Address> {
+    (!asset.is_zero() && asset != Address::repeat_byte(0xee)).then_some(asset)
+}
+
+/// Reads the decimals of an ERC-4626 vault's underlying asset, taking those of native MON from
+/// `cache`.
+async fn asset_decimals(
+    provider: Arc<RpcProvider>,
+    asset: ContractCall<RpcProvider, Address>,
+    cache: &DecimalsCache,
+) -> anyhow::Result<u8> {
+    let asset = asset.call().await.context("Failed to get vault asset")?;
+    let Some(asset) = erc20_asset(asset) else {
+        return Ok(cache.native_decimals());
+    };
+
+    erc20::erc20::new(asset, provider)
+        .decimals()
+        .call()
+        .await
+        .context("Failed to get asset decimals")
+}
+
+/// Shares a stake minted, as read from its receipt by [`LstProtocol::minted_shares`].
+pub(super) fn minted_json(
+    protocol: LstProtocol,
+    minted: &MintedShares,
+    decimals: Decimals,
+) -> serde_json::Value {
+    serde_json::json!({
+        "shares_minted": minted.deposit_event.map(|shares| decimals.format_shares(shares)),
+        "lst_token_received": minted.transfer.map(|shares| decimals.format_shares(shares)),
+        "token": protocol.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{abi::Token, contract::EthEvent, types::Log};
+
+    use super::*;
+    use crate::common::lst::tests::{mon, redeem_request, stub_provider};
+
+    fn log(address: Address, topics: Vec<H256>, data: Vec<Token>) -> Log {
+        Log {
+            address,
+            topics,
+            data: ethers::abi::encode(&data).into(),
+            ..Default::default()
+        }
+    }
+
+    /// Receipt of a shMON stake to `receiver` whose deposit event reports `deposit_shares` and
+    /// whose LST mint transfers `transfer_shares`.
+    fn stake_receipt(
+        receiver: Address,
+        deposit_shares: U256,
+        transfer_shares: U256,
+    ) -> TransactionReceipt {
+        let sender = Address::repeat_byte(0x11);
+        TransactionReceipt {
+            logs: vec![
+                log(
+                    LstProtocol::SHMON.token_address(),
+                    vec![
+                        erc20::TransferFilter::signature(),
+                        H256::from(Address::zero()),
+                        H256::from(receiver),
+                    ],
+                    vec![Token::Uint(transfer_shares)],
+                ),
+                log(
+                    LstProtocol::SHMON.address(),
+                    vec![
+                        shmon::DepositFilter::signature(),
+                        H256::from(sender),
+                        H256::from(receiver),
+                    ],
+                    vec![Token::Uint(mon(1)), Token::Uint(deposit_shares)],
+                ),
+                // A mint to someone else is not the receiver's
+                log(
+                    LstProtocol::SHMON.token_address(),
+                    vec![
+                        erc20::TransferFilter::signature(),
+                        H256::from(Address::zero()),
+                        H256::from(sender),
+                    ],
+                    vec![Token::Uint(mon(5))],
+                ),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn minted_shares_are_read_from_a_synthetic_receipt() {
+        let receiver = Address::repeat_byte(0x22);
+        let decimals = Decimals {
+            shares: 6,
+            assets: 18,
+        };
+        let native = NativeCurrency::default();
+
+        let receipt = stake_receipt(receiver, U256::from(2_500_000), U256::from(2_500_000));
+        let minted = LstProtocol::SHMON.minted_shares(&receipt, receiver);
+        assert_eq!(minted.deposit_event, Some(U256::from(2_500_000)));
+        assert_eq!(minted.transfer, Some(U256::from(2_500_000)));
+        assert!(
+            minted
+                .warning(LstProtocol::SHMON, decimals, &native)
+                .is_none()
+        );
+        assert_eq!(
+            minted_json(LstProtocol::SHMON, &minted, decimals),
+            serde_json::json!({
+                "shares_minted": "2.500000",
+                "lst_token_received": "2.500000",
+                "token": "shMON",
+            })
+        );
+
+        let receipt = stake_receipt(receiver, U256::from(2_500_000), U256::from(2_000_000));
+        let minted = LstProtocol::SHMON.minted_shares(&receipt, receiver);
+        let warning = minted
+            .warning(LstProtocol::SHMON, decimals, &native)
+            .expect("mismatch warns");
+        assert_eq!(warning.code, WarningCode::SharesMismatch);
+        assert!(
+            warning
+                .message
+                .contains("reports 2.500000 shares minted but the token transfer 2.000000")
+        );
+
+        let minted = LstProtocol::SHMON.minted_shares(&TransactionReceipt::default(), receiver);
+        assert_eq!(
+            minted_json(LstProtocol::SHMON, &minted, decimals)["shares_minted"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn deposits_are_checked_against_the_limits_and_warn_near_the_maximum() {
+        let native = NativeCurrency::new("MON", 18).unwrap();
+        let limits = DepositLimits {
+            min: Some(mon(1)),
+            max: Some(mon(100)),
+        };
+        assert!(matches!(
+            limits.check(mon(1) / 2, &native),
+            Err(DepositOutOfBounds::BelowMin { .. })
+        ));
+        assert!(matches!(
+            limits.check(mon(101), &native),
+            Err(DepositOutOfBounds::AboveMax { .. })
+        ));
+        assert!(limits.check(mon(100), &native).is_ok());
+
+        assert!(limits.warning(mon(89), &native).is_none());
+        let warning = limits.warning(mon(90), &native).unwrap();
+        assert_eq!(warning.code, WarningCode::NearMaxDeposit);
+        assert!(limits.warning(mon(100), &native).is_some());
+        // Past the maximum the deposit fails instead
+        assert!(limits.warning(mon(101), &native).is_none());
+
+        let uncapped = DepositLimits {
+            min: None,
+            max: None,
+        };
+        assert!(uncapped.check(mon(1_000_000), &native).is_ok());
+
+        // Messages are worded in the configured currency
+        let test = NativeCurrency::new("TEST", 6).unwrap();
+        let limits = DepositLimits {
+            min: Some(U256::from(1_000_000)),
+            max: None,
+        };
+        let err = limits.check(U256::from(500_000), &test).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Deposit of 0.500000 TEST is below the minimum of 1.000000 TEST"
+        );
+        assert!(uncapped.warning(mon(1_000_000), &native).is_none());
+    }
+
+    #[test]
+    fn only_the_controller_can_redeem_a_request() {
+        let controller = Address::repeat_byte(0xaa);
+        let request = redeem_request(controller);
+        assert!(request.ensure_controller(controller).is_ok());
+
+        let err = request
+            .ensure_controller(Address::repeat_byte(0xbb))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("Redeem request 7 belongs to controller"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn redeem_requests_are_claimable_once_processed_and_confirmed() {
+        let status = |processed_confirmed, now| RedeemStatus {
+            request: redeem_request(Address::repeat_byte(0xaa)),
+            queue: RedeemQueue {
+                last_processed_request_id: U256::from(7),
+                next_request_id: U256::from(8),
+            },
+            processed_confirmed,
+            now,
+        };
+
+        let waiting = status(true, 1_500);
+        assert!(!waiting.claimable_now());
+        let err = waiting.ensure_claimable(3).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RedeemNotMature>().unwrap().remaining,
+            500
+        );
+
+        // Matured, but the queue processed it too recently
+        let unconfirmed = status(false, 2_000);
+        assert!(!unconfirmed.claimable_now());
+        let err = unconfirmed.ensure_claimable(3).unwrap_err().to_string();
+        assert!(err.contains("not been processed for 3 blocks"), "{err}");
+
+        let ready = status(true, 2_000);
+        assert!(ready.claimable_now());
+        assert!(ready.ensure_claimable(3).is_ok());
+    }
+
+    #[test]
+    fn native_deposits_send_value_and_erc20_deposits_approve() {
+        let wmon = Address::repeat_byte(0x77);
+
+        let native = LstProtocol::AprMON.capabilities().deposit;
+        assert_eq!(native, DepositPath::Native);
+        assert_eq!(
+            native.token_to_approve(LstProtocol::AprMON, None).unwrap(),
+            None
+        );
+        assert!(
+            native
+                .token_to_approve(LstProtocol::AprMON, Some(wmon))
+                .is_err()
+        );
+
+        let erc20 = LstProtocol::SHMON.capabilities().deposit;
+        assert_eq!(erc20, DepositPath::Erc20);
+        assert_eq!(
+            erc20
+                .token_to_approve(LstProtocol::SHMON, Some(wmon))
+                .unwrap(),
+            Some(wmon)
+        );
+        assert!(erc20.token_to_approve(LstProtocol::SHMON, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn approvals_are_refused_for_spenders_without_code() {
+        let vault = Address::repeat_byte(0x33);
+        let provider = stub_provider(move |method, params| {
+            assert_eq!(method, "eth_getCode");
+            let address: Address = serde_json::from_value(params[0].clone()).unwrap();
+            serde_json::json!(if address == vault { "0x6080" } else { "0x" })
+        })
+        .await;
+
+        let protocol = LstProtocol::SHMON;
+        assert!(
+            protocol
+                .ensure_spender(provider.clone(), vault)
+                .await
+                .is_ok()
+        );
+
+        let err = protocol
+            .ensure_spender(provider.clone(), Address::repeat_byte(0x44))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("no contract is deployed there"), "{err}");
+
+        let err = protocol
+            .ensure_spender(provider, Address::zero())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("zero address"), "{err}");
+    }
+
+    #[test]
+    fn protocol_schema_accepts_the_spellings_names_parse_from() {
+        let schema = serde_json::to_value(schemars::schema_for!(LstProtocol)).unwrap();
+
+        assert_eq!(schema["type"], "string");
+        assert!(schema.get("enum").is_none(), "{schema}");
+        assert!(
+            schema["pattern"]
+                .as_str()
+                .unwrap()
+                .starts_with("^([aA][pP][rR][mM][oO][nN]|[gG][mM][oO][nN]|[sS][hH][mM][oO][nN]"),
+            "{schema}"
+        );
+        assert_eq!(schema["examples"][0], "aprMON");
+        for name in ["aprmon", "GMON", "ShMon"] {
+            assert!(LstProtocol::try_from(name).is_ok(), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn redeem_requests_decode_from_the_contract_tuple() {
+        let controller = Address::repeat_byte(0xaa);
+        let selector = |signature: &str| {
+            ethers::utils::hex::encode_prefixed(&ethers::utils::id(signature)[..4])
+        };
+        let (requests, wait_time) = (
+            selector("redeemRequests(uint256)"),
+            selector("withdrawalWaitTime()"),
+        );
+        let provider = stub_provider(move |method, params| {
+            assert_eq!(method, "eth_call");
+            let data = params[0]["data"]
+                .as_str()
+                .or(params[0]["input"].as_str())
+                .unwrap();
+            let tokens = if data[..10] == wait_time {
+                vec![Token::Uint(U256::from(600))]
+            } else {
+                assert_eq!(data[..10], requests);
+                let request_id = U256::from_str_radix(&data[10..], 16).unwrap();
+                match request_id.as_u64() {
+                    7 => vec![
+                        Token::Uint(mon(2)),
+                        Token::Address(controller),
+                        Token::Uint(mon(3)),
+                        Token::Bool(true),
+                        Token::Uint(U256::from(1_000)),
+                    ],
+                    // A timestamp no block can have
+                    8 => vec![
+                        Token::Uint(mon(1)),
+                        Token::Address(controller),
+                        Token::Uint(mon(1)),
+                        Token::Bool(false),
+                        Token::Uint(U256::MAX),
+                    ],
+                    _ => vec![
+                        Token::Uint(U256::zero()),
+                        Token::Address(Address::zero()),
+                        Token::Uint(U256::zero()),
+                        Token::Bool(false),
+                        Token::Uint(U256::zero()),
+                    ],
+                }
+            };
+            serde_json::json!(ethers::utils::hex::encode_prefixed(ethers::abi::encode(
+                &tokens
+            )))
+        })
+        .await;
+        let read = |request_id: u64| {
+            LstProtocol::AprMON.redeem_request(provider.clone(), U256::from(request_id))
+        };
+
+        let request = read(7).await.unwrap().unwrap();
+        assert_eq!(request.request_id, U256::from(7));
+        assert_eq!(request.shares, mon(2));
+        assert_eq!(request.controller, controller);
+        assert_eq!(request.assets, mon(3));
+        assert!(request.claimed);
+        assert_eq!((request.timestamp, request.claimable_at), (1_000, 1_600));
+
+        let request = read(8).await.unwrap().unwrap();
+        assert_eq!(
+            (request.timestamp, request.claimable_at),
+            (u64::MAX, u64::MAX)
+        );
+        assert!(!request.is_claimable(u64::MAX - 1));
+
+        // Unknown ids decode to zeros, which read as no request
+        assert!(read(9).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn mon_amounts_convert_to_shares_rounding_down() {
+        // 3 MON back 2 gMON, so one MON is two thirds of a gMON
+        let provider = stub_provider(|_, params| {
+            let data = params[0]["data"]
+                .as_str()
+                .or(params[0]["input"].as_str())
+                .unwrap();
+            let value = match &data[..10] {
+                // decimals()
+                "0x313ce567" => U256::from(18),
+                // totalSupply()
+                "0x18160ddd" => mon(2),
+                // calculateTVL()
+                _ => mon(3),
+            };
+            serde_json::json!(format!("0x{:064x}", value))
+        })
+        .await;
+        let cache = DecimalsCache::default();
+        let to_shares = |amount: U256, unit: AmountUnit| {
+            LstProtocol::GMON.to_shares(provider.clone(), &cache, amount, unit)
+        };
+
+        let shares = to_shares(mon(1), AmountUnit::Assets).await.unwrap();
+        assert_eq!(shares, U256::from(666_666_666_666_666_666u64));
+        // The shares requested are never worth more than the MON asked for
+        let rate = U256::from(1_500_000_000_000_000_000u64);
+        assert!(cache.get(LstProtocol::GMON).unwrap().value_of(shares, rate) < mon(1));
+        let shares = to_shares(U256::from(1), AmountUnit::Assets).await.unwrap();
+        assert_eq!(shares, U256::zero());
+
+        // Shares are taken as given
+        let shares = to_shares(mon(1), AmountUnit::Shares).await.unwrap();
+        assert_eq!(shares, mon(1));
+    }
+
+    #[tokio::test]
+    async fn aprmon_roles_are_labeled_with_their_addresses() {
+        let selector = |signature: &str| {
+            ethers::utils::hex::encode_prefixed(&ethers::utils::id(signature)[..4])
+        };
+        let roles = [
+            (selector("owner()"), Address::repeat_byte(1)),
+            (selector("oracleOperator()"), Address::repeat_byte(2)),
+            (selector("feeVault()"), Address::repeat_byte(3)),
+            (selector("stakePool()"), Address::repeat_byte(4)),
+        ];
+        let provider = stub_provider(move |_, params| {
+            let data = params[0]["data"]
+                .as_str()
+                .or(params[0]["input"].as_str())
+                .unwrap();
+            let (_, address) = roles.iter().find(|(s, _)| *s == data[..10]).unwrap();
+            serde_json::json!(format!("{:?}", H256::from(*address)))
+        })
+        .await;
+
+        let labels = LstProtocol::AprMON
+            .labeled_addresses(provider)
+            .await
+            .unwrap();
+        assert_eq!(
+            labels,
+            [
+                ("token", LstProtocol::AprMON.token_address()),
+                ("owner", Address::repeat_byte(1)),
+                ("oracle_operator", Address::repeat_byte(2)),
+                ("fee_vault", Address::repeat_byte(3)),
+                ("stake_pool", Address::repeat_byte(4)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn zero_addresses_are_reported_unset_without_reading_the_chain() {
+        // Nothing listens here, so any read would fail the check
+        let rpc = crate::common::rpc::FailoverHttp::new(&["http://127.0.0.1:1".to_string()]);
+        let provider = Arc::new(crate::common::rpc::CallLogger::new(
+            ethers::providers::Provider::new(rpc.unwrap()),
+            false,
+        ));
+
+        let reason = LstProtocol::GMON
+            .check_contracts(
+                provider,
+                vec![
+                    ("stake_manager", Address::zero()),
+                    ("token", Address::zero()),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(reason.as_deref(), Some("stake_manager address is unset"));
+    }
+
+    #[tokio::test]
+    async fn oracle_blocks_past_u64_fail_instead_of_panicking() {
+        let provider = |last_processed: U256| {
+            stub_provider(move |method, _| match method {
+                "eth_blockNumber" => serde_json::json!("0x1000"),
+                _ => serde_json::json!(format!("0x{:064x}", last_processed)),
+            })
+        };
+
+        let status = LstProtocol::AprMON
+            .oracle_status(provider(U256::from(4_000)).await)
+            .await
+            .unwrap();
+        assert_eq!(
+            (status.last_processed_block, status.current_block),
+            (4_000, 4_096)
+        );
+
+        let err = LstProtocol::AprMON
+            .oracle_status(provider(U256::from(u64::MAX) + 1).await)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("18446744073709551616 is not a valid block number"),
+            "{err}"
+        );
+    }
+}
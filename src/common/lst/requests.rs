@@ -0,0 +1,496 @@
+//! Argument types of the tools, with the patterns their schemas advertise.
+
+use rmcp::{model::ProgressToken, schemars};
+
+use super::{protocol::LstProtocol, units::AmountUnit};
+use crate::common::warning::{Warning, WarningCode};
+
+/// JSON-schema patterns advertised for tool arguments, and checked against before each call.
+pub(super) const ADDRESS_PATTERN: &str = "^0x[0-9a-fA-F]{40}$";
+const PRIVATE_KEY_PATTERN: &str = "^(0x)?[0-9a-fA-F]{64}$";
+const DECIMAL_PATTERN: &str = r"^[0-9]+(\.[0-9]{1,18})?$";
+const INTEGER_PATTERN: &str = "^[0-9]+$";
+const SIGNATURE_PATTERN: &str = "^(0x)?[0-9a-fA-F]{130}$";
+const TX_HASH_PATTERN: &str = "^0x[0-9a-fA-F]{64}$";
+
+fn example_amount() -> &'static str {
+    "1.5"
+}
+
+fn example_amount_wei() -> &'static str {
+    "1500000000000000000"
+}
+
+fn example_address() -> &'static str {
+    "0x000000000000000000000000000000000000dEaD"
+}
+
+/// Decimal amount argument. The schema asks for a string, but a JSON number is accepted too,
+/// as generated arguments often carry one. Integers are taken exactly; a float is read through
+/// its shortest decimal form and flagged, as the client may already have rounded it.
+#[derive(Debug, Clone)]
+pub struct AmountArg {
+    value: String,
+    from_float: bool,
+}
+
+impl AmountArg {
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// [`WarningCode::FloatAmount`] warning if the amount arrived as a float.
+    pub(super) fn warning(&self) -> Option<Warning> {
+        self.from_float.then(|| {
+            Warning::new(
+                WarningCode::FloatAmount,
+                format!(
+                    "amount was sent as a JSON number and read as {}; send amounts as strings to avoid floating-point rounding",
+                    self.value
+                ),
+            )
+        })
+    }
+}
+
+impl From<AmountArg> for String {
+    fn from(amount: AmountArg) -> Self {
+        amount.value
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AmountArg {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(value) => Ok(AmountArg {
+                value,
+                from_float: false,
+            }),
+            serde_json::Value::Number(number) if !number.is_f64() => Ok(AmountArg {
+                value: number.to_string(),
+                from_float: false,
+            }),
+            // f64's Display never uses exponent notation, unlike JSON's, so 1e-7 reads as
+            // 0.0000001 and parses like any decimal string
+            serde_json::Value::Number(number) => match number.as_f64() {
+                Some(value) if value.is_finite() => Ok(AmountArg {
+                    value: value.to_string(),
+                    from_float: true,
+                }),
+                _ => Err(D::Error::custom(format!(
+                    "amount {} is not a finite number",
+                    number
+                ))),
+            },
+            other => Err(D::Error::custom(format!(
+                "amount must be a decimal string or a number, got {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StakeRequest {
+    /// Liquid staking protocol to use. Defaults to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Amount of MON to stake, as a decimal string with up to 18 decimals, or of the vault's
+    /// asset at its decimals for ERC-20 deposits such as shMON's. Exactly one of `amount` and
+    /// `amount_wei` must be given.
+    #[schemars(
+        with = "Option<String>",
+        regex(path = "DECIMAL_PATTERN"),
+        example = "example_amount"
+    )]
+    pub amount: Option<AmountArg>,
+    /// Amount to stake in the smallest unit (wei), as an integer string, for exact amounts.
+    #[schemars(regex(path = "INTEGER_PATTERN"), example = "example_amount_wei")]
+    pub amount_wei: Option<String>,
+    /// Address receiving the LST tokens. Defaults to the signer.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub receiver: Option<String>,
+    /// Gas limit multiplier for this write, as described on
+    /// [`GasMultiplier`](crate::common::tx::GasMultiplier).
+    #[schemars(range(min = 1.0))]
+    pub gas_limit_multiplier: Option<f64>,
+    /// Token to report this write's steps under, as described on
+    /// [`Progress`](crate::common::progress::Progress).
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub progress_token: Option<ProgressToken>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UnstakeRequest {
+    /// Liquid staking protocol to use. Defaults to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Amount to unstake, as a decimal string with up to 18 decimals, in `amount_unit`.
+    /// Exactly one of `amount` and `amount_wei` must be given.
+    #[schemars(
+        with = "Option<String>",
+        regex(path = "DECIMAL_PATTERN"),
+        example = "example_amount"
+    )]
+    pub amount: Option<AmountArg>,
+    /// Amount to unstake in base units (wei), as an integer string, in `amount_unit`.
+    #[schemars(regex(path = "INTEGER_PATTERN"), example = "example_amount_wei")]
+    pub amount_wei: Option<String>,
+    /// Whether the amount is in MON (`assets`) or LST tokens (`shares`). Defaults to `shares`.
+    #[serde(default)]
+    pub amount_unit: AmountUnit,
+    /// Address controlling the redeem request and receiving the MON. Defaults to the signer.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub controller: Option<String>,
+    /// Address whose LST tokens are redeemed. Defaults to the signer.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub owner: Option<String>,
+    /// Gas limit multiplier for this write, as described on
+    /// [`GasMultiplier`](crate::common::tx::GasMultiplier).
+    #[schemars(range(min = 1.0))]
+    pub gas_limit_multiplier: Option<f64>,
+    /// Token to report this write's steps under, as described on
+    /// [`Progress`](crate::common::progress::Progress).
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub progress_token: Option<ProgressToken>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UnstakeAllRequest {
+    /// Liquid staking protocol to use. Defaults to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Address controlling the redeem request and receiving the MON. Defaults to the signer.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub controller: Option<String>,
+    /// Address whose whole LST balance is redeemed. Defaults to the signer.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub owner: Option<String>,
+    /// Gas limit multiplier for this write, as described on
+    /// [`GasMultiplier`](crate::common::tx::GasMultiplier).
+    #[schemars(range(min = 1.0))]
+    pub gas_limit_multiplier: Option<f64>,
+    /// Token to report this write's steps under, as described on
+    /// [`Progress`](crate::common::progress::Progress).
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub progress_token: Option<ProgressToken>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SplitEntry {
+    /// Liquid staking protocol of this entry.
+    pub protocol: LstProtocol,
+    /// Amount of this entry, as a decimal string with up to 18 decimals. Exactly one of
+    /// `amount` and `amount_wei` must be given.
+    #[schemars(
+        with = "Option<String>",
+        regex(path = "DECIMAL_PATTERN"),
+        example = "example_amount"
+    )]
+    pub amount: Option<AmountArg>,
+    /// Amount of this entry in base units (wei), as an integer string, for exact amounts.
+    #[schemars(regex(path = "INTEGER_PATTERN"), example = "example_amount_wei")]
+    pub amount_wei: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StakeSplitRequest {
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Stakes to make, one transaction each, in order. Amounts are in MON.
+    pub entries: Vec<SplitEntry>,
+    /// Address receiving the LST tokens. Defaults to the signer.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub receiver: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UnstakeSplitRequest {
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Unstakes to make, one transaction each, in order, of the signer's own LST tokens.
+    pub entries: Vec<SplitEntry>,
+    /// Whether the amounts are in MON (`assets`) or LST tokens (`shares`). Defaults to
+    /// `shares`.
+    #[serde(default)]
+    pub amount_unit: AmountUnit,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ResumeJobRequest {
+    /// Id of the job, as returned by `stake_split` or `unstake_split`.
+    pub job_id: String,
+    /// Signer's private key, which must be the job's signer. Defaults to the session signer
+    /// set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QuoteRouteRequest {
+    /// Amount of MON to stake, as a decimal string with up to 18 decimals.
+    #[schemars(
+        with = "String",
+        regex(path = "DECIMAL_PATTERN"),
+        example = "example_amount"
+    )]
+    pub amount: AmountArg,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PreviewUnstakeRequest {
+    /// Liquid staking protocol to use. Defaults to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Amount to unstake, as a decimal string with up to 18 decimals, in `amount_unit`.
+    #[schemars(
+        with = "String",
+        regex(path = "DECIMAL_PATTERN"),
+        example = "example_amount"
+    )]
+    pub amount: AmountArg,
+    /// Whether `amount` is in MON (`assets`) or LST tokens (`shares`). Defaults to `shares`.
+    #[serde(default)]
+    pub amount_unit: AmountUnit,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListProtocolsRequest {
+    /// Network to list protocols for. Defaults to `monadTestnet`, the only one supported.
+    pub network: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct VerifySignatureRequest {
+    /// Address expected to have signed the message.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub address: String,
+    /// Message that was signed, as UTF-8 text.
+    pub message: String,
+    /// 65-byte EIP-191 `personal_sign` signature, hex encoded.
+    #[schemars(regex(path = "SIGNATURE_PATTERN"))]
+    pub signature: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClaimRedeemRequest {
+    /// Liquid staking protocol to use. Defaults to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Ids of the redeem requests to claim, as returned when unstaking. Several ids are
+    /// redeemed in one transaction.
+    #[schemars(length(min = 1), inner(regex(path = "INTEGER_PATTERN")))]
+    pub request_ids: Vec<String>,
+    /// Address receiving the MON. Defaults to the signer.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub receiver: Option<String>,
+    /// Gas limit multiplier for this write, as described on
+    /// [`GasMultiplier`](crate::common::tx::GasMultiplier).
+    #[schemars(range(min = 1.0))]
+    pub gas_limit_multiplier: Option<f64>,
+    /// Token to report this write's steps under, as described on
+    /// [`Progress`](crate::common::progress::Progress).
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub progress_token: Option<ProgressToken>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RedeemWhenReadyRequest {
+    /// Liquid staking protocol to use. Defaults to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Id of the redeem request to claim, as returned when unstaking.
+    #[schemars(regex(path = "INTEGER_PATTERN"))]
+    pub request_id: String,
+    /// Address receiving the MON. Defaults to the signer.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub receiver: Option<String>,
+    /// Longest to wait for the request to become claimable, in seconds, at most 3600.
+    /// Defaults to 300.
+    #[schemars(range(min = 1, max = 3600))]
+    pub max_wait_secs: Option<u64>,
+    /// Gas limit multiplier for this write, as described on
+    /// [`GasMultiplier`](crate::common::tx::GasMultiplier).
+    #[schemars(range(min = 1.0))]
+    pub gas_limit_multiplier: Option<f64>,
+    /// Token to report each check of the request and this write's steps under, as described
+    /// on [`Progress`](crate::common::progress::Progress).
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub progress_token: Option<ProgressToken>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CancelRedeemRequest {
+    /// Liquid staking protocol the request was made on. Defaults to the server's default
+    /// protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Id of the redeem request to cancel, as returned when unstaking.
+    #[schemars(regex(path = "INTEGER_PATTERN"))]
+    pub request_id: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClaimEtaRequest {
+    /// Liquid staking protocol to use. Defaults to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Id of the redeem request, as returned when unstaking.
+    #[schemars(regex(path = "INTEGER_PATTERN"))]
+    pub request_id: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EstimateAprAfterDepositRequest {
+    /// Liquid staking protocol to use. Defaults to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Amount of MON to deposit, as a decimal string with up to 18 decimals.
+    #[schemars(
+        with = "String",
+        regex(path = "DECIMAL_PATTERN"),
+        example = "example_amount"
+    )]
+    pub amount: AmountArg,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RawViewRequest {
+    /// Liquid staking protocol whose contract to call. Defaults to the protocol owning
+    /// `contract`, or else to the server's default protocol, if set.
+    pub protocol: Option<LstProtocol>,
+    /// Contract to call, which must be one of the protocol's registered contracts. Defaults to
+    /// the protocol's LST token.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub contract: Option<String>,
+    /// Human-readable signature of a view or pure function, with its outputs, e.g.
+    /// `function totalAssets() view returns (uint256)`.
+    #[schemars(example = "example_view_signature")]
+    pub signature: String,
+    /// Arguments in the order the signature declares them, each as a string: integers in
+    /// base 10, addresses and bytes as hex, arrays and tuples as `[a,b]` and `(a,b)`.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+fn example_view_signature() -> &'static str {
+    "function totalAssets() view returns (uint256)"
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SignMessageRequest {
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Message to sign, as UTF-8 text.
+    pub message: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SignTypedDataRequest {
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// EIP-712 domain: any of `name`, `version`, `chainId`, `verifyingContract` and `salt`.
+    pub domain: serde_json::Value,
+    /// Struct types by name, each a list of `{ "name", "type" }` fields. `EIP712Domain` may be
+    /// left out.
+    pub types: serde_json::Value,
+    /// Name of the type of `message`, which must be one of `types`.
+    pub primary_type: String,
+    /// Values of the primary type's fields.
+    pub message: serde_json::Value,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SpeedUpRequest {
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Hash of the pending transaction to replace. It must have been sent by the signer.
+    #[schemars(regex(path = "TX_HASH_PATTERN"))]
+    pub tx_hash: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TxStatusRequest {
+    /// Hash of the transaction to look up.
+    #[schemars(regex(path = "TX_HASH_PATTERN"))]
+    pub tx_hash: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CancelTxRequest {
+    /// Signer's private key. Defaults to the session signer set with `set_signer`.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: Option<String>,
+    /// Hash of the pending transaction to cancel. It must have been sent by the signer.
+    /// Exactly one of `tx_hash` and `nonce` must be given.
+    #[schemars(regex(path = "TX_HASH_PATTERN"))]
+    pub tx_hash: Option<String>,
+    /// Nonce of the signer's pending transaction to cancel, for when its hash is unknown.
+    pub nonce: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SetSignerRequest {
+    /// Private key to sign with for the rest of this connection.
+    #[schemars(regex(path = "PRIVATE_KEY_PATTERN"))]
+    pub private_key: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DiagnosticsRequest {
+    /// Address whose nonces to check. Defaults to the server's diagnostics address, if set.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub address: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ResetNonceRequest {
+    /// Address whose nonce to re-sync with the chain.
+    #[schemars(regex(path = "ADDRESS_PATTERN"), example = "example_address")]
+    pub address: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RefreshSnapshotsRequest {
+    /// Protocol to refresh. Defaults to every protocol.
+    pub protocol: Option<LstProtocol>,
+}
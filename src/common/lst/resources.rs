@@ -0,0 +1,845 @@
+//! The resources and resource templates the server lists, and reading them by URI.
+
+use ethers::{
+    types::{Address, U256},
+    utils::to_checksum,
+};
+use rmcp::{
+    Error as McpError,
+    model::{
+        AnnotateAble, RawResource, RawResourceTemplate, ReadResourceResult, Resource,
+        ResourceContents, ResourceTemplate,
+    },
+};
+
+use super::{
+    Lst, NETWORK,
+    protocol::{HistoryCursor, iso_timestamp},
+    rpc_calls_meta,
+};
+use crate::{
+    common::{error::McpServiceError, query::QueryParams},
+    services::constants::{BALANCES_MAX_ADDRESSES, HISTORY_LIMIT},
+};
+
+/// MIME type of every resource this server returns.
+const JSON_MIME_TYPE: &str = "application/json";
+
+impl Lst {
+    fn _create_resource_text(&self, uri: &str, name: &str) -> Resource {
+        RawResource {
+            mime_type: Some(JSON_MIME_TYPE.to_string()),
+            ..RawResource::new(uri, name.to_string())
+        }
+        .no_annotation()
+    }
+
+    pub(super) fn resources(&self) -> Vec<Resource> {
+        vec![
+            self._create_resource_text("evm://networks", "Get supported networks"),
+            self._create_resource_text("evm://health", "Get server health"),
+            self._create_resource_text(
+                "evm://version",
+                "Get the server version, git commit, MCP protocol version and networks",
+            ),
+            self._create_resource_text(
+                "evm://capabilities",
+                "Get which tools and resources need a signer",
+            ),
+        ]
+    }
+
+    pub(super) fn resource_templates() -> Vec<ResourceTemplate> {
+        vec![
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/chain".to_string(),
+                    name: "Latest block and suggested EIP-1559 fees".to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts".to_string(),
+                    name: "List of available LST protocols".to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/compare".to_string(),
+                    name: "Compare LST protocols by APR, TVL and fees".to_string(),
+                    description: Some(
+                        "Served from a periodic snapshot; add `?fresh=true` to read the chain"
+                            .to_string(),
+                    ),
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}".to_string(),
+                    name: "Details of a specific LST protocol".to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}/rate".to_string(),
+                    name: "MON value of one LST token".to_string(),
+                    description: Some(
+                        "Accepts an optional `?block={block}` query: a block number, `latest`, `pending` or `finalized`"
+                            .to_string(),
+                    ),
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}/actions".to_string(),
+                    name: "Tools supported by an LST protocol".to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}/preview-stake?amount={amount}"
+                        .to_string(),
+                    name: "Preview the LST shares received for staking an amount of MON"
+                        .to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}/balances?addresses={addresses}"
+                        .to_string(),
+                    name: "LST balances of several addresses".to_string(),
+                    description: Some(format!(
+                        "Takes up to {} comma-separated addresses and an optional `&block={{block}}` query; a failed address gets an `error` entry",
+                        BALANCES_MAX_ADDRESSES
+                    )),
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}/addresses".to_string(),
+                    name: "Labeled contract and governance addresses of an LST protocol"
+                        .to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}/abi".to_string(),
+                    name: "Functions, events and errors of an LST protocol's contracts, with their selectors"
+                        .to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}/oracle".to_string(),
+                    name: "Freshness of an LST protocol's oracle data".to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/lsts/{lst}/redeems/{request_id}".to_string(),
+                    name: "Details of a redeem request".to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/address/{address}/lsts/{lst}/balance"
+                        .to_string(),
+                    name: "Get balance of LST token for a given address".to_string(),
+                    description: Some(
+                        "Accepts an optional `?block={block}` query: a block number, `latest`, `pending` or `finalized`"
+                            .to_string(),
+                    ),
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/address/{address}/yield".to_string(),
+                    name: "Portfolio value and APR of an address across LST protocols, weighted by value"
+                        .to_string(),
+                    description: None,
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+            ResourceTemplate {
+                raw: RawResourceTemplate {
+                    uri_template: "evm://{network}/address/{address}/lsts/{lst}/history"
+                        .to_string(),
+                    name: "Deposit and redeem history of an address for an LST protocol"
+                        .to_string(),
+                    description: Some(
+                        "Accepts an optional `?from_block={block}` query".to_string(),
+                    ),
+                    mime_type: Some(JSON_MIME_TYPE.to_string()),
+                },
+                annotations: None,
+            },
+        ]
+    }
+
+    pub(super) async fn dispatch_resource(
+        &self,
+        uri: String,
+    ) -> Result<ReadResourceResult, McpError> {
+        // Exact match for networks resource
+        if uri == "evm://networks" {
+            return Ok(ReadResourceResult {
+                contents: vec![json_contents(
+                    serde_json::json!({
+                        "networks": [NETWORK],
+                        "native_currency": {
+                            NETWORK: {
+                                "symbol": self.native.symbol(),
+                                "decimals": self.native.decimals(),
+                            },
+                        },
+                    }),
+                    uri,
+                )],
+            });
+        }
+
+        if uri == "evm://capabilities" {
+            return Ok(ReadResourceResult {
+                contents: vec![json_contents(self.capabilities(), uri)],
+            });
+        }
+
+        if uri == "evm://health" {
+            return Ok(ReadResourceResult {
+                contents: vec![json_contents(self.health(), uri)],
+            });
+        }
+
+        if uri == "evm://version" {
+            return Ok(ReadResourceResult {
+                contents: vec![json_contents(self.version(), uri)],
+            });
+        }
+
+        // Split off and decode the query string, then parse the path into parts
+        let (path, query) = match uri.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (uri.as_str(), None),
+        };
+        let query = QueryParams::parse(query)?;
+        let parts: Vec<&str> = path.split('/').collect();
+        let fresh = query.flag("fresh");
+
+        // Check if the URI starts with evm://
+        if parts.len() >= 2 && parts[0] == "evm:" {
+            let network = parts[2];
+
+            // Validate network
+            if network != NETWORK {
+                return Err(McpServiceError::Unsupported(format!(
+                    "Unsupported network '{}'",
+                    network
+                ))
+                .into());
+            }
+
+            // Pattern: evm://{network}/chain
+            if parts.len() == 4 && parts[3] == "chain" {
+                let chain = self
+                    .chain_fees()
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get chain fees", e))?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(chain, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts
+            if parts.len() == 4 && parts[3] == "lsts" {
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(self.protocol_list(), uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/compare
+            if parts.len() == 5 && parts[3] == "lsts" && parts[4] == "compare" {
+                let comparison = self
+                    .compare_protocols(fresh)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to compare protocols", e))?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(comparison, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}
+            if parts.len() == 5 && parts[3] == "lsts" {
+                let lst_name = parts[4];
+                let protocol = self.protocol_segment(lst_name)?;
+
+                let info = serde_json::to_value(self.protocol_info(protocol, fresh).await)
+                    .map_err(|e| {
+                        McpServiceError::Internal(format!("Failed to render protocol info: {}", e))
+                    })?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(info, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/tvl[?block={block}]
+            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "tvl" {
+                let lst_name = parts[4];
+                let protocol = self.protocol_segment(lst_name)?;
+
+                let block = self.block_param(&query).await?;
+
+                let (tvl, as_of) = self
+                    .protocol_tvl(protocol, fresh, block)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get TVL", e))?;
+                let tvl = self.native.format(tvl);
+
+                let mut body = serde_json::json!({
+                    "protocol": protocol.to_string(),
+                    "tvl": tvl,
+                    "tvl_mon": tvl,
+                    "unit": self.native.symbol(),
+                    "tvl_usd": null,
+                    "usd_price": null,
+                    "as_of": iso_timestamp(as_of),
+                    "block": block.and_then(|b| b.number),
+                });
+                // Prices are only read at the latest block, so historical TVL stays in MON
+                if block.is_none() {
+                    self.add_usd_value(&mut body, &tvl).await;
+                }
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(body, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/rate[?block={block}]
+            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "rate" {
+                let protocol = self.protocol_segment(parts[4])?;
+
+                let block = self.block_param(&query).await?;
+
+                let (rate, as_of) = self
+                    .protocol_rate(protocol, fresh, block)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get exchange rate", e))?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(
+                        serde_json::json!({
+                            "protocol": protocol.to_string(),
+                            "rate": self.native.format_rate(rate),
+                            "unit": self.native.symbol(),
+                            "as_of": iso_timestamp(as_of),
+                            "block": block.and_then(|b| b.number),
+                        }),
+                        uri,
+                    )],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/actions
+            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "actions" {
+                let protocol = self.protocol_segment(parts[4])?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(self.supported_actions(protocol), uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/preview-stake?amount={amount}
+            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "preview-stake" {
+                let protocol = self.protocol_segment(parts[4])?;
+                let amount = query
+                    .get("amount")
+                    .ok_or_else(|| McpServiceError::invalid_input("Missing amount query"))?;
+                let assets = self.native.parse(amount)?;
+
+                let preview = self
+                    .preview_stake(protocol, assets)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to preview stake", e))?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(preview, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/balances?addresses={addresses}[&block={block}]
+            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "balances" {
+                let protocol = self.protocol_segment(parts[4])?;
+                let addresses = query
+                    .get("addresses")
+                    .ok_or_else(|| McpServiceError::invalid_input("Missing addresses query"))?;
+                let block = self.block_param(&query).await?;
+
+                let balances = self.balances(protocol, addresses, block).await?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(balances, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/addresses
+            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "addresses" {
+                let protocol = self.protocol_segment(parts[4])?;
+
+                let addresses = self
+                    .address_book(protocol)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get addresses", e))?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(addresses, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/abi
+            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "abi" {
+                let protocol = self.protocol_segment(parts[4])?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(self.abi_listing(protocol), uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/oracle
+            if parts.len() == 6 && parts[3] == "lsts" && parts[5] == "oracle" {
+                let protocol = self.protocol_segment(parts[4])?;
+
+                let oracle = self
+                    .oracle(protocol)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get oracle status", e))?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(oracle, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/lsts/{lst}/redeems/{request_id}
+            if parts.len() == 7 && parts[3] == "lsts" && parts[5] == "redeems" {
+                let protocol = self.protocol_segment(parts[4])?;
+                let request_id = U256::from_dec_str(parts[6]).map_err(|e| {
+                    McpServiceError::invalid_input(format!("Invalid request id: {}", e))
+                })?;
+
+                let request = self
+                    .redeem_request(protocol, request_id)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get redeem request", e))?
+                    .ok_or_else(|| {
+                        McpServiceError::NotFound(format!(
+                            "Redeem request {} not found",
+                            request_id
+                        ))
+                    })?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(request, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/address/{address}/lsts/{lst}/history[?from_block={block}&limit={n}&before={cursor}]
+            if parts.len() == 8
+                && parts[3] == "address"
+                && parts[5] == "lsts"
+                && parts[7] == "history"
+            {
+                let address = parts[4].parse::<Address>().map_err(|e| {
+                    McpServiceError::invalid_input(format!("Invalid address: {}", e))
+                })?;
+                let protocol = self.protocol_segment(parts[6])?;
+
+                let from_block = match query.get("from_block") {
+                    Some(block) => Some(block.parse::<u64>().map_err(|e| {
+                        McpServiceError::invalid_input(format!("Invalid from_block: {}", e))
+                    })?),
+                    None => None,
+                };
+                let before = match query.get("before") {
+                    Some(cursor) => Some(cursor.parse::<HistoryCursor>().map_err(|e| {
+                        McpServiceError::invalid_input(format!("Invalid before cursor: {:#}", e))
+                    })?),
+                    None => None,
+                };
+                let limit = match query.get("limit") {
+                    Some(limit) => limit.parse::<usize>().map_err(|e| {
+                        McpServiceError::invalid_input(format!("Invalid limit: {}", e))
+                    })?,
+                    None => HISTORY_LIMIT.min(self.history_max_limit),
+                };
+                if limit == 0 || limit > self.history_max_limit {
+                    return Err(McpServiceError::invalid_input(format!(
+                        "limit must be between 1 and {}",
+                        self.history_max_limit
+                    ))
+                    .into());
+                }
+
+                let history = self
+                    .address_history(protocol, address, from_block, before, limit)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get history", e))?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(history, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/address/{address}/yield
+            if parts.len() == 6 && parts[3] == "address" && parts[5] == "yield" {
+                let address = parts[4].parse::<Address>().map_err(|e| {
+                    McpServiceError::invalid_input(format!("Invalid address: {}", e))
+                })?;
+
+                let portfolio_yield = self.portfolio_yield(address).await.map_err(|e| {
+                    McpServiceError::from_anyhow("Failed to get portfolio yield", e)
+                })?;
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(portfolio_yield, uri)],
+                });
+            }
+
+            // Pattern: evm://{network}/address/{address}/lsts/{lst}/balance[?block={block}]
+            if parts.len() == 8
+                && parts[3] == "address"
+                && parts[5] == "lsts"
+                && parts[7] == "balance"
+            {
+                let address_str = parts[4];
+                let lst_name = parts[6];
+
+                // Parse the address
+                let address = address_str.parse::<Address>().map_err(|e| {
+                    McpServiceError::invalid_input(format!("Invalid address: {}", e))
+                })?;
+
+                let protocol = self.protocol_segment(lst_name)?;
+
+                let block = self.block_param(&query).await?;
+
+                // Get balance
+                let balance = self
+                    .read_balance(protocol, address, block)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get balance", e))?;
+
+                let decimals = protocol
+                    .decimals(self.provider.clone(), &self.decimals)
+                    .await
+                    .map_err(|e| McpServiceError::from_anyhow("Failed to get decimals", e))?;
+                let formatted_balance = decimals.format_shares(balance);
+
+                return Ok(ReadResourceResult {
+                    contents: vec![json_contents(
+                        serde_json::json!({
+                            "protocol": protocol.to_string(),
+                            "address": to_checksum(&address, None),
+                            "balance": formatted_balance,
+                            "block": block.and_then(|b| b.number),
+                        }),
+                        uri,
+                    )],
+                });
+            }
+        }
+
+        // No match found
+        tracing::warn!("No match found for URI: {}", uri);
+
+        Err(McpServiceError::NotFound(format!("No resource matches '{}'", uri)).into())
+    }
+}
+
+/// Renders `value` as the body of a resource. Responses are built as [`serde_json::Value`],
+/// whose objects keep their keys sorted, so the same data always renders to the same bytes;
+/// collections are arrays in a fixed order or objects keyed by name or address.
+fn json_contents(value: serde_json::Value, uri: impl Into<String>) -> ResourceContents {
+    ResourceContents::TextResourceContents {
+        uri: uri.into(),
+        mime_type: Some(JSON_MIME_TYPE.to_string()),
+        text: value.to_string(),
+    }
+}
+
+/// Adds `_meta.rpc_calls` to a JSON object resource body, leaving any other contents as is.
+pub(super) fn with_rpc_calls_meta(contents: ResourceContents, rpc_calls: u64) -> ResourceContents {
+    let ResourceContents::TextResourceContents {
+        uri,
+        mime_type,
+        text,
+    } = contents
+    else {
+        return contents;
+    };
+    let text = match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(serde_json::Value::Object(mut body)) => {
+            body.insert(
+                "_meta".to_string(),
+                serde_json::Value::Object(rpc_calls_meta(rpc_calls).0),
+            );
+            serde_json::Value::Object(body).to_string()
+        }
+        _ => text,
+    };
+
+    ResourceContents::TextResourceContents {
+        uri,
+        mime_type,
+        text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        decimal::DecimalU256,
+        lst::{
+            Decimals, LstProtocol, MintedShares, minted_json,
+            tests::{mon, offline_lst, stub_provider},
+        },
+    };
+
+    #[test]
+    fn json_responses_render_byte_stable_with_sorted_keys() {
+        let text = |contents: ResourceContents| match contents {
+            ResourceContents::TextResourceContents { text, .. } => text,
+            ResourceContents::BlobResourceContents { .. } => unreachable!(),
+        };
+
+        let minted = MintedShares {
+            deposit_event: Some(mon(2)),
+            transfer: None,
+        };
+        let decimals = Decimals {
+            shares: 18,
+            assets: 18,
+        };
+        let body = serde_json::json!({
+            "protocol": "aprMON",
+            "minted": minted_json(LstProtocol::AprMON, &minted, decimals),
+            "amount": DecimalU256(U256::MAX),
+            "block": null,
+        });
+        let rendered = text(with_rpc_calls_meta(json_contents(body, "evm://test"), 3));
+
+        assert_eq!(
+            rendered,
+            concat!(
+                r#"{"_meta":{"rpc_calls":3},"#,
+                r#""amount":"115792089237316195423570985008687907853269984665640564039457584007913129639935","#,
+                r#""block":null,"#,
+                r#""minted":{"lst_token_received":null,"shares_minted":"2.000000000000000000","token":"aprMON"},"#,
+                r#""protocol":"aprMON"}"#,
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn a_balance_read_is_served_as_json() {
+        let provider = stub_provider(|_, _| serde_json::json!(format!("0x{:064x}", mon(3)))).await;
+        let lst = Lst::new(provider);
+        let uri = "evm://monadTestnet/address/0x00000000000000000000000000000000000000aa/lsts/aprMON/balance";
+
+        let result = lst.dispatch_resource(uri.to_string()).await.unwrap();
+        let contents = with_rpc_calls_meta(result.contents[0].clone(), 1);
+        let ResourceContents::TextResourceContents {
+            mime_type, text, ..
+        } = contents
+        else {
+            panic!("{uri} is not text");
+        };
+        assert_eq!(mime_type.as_deref(), Some("application/json"));
+        let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(body["protocol"], "aprMON");
+    }
+
+    #[tokio::test]
+    async fn a_protocol_without_claims_reports_claiming_unavailable() {
+        let lst = offline_lst();
+        let actions = |protocol: &'static str| {
+            let uri = format!("evm://monadTestnet/lsts/{protocol}/actions");
+            let lst = lst.clone();
+            async move {
+                let result = lst.dispatch_resource(uri).await.unwrap();
+                let ResourceContents::TextResourceContents { text, .. } = &result.contents[0]
+                else {
+                    panic!("{protocol} actions are not text");
+                };
+                serde_json::from_str::<serde_json::Value>(text).unwrap()
+            }
+        };
+
+        let gmon = actions("gMON").await;
+        assert_eq!(gmon["withdrawal"], "instant");
+        assert_eq!(gmon["actions"]["claim_redeem"], false);
+        assert_eq!(gmon["actions"]["stake"], true);
+
+        let aprmon = actions("aprMON").await;
+        assert_eq!(aprmon["withdrawal"], "delayed");
+        assert_eq!(aprmon["actions"]["claim_redeem"], true);
+    }
+
+    #[tokio::test]
+    async fn preview_stake_quotes_the_shares_preview_deposit_returns() {
+        let preview_deposit =
+            ethers::utils::hex::encode_prefixed(&ethers::utils::id("previewDeposit(uint256)")[..4]);
+        // Deposits mint 90% of the MON staked in shares
+        let provider = stub_provider(move |_, params| {
+            let data = params[0]["data"]
+                .as_str()
+                .or(params[0]["input"].as_str())
+                .unwrap();
+            let value = if data[..10] == preview_deposit {
+                U256::from_str_radix(&data[10..], 16).unwrap() * 9 / 10
+            } else {
+                U256::from(18)
+            };
+            serde_json::json!(format!("0x{:064x}", value))
+        })
+        .await;
+        let lst = Lst::new(provider);
+        let preview = |amount: &'static str| {
+            let lst = lst.clone();
+            async move {
+                let uri = format!("evm://monadTestnet/lsts/aprMON/preview-stake?amount={amount}");
+                let result = lst.dispatch_resource(uri).await.unwrap();
+                let ResourceContents::TextResourceContents { text, .. } = &result.contents[0]
+                else {
+                    panic!("preview of {amount} is not text");
+                };
+                serde_json::from_str::<serde_json::Value>(text).unwrap()
+            }
+        };
+
+        let quote = preview("2").await;
+        assert_eq!(quote["expected_shares"], "1.800000000000000000");
+        assert_eq!(quote["implied_exchange_rate"], "1.111111111111111111");
+
+        // Amounts minting no shares imply no rate
+        for amount in ["0", "0.000000000000000001"] {
+            let quote = preview(amount).await;
+            assert_eq!(quote["expected_shares"], "0.000000000000000000", "{amount}");
+            assert_eq!(quote["implied_exchange_rate"], serde_json::Value::Null);
+        }
+    }
+
+    #[tokio::test]
+    async fn the_version_resource_reports_the_crate_version() {
+        let result = offline_lst()
+            .dispatch_resource("evm://version".to_string())
+            .await
+            .unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+            panic!("version is not text");
+        };
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert!(body["git_commit"].is_string(), "{body}");
+        assert_eq!(
+            body["mcp_protocol_version"],
+            serde_json::to_value(rmcp::model::ProtocolVersion::default()).unwrap()
+        );
+        assert_eq!(body["networks"], serde_json::json!([NETWORK]));
+    }
+
+    #[tokio::test]
+    async fn the_abi_resource_lists_the_deposit_selector() {
+        let uri = format!("evm://{}/lsts/aprMON/abi", NETWORK);
+        let result = offline_lst().dispatch_resource(uri).await.unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+            panic!("abi listing is not text");
+        };
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+
+        assert_eq!(body["protocol"], "aprMON");
+        let token = &body["contracts"][0];
+        assert_eq!(token["role"], "token");
+        let deposit = token["functions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|function| function["signature"] == "deposit(uint256,address)")
+            .unwrap_or_else(|| panic!("deposit is not listed: {body}"));
+        assert_eq!(deposit["name"], "deposit");
+        assert_eq!(deposit["selector"], "0x6e553f65");
+    }
+
+    #[tokio::test]
+    async fn the_chain_resource_reports_fees_from_the_fee_history() {
+        let gwei = |value: u64| U256::from(value) * U256::exp10(9);
+        let provider = stub_provider(move |method, _| match method {
+            "eth_getBlockByNumber" => serde_json::json!({
+                "number": "0x64",
+                "timestamp": "0x0",
+                "baseFeePerGas": format!("{:#x}", gwei(150)),
+            }),
+            "eth_feeHistory" => serde_json::json!({
+                "oldestBlock": "0x5b",
+                "baseFeePerGas": vec![format!("{:#x}", gwei(150)); 11],
+                "gasUsedRatio": vec![0.5; 10],
+                "reward": vec![[format!("{:#x}", gwei(7))]; 10],
+            }),
+            _ => serde_json::Value::Null,
+        })
+        .await;
+        let uri = format!("evm://{}/chain", NETWORK);
+        let result = Lst::new(provider).dispatch_resource(uri).await.unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+            panic!("chain is not text");
+        };
+        let body: serde_json::Value = serde_json::from_str(text).unwrap();
+
+        assert_eq!(body["block_number"], 100, "{body}");
+        assert_eq!(body["base_fee_per_gas"], gwei(150).to_string());
+        // Above 100 gwei the tip is the rewards paid in the history, and the max fee covers the
+        // base fee surging by 40%
+        assert_eq!(body["max_priority_fee_per_gas"], gwei(7).to_string());
+        assert_eq!(body["max_fee_per_gas"], gwei(210).to_string());
+        assert_eq!(body["unit"], "wei");
+    }
+}
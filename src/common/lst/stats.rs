@@ -0,0 +1,379 @@
+//! Yield, fee and exchange-rate math over protocol reads, and how protocols are ranked.
+
+use std::collections::BTreeMap;
+
+use ethers::types::{U256, U512};
+
+use super::{
+    protocol::LstProtocol,
+    units::{AmountOverflow, Decimals, mul_div},
+};
+use crate::services::constants::{MIN_RECOMMENDED_TVL, SECONDS_PER_YEAR};
+
+/// Annualises the growth from `rate_then` to `rate_now` over `elapsed` seconds, in percent.
+pub fn annualize(rate_then: U256, rate_now: U256, elapsed: u64) -> f64 {
+    if rate_then.is_zero() || elapsed == 0 {
+        return 0.0;
+    }
+
+    let growth = (u256_f64(rate_now) - u256_f64(rate_then)) / u256_f64(rate_then);
+    growth * SECONDS_PER_YEAR / elapsed as f64 * 100.0
+}
+
+/// Everything about one protocol, as served by `evm://{network}/lsts/{lst}`. Fields read from
+/// the chain are `None` while the protocol is unavailable, so the shape stays the same.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProtocolInfo {
+    pub protocol: String,
+    pub description: &'static str,
+    pub available: bool,
+    pub unavailable_reason: Option<String>,
+    pub deprecated: bool,
+    pub deprecation_notice: Option<String>,
+    /// Deposit contract.
+    pub address: String,
+    /// LST token, the same as `address` for vaults.
+    pub token_address: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    /// Decimals of the LST token.
+    pub decimals: Option<u8>,
+    /// ERC-20 token deposits are made in, or the native currency's symbol.
+    pub asset: Option<String>,
+    /// Assets the protocol holds, in `deposit_unit`.
+    pub tvl: Option<String>,
+    /// Value of one LST token in `deposit_unit`.
+    pub rate: Option<String>,
+    /// Annualised yield in percent.
+    pub apr: Option<f64>,
+    /// `apr` net of the withdrawal fee and wait, see [`effective_net_apr`].
+    pub effective_net_apr: Option<f64>,
+    pub fees: Option<ProtocolFees>,
+    /// Seconds a withdrawal waits before it can be claimed, zero when redeemed instantly.
+    pub withdrawal_wait_seconds: Option<u64>,
+    pub paused: Option<bool>,
+    pub min_deposit: Option<String>,
+    pub max_deposit: Option<String>,
+    /// Symbol of what deposits are made in, and `tvl`, `min_deposit` and `max_deposit` counted
+    /// in: the native currency, or the asset for ERC-20 deposits.
+    pub deposit_unit: Option<String>,
+    /// When `tvl`, `rate`, `apr` and `fees` were read, from the snapshot.
+    pub as_of: Option<String>,
+    /// Reads that failed, keyed by what was read, with the `error` and `kind` of each. Their
+    /// fields are `None`.
+    pub errors: BTreeMap<&'static str, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct ProtocolFees {
+    /// Fee taken on withdrawal, in percent.
+    pub withdrawal_fee: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProtocolStats {
+    pub protocol: LstProtocol,
+    pub tvl: U256,
+    /// Annualised yield in percent.
+    pub apr: f64,
+    /// Withdrawal fee in percent.
+    pub withdrawal_fee: f64,
+}
+
+impl ProtocolStats {
+    /// Net yield after paying the withdrawal fee once, in percent. Used to rank protocols.
+    pub fn score(&self) -> f64 {
+        self.apr - self.withdrawal_fee
+    }
+}
+
+/// Yield in percent a year of staking at `apr` nets once the exit is paid for: the
+/// `withdrawal_fee`, in percent, is taken once on the way out, and nothing is earned during the
+/// `wait_seconds` before the withdrawal can be claimed, so the year's net yield is spread over
+/// the year plus the wait. Assumes the APR holds for the whole year.
+pub fn effective_net_apr(apr: f64, withdrawal_fee: f64, wait_seconds: u64) -> f64 {
+    (apr - withdrawal_fee) * SECONDS_PER_YEAR / (SECONDS_PER_YEAR + wait_seconds as f64)
+}
+
+/// MON value of one gMON, scaled by 1e18, from the manager's TVL and the token supply.
+pub(super) fn gmon_exchange_rate(
+    tvl: U256,
+    supply: U256,
+    decimals: Decimals,
+) -> Result<U256, AmountOverflow> {
+    if supply.is_zero() {
+        Ok(U256::exp10(18))
+    } else {
+        decimals.rate(mul_div(tvl, decimals.one_share(), supply)?)
+    }
+}
+
+/// MON paid out for `shares` gMON out of a `supply` backed by `tvl` MON, rounded down; 1:1
+/// before anything is minted.
+pub(super) fn gmon_assets_for(shares: U256, tvl: U256, supply: U256) -> U256 {
+    if supply.is_zero() {
+        shares
+    } else {
+        U256::try_from(shares.full_mul(tvl) / U512::from(supply)).unwrap_or(U256::MAX)
+    }
+}
+
+/// Average of the APRs in `positions`, weighted by the value held at each; `None` when
+/// nothing is held.
+pub(super) fn weighted_apr(positions: &[(U256, f64)]) -> Option<f64> {
+    let total: f64 = positions.iter().map(|(value, _)| u256_f64(*value)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    Some(
+        positions
+            .iter()
+            .map(|(value, apr)| u256_f64(*value) * apr)
+            .sum::<f64>()
+            / total,
+    )
+}
+
+/// APR after `deposit` joins `tvl`, assuming the rewards paid per year stay the same and are
+/// shared across the larger pool.
+pub(super) fn diluted_apr(apr: f64, tvl: U256, deposit: U256) -> f64 {
+    let after = u256_f64(tvl.saturating_add(deposit));
+    if after <= 0.0 {
+        return apr;
+    }
+
+    apr * u256_f64(tvl) / after
+}
+
+/// Converts an amount in base units to an `f64`, for ratios and weights between amounts of the
+/// same unit, whatever its decimals.
+fn u256_f64(value: U256) -> f64 {
+    value
+        .0
+        .iter()
+        .rev()
+        .fold(0.0, |total, word| total * 2f64.powi(64) + *word as f64)
+}
+
+/// Converts a fee in basis points to percent.
+pub(super) fn fee_percent(fee_bps: U256, max_bps: U256) -> f64 {
+    if max_bps.is_zero() {
+        return 0.0;
+    }
+    u256_f64(fee_bps) * 100.0 / u256_f64(max_bps)
+}
+
+/// Ranks protocols by score (APR minus withdrawal fee), breaking ties by higher TVL and then
+/// by name so the order is deterministic. The recommended protocol is the highest ranked one
+/// with at least `MIN_RECOMMENDED_TVL` MON locked.
+pub fn rank_protocols(mut stats: Vec<ProtocolStats>) -> (Vec<ProtocolStats>, Option<LstProtocol>) {
+    stats.sort_by(|a, b| {
+        b.score()
+            .total_cmp(&a.score())
+            .then_with(|| b.tvl.cmp(&a.tvl))
+            .then_with(|| a.protocol.to_string().cmp(&b.protocol.to_string()))
+    });
+
+    let min_tvl = U256::from(MIN_RECOMMENDED_TVL) * U256::exp10(18);
+    let recommended = stats.iter().find(|s| s.tvl >= min_tvl).map(|s| s.protocol);
+
+    (stats, recommended)
+}
+
+/// Splits `gross` into the withdrawal fee charged and the net amount paid out,
+/// where `fee_bps` is expressed over `max_bps`.
+pub fn deduct_withdrawal_fee(gross: U256, fee_bps: U256, max_bps: U256) -> (U256, U256) {
+    if max_bps.is_zero() {
+        return (U256::zero(), gross);
+    }
+
+    // The product is taken in 512 bits, so a large amount can't overflow it; a fee over the
+    // maximum caps at the whole amount
+    let fee = U256::try_from(gross.full_mul(fee_bps) / U512::from(max_bps))
+        .map_or(gross, |fee| fee.min(gross));
+    (fee, gross - fee)
+}
+
+#[derive(Debug)]
+pub struct UnstakePreview {
+    pub shares: U256,
+    pub gross_assets: U256,
+    pub withdrawal_fee: U256,
+    pub net_assets: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::lst::tests::mon;
+
+    #[test]
+    fn protocols_rank_by_score_then_tvl_then_name() {
+        let stats = |protocol, apr, withdrawal_fee, tvl| ProtocolStats {
+            protocol,
+            tvl: mon(tvl),
+            apr,
+            withdrawal_fee,
+        };
+        let order =
+            |ranked: &[ProtocolStats]| ranked.iter().map(|s| s.protocol).collect::<Vec<_>>();
+
+        // aprMON's higher APR is outweighed by its fee; gMON and shMON tie on score, so the
+        // larger TVL goes first
+        let (ranked, recommended) = rank_protocols(vec![
+            stats(LstProtocol::AprMON, 6.0, 2.0, 50_000),
+            stats(LstProtocol::SHMON, 5.0, 0.0, 20_000),
+            stats(LstProtocol::GMON, 5.0, 0.0, 30_000),
+        ]);
+        assert_eq!(
+            order(&ranked),
+            [LstProtocol::GMON, LstProtocol::SHMON, LstProtocol::AprMON]
+        );
+        assert_eq!(recommended, Some(LstProtocol::GMON));
+
+        // Equal scores and TVL fall back to the name, and the best protocol is only
+        // recommended with enough TVL
+        let (ranked, recommended) = rank_protocols(vec![
+            stats(LstProtocol::SHMON, 5.0, 0.0, MIN_RECOMMENDED_TVL - 1),
+            stats(LstProtocol::GMON, 5.0, 0.0, MIN_RECOMMENDED_TVL - 1),
+            stats(LstProtocol::AprMON, 4.0, 0.0, MIN_RECOMMENDED_TVL),
+        ]);
+        assert_eq!(
+            order(&ranked),
+            [LstProtocol::GMON, LstProtocol::SHMON, LstProtocol::AprMON]
+        );
+        assert_eq!(recommended, Some(LstProtocol::AprMON));
+
+        let (_, recommended) = rank_protocols(vec![stats(
+            LstProtocol::GMON,
+            5.0,
+            0.0,
+            MIN_RECOMMENDED_TVL - 1,
+        )]);
+        assert_eq!(recommended, None);
+    }
+
+    #[test]
+    fn effective_net_apr_takes_the_fee_once_and_spreads_the_yield_over_the_wait() {
+        let close = |actual: f64, expected: f64| {
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}")
+        };
+        // Nothing to pay on the way out
+        close(effective_net_apr(5.0, 0.0, 0), 5.0);
+        // The fee is taken once, not per period
+        close(effective_net_apr(5.0, 0.5, 0), 4.5);
+        // A year-long wait halves what a year earns
+        close(effective_net_apr(5.0, 1.0, SECONDS_PER_YEAR as u64), 2.0);
+        // A week's wait on 10% with a 0.1% fee
+        let week = 7 * 24 * 60 * 60;
+        close(
+            effective_net_apr(10.0, 0.1, week),
+            9.9 * SECONDS_PER_YEAR / (SECONDS_PER_YEAR + week as f64),
+        );
+        // A fee larger than the yield nets a loss
+        assert!(effective_net_apr(0.5, 1.0, 0) < 0.0);
+    }
+
+    #[test]
+    fn diluted_apr_shares_rewards_across_the_larger_pool() {
+        assert_eq!(diluted_apr(10.0, mon(900), mon(100)), 9.0);
+        assert_eq!(diluted_apr(10.0, U256::zero(), U256::zero()), 10.0);
+        assert_eq!(diluted_apr(10.0, U256::zero(), mon(1)), 0.0);
+        // Amounts beyond u128 don't saturate the ratio
+        let huge = U256::MAX / 4;
+        assert!((diluted_apr(10.0, huge, huge) - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn weighted_apr_weights_each_protocol_by_value_held() {
+        assert_eq!(weighted_apr(&[]), None);
+        assert_eq!(weighted_apr(&[(U256::zero(), 5.0)]), None);
+        assert_eq!(weighted_apr(&[(mon(1), 5.0)]), Some(5.0));
+        assert_eq!(weighted_apr(&[(mon(3), 4.0), (mon(1), 8.0)]), Some(5.0));
+        // Dust below a gwei still counts
+        assert_eq!(
+            weighted_apr(&[(U256::from(1), 2.0), (U256::from(3), 6.0)]),
+            Some(5.0)
+        );
+    }
+
+    #[test]
+    fn gmon_withdrawals_are_priced_at_the_managers_backing() {
+        // 110 MON backing 100 gMON
+        assert_eq!(gmon_assets_for(mon(10), mon(110), mon(100)), mon(11));
+        assert_eq!(
+            gmon_assets_for(U256::from(2), U256::from(3), U256::from(4)),
+            U256::from(1)
+        );
+        assert_eq!(gmon_assets_for(mon(1), U256::zero(), U256::zero()), mon(1));
+        // Withdrawing a whole balance never pays out more than it is worth
+        let (tvl, supply) = (mon(2), mon(3));
+        let balance = mon(1) + 1;
+        let assets = gmon_assets_for(balance, tvl, supply);
+        assert!(assets * supply <= balance * tvl);
+        assert!((assets + 1) * supply > balance * tvl);
+        assert_eq!(
+            gmon_assets_for(U256::MAX, U256::MAX, U256::one()),
+            U256::MAX
+        );
+    }
+
+    #[test]
+    fn withdrawal_fee_is_deducted_in_basis_points() {
+        // 0.5% of 200 MON
+        assert_eq!(
+            deduct_withdrawal_fee(mon(200), U256::from(50), U256::from(10_000)),
+            (mon(1), mon(199))
+        );
+        assert_eq!(
+            deduct_withdrawal_fee(mon(1), U256::zero(), U256::from(10_000)),
+            (U256::zero(), mon(1))
+        );
+        // A fee above the maximum takes everything, and no maximum means no fee
+        assert_eq!(
+            deduct_withdrawal_fee(mon(1), U256::from(20_000), U256::from(10_000)),
+            (mon(1), U256::zero())
+        );
+        assert_eq!(
+            deduct_withdrawal_fee(mon(1), U256::from(50), U256::zero()),
+            (U256::zero(), mon(1))
+        );
+        // Amounts whose product with the fee passes 256 bits still split exactly
+        assert_eq!(
+            deduct_withdrawal_fee(U256::MAX, U256::from(10_000), U256::from(10_000)),
+            (U256::MAX, U256::zero())
+        );
+        let gross = U256::MAX - U256::MAX % 10_000;
+        assert_eq!(
+            deduct_withdrawal_fee(gross, U256::from(50), U256::from(10_000)),
+            (gross / 200, gross - gross / 200)
+        );
+        assert_eq!(
+            deduct_withdrawal_fee(U256::MAX, U256::MAX, U256::one()),
+            (U256::MAX, U256::zero())
+        );
+    }
+
+    #[test]
+    fn rates_past_u128_annualize_without_panicking() {
+        let year = SECONDS_PER_YEAR as u64;
+        assert_eq!(annualize(U256::zero(), mon(1), year), 0.0);
+        assert_eq!(annualize(mon(1), mon(2), 0), 0.0);
+        assert!((annualize(mon(100), mon(105), year) - 5.0).abs() < 1e-9);
+        // Half a year of 2.5% growth is 5% a year
+        assert!((annualize(mon(40), mon(41), year / 2) - 5.0).abs() < 1e-9);
+
+        let huge = U256::from(u128::MAX) * 1_000;
+        assert!((annualize(huge, huge * 11 / 10, year) - 10.0).abs() < 1e-6);
+        assert!((annualize(huge, huge * 9 / 10, year) + 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fees_convert_to_percent_whatever_their_size() {
+        assert_eq!(fee_percent(U256::from(50), U256::from(10_000)), 0.5);
+        assert_eq!(fee_percent(U256::from(50), U256::zero()), 0.0);
+        assert_eq!(fee_percent(U256::MAX, U256::MAX), 100.0);
+        assert_eq!(fee_percent(U256::MAX / 4, U256::MAX / 2), 50.0);
+    }
+}
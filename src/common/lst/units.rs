@@ -0,0 +1,503 @@
+//! Units amounts are counted in: the native currency, the decimals of LST tokens and their
+//! assets, and parsing amount arguments into base units.
+
+use core::fmt;
+use std::{
+    collections::HashMap,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use anyhow::bail;
+use ethers::{
+    types::{U256, U512},
+    utils::{ConversionError, Units, format_units, parse_units},
+};
+use rmcp::schemars;
+
+use super::protocol::LstProtocol;
+use crate::{common::error::McpServiceError, services::constants::METADATA_TTL};
+
+/// Decimals of native MON, the unit transaction values are counted in (wei).
+pub(super) const NATIVE_DECIMALS: u8 = 18;
+
+/// Decimals exchange rates are scaled to, whatever the decimals of the tokens they convert.
+const RATE_DECIMALS: u8 = 18;
+
+/// Most decimals `format_units` and `parse_units` accept.
+const MAX_NATIVE_DECIMALS: u8 = 77;
+
+/// Symbol and decimals of the network's native currency. The symbol labels native amounts in
+/// responses, and native amounts are parsed and formatted at the decimals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeCurrency {
+    symbol: String,
+    decimals: u8,
+    /// Decimals amounts are rounded to in messages; `None` shows every digit.
+    display_decimals: Option<u8>,
+}
+
+impl NativeCurrency {
+    pub fn new(symbol: impl Into<String>, decimals: u8) -> anyhow::Result<Self> {
+        let symbol = symbol.into();
+        if symbol.trim().is_empty() {
+            bail!("Native currency symbol must not be empty");
+        }
+        if decimals > MAX_NATIVE_DECIMALS {
+            bail!(
+                "Native currency decimals must be at most {}, got {}",
+                MAX_NATIVE_DECIMALS,
+                decimals
+            );
+        }
+
+        Ok(NativeCurrency {
+            symbol,
+            decimals,
+            display_decimals: None,
+        })
+    }
+
+    /// Rounds amounts in human-readable messages, such as tool result text and warnings, to
+    /// `decimals` decimals. JSON amount fields always carry every digit.
+    pub fn with_display_decimals(mut self, decimals: u8) -> Self {
+        self.display_decimals = Some(decimals);
+        self
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    pub fn display_decimals(&self) -> Option<u8> {
+        self.display_decimals
+    }
+
+    /// Formats a native amount in its smallest unit as a decimal string.
+    pub fn format(&self, amount: U256) -> String {
+        format_units(amount, u32::from(self.decimals)).unwrap_or_else(|_| amount.to_string())
+    }
+
+    /// Formats an exchange rate from [`Decimals::rate`] as a decimal string.
+    pub fn format_rate(&self, rate: U256) -> String {
+        format_units(rate, u32::from(RATE_DECIMALS)).unwrap_or_else(|_| rate.to_string())
+    }
+
+    /// Formats a native amount for messages, rounded to the display decimals.
+    pub fn display(&self, amount: U256) -> String {
+        self.round(self.format(amount))
+    }
+
+    /// Rounds a formatted decimal amount, native or not, half up to the display decimals.
+    pub fn round(&self, formatted: String) -> String {
+        match self.display_decimals {
+            Some(decimals) => round_half_up(&formatted, usize::from(decimals)),
+            None => formatted,
+        }
+    }
+
+    /// Parses a decimal string into a native amount in its smallest unit.
+    pub fn parse(&self, amount: &str) -> Result<U256, McpServiceError> {
+        parse_units(amount, u32::from(self.decimals))
+            .map(Into::into)
+            .map_err(|e| {
+                McpServiceError::invalid_input(format!(
+                    "Failed to parse amount '{}': {}",
+                    amount, e
+                ))
+            })
+    }
+}
+
+impl Default for NativeCurrency {
+    fn default() -> Self {
+        NativeCurrency {
+            symbol: "MON".to_string(),
+            decimals: NATIVE_DECIMALS,
+            display_decimals: None,
+        }
+    }
+}
+
+/// Protocol decimals read by [`LstProtocol::decimals`], each kept until `ttl` after it was
+/// read. Protocols staking the native currency take its decimals from here.
+#[derive(Debug)]
+pub struct DecimalsCache {
+    entries: RwLock<HashMap<LstProtocol, (Decimals, Instant)>>,
+    ttl: Duration,
+    native: u8,
+}
+
+impl Default for DecimalsCache {
+    fn default() -> Self {
+        DecimalsCache::new(METADATA_TTL)
+    }
+}
+
+impl DecimalsCache {
+    pub fn new(ttl: Duration) -> Self {
+        DecimalsCache {
+            entries: RwLock::default(),
+            ttl,
+            native: NATIVE_DECIMALS,
+        }
+    }
+
+    /// Sets the decimals of the native currency, which defaults to [`NATIVE_DECIMALS`].
+    pub fn with_native_decimals(mut self, decimals: u8) -> Self {
+        self.native = decimals;
+        self
+    }
+
+    pub(super) fn native_decimals(&self) -> u8 {
+        self.native
+    }
+
+    pub(super) fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub(super) fn get(&self, protocol: LstProtocol) -> Option<Decimals> {
+        let entries = self.entries.read().unwrap();
+        let (decimals, read_at) = entries.get(&protocol)?;
+        (read_at.elapsed() < self.ttl).then_some(*decimals)
+    }
+
+    pub(super) fn store(&self, protocol: LstProtocol, decimals: Decimals) {
+        let mut entries = self.entries.write().unwrap();
+        entries.insert(protocol, (decimals, Instant::now()));
+    }
+
+    /// Drops every entry so the next read goes to the chain. Returns how many were dropped.
+    pub(super) fn clear(&self) -> usize {
+        let mut entries = self.entries.write().unwrap();
+        let count = entries.len();
+        entries.clear();
+        count
+    }
+}
+
+/// Decimals of an LST token (`shares`) and of the asset it is redeemable for (`assets`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decimals {
+    pub shares: u8,
+    pub assets: u8,
+}
+
+impl Decimals {
+    /// One whole LST token in its smallest unit.
+    pub fn one_share(&self) -> U256 {
+        U256::exp10(self.shares.into())
+    }
+
+    /// Exchange rate scaled by 1e18, from the assets (in the asset's smallest unit) that one
+    /// whole share is worth.
+    pub fn rate(&self, assets_per_share: U256) -> Result<U256, AmountOverflow> {
+        rescale(assets_per_share, self.assets, RATE_DECIMALS)
+    }
+
+    /// Shares, in the token's smallest unit, worth `assets` at an exchange rate from
+    /// [`Decimals::rate`].
+    pub fn shares_for(&self, assets: U256, rate: U256) -> Result<U256, AmountOverflow> {
+        mul_div(
+            rescale(assets, self.assets, RATE_DECIMALS)?,
+            self.one_share(),
+            rate,
+        )
+    }
+
+    /// Value of `shares`, in the asset's smallest unit, at an exchange rate from
+    /// [`Decimals::rate`].
+    pub fn value_of(&self, shares: U256, rate: U256) -> U256 {
+        let value = shares.saturating_mul(rate) / self.one_share();
+        rescale(value, RATE_DECIMALS, self.assets).unwrap_or(U256::MAX)
+    }
+
+    /// Decimals of an amount given in `unit`.
+    pub fn of(&self, unit: AmountUnit) -> u8 {
+        match unit {
+            AmountUnit::Assets => self.assets,
+            AmountUnit::Shares => self.shares,
+        }
+    }
+
+    /// Formats an LST amount, in the token's smallest unit, as a decimal string.
+    pub fn format_shares(&self, shares: U256) -> String {
+        format_units(shares, u32::from(self.shares)).unwrap_or_else(|_| shares.to_string())
+    }
+
+    /// Formats an amount of the asset, in its smallest unit, as a decimal string.
+    pub fn format_assets(&self, assets: U256) -> String {
+        format_units(assets, u32::from(self.assets)).unwrap_or_else(|_| assets.to_string())
+    }
+}
+
+/// Rescales `value` from `from` to `to` decimals, rounding down.
+fn rescale(value: U256, from: u8, to: u8) -> Result<U256, AmountOverflow> {
+    let exp10 = |decimals: u8| U256::from(10).checked_pow(decimals.into());
+    if from < to {
+        exp10(to - from)
+            .and_then(|scale| value.checked_mul(scale))
+            .ok_or(AmountOverflow { amount: value })
+    } else {
+        // Past 10^77 the divisor outgrows any U256, leaving nothing
+        Ok(exp10(from - to).map_or(U256::zero(), |scale| value / scale))
+    }
+}
+
+/// `value * numerator / denominator`, rounding down. The product is taken in 512 bits, so this
+/// only fails when the result itself does not fit.
+pub(super) fn mul_div(
+    value: U256,
+    numerator: U256,
+    denominator: U256,
+) -> Result<U256, AmountOverflow> {
+    U256::try_from(value.full_mul(numerator) / U512::from(denominator))
+        .map_err(|_| AmountOverflow { amount: value })
+}
+
+/// An amount too large to convert between units without overflowing 256 bits. Only absurd
+/// amounts get here, so it is the caller's input at fault.
+#[derive(Debug)]
+pub struct AmountOverflow {
+    pub amount: U256,
+}
+
+impl fmt::Display for AmountOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Amount {} is too large to convert without overflowing",
+            self.amount
+        )
+    }
+}
+
+impl std::error::Error for AmountOverflow {}
+
+/// Unit an unstake amount is given in: MON (`assets`) or LST tokens (`shares`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountUnit {
+    Assets,
+    #[default]
+    Shares,
+}
+
+impl fmt::Display for AmountUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountUnit::Assets => write!(f, "assets"),
+            AmountUnit::Shares => write!(f, "shares"),
+        }
+    }
+}
+
+/// Parses the amount of a write, given either as a decimal `amount` scaled by `units` or as
+/// raw `amount_wei`, which is taken as is without decimal scaling. Zero is rejected with the
+/// `AMOUNT_ZERO` kind, as a write of nothing would only waste gas or revert.
+pub(super) fn parse_amount<K>(
+    amount: Option<String>,
+    amount_wei: Option<String>,
+    units: K,
+) -> Result<U256, McpServiceError>
+where
+    K: TryInto<Units, Error = ConversionError> + Copy,
+{
+    let parsed = match (amount, amount_wei) {
+        (Some(amount), None) => parse_units(&amount, units).map(Into::into).map_err(|e| {
+            McpServiceError::invalid_input(format!("Failed to parse amount '{}': {}", amount, e))
+        }),
+        (None, Some(amount_wei)) => U256::from_dec_str(&amount_wei).map_err(|e| {
+            McpServiceError::invalid_input(format!(
+                "Failed to parse amount_wei '{}': {}",
+                amount_wei, e
+            ))
+        }),
+        (Some(_), Some(_)) => Err(McpServiceError::invalid_input(
+            "amount and amount_wei are mutually exclusive",
+        )),
+        (None, None) => Err(McpServiceError::invalid_input(
+            "One of amount and amount_wei is required",
+        )),
+    }?;
+    if parsed.is_zero() {
+        return Err(McpServiceError::AmountZero(
+            "Amount must be greater than zero".to_string(),
+        ));
+    }
+
+    Ok(parsed)
+}
+
+/// Rounds a decimal string half up to `decimals` decimals, working on the digits so the result
+/// is exact. Shorter fractions are left as they are.
+fn round_half_up(value: &str, decimals: usize) -> String {
+    let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+    if frac.len() <= decimals {
+        return value.to_string();
+    }
+
+    let mut digits = format!("{}{}", int, &frac[..decimals]).into_bytes();
+    if frac.as_bytes()[decimals] >= b'5' {
+        let carried = digits.iter_mut().rev().all(|digit| {
+            if *digit == b'9' {
+                *digit = b'0';
+                true
+            } else {
+                *digit += 1;
+                false
+            }
+        });
+        if carried {
+            digits.insert(0, b'1');
+        }
+    }
+
+    let digits = String::from_utf8(digits).expect("decimal digits are ASCII");
+    let (int, frac) = digits.split_at(digits.len() - decimals);
+    if frac.is_empty() {
+        int.to_string()
+    } else {
+        format!("{}.{}", int, frac)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::lst::tests::mon;
+
+    #[test]
+    fn amounts_parse_at_their_decimals_or_as_raw_wei() {
+        assert_eq!(
+            parse_amount(Some("1.5".to_string()), None, 18).unwrap(),
+            mon(3) / 2
+        );
+        assert_eq!(
+            parse_amount(Some("1.5".to_string()), None, 6).unwrap(),
+            U256::from(1_500_000)
+        );
+        assert_eq!(
+            parse_amount(None, Some("15".to_string()), 18).unwrap(),
+            U256::from(15)
+        );
+
+        let kind = |amount: Option<&str>, amount_wei: Option<&str>| {
+            parse_amount(amount.map(String::from), amount_wei.map(String::from), 18)
+                .unwrap_err()
+                .kind()
+        };
+        assert_eq!(kind(Some("0"), None), "AMOUNT_ZERO");
+        assert_eq!(kind(Some("0.000"), None), "AMOUNT_ZERO");
+        assert_eq!(kind(None, Some("0")), "AMOUNT_ZERO");
+        assert_eq!(kind(Some("1"), Some("1")), "INVALID_INPUT");
+        assert_eq!(kind(None, None), "INVALID_INPUT");
+        assert_eq!(kind(Some("one"), None), "INVALID_INPUT");
+        assert_eq!(kind(None, Some("1.5")), "INVALID_INPUT");
+    }
+
+    #[test]
+    fn native_amounts_are_parsed_and_formatted_at_the_configured_decimals() {
+        let native = NativeCurrency::new("TEST", 6).unwrap();
+        assert_eq!(native.symbol(), "TEST");
+        assert_eq!(native.decimals(), 6);
+        assert_eq!(native.parse("1.5").unwrap(), U256::from(1_500_000));
+        assert_eq!(native.parse("0.000001").unwrap(), U256::one());
+        assert_eq!(native.format(U256::from(1_500_000)), "1.500000");
+        // Rates keep their 18 decimals whatever the currency's
+        assert_eq!(native.format_rate(mon(3) / 2), "1.500000000000000000");
+
+        let decimals = DecimalsCache::default().with_native_decimals(6);
+        assert_eq!(decimals.native_decimals(), 6);
+        let gmon = Decimals {
+            shares: 18,
+            assets: decimals.native_decimals(),
+        };
+        // 2 shares at 1.5 TEST each, in the currency's 6-decimal base units
+        let rate = gmon.rate(U256::from(1_500_000)).unwrap();
+        assert_eq!(native.format(gmon.value_of(mon(2), rate)), "3.000000");
+    }
+
+    #[test]
+    fn amounts_are_formatted_at_their_own_decimals() {
+        let decimals = Decimals {
+            shares: 6,
+            assets: 18,
+        };
+        assert_eq!(decimals.of(AmountUnit::Shares), 6);
+        assert_eq!(decimals.of(AmountUnit::Assets), 18);
+        assert_eq!(decimals.format_shares(U256::from(1_500_000)), "1.500000");
+        assert_eq!(decimals.format_assets(mon(2)), "2.000000000000000000");
+
+        // 1 share worth 2 MON, whatever the share decimals, formatted as a rate
+        let rate = decimals.rate(mon(2)).unwrap();
+        let native = NativeCurrency::new("MON", 6).unwrap();
+        assert_eq!(native.format_rate(rate), "2.000000000000000000");
+        assert_eq!(
+            decimals.shares_for(mon(3), rate).unwrap(),
+            U256::from(1_500_000)
+        );
+        assert_eq!(decimals.value_of(U256::from(1_500_000), rate), mon(3));
+    }
+
+    #[test]
+    fn rates_scale_an_8_decimal_asset_up_to_18_and_reject_overflow() {
+        let decimals = Decimals {
+            shares: 18,
+            assets: 8,
+        };
+        // 1 share worth 1.25 of the asset, in its 8-decimal base units
+        let rate = decimals.rate(U256::from(125_000_000)).unwrap();
+        assert_eq!(rate, mon(5) / 4);
+        assert_eq!(
+            NativeCurrency::default().format_rate(rate),
+            "1.250000000000000000"
+        );
+        assert_eq!(
+            decimals.shares_for(U256::from(250_000_000), rate).unwrap(),
+            mon(2)
+        );
+
+        // Scaling up past 256 bits fails instead of wrapping or panicking
+        assert!(decimals.rate(U256::MAX / 1_000).is_err());
+        let err = decimals.shares_for(U256::MAX / 1_000, rate).unwrap_err();
+        let err = McpServiceError::from_anyhow("Failed to preview", err.into());
+        assert_eq!(err.kind(), "INVALID_INPUT");
+        // A product past 256 bits is fine as long as the result fits
+        let decimals = Decimals {
+            shares: 18,
+            assets: 18,
+        };
+        assert_eq!(
+            decimals.shares_for(U256::MAX / 2, mon(1)).unwrap(),
+            U256::MAX / 2
+        );
+        assert_eq!(mul_div(U256::MAX, U256::MAX, U256::MAX).unwrap(), U256::MAX);
+        assert_eq!(rescale(mon(1), 18, 100).unwrap_err().amount, mon(1));
+        assert_eq!(rescale(mon(1), 100, 18).unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn display_rounds_half_up_to_the_configured_decimals_while_raw_stays_exact() {
+        let native = NativeCurrency::new("MON", 18)
+            .unwrap()
+            .with_display_decimals(6);
+        let amount = U256::from(1_234_567_500_000_000_000u64);
+        assert_eq!(native.format(amount), "1.234567500000000000");
+        assert_eq!(native.display(amount), "1.234568");
+        assert_eq!(native.display(amount - 1), "1.234567");
+        // Rounding up carries into the integer digits
+        assert_eq!(native.round("9.9999995".to_string()), "10.000000");
+        // Shorter fractions are not padded
+        assert_eq!(native.round("1.5".to_string()), "1.5");
+
+        // Without a display precision every digit is shown
+        let native = NativeCurrency::new("MON", 18).unwrap();
+        assert_eq!(native.display(amount), "1.234567500000000000");
+    }
+}
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::bail;
+use ethers::{
+    prelude::Lazy,
+    types::{Address, H160, H256, TransactionReceipt, U64, U256},
+};
+
+/// Address reported for the mock LST, which has no deployed contract.
+pub const MOCK_ADDRESS: Address = H160([0x4d; 20]);
+
+/// The process-wide mock LST, shared by every connection.
+pub static MOCK_LST: Lazy<MockLst> = Lazy::new(MockLst::default);
+
+/// In-memory LST vault for demos: stakes and unstakes update balances in process and are
+/// "mined" instantly, so the full flow runs without a deployed contract.
+#[derive(Debug, Default)]
+pub struct MockLst {
+    state: Mutex<MockState>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    balances: HashMap<Address, U256>,
+    total_assets: U256,
+    total_supply: U256,
+    nonce: u64,
+}
+
+impl MockState {
+    fn convert_to_shares(&self, assets: U256) -> U256 {
+        if self.total_supply.is_zero() || self.total_assets.is_zero() {
+            assets
+        } else {
+            assets * self.total_supply / self.total_assets
+        }
+    }
+
+    fn convert_to_assets(&self, shares: U256) -> U256 {
+        if self.total_supply.is_zero() {
+            shares
+        } else {
+            shares * self.total_assets / self.total_supply
+        }
+    }
+
+    /// Synthetic receipt for an instantly mined transaction from `from`.
+    fn receipt(&mut self, from: Address) -> TransactionReceipt {
+        self.nonce += 1;
+        TransactionReceipt {
+            transaction_hash: H256::from_low_u64_be(self.nonce),
+            from,
+            to: Some(MOCK_ADDRESS),
+            status: Some(U64::one()),
+            ..Default::default()
+        }
+    }
+}
+
+impl MockLst {
+    pub fn balance_of(&self, owner: Address) -> U256 {
+        let state = self.state.lock().unwrap();
+        state.balances.get(&owner).copied().unwrap_or_default()
+    }
+
+    pub fn total_assets(&self) -> U256 {
+        self.state.lock().unwrap().total_assets
+    }
+
+    pub fn convert_to_shares(&self, assets: U256) -> U256 {
+        self.state.lock().unwrap().convert_to_shares(assets)
+    }
+
+    pub fn convert_to_assets(&self, shares: U256) -> U256 {
+        self.state.lock().unwrap().convert_to_assets(shares)
+    }
+
+    /// Mints shares to `receiver` for `assets` MON staked by `from`.
+    pub fn deposit(&self, from: Address, receiver: Address, assets: U256) -> TransactionReceipt {
+        let mut state = self.state.lock().unwrap();
+        let shares = state.convert_to_shares(assets);
+        *state.balances.entry(receiver).or_default() += shares;
+        state.total_assets += assets;
+        state.total_supply += shares;
+        state.receipt(from)
+    }
+
+    /// Burns `shares` of `owner` and pays out their MON value instantly. Like a vault redeem
+    /// without an allowance, it reverts unless `from` is the owner, as the mock has no
+    /// `approve` to grant one.
+    pub fn redeem(
+        &self,
+        from: Address,
+        owner: Address,
+        shares: U256,
+    ) -> anyhow::Result<TransactionReceipt> {
+        if from != owner {
+            bail!(
+                "Mock redeem reverted: {:?} has no allowance to redeem shares of {:?}",
+                from,
+                owner
+            );
+        }
+        let mut state = self.state.lock().unwrap();
+        let balance = state.balances.get(&owner).copied().unwrap_or_default();
+        if shares > balance {
+            bail!(
+                "Insufficient mock shares: requested {} but balance is {}",
+                shares,
+                balance
+            );
+        }
+
+        let assets = state.convert_to_assets(shares);
+        state.balances.insert(owner, balance - shares);
+        state.total_assets -= assets;
+        state.total_supply -= shares;
+        Ok(state.receipt(from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_owners_redeem_their_shares() {
+        let mock = MockLst::default();
+        let owner = Address::repeat_byte(0x11);
+        let other = Address::repeat_byte(0x22);
+        mock.deposit(other, owner, U256::from(100));
+        assert_eq!(mock.balance_of(owner), U256::from(100));
+
+        let err = mock.redeem(other, owner, U256::from(40)).unwrap_err();
+        assert!(err.to_string().contains("no allowance"), "{err}");
+        assert_eq!(mock.balance_of(owner), U256::from(100));
+
+        assert!(mock.redeem(owner, owner, U256::from(101)).is_err());
+        mock.redeem(owner, owner, U256::from(40)).unwrap();
+        assert_eq!(mock.balance_of(owner), U256::from(60));
+        assert_eq!(mock.total_assets(), U256::from(60));
+    }
+}
@@ -1 +1,19 @@
+pub mod admin;
+pub mod arguments;
+pub mod batch;
+pub mod decimal;
+pub mod error;
+pub mod events;
+pub mod health;
 pub mod lst;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod price;
+pub mod progress;
+pub mod query;
+pub mod registry;
+pub mod rpc;
+pub mod session;
+pub mod snapshot;
+pub mod tx;
+pub mod warning;
@@ -0,0 +1,282 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, bail};
+use ethers::{
+    types::{Address, I256},
+    utils::to_checksum,
+};
+use reqwest::Url;
+
+use crate::{
+    bindings::aggregatorv3::aggregator_v3::AggregatorV3,
+    common::rpc::RpcProvider,
+    services::constants::{PRICE_CACHE_TTL, PRICE_FEED_TIMEOUT, PRICE_MAX_AGE},
+};
+
+/// Where the USD price of the native currency is read from.
+#[derive(Debug, Clone)]
+pub enum PriceSource {
+    /// Chainlink-style aggregator, read through `latestRoundData()` and `decimals()`.
+    Oracle(Address),
+    /// HTTP endpoint answering JSON with the price at `pointer`, an RFC 6901 JSON pointer, as
+    /// a number or a numeric string.
+    Feed { url: Url, pointer: String },
+}
+
+impl PriceSource {
+    pub fn feed(url: &str, pointer: impl Into<String>) -> anyhow::Result<Self> {
+        let url = Url::parse(url).with_context(|| format!("Invalid price feed URL '{}'", url))?;
+        let pointer = pointer.into();
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            bail!("Price feed pointer '{}' must start with '/'", pointer);
+        }
+
+        Ok(PriceSource::Feed { url, pointer })
+    }
+
+    /// Names the source for responses. A feed is named by its origin only, as the path or
+    /// query often carries an API key.
+    pub fn describe(&self) -> String {
+        match self {
+            PriceSource::Oracle(address) => format!("oracle {}", to_checksum(address, None)),
+            PriceSource::Feed { url, .. } => format!("feed {}", url.origin().ascii_serialization()),
+        }
+    }
+}
+
+/// USD price of one unit of the native currency.
+#[derive(Debug, Clone, Copy)]
+pub struct UsdPrice {
+    pub price: f64,
+    /// Unix time the price was last updated: the oracle round's `updatedAt`, or when a feed
+    /// was fetched.
+    pub updated_at: u64,
+}
+
+/// Reads USD prices from a [`PriceSource`], caching each for [`PRICE_CACHE_TTL`] so that busy
+/// clients don't hit the source on every read. Prices last updated more than `max_age` ago are
+/// refused. Clones share the cache.
+#[derive(Debug, Clone)]
+pub struct UsdPricing {
+    source: PriceSource,
+    client: reqwest::Client,
+    cached: Arc<RwLock<Option<(Instant, UsdPrice)>>>,
+    ttl: Duration,
+    max_age: Duration,
+}
+
+impl UsdPricing {
+    pub fn new(source: PriceSource) -> Self {
+        UsdPricing {
+            source,
+            client: reqwest::Client::new(),
+            cached: Arc::default(),
+            ttl: PRICE_CACHE_TTL,
+            max_age: PRICE_MAX_AGE,
+        }
+    }
+
+    /// Sets how long after its last update a price is still served.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn source(&self) -> &PriceSource {
+        &self.source
+    }
+
+    pub async fn price(&self, provider: Arc<RpcProvider>) -> anyhow::Result<UsdPrice> {
+        let cached = *self.cached.read().unwrap();
+        let price = match cached.filter(|(fetched, _)| fetched.elapsed() < self.ttl) {
+            Some((_, price)) => price,
+            None => {
+                let price = match &self.source {
+                    PriceSource::Oracle(address) => oracle_price(provider, *address).await?,
+                    PriceSource::Feed { url, pointer } => self.feed_price(url, pointer).await?,
+                };
+                *self.cached.write().unwrap() = Some((Instant::now(), price));
+                price
+            }
+        };
+
+        // A cached price keeps ageing, so it is checked on every read
+        ensure_fresh(
+            price,
+            self.max_age,
+            chrono::Utc::now().timestamp().max(0) as u64,
+        )?;
+        Ok(price)
+    }
+
+    async fn feed_price(&self, url: &Url, pointer: &str) -> anyhow::Result<UsdPrice> {
+        let body: serde_json::Value = self
+            .client
+            .get(url.clone())
+            .timeout(PRICE_FEED_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            // The URL often carries an API key, so it is kept out of errors
+            .map_err(reqwest::Error::without_url)
+            .context("Failed to fetch the price feed")?
+            .json()
+            .await
+            .map_err(reqwest::Error::without_url)
+            .context("Price feed did not return JSON")?;
+
+        let value = body
+            .pointer(pointer)
+            .with_context(|| format!("Price feed response has no value at '{}'", pointer))?;
+        let price = match value {
+            serde_json::Value::Number(number) => number.as_f64(),
+            serde_json::Value::String(text) => text.trim().parse::<f64>().ok(),
+            _ => None,
+        }
+        .with_context(|| format!("Price feed value {} is not a number", value))?;
+
+        Ok(UsdPrice {
+            price: positive_price(price)?,
+            updated_at: chrono::Utc::now().timestamp().max(0) as u64,
+        })
+    }
+}
+
+async fn oracle_price(provider: Arc<RpcProvider>, address: Address) -> anyhow::Result<UsdPrice> {
+    let oracle = AggregatorV3::new(address, provider);
+    let decimals = oracle.decimals();
+    let round = oracle.latest_round_data();
+    let (decimals, (_, answer, _, updated_at, _)) = tokio::try_join!(decimals.call(), round.call())
+        .context("Failed to read the price oracle")?;
+
+    Ok(UsdPrice {
+        price: positive_price(scale_answer(answer, decimals))?,
+        updated_at: updated_at.low_u64(),
+    })
+}
+
+/// Fails if `price` was last updated more than `max_age` before `now`, as happens when an
+/// oracle stops being updated.
+fn ensure_fresh(price: UsdPrice, max_age: Duration, now: u64) -> anyhow::Result<()> {
+    let age = now.saturating_sub(price.updated_at);
+    if age > max_age.as_secs() {
+        bail!(
+            "Price is stale: last updated {}s ago, more than the {}s allowed",
+            age,
+            max_age.as_secs()
+        );
+    }
+
+    Ok(())
+}
+
+/// Converts an aggregator answer with `decimals` decimals to a plain number.
+fn scale_answer(answer: I256, decimals: u8) -> f64 {
+    answer.to_string().parse::<f64>().unwrap_or(f64::NAN) / 10f64.powi(i32::from(decimals))
+}
+
+fn positive_price(price: f64) -> anyhow::Result<f64> {
+    if !price.is_finite() || price <= 0.0 {
+        bail!("Price {} is not a positive number", price);
+    }
+
+    Ok(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        abi::{Token, encode},
+        providers::Provider,
+    };
+
+    use super::*;
+    use crate::common::rpc::{CallLogger, FailoverHttp};
+
+    /// Provider whose oracle answers `latestRoundData()` with `answer` at 8 decimals, last
+    /// updated at `updated_at`.
+    async fn oracle_provider(answer: u64, updated_at: u64) -> Arc<RpcProvider> {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(
+                move |axum::Json(call): axum::Json<serde_json::Value>| async move {
+                    let tx = &call["params"][0];
+                    let data = tx["input"]
+                        .as_str()
+                        .or(tx["data"].as_str())
+                        .unwrap_or_default();
+                    let output = match &data[..10] {
+                        // decimals()
+                        "0x313ce567" => encode(&[Token::Uint(8.into())]),
+                        // latestRoundData()
+                        _ => encode(&[
+                            Token::Uint(1.into()),
+                            Token::Int(answer.into()),
+                            Token::Uint(updated_at.into()),
+                            Token::Uint(updated_at.into()),
+                            Token::Uint(1.into()),
+                        ]),
+                    };
+                    axum::Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "result": ethers::types::Bytes::from(output),
+                    }))
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Arc::new(CallLogger::new(
+            Provider::new(FailoverHttp::new(&[url]).unwrap()),
+            false,
+        ))
+    }
+
+    fn oracle() -> UsdPricing {
+        UsdPricing::new(PriceSource::Oracle(Address::repeat_byte(0x11)))
+    }
+
+    #[tokio::test]
+    async fn oracle_prices_are_scaled_and_refused_once_stale() {
+        let now = chrono::Utc::now().timestamp() as u64;
+
+        let price = oracle()
+            .price(oracle_provider(250_000_000, now - 60).await)
+            .await
+            .unwrap();
+        assert_eq!(price.price, 2.5);
+        assert_eq!(price.updated_at, now - 60);
+
+        let err = oracle()
+            .price(oracle_provider(250_000_000, now - 7_200).await)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("Price is stale"), "{err:#}");
+
+        let lenient = oracle().with_max_age(Duration::from_secs(3 * 60 * 60));
+        let price = lenient
+            .price(oracle_provider(250_000_000, now - 7_200).await)
+            .await
+            .unwrap();
+        assert_eq!(price.price, 2.5);
+    }
+
+    #[test]
+    fn prices_are_fresh_up_to_the_max_age() {
+        let price = UsdPrice {
+            price: 1.0,
+            updated_at: 1_000,
+        };
+        let max_age = Duration::from_secs(100);
+
+        assert!(ensure_fresh(price, max_age, 1_100).is_ok());
+        // An update stamped ahead of the local clock is not stale
+        assert!(ensure_fresh(price, max_age, 900).is_ok());
+        assert!(ensure_fresh(price, max_age, 1_101).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    fmt,
+    future::Future,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+use ethers::types::H256;
+use rmcp::{
+    Peer, RoleServer,
+    model::{CallToolRequestParam, Meta, ProgressNotificationParam, ProgressToken},
+};
+
+use crate::common::lst::WRITE_TOOLS;
+
+/// Step of a write, reported to the client as it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteStep {
+    ResettingAllowance,
+    Approving,
+    Depositing,
+    Redeeming,
+    AwaitingConfirmation,
+    AwaitingClaimable,
+}
+
+impl fmt::Display for WriteStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteStep::ResettingAllowance => write!(f, "resetting allowance"),
+            WriteStep::Approving => write!(f, "approving"),
+            WriteStep::Depositing => write!(f, "depositing"),
+            WriteStep::Redeeming => write!(f, "redeeming"),
+            WriteStep::AwaitingConfirmation => write!(f, "awaiting confirmation"),
+            WriteStep::AwaitingClaimable => write!(f, "awaiting claimable"),
+        }
+    }
+}
+
+/// How far a write got with putting transactions on chain, as tracked by [`Progress`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Broadcast {
+    /// Nothing has been sent, so the write can still be abandoned.
+    #[default]
+    None,
+    /// A transaction is being sent and may already be out.
+    Sending,
+    /// The last transaction sent.
+    Sent(H256),
+}
+
+/// Reports the steps of a write as MCP progress notifications, numbered from 1 in the order
+/// they start. Without a progress token from the client, steps are only logged. Also tracks
+/// the transactions the write sends, so it is not abandoned once one is out.
+///
+/// Write tools take the token as `progress_token`, a string or integer, and default to the
+/// call's `_meta.progressToken`, as the MCP spec places it; see [`progress_token_from_meta`].
+#[derive(Clone, Default)]
+pub struct Progress {
+    target: Option<(Peer<RoleServer>, ProgressToken)>,
+    steps: Arc<AtomicU32>,
+    /// Step last reported, which the transactions sent from then on belong to.
+    step: Arc<Mutex<Option<WriteStep>>>,
+    broadcast: Arc<Mutex<Broadcast>>,
+    transfer: Arc<Mutex<Option<H256>>>,
+}
+
+impl Progress {
+    pub fn new(peer: Option<Peer<RoleServer>>, token: Option<ProgressToken>) -> Self {
+        Progress {
+            target: peer.zip(token),
+            ..Default::default()
+        }
+    }
+
+    pub fn broadcast(&self) -> Broadcast {
+        *self.broadcast.lock().unwrap()
+    }
+
+    /// The last transaction sent while depositing or redeeming, the one that moves the write's
+    /// funds. Allowance changes sent before it are left out, as running the write again skips
+    /// those already in place where it would move the funds twice.
+    pub fn transfer(&self) -> Option<H256> {
+        *self.transfer.lock().unwrap()
+    }
+
+    /// Runs `send`, which sends a transaction, tracking it as [`Broadcast::Sending`] until it
+    /// returns, then as sent with the hash `tx_hash` reads from its output. A failed send
+    /// leaves the last transaction sent before it, if any, as the latest.
+    pub async fn track_send<T, E>(
+        &self,
+        send: impl Future<Output = Result<T, E>>,
+        tx_hash: impl FnOnce(&T) -> H256,
+    ) -> Result<T, E> {
+        let previous = std::mem::replace(&mut *self.broadcast.lock().unwrap(), Broadcast::Sending);
+        let result = send.await;
+        *self.broadcast.lock().unwrap() = match &result {
+            Ok(sent) => {
+                let tx_hash = tx_hash(sent);
+                if matches!(
+                    *self.step.lock().unwrap(),
+                    Some(WriteStep::Depositing | WriteStep::Redeeming)
+                ) {
+                    *self.transfer.lock().unwrap() = Some(tx_hash);
+                }
+                Broadcast::Sent(tx_hash)
+            }
+            Err(_) => previous,
+        };
+
+        result
+    }
+
+    pub async fn report(&self, step: WriteStep) {
+        let progress = self.steps.fetch_add(1, Ordering::Relaxed) + 1;
+        *self.step.lock().unwrap() = Some(step);
+        tracing::info!(%step, progress, "Write progress");
+
+        let Some((peer, token)) = &self.target else {
+            return;
+        };
+        // Progress is informational, so a client that went away doesn't fail the write
+        if let Err(e) = peer
+            .notify_progress(ProgressNotificationParam {
+                progress_token: token.clone(),
+                progress,
+                total: None,
+            })
+            .await
+        {
+            tracing::debug!(error = %e, "Failed to send progress notification");
+        }
+    }
+}
+
+/// Passes the `_meta.progressToken` a write tool call was sent with to the call's
+/// `progress_token` argument, keeping one the call already passes. Other tools reject unknown
+/// arguments, so they are left alone.
+pub fn progress_token_from_meta(request: &mut CallToolRequestParam, meta: &Meta) {
+    if !WRITE_TOOLS.contains(&request.name.as_ref()) {
+        return;
+    }
+    let Some(token) = meta
+        .get_progress_token()
+        .and_then(|token| serde_json::to_value(token).ok())
+    else {
+        return;
+    };
+
+    request
+        .arguments
+        .get_or_insert_with(Default::default)
+        .entry("progress_token")
+        .or_insert(token);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    #[test]
+    fn progress_tokens_pass_from_meta_into_write_arguments() {
+        let meta: Meta = serde_json::from_value(json!({ "progressToken": "stake-1" })).unwrap();
+        let call = |name: &str, arguments: Value| {
+            let mut request = CallToolRequestParam {
+                name: name.to_string().into(),
+                arguments: arguments.as_object().cloned(),
+            };
+            progress_token_from_meta(&mut request, &meta);
+            request.arguments.map(Value::Object)
+        };
+
+        assert_eq!(
+            call("stake", json!({ "amount": "1" })),
+            Some(json!({ "amount": "1", "progress_token": "stake-1" }))
+        );
+        // Calls without arguments get them, and a token the call passes is kept
+        assert_eq!(
+            call("unstake_all", Value::Null),
+            Some(json!({ "progress_token": "stake-1" }))
+        );
+        assert_eq!(
+            call("unstake", json!({ "progress_token": 7 })),
+            Some(json!({ "progress_token": 7 }))
+        );
+        // Reads reject unknown arguments, so they are left alone
+        assert_eq!(call("balance", json!({})), Some(json!({})));
+
+        let mut request = CallToolRequestParam {
+            name: "claim_redeem".into(),
+            arguments: None,
+        };
+        progress_token_from_meta(&mut request, &Meta::default());
+        assert_eq!(request.arguments, None);
+    }
+
+    #[tokio::test]
+    async fn broadcast_tracks_the_last_transaction_sent() {
+        let progress = Progress::default();
+        let watcher = progress.clone();
+        assert_eq!(watcher.broadcast(), Broadcast::None);
+
+        let sent = progress
+            .track_send(
+                async {
+                    assert_eq!(watcher.broadcast(), Broadcast::Sending);
+                    Ok::<_, ()>(H256::repeat_byte(1))
+                },
+                |hash| *hash,
+            )
+            .await;
+        assert_eq!(sent, Ok(H256::repeat_byte(1)));
+        assert_eq!(watcher.broadcast(), Broadcast::Sent(H256::repeat_byte(1)));
+
+        // A failed send leaves the earlier transaction as the latest
+        let failed = progress
+            .track_send(async { Err::<H256, _>("rejected") }, |hash| *hash)
+            .await;
+        assert_eq!(failed, Err("rejected"));
+        assert_eq!(watcher.broadcast(), Broadcast::Sent(H256::repeat_byte(1)));
+    }
+
+    #[tokio::test]
+    async fn only_deposits_and_redeems_count_as_transfers() {
+        let progress = Progress::default();
+        let send = |byte: u8| {
+            progress.track_send(
+                async move { Ok::<_, ()>(H256::repeat_byte(byte)) },
+                |hash| *hash,
+            )
+        };
+
+        progress.report(WriteStep::Approving).await;
+        send(1).await.unwrap();
+        assert_eq!(progress.transfer(), None);
+
+        progress.report(WriteStep::Depositing).await;
+        send(2).await.unwrap();
+        progress.report(WriteStep::AwaitingConfirmation).await;
+        assert_eq!(progress.transfer(), Some(H256::repeat_byte(2)));
+        assert_eq!(progress.broadcast(), Broadcast::Sent(H256::repeat_byte(2)));
+    }
+}
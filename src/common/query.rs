@@ -0,0 +1,104 @@
+use crate::common::error::McpServiceError;
+
+/// Query string of a resource URI, split into its `key=value` pairs with both sides
+/// percent-decoded, `+` standing for a space as in HTML forms.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryParams {
+    pairs: Vec<(String, String)>,
+}
+
+impl QueryParams {
+    /// Parses `query`, the part of a URI after `?`. A key without `=` gets an empty value.
+    pub fn parse(query: Option<&str>) -> Result<Self, McpServiceError> {
+        let pairs = query
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                Ok((percent_decode(key)?, percent_decode(value)?))
+            })
+            .collect::<Result<_, McpServiceError>>()?;
+
+        Ok(QueryParams { pairs })
+    }
+
+    /// Value of the first `name` parameter, if any.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether `name` is set to `true`.
+    pub fn flag(&self, name: &str) -> bool {
+        self.get(name) == Some("true")
+    }
+}
+
+fn percent_decode(text: &str) -> Result<String, McpServiceError> {
+    let invalid =
+        || McpServiceError::invalid_input(format!("Invalid percent-encoding in query '{}'", text));
+
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let (high, low) = match rest {
+                    [high, low, ..] if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                        (hex_value(*high), hex_value(*low))
+                    }
+                    _ => return Err(invalid()),
+                };
+                bytes.push(high << 4 | low);
+                rest = &rest[2..];
+            }
+            _ => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Value of an ASCII hex digit.
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_escapes_and_plus() {
+        let params = QueryParams::parse(Some("owner=0xAb%2fcd&note=a+b%20c&flag")).unwrap();
+        assert_eq!(params.get("owner"), Some("0xAb/cd"));
+        assert_eq!(params.get("note"), Some("a b c"));
+        assert_eq!(params.get("flag"), Some(""));
+        assert_eq!(params.get("missing"), None);
+    }
+
+    #[test]
+    fn first_value_wins_and_flags_need_true() {
+        let params = QueryParams::parse(Some("a=1&a=2&b=true&c=1&&")).unwrap();
+        assert_eq!(params.get("a"), Some("1"));
+        assert!(params.flag("b"));
+        assert!(!params.flag("c"));
+        assert_eq!(QueryParams::parse(None).unwrap(), QueryParams::default());
+    }
+
+    #[test]
+    fn rejects_malformed_escapes() {
+        for query in ["a=%+f", "a=%-1", "a=%g0", "a=%4", "a=%", "a=%ff"] {
+            assert!(QueryParams::parse(Some(query)).is_err(), "{}", query);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+use anyhow::{Context, bail, ensure};
+use ethers::types::Address;
+use serde::Deserialize;
+
+use crate::common::lst::LstProtocol;
+
+/// Contract addresses replacing a built-in protocol's deployment, such as a vault deployed for
+/// testing.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProtocolAddresses {
+    /// Contract staked into and redeemed from.
+    pub address: Address,
+    /// LST token. Defaults to `address`, as aprMON and shMON are their own vaults.
+    pub token_address: Option<Address>,
+}
+
+/// Overrides installed at startup, read by [`LstProtocol`] wherever it needs an address.
+static OVERRIDES: OnceLock<HashMap<LstProtocol, ProtocolAddresses>> = OnceLock::new();
+
+/// Protocol addresses from a JSON file, an object keyed by protocol name, merged over the
+/// built-in deployments. Only built-in protocols can be overridden, as each one's contracts
+/// are called through their own bindings.
+#[derive(Debug, Default)]
+pub struct ProtocolOverrides(HashMap<LstProtocol, ProtocolAddresses>);
+
+impl ProtocolOverrides {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read protocols file {}", path.display()))?;
+        let overrides: HashMap<LstProtocol, ProtocolAddresses> = serde_json::from_str(&file)
+            .with_context(|| format!("Invalid protocols file {}", path.display()))?;
+
+        for (protocol, addresses) in &overrides {
+            #[cfg(feature = "mock")]
+            ensure!(
+                *protocol != LstProtocol::Mock,
+                "The mock protocol has no contracts to override"
+            );
+            ensure!(
+                !addresses.address.is_zero() && addresses.token_address != Some(Address::zero()),
+                "Addresses of {} must not be the zero address",
+                protocol
+            );
+            if *protocol == LstProtocol::GMON && addresses.token_address.is_none() {
+                bail!("gMON needs a token_address, as its stake manager is not the token");
+            }
+        }
+
+        Ok(ProtocolOverrides(overrides))
+    }
+
+    /// Protocols given addresses, in [`LstProtocol::ALL`] order.
+    pub fn protocols(&self) -> Vec<LstProtocol> {
+        LstProtocol::ALL
+            .into_iter()
+            .filter(|protocol| self.0.contains_key(protocol))
+            .collect()
+    }
+
+    /// Makes the overrides take effect for the rest of the process. Fails if overrides were
+    /// already installed.
+    pub fn install(self) -> anyhow::Result<()> {
+        OVERRIDES
+            .set(self.0)
+            .map_err(|_| anyhow::anyhow!("Protocol overrides are already installed"))
+    }
+}
+
+/// Addresses installed for `protocol`, if it is overridden.
+pub(crate) fn overridden(protocol: LstProtocol) -> Option<ProtocolAddresses> {
+    OVERRIDES.get()?.get(&protocol).copied()
+}
@@ -0,0 +1,844 @@
+use std::{
+    cell::Cell,
+    fmt::{self, Debug},
+    future::Future,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use anyhow::{Context, bail, ensure};
+use async_trait::async_trait;
+use ethers::{
+    providers::{
+        Http, HttpClientError, JsonRpcClient, JsonRpcError, Middleware, PendingTransaction,
+        Provider, ProviderError, RpcError,
+    },
+    types::{
+        Block, BlockId, Bytes, Filter, Log, NameOrAddress, Transaction, TransactionReceipt, TxHash,
+        U64, U256, transaction::eip2718::TypedTransaction,
+    },
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::services::constants::MAX_CONCURRENT_READS;
+
+tokio::task_local! {
+    /// JSON-RPC calls made so far by the request being served on this task.
+    static RPC_CALLS: Cell<u64>;
+}
+
+/// Runs `fut`, counting the JSON-RPC calls it sends through [`FailoverHttp`], and returns its
+/// output along with the count. Every call in a batch and every endpoint tried on failover
+/// counts, as each is billed against the provider's quota. Only calls made on the current task
+/// are seen, not those of tasks it spawns.
+pub async fn count_rpc_calls<F: Future>(fut: F) -> (F::Output, u64) {
+    RPC_CALLS
+        .scope(Cell::new(0), async {
+            let output = fut.await;
+            (output, RPC_CALLS.with(Cell::get))
+        })
+        .await
+}
+
+fn record_rpc_calls(count: usize) {
+    // Background work such as the snapshot refresher runs outside any count
+    let _ = RPC_CALLS.try_with(|calls| calls.set(calls.get() + count as u64));
+}
+
+/// Provider stack the service reads and sends transactions through.
+pub type RpcProvider = CallLogger<Provider<FailoverHttp>>;
+
+/// Methods that submit a transaction rather than read the chain.
+const WRITE_METHODS: [&str; 2] = ["eth_sendRawTransaction", "eth_sendTransaction"];
+
+/// HTTP transport over a list of RPC endpoints. Requests go to the last endpoint that
+/// answered and fail over to the next one, in order, when it can't be reached. At most
+/// `read_limit` reads are in flight at once; the others wait for a free slot. Writes are not
+/// capped, so a signed transaction is never held up behind a long scan.
+#[derive(Debug)]
+pub struct FailoverHttp {
+    endpoints: Vec<Http>,
+    active: AtomicUsize,
+    client: reqwest::Client,
+    read_limit: Semaphore,
+}
+
+/// Why [`FailoverHttp::batch_call`] failed.
+#[derive(Debug)]
+pub enum BatchError {
+    /// No endpoint could be reached or served the batch; the calls may still succeed one by
+    /// one.
+    Unavailable(anyhow::Error),
+    /// An endpoint served the batch but a call in it failed, e.g. reverted, which sending it on
+    /// its own would not change.
+    Call { id: usize, error: JsonRpcError },
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchError::Unavailable(e) => write!(f, "Batch request failed: {:#}", e),
+            BatchError::Call { id, error } => write!(f, "Batched call {} failed: {}", id, error),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// One entry of a JSON-RPC batch response.
+#[derive(Debug, Deserialize)]
+struct BatchResponse {
+    id: usize,
+    result: Option<Bytes>,
+    error: Option<JsonRpcError>,
+}
+
+impl FailoverHttp {
+    pub fn new(urls: &[String]) -> anyhow::Result<Self> {
+        ensure!(!urls.is_empty(), "At least one RPC URL is required");
+
+        let endpoints = urls
+            .iter()
+            .map(|url| Http::from_str(url).with_context(|| format!("Invalid RPC URL '{}'", url)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(FailoverHttp {
+            endpoints,
+            active: AtomicUsize::new(0),
+            client: reqwest::Client::new(),
+            read_limit: Semaphore::new(MAX_CONCURRENT_READS),
+        })
+    }
+
+    /// Caps how many reads, a batch counting as one, are sent at once so fan-outs and long log
+    /// scans don't trip the endpoint's rate limits. A permit is held for a single request only,
+    /// so a long scan takes turns with the reads around it. `limit` must be non-zero.
+    pub fn with_max_concurrent_reads(mut self, limit: usize) -> Self {
+        self.read_limit = Semaphore::new(limit);
+        self
+    }
+
+    /// Origin of the endpoint currently serving requests. The path is left out as it often
+    /// carries an API key.
+    pub fn active_endpoint(&self) -> String {
+        self.endpoints[self.active.load(Ordering::Relaxed)]
+            .url()
+            .origin()
+            .ascii_serialization()
+    }
+
+    /// Replaces every endpoint URL in `text`, such as an error message, with its origin, so
+    /// API keys in the path don't reach clients.
+    pub fn redact_urls(&self, text: &str) -> String {
+        self.endpoints
+            .iter()
+            .fold(text.to_string(), |text, endpoint| {
+                let url = endpoint.url();
+                text.replace(url.as_str(), &url.origin().ascii_serialization())
+            })
+    }
+
+    /// Sends every `(tx, block)` as an `eth_call` in a single JSON-RPC batch, returning the
+    /// outputs in order. Like single requests, the batch goes to the active endpoint and fails
+    /// over to the next ones in order when it can't be reached or doesn't serve batches. Once
+    /// an endpoint has answered the batch, a call failing in it fails the whole batch.
+    pub async fn batch_call(
+        &self,
+        calls: &[(TypedTransaction, BlockId)],
+    ) -> Result<Vec<Bytes>, BatchError> {
+        let body = calls
+            .iter()
+            .enumerate()
+            .map(|(id, (tx, block))| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "eth_call",
+                    "params": [tx, block],
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let start = self.active.load(Ordering::Relaxed);
+        let mut last_error = None;
+        for offset in 0..self.endpoints.len() {
+            let index = (start + offset) % self.endpoints.len();
+            let url = self.endpoints[index].url();
+
+            record_rpc_calls(calls.len());
+            let responses = {
+                let _permit = self.read_permit("eth_call").await;
+                self.send_batch(url, &body, calls.len()).await
+            };
+            match responses {
+                Ok(outputs) => {
+                    if index != start {
+                        self.active.store(index, Ordering::Relaxed);
+                        tracing::warn!(
+                            "Failed over to RPC endpoint {}",
+                            url.origin().ascii_serialization()
+                        );
+                    }
+                    return outputs;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "RPC endpoint {} failed the batch: {:#}",
+                        url.origin().ascii_serialization(),
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(BatchError::Unavailable(
+            last_error.expect("at least one endpoint"),
+        ))
+    }
+
+    /// Posts a batch to one endpoint. The outer error means the endpoint couldn't be reached or
+    /// didn't answer with a batch, so another endpoint, or single calls, may still succeed; the
+    /// inner one that it answered and a call in the batch failed.
+    async fn send_batch(
+        &self,
+        url: &reqwest::Url,
+        body: &[serde_json::Value],
+        len: usize,
+    ) -> anyhow::Result<Result<Vec<Bytes>, BatchError>> {
+        let responses: Vec<BatchResponse> = self
+            .client
+            .post(url.clone())
+            .json(body)
+            .send()
+            .await
+            .context("Failed to send batch request")?
+            .error_for_status()
+            .context("Batch request rejected")?
+            .json()
+            .await
+            .context("Endpoint did not return a batch response")?;
+
+        let mut outputs = vec![None; len];
+        for response in responses {
+            let Some(output) = outputs.get_mut(response.id) else {
+                bail!("Unexpected id {} in batch response", response.id);
+            };
+            match (response.result, response.error) {
+                (Some(result), None) => *output = Some(result),
+                (_, Some(error)) => {
+                    return Ok(Err(BatchError::Call {
+                        id: response.id,
+                        error,
+                    }));
+                }
+                (None, None) => bail!("Batched call {} returned no result", response.id),
+            }
+        }
+
+        let outputs = outputs
+            .into_iter()
+            .enumerate()
+            .map(|(id, output)| output.with_context(|| format!("Batched call {} is missing", id)))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Ok(outputs))
+    }
+
+    /// Waits for a slot under the read cap, or returns `None` right away for a write.
+    async fn read_permit(&self, method: &str) -> Option<SemaphorePermit<'_>> {
+        if WRITE_METHODS.contains(&method) {
+            return None;
+        }
+
+        let permit = self
+            .read_limit
+            .acquire()
+            .await
+            .expect("read limit semaphore is never closed");
+        Some(permit)
+    }
+
+    /// Sends the request to the active endpoint, failing over to the next ones in order when it
+    /// can't be reached.
+    async fn request_with_failover<T, R>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R, HttpClientError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let start = self.active.load(Ordering::Relaxed);
+        let mut last_error = None;
+
+        for offset in 0..self.endpoints.len() {
+            let index = (start + offset) % self.endpoints.len();
+            let endpoint = &self.endpoints[index];
+
+            record_rpc_calls(1);
+            let result = {
+                let _permit = self.read_permit(method).await;
+                endpoint.request(method, &params).await
+            };
+            match result {
+                Ok(result) => {
+                    if index != start {
+                        self.active.store(index, Ordering::Relaxed);
+                        tracing::warn!(
+                            "Failed over to RPC endpoint {}",
+                            endpoint.url().origin().ascii_serialization()
+                        );
+                    }
+                    return Ok(result);
+                }
+                // The endpoint answered, so there is nothing to fail over from
+                Err(e) if e.is_error_response() => return Err(e),
+                Err(e) => {
+                    tracing::warn!(
+                        "RPC endpoint {} failed: {}",
+                        endpoint.url().origin().ascii_serialization(),
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one endpoint"))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for FailoverHttp {
+    type Error = HttpClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        self.request_with_failover(method, params).await
+    }
+}
+
+/// Middleware logging the method, params and duration of each call made through it at debug
+/// level, when enabled. Signed transactions are redacted from the params. Steps that send
+/// several requests, such as filling a transaction, are logged once under their own name, and
+/// requests the provider makes on its own, such as polling for a receipt, are not seen.
+#[derive(Debug)]
+pub struct CallLogger<M> {
+    inner: M,
+    enabled: bool,
+}
+
+impl<M> CallLogger<M> {
+    pub fn new(inner: M, enabled: bool) -> Self {
+        CallLogger { inner, enabled }
+    }
+
+    /// `params` as logged, or `None` when logging is off so they aren't serialized for nothing.
+    fn params(&self, params: impl Serialize) -> Option<String> {
+        self.enabled
+            .then(|| serde_json::to_string(&params).unwrap_or_default())
+    }
+
+    async fn logged<F, R, E>(&self, method: &str, params: Option<String>, call: F) -> Result<R, E>
+    where
+        F: Future<Output = Result<R, E>>,
+    {
+        let Some(params) = params else {
+            return call.await;
+        };
+
+        let started = Instant::now();
+        let result = call.await;
+        tracing::debug!(
+            method,
+            params,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            ok = result.is_ok(),
+            "RPC call"
+        );
+        result
+    }
+}
+
+impl CallLogger<Provider<FailoverHttp>> {
+    /// Sends a raw JSON-RPC request, for methods ethers has no call for.
+    pub async fn request<T, R>(&self, method: &str, params: T) -> Result<R, ProviderError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: Serialize + DeserializeOwned + Debug + Send,
+    {
+        let logged = self.params(&params);
+        self.logged(method, logged, self.inner.request(method, params))
+            .await
+    }
+
+    /// See [`FailoverHttp::batch_call`]. The batch is logged as one call.
+    pub async fn batch_call(
+        &self,
+        calls: &[(TypedTransaction, BlockId)],
+    ) -> Result<Vec<Bytes>, BatchError> {
+        let params = self.enabled.then(|| format!("{} calls", calls.len()));
+        self.logged("eth_call", params, self.inner.as_ref().batch_call(calls))
+            .await
+    }
+}
+
+#[async_trait]
+impl<M> Middleware for CallLogger<M>
+where
+    M: Middleware<Error = ProviderError>,
+{
+    type Error = ProviderError;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        let params = self.params((&*tx, block));
+        self.logged(
+            "fill_transaction",
+            params,
+            self.inner.fill_transaction(tx, block),
+        )
+        .await
+    }
+
+    async fn get_block_number(&self) -> Result<U64, Self::Error> {
+        let params = self.params(());
+        self.logged("eth_blockNumber", params, self.inner.get_block_number())
+            .await
+    }
+
+    async fn get_block<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<TxHash>>, Self::Error> {
+        let block = block_hash_or_number.into();
+        let method = match block {
+            BlockId::Hash(_) => "eth_getBlockByHash",
+            BlockId::Number(_) => "eth_getBlockByNumber",
+        };
+        let params = self.params((block,));
+        self.logged(method, params, self.inner.get_block(block))
+            .await
+    }
+
+    async fn get_transaction_count<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let from = from.into();
+        let params = self.params((&from, block));
+        self.logged(
+            "eth_getTransactionCount",
+            params,
+            self.inner.get_transaction_count(from, block),
+        )
+        .await
+    }
+
+    async fn estimate_gas(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let params = self.params((tx, block));
+        self.logged(
+            "eth_estimateGas",
+            params,
+            self.inner.estimate_gas(tx, block),
+        )
+        .await
+    }
+
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let params = self.params((tx, block));
+        self.logged("eth_call", params, self.inner.call(tx, block))
+            .await
+    }
+
+    async fn get_chainid(&self) -> Result<U256, Self::Error> {
+        let params = self.params(());
+        self.logged("eth_chainId", params, self.inner.get_chainid())
+            .await
+    }
+
+    async fn get_balance<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let from = from.into();
+        let params = self.params((&from, block));
+        self.logged(
+            "eth_getBalance",
+            params,
+            self.inner.get_balance(from, block),
+        )
+        .await
+    }
+
+    async fn get_transaction<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        let hash = transaction_hash.into();
+        let params = self.params((hash,));
+        self.logged(
+            "eth_getTransactionByHash",
+            params,
+            self.inner.get_transaction(hash),
+        )
+        .await
+    }
+
+    async fn get_transaction_receipt<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<TransactionReceipt>, Self::Error> {
+        let hash = transaction_hash.into();
+        let params = self.params((hash,));
+        self.logged(
+            "eth_getTransactionReceipt",
+            params,
+            self.inner.get_transaction_receipt(hash),
+        )
+        .await
+    }
+
+    async fn get_gas_price(&self) -> Result<U256, Self::Error> {
+        let params = self.params(());
+        self.logged("eth_gasPrice", params, self.inner.get_gas_price())
+            .await
+    }
+
+    async fn estimate_eip1559_fees(
+        &self,
+        estimator: Option<fn(U256, Vec<Vec<U256>>) -> (U256, U256)>,
+    ) -> Result<(U256, U256), Self::Error> {
+        let params = self.params(());
+        self.logged(
+            "estimate_eip1559_fees",
+            params,
+            self.inner.estimate_eip1559_fees(estimator),
+        )
+        .await
+    }
+
+    async fn send_raw_transaction<'a>(
+        &'a self,
+        tx: Bytes,
+    ) -> Result<PendingTransaction<'a, Self::Provider>, Self::Error> {
+        // Raw transactions are signed with the caller's key and can be broadcast by anyone
+        let params = self.enabled.then(|| "[redacted]".to_string());
+        self.logged(
+            "eth_sendRawTransaction",
+            params,
+            self.inner.send_raw_transaction(tx),
+        )
+        .await
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, Self::Error> {
+        let params = self.params((filter,));
+        self.logged("eth_getLogs", params, self.inner.get_logs(filter))
+            .await
+    }
+
+    async fn get_code<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        at: T,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let at = at.into();
+        let params = self.params((&at, block));
+        self.logged("eth_getCode", params, self.inner.get_code(at, block))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use ethers::types::{Address, BlockNumber, TransactionRequest};
+
+    use super::*;
+
+    /// Serves `respond(batch)` as the raw JSON answer to every POST, returning the URL.
+    async fn stub_endpoint<F>(respond: F) -> String
+    where
+        F: Fn(&serde_json::Value) -> serde_json::Value + Send + Sync + 'static,
+    {
+        let respond = std::sync::Arc::new(respond);
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                let respond = respond.clone();
+                async move { axum::Json(respond(&body)) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    /// Answers every call in a batch with its id as a one-byte output.
+    fn echo_ids(batch: &serde_json::Value) -> serde_json::Value {
+        batch
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|call| {
+                let id = call["id"].as_u64().unwrap();
+                serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": format!("0x{:02x}", id) })
+            })
+            .collect()
+    }
+
+    fn calls() -> Vec<(TypedTransaction, BlockId)> {
+        (0..2)
+            .map(|_| {
+                let tx = TransactionRequest::new().to(Address::repeat_byte(0x11));
+                (tx.into(), BlockId::Number(BlockNumber::Latest))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn batches_fail_over_past_unreachable_endpoints() {
+        let live = stub_endpoint(echo_ids).await;
+        let http = FailoverHttp::new(&["http://127.0.0.1:1".to_string(), live.clone()]).unwrap();
+
+        let outputs = http.batch_call(&calls()).await.unwrap();
+
+        assert_eq!(outputs, vec![Bytes::from(vec![0]), Bytes::from(vec![1])]);
+        assert_eq!(http.active_endpoint(), live);
+    }
+
+    #[tokio::test]
+    async fn requests_fail_over_from_an_erroring_endpoint_and_stay_there() {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(|| async { axum::http::StatusCode::SERVICE_UNAVAILABLE }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let primary = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let secondary = stub_endpoint(
+            |call| serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": "0x279f" }),
+        )
+        .await;
+        let http = FailoverHttp::new(&[primary.clone(), secondary.clone()]).unwrap();
+        assert_eq!(http.active_endpoint(), primary);
+
+        let chain_id = || http.request::<_, ethers::types::U64>("eth_chainId", [(); 0]);
+        let (first, calls) = count_rpc_calls(chain_id()).await;
+        assert_eq!(first.unwrap().as_u64(), 10143);
+        assert_eq!(calls, 2);
+        assert_eq!(http.active_endpoint(), secondary);
+
+        // Later requests go straight to the endpoint that answered
+        let (second, calls) = count_rpc_calls(chain_id()).await;
+        assert_eq!(second.unwrap().as_u64(), 10143);
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn a_failing_call_fails_the_served_batch() {
+        let url = stub_endpoint(|batch| {
+            let mut responses = echo_ids(batch);
+            responses[1] = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": 3, "message": "execution reverted" },
+            });
+            responses
+        })
+        .await;
+        let http = FailoverHttp::new(&[url]).unwrap();
+
+        match http.batch_call(&calls()).await {
+            Err(BatchError::Call { id, error }) => {
+                assert_eq!(id, 1);
+                assert_eq!(error.message, "execution reverted");
+            }
+            other => panic!("expected a call error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn endpoints_without_batch_support_leave_the_batch_unavailable() {
+        let url = stub_endpoint(|_| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32600, "message": "batches are not supported" },
+            })
+        })
+        .await;
+        let http = FailoverHttp::new(&["http://127.0.0.1:1".to_string(), url]).unwrap();
+
+        assert!(matches!(
+            http.batch_call(&calls()).await,
+            Err(BatchError::Unavailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn requests_past_the_cap_wait_for_a_free_slot() {
+        use std::sync::{Arc, atomic::AtomicUsize};
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (current, max) = (in_flight.clone(), peak.clone());
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(call): axum::Json<serde_json::Value>| {
+                let (current, max) = (current.clone(), max.clone());
+                async move {
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    current.fetch_sub(1, Ordering::SeqCst);
+                    axum::Json(
+                        serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": "0x1" }),
+                    )
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let http = FailoverHttp::new(&[url])
+            .unwrap()
+            .with_max_concurrent_reads(2);
+
+        let requests = (0..8).map(|_| {
+            http.request::<_, ethers::types::U64>("eth_blockNumber", serde_json::json!([]))
+        });
+        let results = futures::future::join_all(requests).await;
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn writes_are_sent_while_reads_hold_every_slot() {
+        let release = Arc::new(tokio::sync::Notify::new());
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post({
+                let release = release.clone();
+                move |axum::Json(call): axum::Json<serde_json::Value>| {
+                    let release = release.clone();
+                    async move {
+                        // Reads are only answered once the write has gone through
+                        if call["method"] == "eth_call" {
+                            release.notified().await;
+                        }
+                        axum::Json(
+                            serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": "0x01" }),
+                        )
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let http = FailoverHttp::new(&[url])
+            .unwrap()
+            .with_max_concurrent_reads(1);
+
+        let read = http.request::<_, Bytes>("eth_call", serde_json::json!([]));
+        let write = async {
+            let sent = tokio::time::timeout(
+                std::time::Duration::from_secs(5),
+                http.request::<_, Bytes>("eth_sendRawTransaction", ["0x01"]),
+            )
+            .await;
+            release.notify_one();
+            sent
+        };
+        let (read, write) = tokio::join!(read, write);
+
+        assert!(write.expect("the write waited for a read slot").is_ok());
+        assert!(read.is_ok());
+    }
+
+    /// Log output captured in memory.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn balance_reads_are_logged_only_when_enabled() {
+        let url = stub_endpoint(
+            |call| serde_json::json!({ "jsonrpc": "2.0", "id": call["id"], "result": "0x2a" }),
+        )
+        .await;
+        let logs = Captured::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let holder = Address::repeat_byte(0x11);
+
+        for enabled in [false, true] {
+            let provider = CallLogger::new(
+                Provider::new(FailoverHttp::new(std::slice::from_ref(&url)).unwrap()),
+                enabled,
+            );
+            let balance = provider.get_balance(holder, None).await.unwrap();
+            assert_eq!(balance, U256::from(42));
+
+            let logged = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+            let calls = logged
+                .lines()
+                .filter(|line| line.contains("RPC call"))
+                .collect::<Vec<_>>();
+            if enabled {
+                assert_eq!(calls.len(), 1, "{logged}");
+                assert!(calls[0].contains("eth_getBalance"), "{logged}");
+                assert!(calls[0].contains(&format!("{:?}", holder)), "{logged}");
+            } else {
+                assert!(calls.is_empty(), "{logged}");
+            }
+        }
+    }
+}
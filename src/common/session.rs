@@ -0,0 +1,25 @@
+use std::sync::{Arc, RwLock};
+
+use ethers::signers::LocalWallet;
+
+/// State scoped to a single client connection. It lives in memory only and is dropped with
+/// the connection, so nothing stored here outlives the session.
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    signer: Arc<RwLock<Option<LocalWallet>>>,
+}
+
+impl Session {
+    pub fn signer(&self) -> Option<LocalWallet> {
+        self.signer.read().unwrap().clone()
+    }
+
+    pub fn set_signer(&self, signer: LocalWallet) {
+        *self.signer.write().unwrap() = Some(signer);
+    }
+
+    /// Removes the session signer, returning whether one was set.
+    pub fn clear_signer(&self) -> bool {
+        self.signer.write().unwrap().take().is_some()
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::{Arc, RwLock};
+
+use ethers::types::U256;
+
+use crate::common::lst::{LstProtocol, ProtocolStats};
+
+/// Stats and exchange rate of one protocol, as captured by a snapshot.
+#[derive(Debug, Clone)]
+pub struct ProtocolSnapshot {
+    pub stats: ProtocolStats,
+    /// MON value of one LST token, scaled by 1e18.
+    pub exchange_rate: U256,
+    /// Unix timestamp at which this protocol was read.
+    pub as_of: u64,
+}
+
+/// A protocol whose stats could not be read for a snapshot, and why.
+#[derive(Debug, Clone)]
+pub struct SnapshotError {
+    pub protocol: LstProtocol,
+    pub error: String,
+}
+
+/// Stats of every protocol, normally read at the same moment. A protocol that failed to read
+/// is left out of `protocols` and listed in `errors` instead, so the others are still served.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Unix timestamp at which the snapshot was taken. Once a single protocol has been
+    /// refreshed, this is when the oldest of its protocols was read.
+    pub as_of: u64,
+    pub protocols: Vec<ProtocolSnapshot>,
+    pub errors: Vec<SnapshotError>,
+}
+
+impl Snapshot {
+    pub fn get(&self, protocol: LstProtocol) -> Option<&ProtocolSnapshot> {
+        self.protocols.iter().find(|p| p.stats.protocol == protocol)
+    }
+
+    /// Why `protocol` is missing from the snapshot, if it failed to read.
+    pub fn error(&self, protocol: LstProtocol) -> Option<&str> {
+        self.errors
+            .iter()
+            .find(|e| e.protocol == protocol)
+            .map(|e| e.error.as_str())
+    }
+
+    /// Replaces what this snapshot holds for `protocol` with what `refreshed` holds for it,
+    /// keeping the other protocols in their order.
+    pub fn merge(&self, protocol: LstProtocol, refreshed: Snapshot) -> Snapshot {
+        let mut protocols = self
+            .protocols
+            .iter()
+            .filter(|p| p.stats.protocol != protocol)
+            .cloned()
+            .chain(refreshed.protocols)
+            .collect::<Vec<_>>();
+        protocols.sort_by_key(|p| position(p.stats.protocol));
+        let mut errors = self
+            .errors
+            .iter()
+            .filter(|e| e.protocol != protocol)
+            .cloned()
+            .chain(refreshed.errors)
+            .collect::<Vec<_>>();
+        errors.sort_by_key(|e| position(e.protocol));
+
+        Snapshot {
+            as_of: protocols
+                .iter()
+                .map(|p| p.as_of)
+                .min()
+                .unwrap_or(refreshed.as_of),
+            protocols,
+            errors,
+        }
+    }
+}
+
+fn position(protocol: LstProtocol) -> usize {
+    LstProtocol::ALL
+        .iter()
+        .position(|p| *p == protocol)
+        .unwrap_or(usize::MAX)
+}
+
+/// Latest snapshot, shared by every connection.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotCache {
+    latest: Arc<RwLock<Option<Arc<Snapshot>>>>,
+}
+
+impl SnapshotCache {
+    pub fn get(&self) -> Option<Arc<Snapshot>> {
+        self.latest.read().unwrap().clone()
+    }
+
+    /// Stores what a refresh read, merging it into the latest snapshot under the lock so
+    /// concurrent refreshes can't drop each other's results. With `only` set, just that
+    /// protocol is replaced. Otherwise every protocol is, except those the latest snapshot read
+    /// after the refresh did, e.g. by a single-protocol refresh that finished first.
+    pub fn update(&self, only: Option<LstProtocol>, refreshed: Snapshot) -> Arc<Snapshot> {
+        let mut latest = self.latest.write().unwrap();
+        let snapshot = match (only, latest.as_deref()) {
+            (Some(protocol), Some(current)) => current.merge(protocol, refreshed),
+            (None, Some(current)) => {
+                let read_at = refreshed.as_of;
+                current.protocols.iter().filter(|p| p.as_of > read_at).fold(
+                    refreshed,
+                    |snapshot, newer| {
+                        let kept = Snapshot {
+                            as_of: newer.as_of,
+                            protocols: vec![newer.clone()],
+                            errors: Vec::new(),
+                        };
+                        snapshot.merge(newer.stats.protocol, kept)
+                    },
+                )
+            }
+            (_, None) => refreshed,
+        };
+
+        let snapshot = Arc::new(snapshot);
+        *latest = Some(snapshot.clone());
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(protocol: LstProtocol, apr: f64, as_of: u64) -> ProtocolSnapshot {
+        ProtocolSnapshot {
+            stats: ProtocolStats {
+                protocol,
+                tvl: U256::zero(),
+                apr,
+                withdrawal_fee: 0.0,
+            },
+            exchange_rate: U256::exp10(18),
+            as_of,
+        }
+    }
+
+    fn snapshot(as_of: u64, protocols: Vec<ProtocolSnapshot>) -> Snapshot {
+        Snapshot {
+            as_of,
+            protocols,
+            errors: Vec::new(),
+        }
+    }
+
+    fn aprs(snapshot: &Snapshot) -> Vec<(LstProtocol, f64)> {
+        snapshot
+            .protocols
+            .iter()
+            .map(|p| (p.stats.protocol, p.stats.apr))
+            .collect()
+    }
+
+    #[test]
+    fn concurrent_refreshes_keep_each_others_newer_reads() {
+        let cache = SnapshotCache::default();
+        cache.update(
+            None,
+            snapshot(
+                100,
+                vec![
+                    read(LstProtocol::AprMON, 1.0, 100),
+                    read(LstProtocol::GMON, 1.0, 100),
+                ],
+            ),
+        );
+
+        // A full refresh reads at 110, but a refresh of gMON alone reads at 120 and stores first
+        cache.update(
+            Some(LstProtocol::GMON),
+            snapshot(120, vec![read(LstProtocol::GMON, 3.0, 120)]),
+        );
+        let latest = cache.update(
+            None,
+            snapshot(
+                110,
+                vec![
+                    read(LstProtocol::AprMON, 2.0, 110),
+                    read(LstProtocol::GMON, 2.0, 110),
+                ],
+            ),
+        );
+
+        assert_eq!(
+            aprs(&latest),
+            vec![(LstProtocol::AprMON, 2.0), (LstProtocol::GMON, 3.0)]
+        );
+        assert_eq!(latest.as_of, 110);
+
+        // The other way round, the single refresh merges into what the full one stored
+        let latest = cache.update(
+            Some(LstProtocol::AprMON),
+            snapshot(130, vec![read(LstProtocol::AprMON, 4.0, 130)]),
+        );
+        assert_eq!(
+            aprs(&latest),
+            vec![(LstProtocol::AprMON, 4.0), (LstProtocol::GMON, 3.0)]
+        );
+        assert_eq!(aprs(&cache.get().unwrap()), aprs(&latest));
+    }
+}
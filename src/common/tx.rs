@@ -0,0 +1,599 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, bail};
+use ethers::{
+    abi::Detokenize,
+    contract::builders::ContractCall,
+    middleware::SignerMiddleware,
+    prelude::Lazy,
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::{
+        Address, BlockNumber, Bytes, Eip1559TransactionRequest, H256, Transaction,
+        TransactionReceipt, TransactionRequest, U64, U256, transaction::eip2718::TypedTransaction,
+    },
+    utils::hex::encode_prefixed,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{
+        progress::{Progress, WriteStep},
+        rpc::RpcProvider,
+    },
+    services::constants::{GAS_LIMIT_MULTIPLIER, MAX_GAS_LIMIT_MULTIPLIER},
+};
+
+pub type WalletSigner = SignerMiddleware<Arc<RpcProvider>, LocalWallet>;
+
+/// Nonces handed out by [`send_checked`], shared by every connection.
+static NONCES: Lazy<NonceTracker> = Lazy::new(NonceTracker::default);
+
+/// Next nonce of each signer, handed out locally so that concurrent writes from one key don't
+/// pick the same one. The chain's pending nonce wins when it is higher, as after transactions
+/// sent elsewhere. A local nonce that has run ahead of the chain, e.g. when a transaction was
+/// dropped, leaves a gap later transactions wait behind until [`reset_nonce`] forgets it.
+#[derive(Debug, Default)]
+pub struct NonceTracker {
+    next: Mutex<HashMap<Address, U256>>,
+}
+
+impl NonceTracker {
+    /// Hands out the nonce of the next transaction from `address`, given its pending nonce on
+    /// chain.
+    pub fn next(&self, address: Address, chain_nonce: U256) -> U256 {
+        let mut next = self.next.lock().unwrap();
+        let nonce = next
+            .get(&address)
+            .map_or(chain_nonce, |&local| local.max(chain_nonce));
+        next.insert(address, nonce + 1);
+        nonce
+    }
+
+    /// Takes back `nonce` when the transaction it was handed out for was never sent, unless a
+    /// later one has been handed out since.
+    pub fn release(&self, address: Address, nonce: U256) {
+        let mut next = self.next.lock().unwrap();
+        if next.get(&address) == Some(&(nonce + 1)) {
+            next.insert(address, nonce);
+        }
+    }
+
+    /// Forgets the nonce `address` would be handed out next, returning it, so the next one is
+    /// the chain's.
+    pub fn reset(&self, address: Address) -> Option<U256> {
+        self.next.lock().unwrap().remove(&address)
+    }
+}
+
+/// Re-syncs the nonces writes from `address` use with the chain: forgets the local one and
+/// reads the pending nonce the next write starts from. Returns both, the local one `None` when
+/// no write from `address` has been sent.
+pub async fn reset_nonce(
+    provider: &RpcProvider,
+    address: Address,
+) -> anyhow::Result<(Option<U256>, U256)> {
+    let chain_nonce = provider
+        .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+        .await
+        .context("Failed to get pending nonce")?;
+
+    Ok((NONCES.reset(address), chain_nonce))
+}
+
+/// The signer cannot cover the transaction value plus its maximum gas cost.
+#[derive(Debug)]
+pub struct InsufficientFundsForGas {
+    pub required: U256,
+    pub balance: U256,
+}
+
+impl InsufficientFundsForGas {
+    pub fn shortfall(&self) -> U256 {
+        self.required.saturating_sub(self.balance)
+    }
+}
+
+/// Amounts are in the native currency's smallest unit, as this module doesn't know its symbol or
+/// decimals; tools describe the error in the configured currency instead.
+impl fmt::Display for InsufficientFundsForGas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Insufficient funds for gas: requires {} but balance is {} (short {}) in the smallest unit",
+            self.required,
+            self.balance,
+            self.shortfall(),
+        )
+    }
+}
+
+impl std::error::Error for InsufficientFundsForGas {}
+
+/// Fails with [`InsufficientFundsForGas`] when `balance` can't cover `value` plus `gas_cost`.
+pub fn check_gas_funds(balance: U256, value: U256, gas_cost: U256) -> anyhow::Result<()> {
+    let required = value.saturating_add(gas_cost);
+    if balance < required {
+        return Err(InsufficientFundsForGas { required, balance }.into());
+    }
+
+    Ok(())
+}
+
+/// Factor applied to estimated gas to set the gas limit of a transaction, leaving headroom for
+/// estimates that turn out tight. Always between 1 and [`MAX_GAS_LIMIT_MULTIPLIER`].
+///
+/// Write tools take it per call as `gas_limit_multiplier`, a number of at least 1 that is
+/// clamped to the maximum, and fall back to the server's multiplier when it is omitted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GasMultiplier(f64);
+
+impl GasMultiplier {
+    /// Fails for values below 1, which would leave the transaction short of gas; values above
+    /// [`MAX_GAS_LIMIT_MULTIPLIER`] are clamped to it.
+    pub fn new(value: f64) -> anyhow::Result<Self> {
+        if !value.is_finite() || value < 1.0 {
+            bail!("Gas limit multiplier must be at least 1, got {}", value);
+        }
+
+        Ok(GasMultiplier(value.min(MAX_GAS_LIMIT_MULTIPLIER)))
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    /// Scales `gas` by the multiplier, to a precision of 0.0001 and rounding up.
+    pub fn apply(self, gas: U256) -> U256 {
+        let basis_points = (self.0 * 10_000.0).round() as u64;
+        gas.saturating_mul(basis_points.into())
+            .saturating_add(9_999.into())
+            / 10_000
+    }
+}
+
+impl Default for GasMultiplier {
+    fn default() -> Self {
+        GasMultiplier(GAS_LIMIT_MULTIPLIER)
+    }
+}
+
+/// ERC-20 tokens that revert when an allowance is changed from one non-zero value to another,
+/// guarding against the approve race. Approvals of these tokens set a non-zero allowance to
+/// zero first.
+#[derive(Clone, Debug, Default)]
+pub struct AllowanceResets(Arc<[Address]>);
+
+impl AllowanceResets {
+    pub fn new(tokens: impl IntoIterator<Item = Address>) -> Self {
+        AllowanceResets(tokens.into_iter().collect())
+    }
+
+    /// Whether raising the allowance of `token` from `allowance` needs a reset to zero first.
+    pub fn needs_reset(&self, token: Address, allowance: U256) -> bool {
+        !allowance.is_zero() && self.0.contains(&token)
+    }
+
+    /// The allowances to approve, in order, for `token`'s `allowance` to cover `amount`: none
+    /// when it already does, and zero before `amount` when the token needs a reset first.
+    pub fn approvals(&self, token: Address, allowance: U256, amount: U256) -> Vec<U256> {
+        if allowance >= amount {
+            vec![]
+        } else if self.needs_reset(token, allowance) {
+            vec![U256::zero(), amount]
+        } else {
+            vec![amount]
+        }
+    }
+}
+
+/// Sends `call` with its estimated gas scaled by `gas_multiplier` as the gas limit, once the
+/// signer is known to afford its value and that gas, then waits for one confirmation, reporting
+/// that step to `progress`. `action` names the call in error messages.
+///
+/// The value alone is checked against the balance before estimating, as nodes fail the
+/// estimate of a call the sender can't fund with an opaque error rather than the shortfall.
+///
+/// The transaction is pinned to the signer's chain id rather than left for the middleware to
+/// fill in, so even a legacy transaction is signed with EIP-155 replay protection. Its nonce
+/// comes from the shared [`NonceTracker`].
+pub async fn send_checked<D: Detokenize>(
+    call: ContractCall<WalletSigner, D>,
+    signer: &WalletSigner,
+    signer_address: Address,
+    action: &str,
+    gas_multiplier: GasMultiplier,
+    progress: &Progress,
+) -> anyhow::Result<Option<TransactionReceipt>> {
+    let balance = signer
+        .get_balance(signer_address, None)
+        .await
+        .context("Failed to get balance")?;
+    let value = call.tx.value().copied().unwrap_or_default();
+    check_gas_funds(balance, value, U256::zero())?;
+
+    let estimate = call
+        .estimate_gas()
+        .await
+        .with_context(|| format!("Failed to estimate gas to {}", action))?;
+    let gas = gas_multiplier.apply(estimate);
+    let mut call = call.gas(gas);
+    call.tx.set_chain_id(signer.signer().chain_id());
+    let (max_fee_per_gas, _) = signer
+        .estimate_eip1559_fees(None)
+        .await
+        .context("Failed to estimate fees")?;
+    check_gas_funds(balance, value, gas.saturating_mul(max_fee_per_gas))?;
+
+    let chain_nonce = signer
+        .get_transaction_count(signer_address, Some(BlockNumber::Pending.into()))
+        .await
+        .context("Failed to get pending nonce")?;
+    let nonce = NONCES.next(signer_address, chain_nonce);
+    call.tx.set_nonce(nonce);
+
+    let pending = match progress.track_send(call.send(), |pending| **pending).await {
+        Ok(pending) => pending,
+        Err(e) => {
+            NONCES.release(signer_address, nonce);
+            return Err(e).with_context(|| format!("Failed to {}", action));
+        }
+    };
+    let tx_hash = *pending;
+    progress.report(WriteStep::AwaitingConfirmation).await;
+    // The transaction is out by now, so the hash lets the caller follow it up with `tx_status`
+    let receipt = pending.confirmations(1).await.with_context(|| {
+        format!(
+            "Failed to confirm {} in transaction {}",
+            action,
+            encode_prefixed(tx_hash)
+        )
+    })?;
+
+    Ok(receipt)
+}
+
+/// Percentage by which a replacement raises the fees of the transaction it replaces, the
+/// minimum nodes accept.
+const FEE_BUMP_PERCENT: u64 = 10;
+
+/// Gas used by a plain MON transfer to an address without code.
+pub const TRANSFER_GAS: u64 = 21_000;
+
+/// Raises `fee` by [`FEE_BUMP_PERCENT`], rounding up. Fails for a fee too large to raise, which
+/// only a misbehaving node would report.
+pub fn bump_fee(fee: U256) -> anyhow::Result<U256> {
+    fee.checked_mul(U256::from(100 + FEE_BUMP_PERCENT))
+        .and_then(|scaled| scaled.checked_add(U256::from(99)))
+        .map(|scaled| scaled / 100)
+        .with_context(|| format!("Fee {} is too large to raise", fee))
+}
+
+/// Looks up a transaction sent by the signer that has not been mined yet.
+pub async fn pending_transaction(
+    signer: &WalletSigner,
+    tx_hash: H256,
+) -> anyhow::Result<Transaction> {
+    let tx = signer
+        .get_transaction(tx_hash)
+        .await
+        .context("Failed to get transaction")?
+        .with_context(|| format!("Transaction {} not found", encode_prefixed(tx_hash)))?;
+
+    if tx.block_number.is_some() {
+        bail!(
+            "Transaction {} has already been mined",
+            encode_prefixed(tx_hash)
+        );
+    }
+    if tx.from != signer.address() {
+        bail!(
+            "Transaction {} was not sent by the signer",
+            encode_prefixed(tx_hash)
+        );
+    }
+
+    Ok(tx)
+}
+
+/// Where a transaction stands, from [`transaction_status`].
+#[derive(Clone, Copy, Debug)]
+pub enum TxStatus {
+    /// Known to the node but not mined yet.
+    Pending,
+    Mined {
+        block_number: u64,
+        /// `None` when the receipt has no status, as before Byzantium.
+        success: Option<bool>,
+        gas_used: Option<U256>,
+        /// The receipt's `effectiveGasPrice`, or the transaction's gas price when the node
+        /// leaves it out.
+        effective_gas_price: Option<U256>,
+    },
+}
+
+impl TxStatus {
+    /// Gas paid for a mined transaction, if the receipt says how much it used and at what price.
+    pub fn fee(&self) -> Option<U256> {
+        match self {
+            TxStatus::Pending => None,
+            TxStatus::Mined {
+                gas_used,
+                effective_gas_price,
+                ..
+            } => Some((*gas_used)?.saturating_mul((*effective_gas_price)?)),
+        }
+    }
+}
+
+/// Receipt fields read by [`transaction_status`]. Every field is optional, as some nodes answer
+/// with nulls or leave fields out where ethers' receipt type expects values. `Serialize` is only
+/// there because `RpcProvider::request` asks for it.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LenientReceipt {
+    block_number: Option<U64>,
+    status: Option<U64>,
+    gas_used: Option<U256>,
+    effective_gas_price: Option<U256>,
+}
+
+/// Transaction fields read by [`transaction_status`], as leniently as [`LenientReceipt`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LenientTransaction {
+    gas_price: Option<U256>,
+}
+
+/// Reads whether a transaction is pending or mined. A null receipt, or one without a block
+/// number, means the transaction is still pending, provided the node knows it. Returns `None`
+/// when the node knows neither a receipt nor the transaction.
+pub async fn transaction_status(
+    provider: &RpcProvider,
+    tx_hash: H256,
+) -> anyhow::Result<Option<TxStatus>> {
+    let (receipt, tx) = futures::try_join!(
+        provider.request::<_, Option<LenientReceipt>>("eth_getTransactionReceipt", [tx_hash]),
+        provider.request::<_, Option<LenientTransaction>>("eth_getTransactionByHash", [tx_hash]),
+    )
+    .context("Failed to get transaction")?;
+
+    let receipt = receipt.and_then(|receipt| Some((receipt.block_number?, receipt)));
+    Ok(match (receipt, tx) {
+        (Some((block_number, receipt)), tx) => Some(TxStatus::Mined {
+            block_number: block_number.as_u64(),
+            success: receipt.status.map(|status| status == U64::one()),
+            gas_used: receipt.gas_used,
+            effective_gas_price: receipt
+                .effective_gas_price
+                .or_else(|| tx.and_then(|tx| tx.gas_price)),
+        }),
+        (None, Some(_)) => Some(TxStatus::Pending),
+        (None, None) => None,
+    })
+}
+
+/// Fees of a pending transaction, which a replacement must outbid.
+#[derive(Clone, Copy, Debug)]
+pub enum ReplacedFees {
+    Eip1559 { max_fee: U256, priority_fee: U256 },
+    Legacy { gas_price: U256 },
+}
+
+impl From<&Transaction> for ReplacedFees {
+    fn from(tx: &Transaction) -> Self {
+        match tx.max_priority_fee_per_gas {
+            Some(priority_fee) => Self::Eip1559 {
+                max_fee: tx.max_fee_per_gas.unwrap_or_default(),
+                priority_fee,
+            },
+            None => Self::Legacy {
+                gas_price: tx.gas_price.unwrap_or_default(),
+            },
+        }
+    }
+}
+
+/// Checks that the signer has a transaction at `nonce` that has not been mined yet, i.e. that
+/// `nonce` lies between the latest and pending transaction counts.
+pub async fn ensure_nonce_pending(signer: &WalletSigner, nonce: U256) -> anyhow::Result<()> {
+    let address = signer.address();
+    let (latest, pending) = futures::try_join!(
+        signer.get_transaction_count(address, Some(BlockNumber::Latest.into())),
+        signer.get_transaction_count(address, Some(BlockNumber::Pending.into())),
+    )
+    .context("Failed to get transaction count")?;
+
+    if nonce < latest {
+        bail!(
+            "Nonce {} has already been used by a mined transaction",
+            nonce
+        );
+    }
+    if nonce >= pending {
+        bail!("No pending transaction with nonce {}", nonce);
+    }
+
+    Ok(())
+}
+
+/// Sends a transaction at `nonce`, with fees raised by at least [`FEE_BUMP_PERCENT`] over
+/// `replaced` and to no less than the current estimate, so that it replaces the pending
+/// transaction at that nonce. Returns the replacement's hash without waiting for it to be mined.
+pub async fn send_replacement(
+    signer: &WalletSigner,
+    nonce: U256,
+    replaced: ReplacedFees,
+    to: Option<Address>,
+    value: U256,
+    data: Bytes,
+    gas: U256,
+) -> anyhow::Result<H256> {
+    let mut tx: TypedTransaction = match replaced {
+        ReplacedFees::Eip1559 {
+            max_fee: replaced_max_fee,
+            priority_fee,
+        } => {
+            let (max_fee, estimated_priority_fee) = signer
+                .estimate_eip1559_fees(None)
+                .await
+                .context("Failed to estimate fees")?;
+            let priority_fee = bump_fee(priority_fee)?.max(estimated_priority_fee);
+            let max_fee = bump_fee(replaced_max_fee)?.max(max_fee).max(priority_fee);
+            Eip1559TransactionRequest::new()
+                .max_fee_per_gas(max_fee)
+                .max_priority_fee_per_gas(priority_fee)
+                .into()
+        }
+        ReplacedFees::Legacy {
+            gas_price: replaced_gas_price,
+        } => {
+            let gas_price = signer
+                .get_gas_price()
+                .await
+                .context("Failed to get gas price")?;
+            TransactionRequest::new()
+                .gas_price(bump_fee(replaced_gas_price)?.max(gas_price))
+                .into()
+        }
+    };
+    tx.set_from(signer.address())
+        .set_value(value)
+        .set_data(data)
+        .set_gas(gas)
+        .set_nonce(nonce)
+        .set_chain_id(signer.signer().chain_id());
+    if let Some(to) = to {
+        tx.set_to(to);
+    }
+
+    let balance = signer
+        .get_balance(signer.address(), None)
+        .await
+        .context("Failed to get balance")?;
+    let gas_price = match &tx {
+        TypedTransaction::Eip1559(tx) => tx.max_fee_per_gas,
+        _ => tx.gas_price(),
+    };
+    check_gas_funds(
+        balance,
+        value,
+        gas.saturating_mul(gas_price.unwrap_or_default()),
+    )?;
+
+    let pending = signer
+        .send_transaction(tx, None)
+        .await
+        .context("Failed to send replacement transaction")?;
+
+    Ok(pending.tx_hash())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_funds_check_reports_the_shortfall() {
+        let err = check_gas_funds(U256::from(5), U256::from(10), U256::zero()).unwrap_err();
+        let err = err.downcast_ref::<InsufficientFundsForGas>().unwrap();
+        assert_eq!(err.required, U256::from(10));
+        assert_eq!(err.shortfall(), U256::from(5));
+
+        let err = check_gas_funds(U256::from(10), U256::from(10), U256::one()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<InsufficientFundsForGas>()
+                .unwrap()
+                .shortfall(),
+            U256::one()
+        );
+
+        assert!(check_gas_funds(U256::from(11), U256::from(10), U256::one()).is_ok());
+        assert!(check_gas_funds(U256::zero(), U256::zero(), U256::zero()).is_ok());
+    }
+
+    #[test]
+    fn nonces_are_handed_out_in_turn_and_reset_to_the_chain() {
+        let nonces = NonceTracker::default();
+        let signer = Address::repeat_byte(0x11);
+
+        assert_eq!(nonces.next(signer, U256::from(5)), U256::from(5));
+        // A concurrent write gets the next one before the chain has seen the first
+        assert_eq!(nonces.next(signer, U256::from(5)), U256::from(6));
+        // Transactions sent elsewhere move the chain ahead
+        assert_eq!(nonces.next(signer, U256::from(10)), U256::from(10));
+
+        // A nonce never sent is taken back only while it is the latest
+        nonces.release(signer, U256::from(10));
+        assert_eq!(nonces.next(signer, U256::from(5)), U256::from(10));
+
+        // Dropped transactions leave the local nonce ahead until it is reset
+        assert_eq!(nonces.next(signer, U256::from(7)), U256::from(11));
+        assert_eq!(nonces.reset(signer), Some(U256::from(12)));
+        assert_eq!(nonces.next(signer, U256::from(7)), U256::from(7));
+        assert_eq!(nonces.reset(Address::repeat_byte(0x22)), None);
+    }
+
+    #[test]
+    fn listed_tokens_reset_a_non_zero_allowance_first() {
+        let token = Address::repeat_byte(0x77);
+        let resets = AllowanceResets::new([token]);
+        let amount = U256::from(100);
+
+        assert_eq!(
+            resets.approvals(token, U256::from(40), amount),
+            vec![U256::zero(), amount]
+        );
+        // Nothing to reset from zero, and nothing to approve once the allowance covers it
+        assert_eq!(resets.approvals(token, U256::zero(), amount), vec![amount]);
+        assert!(resets.approvals(token, amount, amount).is_empty());
+
+        let unlisted = Address::repeat_byte(0x88);
+        assert_eq!(
+            resets.approvals(unlisted, U256::from(40), amount),
+            vec![amount]
+        );
+        assert_eq!(
+            AllowanceResets::default().approvals(token, U256::from(40), amount),
+            vec![amount]
+        );
+    }
+
+    #[test]
+    fn fees_are_bumped_rounding_up_and_refused_past_256_bits() {
+        assert_eq!(bump_fee(U256::from(100)).unwrap(), U256::from(110));
+        assert_eq!(bump_fee(U256::from(7)).unwrap(), U256::from(8));
+        assert!(bump_fee(U256::MAX).is_err());
+        assert!(bump_fee(U256::MAX / 100).is_err());
+    }
+
+    #[test]
+    fn gas_limits_default_to_a_tight_margin() {
+        let estimate = U256::from(100_000);
+
+        assert_eq!(
+            GasMultiplier::default().apply(estimate),
+            U256::from(105_000)
+        );
+        assert_eq!(GasMultiplier::new(1.0).unwrap().apply(estimate), estimate);
+        assert_eq!(
+            GasMultiplier::new(1.00005).unwrap().apply(U256::from(3)),
+            U256::from(4)
+        );
+        assert_eq!(
+            GasMultiplier::new(10.0).unwrap().value(),
+            MAX_GAS_LIMIT_MULTIPLIER
+        );
+        assert!(GasMultiplier::new(0.9).is_err());
+        assert!(GasMultiplier::new(f64::NAN).is_err());
+        // A limit past 256 bits saturates instead of overflowing
+        assert_eq!(
+            GasMultiplier::default().apply(U256::MAX),
+            U256::MAX / 10_000
+        );
+    }
+}
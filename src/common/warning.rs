@@ -0,0 +1,64 @@
+use std::fmt;
+
+/// Advisory attached to a successful response: the operation went through, but with a caveat
+/// the agent should relay to the user.
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub code: WarningCode,
+    pub message: String,
+}
+
+/// Stable identifier of a [`Warning`], reported as `code` so clients can branch on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningCode {
+    /// The protocol's oracle trails the chain head by more than the stale threshold.
+    OracleStale,
+    /// The withdrawal fee takes a large share of the amount paid out.
+    HighWithdrawalFee,
+    /// The deposit uses most of the room left under the protocol's maximum.
+    NearMaxDeposit,
+    /// The operator marked the protocol deprecated.
+    Deprecated,
+    /// An amount arrived as a JSON float, which the client may already have rounded.
+    FloatAmount,
+    /// The stake's deposit event and the LST's mint disagree on the shares minted.
+    SharesMismatch,
+    /// A scan started later than asked, as the range reached past the lookback limit.
+    RangeClamped,
+}
+
+impl fmt::Display for WarningCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningCode::OracleStale => write!(f, "ORACLE_STALE"),
+            WarningCode::HighWithdrawalFee => write!(f, "HIGH_WITHDRAWAL_FEE"),
+            WarningCode::NearMaxDeposit => write!(f, "NEAR_MAX_DEPOSIT"),
+            WarningCode::Deprecated => write!(f, "PROTOCOL_DEPRECATED"),
+            WarningCode::FloatAmount => write!(f, "FLOAT_AMOUNT"),
+            WarningCode::SharesMismatch => write!(f, "SHARES_MISMATCH"),
+            WarningCode::RangeClamped => write!(f, "RANGE_CLAMPED"),
+        }
+    }
+}
+
+impl Warning {
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Warning {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Renders `warnings` as the `warnings` array of a response.
+pub fn warnings_json(warnings: &[Warning]) -> serde_json::Value {
+    warnings
+        .iter()
+        .map(|warning| {
+            serde_json::json!({
+                "code": warning.code.to_string(),
+                "message": warning.message,
+            })
+        })
+        .collect()
+}
@@ -1,7 +1,98 @@
+use std::time::Duration;
+
 use ethers::{prelude::Lazy, types::Address};
 
 pub const MONAD_TESTNET_CHAIN_ID: u64 = 10143;
 
+pub const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+/// Number of blocks to look back when deriving APR from exchange-rate growth (~1 day).
+pub const APR_LOOKBACK_BLOCKS: u64 = 172_800;
+
+/// Default block range per `eth_getLogs` request, within what the public RPC accepts.
+pub const EVENT_CHUNK_SIZE: u64 = 100;
+
+/// How far back event-scanning resources look by default, in blocks.
+pub const EVENT_MAX_LOOKBACK_BLOCKS: u64 = 10_000;
+
+/// Upper bounds on the configurable event scan settings.
+pub const EVENT_CHUNK_SIZE_LIMIT: u64 = 10_000;
+pub const EVENT_MAX_LOOKBACK_BLOCKS_LIMIT: u64 = 1_000_000;
+
+/// How long protocol metadata such as decimals is cached by default.
+pub const METADATA_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long a USD price is reused before the price source is read again.
+pub const PRICE_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How old a USD price may be, from its last update, before it is refused as stale.
+pub const PRICE_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// How long a price feed request may take.
+pub const PRICE_FEED_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long each check of the `diagnostics` tool may take before it is reported failed.
+pub const DIAGNOSTIC_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time limit of read tools and resources.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time limit of write tools, which wait for the transaction to be confirmed.
+pub const WRITE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Activities returned per address history page unless the request sets `limit`.
+pub const HISTORY_LIMIT: usize = 100;
+
+/// Largest `limit` an address history request may set.
+pub const HISTORY_MAX_LIMIT: usize = 1_000;
+
+/// Most addresses a multi-address balance read may list.
+pub const BALANCES_MAX_ADDRESSES: usize = 50;
+
+/// Blocks the aprMON oracle may trail the chain head before its data is reported stale.
+pub const ORACLE_STALE_BLOCKS: u64 = 1_000;
+
+/// Blocks a redeem request must have been processed for before it is reported claimable.
+pub const CLAIM_CONFIRMATIONS: u64 = 0;
+
+/// How often `redeem_when_ready` checks whether a redeem request can be claimed yet.
+pub const REDEEM_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long `redeem_when_ready` waits for a redeem request unless the call sets `max_wait_secs`.
+pub const REDEEM_MAX_WAIT: Duration = Duration::from_secs(300);
+
+/// Longest `max_wait_secs` a `redeem_when_ready` call may set.
+pub const REDEEM_MAX_WAIT_LIMIT: Duration = Duration::from_secs(3_600);
+
+/// How long a `stake_split` or `unstake_split` job can be resumed after it last ran.
+pub const JOB_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Most entries a `stake_split` or `unstake_split` call may list.
+pub const JOB_MAX_ENTRIES: usize = 10;
+
+/// Factor applied to estimated gas for the gas limit of write tools, as estimates can be tight.
+/// Monad charges for the whole gas limit rather than the gas used, so every bit of headroom is
+/// paid on every write: 1.05 costs 5% more than the estimate.
+pub const GAS_LIMIT_MULTIPLIER: f64 = 1.05;
+
+/// Largest gas limit multiplier the server or a tool call may set.
+pub const MAX_GAS_LIMIT_MULTIPLIER: f64 = 3.0;
+
+/// Minimum TVL, in MON, for a protocol to be eligible as the recommended one.
+pub const MIN_RECOMMENDED_TVL: u64 = 1_000;
+
+/// RPC reads allowed in flight at once, across all connections.
+pub const MAX_CONCURRENT_READS: usize = 8;
+
+/// Client connections served at once before new ones are refused.
+pub const MAX_CONNECTIONS: usize = 256;
+
+/// Withdrawal fee, in basis points of the amount redeemed, above which a preview warns.
+pub const HIGH_WITHDRAWAL_FEE_BPS: u64 = 100;
+
+/// Share of the maximum deposit, in percent, from which a stake warns it is near the limit.
+pub const NEAR_MAX_DEPOSIT_PERCENT: u64 = 90;
+
 pub static APRMON_ADDRESS: Lazy<Address> = Lazy::new(|| {
     "0xb2f82D0f38dc453D596Ad40A37799446Cc89274A"
         .parse()